@@ -0,0 +1,64 @@
+/// Chunk hashing throughput: streaming build vs slice build
+///
+/// Run with and without the thread pool to compare:
+///
+///   cargo bench -p mythos-blob --bench chunk_hashing
+///   cargo bench -p mythos-blob --bench chunk_hashing --features parallel
+///
+/// `MYTHOS_BENCH_MB` sets the input size (default 512).
+use mythos_blob::{ChunkedBlobBuilder, DEFAULT_CHUNK_SIZE};
+use std::time::{Duration, Instant};
+
+const RUNS: u32 = 3;
+
+fn main() {
+    let mb: usize = std::env::var("MYTHOS_BENCH_MB")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(512);
+    let data: Vec<u8> = (0..mb * 1024 * 1024)
+        .map(|i| (i * 31 % 251) as u8)
+        .collect();
+    let builder = ChunkedBlobBuilder::new().chunk_size(DEFAULT_CHUNK_SIZE);
+
+    let parallel = cfg!(feature = "parallel");
+    println!(
+        "{} MiB, {} MiB chunks, parallel feature {}",
+        mb,
+        DEFAULT_CHUNK_SIZE >> 20,
+        if parallel { "on" } else { "off" }
+    );
+
+    let (streaming, root_a) = time(|| builder.build(&data[..]).unwrap().root);
+    report("build (streaming)", mb, streaming);
+
+    let (slice, root_b) = time(|| builder.build_slice(&data).unwrap().root);
+    report("build_slice", mb, slice);
+
+    assert_eq!(root_a, root_b, "slice build must match streaming build");
+    println!(
+        "speedup: {:.2}x",
+        streaming.as_secs_f64() / slice.as_secs_f64()
+    );
+}
+
+/// Best of RUNS
+fn time<T>(mut f: impl FnMut() -> T) -> (Duration, T) {
+    let mut best = Duration::MAX;
+    let mut out = None;
+    for _ in 0..RUNS {
+        let start = Instant::now();
+        out = Some(f());
+        best = best.min(start.elapsed());
+    }
+    (best, out.expect("at least one run"))
+}
+
+fn report(name: &str, mb: usize, elapsed: Duration) {
+    println!(
+        "{:<20} {:>8.1} ms  {:>8.1} MiB/s",
+        name,
+        elapsed.as_secs_f64() * 1000.0,
+        mb as f64 / elapsed.as_secs_f64()
+    );
+}
@@ -0,0 +1,476 @@
+/// BlobRef, EncryptRef and ProvenanceRef (RFC-0001 §13, Appendix A.6–A.8)
+///
+/// Typed structs with canonical MYTHOS-CAN encode/decode. For chunked blobs
+/// `size` is the DAG's total_size (RFC-0004 §6.6), which `check_dag`
+/// verifies. The sizes of the bytes before the codec and before the cipher
+/// are recorded as provenance params by the builder.
+use crate::build::{hash_to_can, ChunkedBlobBuild};
+use crate::source::{fetch_verified, NodeSource};
+use crate::validation::{decode_chunk_node, parse_hash_bytes, Error, Result};
+use mythos_can::Value;
+use std::collections::BTreeMap;
+
+/// Reference to blob bytes by CID, with size and media metadata
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlobRef {
+    pub cid: Vec<u8>,  // Field 1 - Hash bytes (alg=1), root CID if chunked
+    pub size: u64,     // Field 2 - total blob size in bytes
+    pub media: String, // Field 3 - media type
+    pub codec: u8,     // Field 4 - codec id (0 = raw)
+    pub chunks: u32,   // Field 5 - chunk count, 0 if unchunked or > u32::MAX
+    pub encryption: Option<EncryptRef>, // Field 6 - optional
+    pub provenance: Option<ProvenanceRef>, // Field 7 - optional
+}
+
+/// Encryption parameters for a blob (Appendix A.6)
+#[derive(Debug, Clone, PartialEq)]
+pub struct EncryptRef {
+    pub scheme: u8,                  // Field 1 - scheme id
+    pub key_handle: String,          // Field 2 - resolved by the key provider
+    pub nonce: Vec<u8>,              // Field 3 - never reused under one key
+    pub aad: Option<Vec<u8>>,        // Field 4 - optional
+    pub plain_hash: Option<Vec<u8>>, // Field 5 - optional Hash bytes of the plaintext
+}
+
+/// Derivation record for a blob (Appendix A.7)
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProvenanceRef {
+    pub parents: Vec<Vec<u8>>,            // Field 1 - parent Hash bytes
+    pub transform: String,                // Field 2 - transform name
+    pub params: BTreeMap<String, String>, // Field 3 - transform parameters
+    pub code_hash: Vec<u8>,               // Field 4 - Hash bytes of the transform code
+    pub time_observed: Option<i64>,       // Field 5 - optional, microseconds since epoch
+}
+
+pub const DEFAULT_MEDIA: &str = "application/octet-stream";
+
+/// ProvenanceRef.params key for the blob size before its codec was applied
+pub const PARAM_DECODED_SIZE: &str = "decoded_size";
+
+/// ProvenanceRef.params keys for the plaintext handed to the cipher: its
+/// size and, when the build opts in, its hex SHA-256 (§13.6)
+pub const PARAM_PLAIN_SIZE: &str = "plain_size";
+pub const PARAM_PLAIN_HASH: &str = "plain_hash";
+
+// Codec ids (Appendix A.8)
+pub const CODEC_RAW: u8 = 0;
+pub const CODEC_ZSTD: u8 = 1;
+pub const CODEC_GZIP: u8 = 2;
+pub const CODEC_JPEG: u8 = 3;
+pub const CODEC_PNG: u8 = 4;
+pub const CODEC_JSONCBOR: u8 = 5;
+pub const CODEC_PARQUET: u8 = 6;
+
+// Encryption scheme ids (Appendix A.6)
+pub const SCHEME_AGE: u8 = 1;
+pub const SCHEME_AES_GCM: u8 = 2;
+pub const SCHEME_KMS_ENVELOPE: u8 = 3;
+
+/// Largest payload that may be carried inline instead of by BlobRef (§13.5)
+pub const INLINE_BLOB_LIMIT: usize = 8 * 1024;
+
+/// BlobRef.chunks for a chunk count (RFC-0004 §6.6)
+///
+/// Counts that do not fit in u32 are recorded as 0; the real count must
+/// then be read from the root node.
+pub fn blob_ref_chunks(chunk_count: u64) -> u32 {
+    u32::try_from(chunk_count).unwrap_or(0)
+}
+
+/// Check that a payload is small enough to carry inline (§13.5)
+pub fn check_inline(bytes: &[u8]) -> Result<()> {
+    if bytes.len() > INLINE_BLOB_LIMIT {
+        return Err(Error::InlineTooLarge(bytes.len()));
+    }
+    Ok(())
+}
+
+impl BlobRef {
+    /// Raw, unencrypted BlobRef for a built ChunkedBlob DAG
+    pub fn from_build(build: &ChunkedBlobBuild, media: impl Into<String>) -> Self {
+        Self::chunked(
+            &build.root,
+            build.total_size,
+            build.chunk_count,
+            media.into(),
+        )
+    }
+
+    pub(crate) fn chunked(root: &[u8; 32], size: u64, chunk_count: u64, media: String) -> Self {
+        BlobRef {
+            cid: root.to_vec(),
+            size,
+            media,
+            codec: CODEC_RAW,
+            chunks: blob_ref_chunks(chunk_count),
+            encryption: None,
+            provenance: None,
+        }
+    }
+
+    /// Check field ranges and nested structs
+    pub fn validate(&self) -> Result<()> {
+        if self.cid.len() != 32 {
+            return Err(Error::InvalidHashLength(self.cid.len()));
+        }
+        if self.media.is_empty() {
+            return Err(Error::InvalidBlobRef("media must not be empty".into()));
+        }
+        if self.codec > CODEC_PARQUET {
+            return Err(Error::InvalidBlobRef(format!(
+                "Unknown codec {}",
+                self.codec
+            )));
+        }
+        if self.chunks > 0 && self.size == 0 {
+            return Err(Error::InvalidBlobRef(
+                "Chunked blob must have non-zero size".into(),
+            ));
+        }
+        if let Some(encryption) = &self.encryption {
+            encryption.validate()?;
+        }
+        if let Some(provenance) = &self.provenance {
+            provenance.validate()?;
+        }
+        Ok(())
+    }
+
+    /// Check `chunks` and `size` against the DAG under `cid`
+    pub fn check_dag<S: NodeSource + ?Sized>(&self, source: &S) -> Result<()> {
+        let cid: [u8; 32] = self
+            .cid
+            .as_slice()
+            .try_into()
+            .map_err(|_| Error::InvalidHashLength(self.cid.len()))?;
+        let root = decode_chunk_node(&fetch_verified(source, &cid)?)?;
+
+        let expected = blob_ref_chunks(root.chunk_count());
+        if self.chunks != expected {
+            return Err(Error::ChunkCountMismatch {
+                expected: root.chunk_count(),
+                actual: self.chunks as u64,
+            });
+        }
+
+        if self.size != root.total_size() {
+            return Err(Error::SizeMismatch {
+                expected: root.total_size(),
+                actual: self.size,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Size of the blob once its codec is undone, if known
+    ///
+    /// Raw blobs decode to their stored bytes, or to their plaintext when
+    /// encrypted. Otherwise the size comes from the `decoded_size`
+    /// provenance param.
+    pub fn decoded_size(&self) -> Result<Option<u64>> {
+        if let Some(size) = self.size_param(PARAM_DECODED_SIZE)? {
+            return Ok(Some(size));
+        }
+        match (self.codec, &self.encryption) {
+            (CODEC_RAW, None) => Ok(Some(self.size)),
+            (CODEC_RAW, Some(_)) => self.plain_size(),
+            _ => Ok(None),
+        }
+    }
+
+    /// Size of the plaintext under the cipher, if recorded
+    pub fn plain_size(&self) -> Result<Option<u64>> {
+        self.size_param(PARAM_PLAIN_SIZE)
+    }
+
+    fn size_param(&self, key: &str) -> Result<Option<u64>> {
+        let Some(value) = self
+            .provenance
+            .as_ref()
+            .and_then(|provenance| provenance.params.get(key))
+        else {
+            return Ok(None);
+        };
+        value.parse().map(Some).map_err(|_| {
+            Error::InvalidBlobRef(format!(
+                "Provenance param {} is not a size: {:?}",
+                key, value
+            ))
+        })
+    }
+
+    pub fn to_value(&self) -> Value {
+        let mut fields = vec![
+            // Field 1: cid (Hash)
+            (Value::UVarint(1), hash_to_can(&self.cid)),
+            // Field 2: size
+            (Value::UVarint(2), Value::UVarint(self.size)),
+            // Field 3: media
+            (Value::UVarint(3), Value::Text(self.media.clone())),
+            // Field 4: codec
+            (Value::UVarint(4), Value::UVarint(self.codec as u64)),
+            // Field 5: chunks
+            (Value::UVarint(5), Value::UVarint(self.chunks as u64)),
+        ];
+
+        // Field 6: encryption (optional)
+        if let Some(encryption) = &self.encryption {
+            fields.push((Value::UVarint(6), encryption.to_value()));
+        }
+
+        // Field 7: provenance (optional)
+        if let Some(provenance) = &self.provenance {
+            fields.push((Value::UVarint(7), provenance.to_value()));
+        }
+
+        Value::Map(fields)
+    }
+
+    pub fn from_value(value: &Value) -> Result<Self> {
+        let fields = struct_fields(value, "BlobRef")?;
+        let get_field = |n: u64| field(fields, n);
+
+        let blob_ref = BlobRef {
+            cid: required_hash(get_field(1), "BlobRef.cid")?,
+            size: required_uint(get_field(2), "BlobRef.size", u64::MAX)?,
+            media: required_text(get_field(3), "BlobRef.media")?,
+            codec: required_uint(get_field(4), "BlobRef.codec", u8::MAX as u64)? as u8,
+            chunks: required_uint(get_field(5), "BlobRef.chunks", u32::MAX as u64)? as u32,
+            encryption: get_field(6).map(EncryptRef::from_value).transpose()?,
+            provenance: get_field(7).map(ProvenanceRef::from_value).transpose()?,
+        };
+
+        blob_ref.validate()?;
+        Ok(blob_ref)
+    }
+
+    /// Encode as canonical MYTHOS-CAN bytes
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        encode(&self.to_value())
+    }
+
+    /// Decode and validate canonical MYTHOS-CAN bytes
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        Self::from_value(&decode(bytes)?)
+    }
+}
+
+impl EncryptRef {
+    /// Check scheme, key handle, nonce and plain_hash
+    pub fn validate(&self) -> Result<()> {
+        if !(SCHEME_AGE..=SCHEME_KMS_ENVELOPE).contains(&self.scheme) {
+            return Err(Error::InvalidBlobRef(format!(
+                "Unknown encryption scheme {}",
+                self.scheme
+            )));
+        }
+        if self.key_handle.is_empty() {
+            return Err(Error::InvalidBlobRef("key_handle must not be empty".into()));
+        }
+        if self.nonce.is_empty() {
+            return Err(Error::InvalidBlobRef("nonce must not be empty".into()));
+        }
+        if let Some(plain_hash) = &self.plain_hash {
+            if plain_hash.len() != 32 {
+                return Err(Error::InvalidHashLength(plain_hash.len()));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn to_value(&self) -> Value {
+        let mut fields = vec![
+            (Value::UVarint(1), Value::UVarint(self.scheme as u64)),
+            (Value::UVarint(2), Value::Text(self.key_handle.clone())),
+            (Value::UVarint(3), Value::Bytes(self.nonce.clone())),
+        ];
+
+        if let Some(aad) = &self.aad {
+            fields.push((Value::UVarint(4), Value::Bytes(aad.clone())));
+        }
+        if let Some(plain_hash) = &self.plain_hash {
+            fields.push((Value::UVarint(5), hash_to_can(plain_hash)));
+        }
+
+        Value::Map(fields)
+    }
+
+    pub fn from_value(value: &Value) -> Result<Self> {
+        let fields = struct_fields(value, "EncryptRef")?;
+        let get_field = |n: u64| field(fields, n);
+
+        let aad = match get_field(4) {
+            None => None,
+            Some(Value::Bytes(b)) => Some(b.clone()),
+            Some(_) => return Err(Error::InvalidBlobRef("EncryptRef.aad must be BYTES".into())),
+        };
+
+        let encryption = EncryptRef {
+            scheme: required_uint(get_field(1), "EncryptRef.scheme", u8::MAX as u64)? as u8,
+            key_handle: required_text(get_field(2), "EncryptRef.key_handle")?,
+            nonce: required_bytes(get_field(3), "EncryptRef.nonce")?,
+            aad,
+            plain_hash: get_field(5)
+                .map(|v| required_hash(Some(v), "EncryptRef.plain_hash"))
+                .transpose()?,
+        };
+
+        encryption.validate()?;
+        Ok(encryption)
+    }
+}
+
+impl ProvenanceRef {
+    /// Check hash lengths and transform name
+    pub fn validate(&self) -> Result<()> {
+        for parent in &self.parents {
+            if parent.len() != 32 {
+                return Err(Error::InvalidHashLength(parent.len()));
+            }
+        }
+        if self.transform.is_empty() {
+            return Err(Error::InvalidBlobRef("transform must not be empty".into()));
+        }
+        if self.code_hash.len() != 32 {
+            return Err(Error::InvalidHashLength(self.code_hash.len()));
+        }
+        Ok(())
+    }
+
+    pub fn to_value(&self) -> Value {
+        let mut fields = vec![
+            (
+                Value::UVarint(1),
+                Value::List(self.parents.iter().map(|p| hash_to_can(p)).collect()),
+            ),
+            (Value::UVarint(2), Value::Text(self.transform.clone())),
+            (
+                Value::UVarint(3),
+                Value::Map(
+                    self.params
+                        .iter()
+                        .map(|(k, v)| (Value::Text(k.clone()), Value::Text(v.clone())))
+                        .collect(),
+                ),
+            ),
+            (Value::UVarint(4), hash_to_can(&self.code_hash)),
+        ];
+
+        if let Some(time) = self.time_observed {
+            fields.push((Value::UVarint(5), Value::IVarint(time)));
+        }
+
+        Value::Map(fields)
+    }
+
+    pub fn from_value(value: &Value) -> Result<Self> {
+        let fields = struct_fields(value, "ProvenanceRef")?;
+        let get_field = |n: u64| field(fields, n);
+
+        let parents = match get_field(1) {
+            Some(Value::List(items)) => items
+                .iter()
+                .map(|item| required_hash(Some(item), "ProvenanceRef.parents"))
+                .collect::<Result<Vec<_>>>()?,
+            _ => {
+                return Err(Error::InvalidBlobRef(
+                    "ProvenanceRef.parents must be LIST".into(),
+                ))
+            }
+        };
+
+        let params = match get_field(3) {
+            Some(Value::Map(pairs)) => pairs
+                .iter()
+                .map(|(k, v)| match (k, v) {
+                    (Value::Text(k), Value::Text(v)) => Ok((k.clone(), v.clone())),
+                    _ => Err(Error::InvalidBlobRef(
+                        "ProvenanceRef.params must map TEXT to TEXT".into(),
+                    )),
+                })
+                .collect::<Result<BTreeMap<_, _>>>()?,
+            _ => {
+                return Err(Error::InvalidBlobRef(
+                    "ProvenanceRef.params must be MAP".into(),
+                ))
+            }
+        };
+
+        let time_observed = match get_field(5) {
+            None => None,
+            Some(Value::IVarint(t)) => Some(*t),
+            Some(_) => {
+                return Err(Error::InvalidBlobRef(
+                    "ProvenanceRef.time_observed must be IVARINT".into(),
+                ))
+            }
+        };
+
+        let provenance = ProvenanceRef {
+            parents,
+            transform: required_text(get_field(2), "ProvenanceRef.transform")?,
+            params,
+            code_hash: required_hash(get_field(4), "ProvenanceRef.code_hash")?,
+            time_observed,
+        };
+
+        provenance.validate()?;
+        Ok(provenance)
+    }
+}
+
+fn encode(value: &Value) -> Result<Vec<u8>> {
+    mythos_can::encode_value(value).map_err(|e| Error::InvalidStructure(format!("Encode: {}", e)))
+}
+
+fn decode(bytes: &[u8]) -> Result<Value> {
+    mythos_can::decode_value_exact(bytes)
+        .map_err(|e| Error::InvalidStructure(format!("Decode: {}", e)))
+}
+
+fn struct_fields<'a>(value: &'a Value, name: &str) -> Result<&'a [(Value, Value)]> {
+    match value {
+        Value::Map(pairs) => Ok(pairs),
+        _ => Err(Error::InvalidBlobRef(format!("{} must be MAP", name))),
+    }
+}
+
+fn field(fields: &[(Value, Value)], n: u64) -> Option<&Value> {
+    fields
+        .iter()
+        .find(|(k, _)| matches!(k, Value::UVarint(x) if *x == n))
+        .map(|(_, v)| v)
+}
+
+fn required_uint(value: Option<&Value>, name: &str, max: u64) -> Result<u64> {
+    match value {
+        Some(Value::UVarint(n)) if *n <= max => Ok(*n),
+        Some(Value::UVarint(n)) => Err(Error::InvalidBlobRef(format!(
+            "{} out of range: {}",
+            name, n
+        ))),
+        _ => Err(Error::InvalidBlobRef(format!("Missing {}", name))),
+    }
+}
+
+fn required_text(value: Option<&Value>, name: &str) -> Result<String> {
+    match value {
+        Some(Value::Text(s)) => Ok(s.clone()),
+        _ => Err(Error::InvalidBlobRef(format!("Missing {}", name))),
+    }
+}
+
+fn required_bytes(value: Option<&Value>, name: &str) -> Result<Vec<u8>> {
+    match value {
+        Some(Value::Bytes(b)) => Ok(b.clone()),
+        _ => Err(Error::InvalidBlobRef(format!("Missing {}", name))),
+    }
+}
+
+fn required_hash(value: Option<&Value>, name: &str) -> Result<Vec<u8>> {
+    match value {
+        Some(Value::Map(pairs)) => parse_hash_bytes(pairs),
+        _ => Err(Error::InvalidBlobRef(format!("Missing {}", name))),
+    }
+}
@@ -0,0 +1,443 @@
+/// ChunkedBlob construction (RFC-0004 §6.5)
+///
+/// Streams bytes from any reader, splits them into fixed-size chunks and
+/// builds ChunkLeaf nodes of up to FANOUT chunks, then ChunkInternal layers
+/// until a single root remains.
+///
+/// As with MerkleList construction, a layer whose last group would hold a
+/// single child carries that child up unchanged, since ChunkInternal nodes
+/// must contain 2 to FANOUT children.
+use crate::blob_ref::{BlobRef, CODEC_RAW, DEFAULT_MEDIA, PARAM_DECODED_SIZE};
+use crate::cid_from_bytes;
+use crate::codec::{build_provenance, encode_reader, CountingReader};
+use crate::types::*;
+use crate::validation::{compute_chunk_hashes, Error, Result};
+use mythos_can::Value;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+/// Result of building a ChunkedBlob DAG
+#[derive(Debug, Clone)]
+pub struct ChunkedBlobBuild {
+    /// Root CID of the DAG
+    pub root: [u8; 32],
+    /// Every node as (cid, canonical bytes), leaves first, root last
+    pub nodes: Vec<([u8; 32], Vec<u8>)>,
+    pub total_size: u64,
+    pub chunk_count: u64,
+    pub blob_ref: BlobRef,
+}
+
+/// Builder for ChunkedBlob DAGs
+#[derive(Debug, Clone)]
+pub struct ChunkedBlobBuilder {
+    pub(crate) chunk_size: u64,
+    media: String,
+    pub(crate) codec: u8,
+    #[cfg(feature = "encryption")]
+    pub(crate) record_plain_hash: bool,
+}
+
+impl Default for ChunkedBlobBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ChunkedBlobBuilder {
+    /// Builder with the default 4 MiB chunk size
+    pub fn new() -> Self {
+        ChunkedBlobBuilder {
+            chunk_size: DEFAULT_CHUNK_SIZE,
+            media: DEFAULT_MEDIA.to_string(),
+            codec: CODEC_RAW,
+            #[cfg(feature = "encryption")]
+            record_plain_hash: false,
+        }
+    }
+
+    /// Set the chunk size (checked against [256 KiB, 16 MiB] at build time)
+    pub fn chunk_size(mut self, chunk_size: u64) -> Self {
+        self.chunk_size = chunk_size;
+        self
+    }
+
+    /// Set the media type recorded in the BlobRef
+    pub fn media(mut self, media: impl Into<String>) -> Self {
+        self.media = media.into();
+        self
+    }
+
+    /// Set the codec applied before chunking (raw or zstd)
+    ///
+    /// The DAG and CID cover the post-codec bytes (RFC-0001 §13.2), so
+    /// BlobRef.size is the encoded size; the decoded size is recorded in
+    /// the BlobRef's provenance under `PARAM_DECODED_SIZE`.
+    pub fn codec(mut self, codec: u8) -> Self {
+        self.codec = codec;
+        self
+    }
+
+    /// Record the plaintext hash in EncryptRef.plain_hash for encrypted builds
+    #[cfg(feature = "encryption")]
+    pub fn plain_hash(mut self, record: bool) -> Self {
+        self.record_plain_hash = record;
+        self
+    }
+
+    /// Build the DAG, discarding chunk bytes once hashed
+    pub fn build<R: Read>(&self, reader: R) -> Result<ChunkedBlobBuild> {
+        self.build_with(reader, |_, _, _| Ok(()))
+    }
+
+    /// Build the DAG, handing each chunk to `on_chunk(index, hash, bytes)`
+    ///
+    /// Only one chunk is buffered at a time, so this is the path for
+    /// storing chunks while ingesting arbitrarily large blobs. Chunks are
+    /// post-codec bytes.
+    pub fn build_with<R, F>(&self, reader: R, on_chunk: F) -> Result<ChunkedBlobBuild>
+    where
+        R: Read,
+        F: FnMut(u64, &[u8; 32], &[u8]) -> Result<()>,
+    {
+        let mut counter = CountingReader::new(reader);
+        let encoded = encode_reader(self.codec, &mut counter)?;
+        let mut build = self.build_stream(encoded, on_chunk)?;
+
+        build.blob_ref.codec = self.codec;
+        if self.codec != CODEC_RAW {
+            let params =
+                BTreeMap::from([(PARAM_DECODED_SIZE.to_string(), counter.count().to_string())]);
+            build.blob_ref.provenance = Some(build_provenance(params));
+        }
+        Ok(build)
+    }
+
+    /// Chunk `reader` as-is and build the DAG over it
+    pub(crate) fn build_stream<R, F>(
+        &self,
+        mut reader: R,
+        mut on_chunk: F,
+    ) -> Result<ChunkedBlobBuild>
+    where
+        R: Read,
+        F: FnMut(u64, &[u8; 32], &[u8]) -> Result<()>,
+    {
+        if !(MIN_CHUNK_SIZE..=MAX_CHUNK_SIZE).contains(&self.chunk_size) {
+            return Err(Error::InvalidChunkSize(self.chunk_size));
+        }
+
+        let mut buf = vec![0u8; self.chunk_size as usize];
+        let mut tree = TreeBuilder::new(self.chunk_size);
+
+        loop {
+            let n = read_full(&mut reader, &mut buf)?;
+            if n == 0 {
+                break;
+            }
+
+            let chunk = &buf[..n];
+            let hash = cid_from_bytes(chunk);
+            on_chunk(tree.chunk_count, &hash, chunk)?;
+            tree.push_chunk(ChunkDesc {
+                hash: hash.to_vec(),
+                len: n as u64,
+            })?;
+
+            if n < buf.len() {
+                break;
+            }
+        }
+
+        self.finish(tree)
+    }
+
+    /// Build the DAG over an in-memory slice
+    ///
+    /// For raw blobs all chunk hashes are computed up front through
+    /// `compute_chunk_hashes`, which runs on a thread pool with the
+    /// `parallel` feature. The DAG is identical to the streaming build.
+    pub fn build_slice(&self, data: &[u8]) -> Result<ChunkedBlobBuild> {
+        self.build_slice_with(data, |_, _, _| Ok(()))
+    }
+
+    /// As `build_slice`, handing each chunk to `on_chunk` in chunk order
+    pub fn build_slice_with<F>(&self, data: &[u8], mut on_chunk: F) -> Result<ChunkedBlobBuild>
+    where
+        F: FnMut(u64, &[u8; 32], &[u8]) -> Result<()>,
+    {
+        if self.codec != CODEC_RAW {
+            return self.build_with(data, on_chunk);
+        }
+        if !(MIN_CHUNK_SIZE..=MAX_CHUNK_SIZE).contains(&self.chunk_size) {
+            return Err(Error::InvalidChunkSize(self.chunk_size));
+        }
+
+        let chunk_size = self.chunk_size as usize;
+        let hashes = compute_chunk_hashes(data, chunk_size);
+        let mut tree = TreeBuilder::new(self.chunk_size);
+
+        for (chunk, hash) in data.chunks(chunk_size).zip(&hashes) {
+            on_chunk(tree.chunk_count, hash, chunk)?;
+            tree.push_chunk(ChunkDesc {
+                hash: hash.to_vec(),
+                len: chunk.len() as u64,
+            })?;
+        }
+
+        self.finish(tree)
+    }
+
+    /// Build the DAG over a file, streaming it through `build_with`
+    pub fn build_file<P: AsRef<Path>>(&self, path: P) -> Result<ChunkedBlobBuild> {
+        self.build_file_with(path, |_, _, _| Ok(()))
+    }
+
+    /// As `build_file`, handing each chunk to `on_chunk` in chunk order
+    pub fn build_file_with<P, F>(&self, path: P, on_chunk: F) -> Result<ChunkedBlobBuild>
+    where
+        P: AsRef<Path>,
+        F: FnMut(u64, &[u8; 32], &[u8]) -> Result<()>,
+    {
+        let file = File::open(path)?;
+        if file.metadata()?.len() == 0 {
+            return Err(Error::EmptyBlob);
+        }
+        self.build_with(file, on_chunk)
+    }
+
+    /// Build the DAG over a memory-mapped file through `build_slice`
+    ///
+    /// # Safety
+    ///
+    /// The file must not be truncated or written while the build runs. A
+    /// truncating writer is undefined behaviour, as with any mmap; one that
+    /// changes bytes yields a DAG that does not match the file afterwards.
+    #[cfg(feature = "mmap")]
+    pub unsafe fn build_file_mmap<P: AsRef<Path>>(&self, path: P) -> Result<ChunkedBlobBuild> {
+        self.build_file_mmap_with(path, |_, _, _| Ok(()))
+    }
+
+    /// As `build_file_mmap`, handing each chunk to `on_chunk` in chunk order
+    ///
+    /// # Safety
+    ///
+    /// As for `build_file_mmap`.
+    #[cfg(feature = "mmap")]
+    pub unsafe fn build_file_mmap_with<P, F>(
+        &self,
+        path: P,
+        on_chunk: F,
+    ) -> Result<ChunkedBlobBuild>
+    where
+        P: AsRef<Path>,
+        F: FnMut(u64, &[u8; 32], &[u8]) -> Result<()>,
+    {
+        let file = File::open(path)?;
+        if file.metadata()?.len() == 0 {
+            return Err(Error::EmptyBlob);
+        }
+
+        // SAFETY: the caller keeps the file unchanged while it is mapped;
+        // the map is read-only and dropped before returning
+        let map = unsafe { memmap2::Mmap::map(&file)? };
+        self.build_slice_with(&map, on_chunk)
+    }
+
+    fn finish(&self, tree: TreeBuilder) -> Result<ChunkedBlobBuild> {
+        let tree = tree.finish()?;
+
+        Ok(ChunkedBlobBuild {
+            root: tree.root,
+            nodes: tree.nodes,
+            total_size: tree.total_size,
+            chunk_count: tree.chunk_count,
+            blob_ref: BlobRef::chunked(
+                &tree.root,
+                tree.total_size,
+                tree.chunk_count,
+                self.media.clone(),
+            ),
+        })
+    }
+}
+
+/// Finished DAG produced by `TreeBuilder`
+pub(crate) struct ChunkTree {
+    pub(crate) root: [u8; 32],
+    pub(crate) nodes: Vec<([u8; 32], Vec<u8>)>,
+    pub(crate) total_size: u64,
+    pub(crate) chunk_count: u64,
+}
+
+/// Accumulates ChunkDescs into leaves, then builds the internal layers
+pub(crate) struct TreeBuilder {
+    chunk_size: u64,
+    pending: Vec<ChunkDesc>,
+    /// Finished leaves as (cid, total_size, chunk_count)
+    leaves: Vec<([u8; 32], u64, u64)>,
+    nodes: Vec<([u8; 32], Vec<u8>)>,
+    chunk_count: u64,
+}
+
+impl TreeBuilder {
+    pub(crate) fn new(chunk_size: u64) -> Self {
+        TreeBuilder {
+            chunk_size,
+            pending: Vec::new(),
+            leaves: Vec::new(),
+            nodes: Vec::new(),
+            chunk_count: 0,
+        }
+    }
+
+    pub(crate) fn push_chunk(&mut self, desc: ChunkDesc) -> Result<()> {
+        self.pending.push(desc);
+        self.chunk_count += 1;
+        if self.pending.len() == FANOUT {
+            self.flush_leaf()?;
+        }
+        Ok(())
+    }
+
+    fn flush_leaf(&mut self) -> Result<()> {
+        let bytes = encode_chunk_leaf(self.chunk_size, &self.pending)?;
+        let cid = cid_from_bytes(&bytes);
+        let total_size = self.pending.iter().map(|c| c.len).sum();
+        self.leaves
+            .push((cid, total_size, self.pending.len() as u64));
+        self.nodes.push((cid, bytes));
+        self.pending.clear();
+        Ok(())
+    }
+
+    pub(crate) fn finish(mut self) -> Result<ChunkTree> {
+        if !self.pending.is_empty() {
+            self.flush_leaf()?;
+        }
+        if self.leaves.is_empty() {
+            return Err(Error::EmptyBlob);
+        }
+
+        let mut layer = std::mem::take(&mut self.leaves);
+        while layer.len() > 1 {
+            let mut next = Vec::with_capacity(layer.len().div_ceil(FANOUT));
+            for group in layer.chunks(FANOUT) {
+                if group.len() == 1 {
+                    // A trailing single child is carried up unchanged
+                    next.push(group[0]);
+                    continue;
+                }
+
+                let children: Vec<[u8; 32]> = group.iter().map(|(cid, _, _)| *cid).collect();
+                let total_size = group.iter().map(|(_, size, _)| size).sum();
+                let chunk_count = group.iter().map(|(_, _, count)| count).sum();
+                let bytes =
+                    encode_chunk_internal(self.chunk_size, &children, total_size, chunk_count)?;
+                let cid = cid_from_bytes(&bytes);
+                next.push((cid, total_size, chunk_count));
+                self.nodes.push((cid, bytes));
+            }
+            layer = next;
+        }
+
+        let (root, total_size, chunk_count) = layer[0];
+        Ok(ChunkTree {
+            root,
+            nodes: self.nodes,
+            total_size,
+            chunk_count,
+        })
+    }
+}
+
+/// Encode the outer node wrapper around a canonical payload
+pub fn encode_chunked_blob_node(kind: u64, payload: Vec<u8>) -> Result<Vec<u8>> {
+    let node = Value::Map(vec![
+        (Value::UVarint(1), Value::UVarint(VERSION)),
+        (Value::UVarint(2), Value::UVarint(kind)),
+        (Value::UVarint(3), Value::Bytes(payload)),
+    ]);
+
+    mythos_can::encode_value(&node)
+        .map_err(|e| Error::InvalidStructure(format!("Node encode: {}", e)))
+}
+
+/// Encode a ChunkLeaf node (kind 3); total_size is the sum of chunk lengths
+pub fn encode_chunk_leaf(chunk_size: u64, chunks: &[ChunkDesc]) -> Result<Vec<u8>> {
+    if chunks.is_empty() || chunks.len() > FANOUT {
+        return Err(Error::InvalidChunkCount(chunks.len()));
+    }
+
+    let descs = chunks
+        .iter()
+        .map(|c| {
+            Value::Map(vec![
+                (Value::UVarint(1), hash_to_can(&c.hash)),
+                (Value::UVarint(2), Value::UVarint(c.len)),
+            ])
+        })
+        .collect();
+    let total_size: u64 = chunks.iter().map(|c| c.len).sum();
+
+    let payload = Value::Map(vec![
+        (Value::UVarint(1), Value::UVarint(chunk_size)),
+        (Value::UVarint(2), Value::List(descs)),
+        (Value::UVarint(3), Value::UVarint(total_size)),
+    ]);
+
+    let payload_bytes = mythos_can::encode_value(&payload)
+        .map_err(|e| Error::InvalidStructure(format!("Payload encode: {}", e)))?;
+
+    encode_chunked_blob_node(KIND_CHUNK_LEAF, payload_bytes)
+}
+
+/// Encode a ChunkInternal node (kind 4)
+pub fn encode_chunk_internal(
+    chunk_size: u64,
+    children: &[[u8; 32]],
+    total_size: u64,
+    chunk_count: u64,
+) -> Result<Vec<u8>> {
+    if children.len() < 2 || children.len() > FANOUT {
+        return Err(Error::InvalidChildCount(children.len()));
+    }
+
+    let payload = Value::Map(vec![
+        (Value::UVarint(1), Value::UVarint(chunk_size)),
+        (
+            Value::UVarint(2),
+            Value::List(children.iter().map(|c| hash_to_can(c)).collect()),
+        ),
+        (Value::UVarint(3), Value::UVarint(total_size)),
+        (Value::UVarint(4), Value::UVarint(chunk_count)),
+    ]);
+
+    let payload_bytes = mythos_can::encode_value(&payload)
+        .map_err(|e| Error::InvalidStructure(format!("Payload encode: {}", e)))?;
+
+    encode_chunked_blob_node(KIND_CHUNK_INTERNAL, payload_bytes)
+}
+
+pub(crate) fn hash_to_can(bytes: &[u8]) -> Value {
+    Value::Map(vec![
+        (Value::UVarint(1), Value::UVarint(1)), // alg=1 (SHA-256)
+        (Value::UVarint(2), Value::Bytes(bytes.to_vec())),
+    ])
+}
+
+/// Read until `buf` is full or the reader is exhausted
+pub(crate) fn read_full<R: Read>(reader: &mut R, buf: &mut [u8]) -> std::io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(filled)
+}
@@ -0,0 +1,196 @@
+/// Blob codecs (RFC-0001 §13.2, Appendix A.8)
+///
+/// A codec is applied to the whole byte stream before chunking, so the DAG
+/// and CID cover post-codec bytes. Only raw (0) and zstd (1, with the
+/// `zstd` feature) are applied here; other codec ids describe formats the
+/// payload already has.
+///
+/// BlobRef.size is the size of the stored DAG, so a coded build records
+/// the decoded size in its provenance (`PARAM_DECODED_SIZE`).
+/// `DecodingReader` undoes the codec on top of an already verified stream
+/// and never yields more than that decoded size, so a small verified blob
+/// cannot expand into an unbounded decompression bomb.
+#[cfg(feature = "zstd")]
+use crate::blob_ref::CODEC_ZSTD;
+use crate::blob_ref::{BlobRef, ProvenanceRef, CODEC_RAW};
+use crate::validation::{Error, Result};
+use crate::verify::into_io;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+#[cfg(feature = "zstd")]
+use std::io::BufReader;
+use std::io::{self, Read};
+
+/// zstd level used when building blobs
+///
+/// The CID depends on the encoder output, so changing this changes the CID
+/// of every newly built zstd blob.
+pub const ZSTD_LEVEL: i32 = 3;
+
+/// ProvenanceRef.transform recorded by builds that apply a codec or cipher
+pub const BUILD_TRANSFORM: &str = "mythos.blob.build";
+
+/// ProvenanceRef.code_hash recorded with `BUILD_TRANSFORM`
+///
+/// SHA-256 of the codec and encryption source this crate was built from,
+/// so a change to either transform changes the recorded hash.
+pub fn build_code_hash() -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(include_bytes!("codec.rs"));
+    hasher.update(include_bytes!("encrypt.rs"));
+    hasher.finalize().into()
+}
+
+/// Provenance for a build that transformed its input before chunking
+pub(crate) fn build_provenance(params: BTreeMap<String, String>) -> ProvenanceRef {
+    ProvenanceRef {
+        parents: Vec::new(),
+        transform: BUILD_TRANSFORM.to_string(),
+        params,
+        code_hash: build_code_hash().to_vec(),
+        time_observed: None,
+    }
+}
+
+/// Reader that counts bytes passing through it
+pub(crate) struct CountingReader<R> {
+    inner: R,
+    count: u64,
+}
+
+impl<R> CountingReader<R> {
+    pub(crate) fn new(inner: R) -> Self {
+        CountingReader { inner, count: 0 }
+    }
+
+    pub(crate) fn count(&self) -> u64 {
+        self.count
+    }
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.count += n as u64;
+        Ok(n)
+    }
+}
+
+/// Wrap `reader` so that it yields encoded bytes for `codec`
+pub(crate) fn encode_reader<'a, R: Read + 'a>(codec: u8, reader: R) -> Result<Box<dyn Read + 'a>> {
+    match codec {
+        CODEC_RAW => Ok(Box::new(reader)),
+        #[cfg(feature = "zstd")]
+        CODEC_ZSTD => Ok(Box::new(zstd::stream::read::Encoder::new(
+            reader, ZSTD_LEVEL,
+        )?)),
+        other => Err(Error::UnsupportedCodec(other)),
+    }
+}
+
+enum Decoder<R: Read> {
+    Raw(R),
+    #[cfg(feature = "zstd")]
+    Zstd(Box<zstd::stream::read::Decoder<'static, BufReader<R>>>),
+}
+
+/// Reader that undoes a blob's codec, bounded by its decoded size
+pub struct DecodingReader<R: Read> {
+    decoder: Decoder<R>,
+    /// Exact decoded size, when the BlobRef declares one
+    limit: Option<u64>,
+    seen: u64,
+    failed: bool,
+}
+
+impl<R: Read> DecodingReader<R> {
+    /// Decode `inner`, a verified stream of the bytes `blob_ref` addresses
+    ///
+    /// For encrypted blobs `inner` is the decrypted stream. A coded blob
+    /// must declare its decoded size (`BlobRef::decoded_size`); a raw one
+    /// without it is only bounded by the stream underneath.
+    pub fn new(blob_ref: &BlobRef, inner: R) -> Result<Self> {
+        let limit = blob_ref.decoded_size()?;
+        let decoder = match blob_ref.codec {
+            CODEC_RAW => Decoder::Raw(inner),
+            #[cfg(feature = "zstd")]
+            CODEC_ZSTD if limit.is_none() => {
+                return Err(Error::InvalidBlobRef(
+                    "zstd blob does not declare its decoded size".into(),
+                ))
+            }
+            #[cfg(feature = "zstd")]
+            CODEC_ZSTD => Decoder::Zstd(Box::new(zstd::stream::read::Decoder::new(inner)?)),
+            other => return Err(Error::UnsupportedCodec(other)),
+        };
+
+        Ok(DecodingReader {
+            decoder,
+            limit,
+            seen: 0,
+            failed: false,
+        })
+    }
+
+    /// Decoded bytes yielded so far
+    pub fn decoded(&self) -> u64 {
+        self.seen
+    }
+
+    fn read_decoded(&mut self, out: &mut [u8]) -> Result<usize> {
+        if out.is_empty() {
+            return Ok(0);
+        }
+        let Some(limit) = self.limit else {
+            let n = self.read_inner(out)?;
+            self.seen += n as u64;
+            return Ok(n);
+        };
+
+        let room = limit - self.seen;
+        if room == 0 {
+            // Declared size reached: the stream must end here
+            let mut probe = [0u8; 1];
+            if self.read_inner(&mut probe)? != 0 {
+                return Err(Error::DecodedSizeExceeded(limit));
+            }
+            return Ok(0);
+        }
+
+        let want = out.len().min(usize::try_from(room).unwrap_or(usize::MAX));
+        let n = self.read_inner(&mut out[..want])?;
+        if n == 0 {
+            return Err(Error::SizeMismatch {
+                expected: limit,
+                actual: self.seen,
+            });
+        }
+
+        self.seen += n as u64;
+        Ok(n)
+    }
+
+    fn read_inner(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match &mut self.decoder {
+            Decoder::Raw(r) => r.read(buf),
+            #[cfg(feature = "zstd")]
+            Decoder::Zstd(d) => d.read(buf),
+        }
+    }
+}
+
+impl<R: Read> Read for DecodingReader<R> {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        if self.failed {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "blob decoding already failed",
+            ));
+        }
+
+        self.read_decoded(out).map_err(|e| {
+            self.failed = true;
+            into_io(e)
+        })
+    }
+}
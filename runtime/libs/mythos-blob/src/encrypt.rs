@@ -0,0 +1,382 @@
+/// Blob encryption (RFC-0001 §13.6)
+///
+/// Scheme 2 (aes-gcm): AES-256-GCM applied per chunk before chunking, so
+/// the blob CID addresses ciphertext as v0.2 recommends. Plaintext is cut
+/// into pieces of `chunk_size - 16` bytes; each sealed piece is exactly one
+/// ciphertext chunk, keeping the DAG's chunk length rule intact.
+///
+/// Chunk `i` is sealed with the EncryptRef nonce XORed with `i` in its last
+/// eight bytes (big-endian), and with the EncryptRef aad, if any. The
+/// builder draws that nonce at random for every blob: reusing a nonce under
+/// one key leaks the XOR of two plaintexts and lets tags be forged.
+///
+/// Decryption always sits on top of `VerifyingReader`: a ciphertext chunk
+/// is hashed against the DAG before it is handed to the cipher.
+use crate::blob_ref::{
+    BlobRef, EncryptRef, CODEC_RAW, PARAM_DECODED_SIZE, PARAM_PLAIN_HASH, PARAM_PLAIN_SIZE,
+    SCHEME_AES_GCM,
+};
+use crate::build::{read_full, ChunkedBlobBuild, ChunkedBlobBuilder};
+use crate::codec::{build_provenance, encode_reader, CountingReader, DecodingReader};
+use crate::source::NodeSource;
+use crate::types::{MAX_CHUNK_SIZE, MIN_CHUNK_SIZE};
+use crate::validation::{Error, Result};
+use crate::verify::{into_io, VerifyingReader};
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use aes_gcm::{Aes256Gcm, Nonce};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::io::{self, Read};
+
+/// AES-GCM nonce length carried in EncryptRef.nonce
+pub const AES_GCM_NONCE_LEN: usize = 12;
+
+/// Authentication tag added to every ciphertext chunk
+pub const AES_GCM_TAG_LEN: usize = 16;
+
+/// Resolve EncryptRef.key_handle to key material
+pub trait KeyProvider {
+    /// Return the 256-bit key for `key_handle`, or `Error::KeyNotFound`
+    fn resolve_key(&self, key_handle: &str) -> Result<[u8; 32]>;
+}
+
+impl KeyProvider for HashMap<String, [u8; 32]> {
+    fn resolve_key(&self, key_handle: &str) -> Result<[u8; 32]> {
+        self.get(key_handle)
+            .copied()
+            .ok_or_else(|| Error::KeyNotFound(key_handle.to_string()))
+    }
+}
+
+impl<K: KeyProvider + ?Sized> KeyProvider for &K {
+    fn resolve_key(&self, key_handle: &str) -> Result<[u8; 32]> {
+        (**self).resolve_key(key_handle)
+    }
+}
+
+/// Nonce for chunk `index`: base nonce with `index` XORed into the last 8 bytes
+pub fn chunk_nonce(base: &[u8], index: u64) -> Result<[u8; AES_GCM_NONCE_LEN]> {
+    let mut nonce: [u8; AES_GCM_NONCE_LEN] = base
+        .try_into()
+        .map_err(|_| Error::InvalidNonceLength(base.len()))?;
+    for (b, i) in nonce[4..].iter_mut().zip(index.to_be_bytes()) {
+        *b ^= i;
+    }
+    Ok(nonce)
+}
+
+impl ChunkedBlobBuilder {
+    /// Encrypt `reader` under `encryption` and build the DAG over the ciphertext
+    ///
+    /// `encryption.nonce` is replaced with a fresh random 96-bit nonce, so
+    /// the same plaintext encrypts to a different blob every time.
+    /// The builder's codec is applied first, so encryption sees encoded
+    /// bytes. The returned BlobRef carries `encryption`, with `size` set to
+    /// the ciphertext total_size and the plaintext size recorded in its
+    /// provenance. The plaintext hash covers the bytes handed to the cipher
+    /// and is only recorded, in EncryptRef.plain_hash and provenance, when
+    /// the builder was configured with `plain_hash(true)`, since it reveals
+    /// plaintext equality across blobs.
+    pub fn build_encrypted<R, K>(
+        &self,
+        reader: R,
+        encryption: EncryptRef,
+        keys: &K,
+    ) -> Result<ChunkedBlobBuild>
+    where
+        R: Read,
+        K: KeyProvider + ?Sized,
+    {
+        self.build_encrypted_with(reader, encryption, keys, |_, _, _| Ok(()))
+    }
+
+    /// As `build_encrypted`, handing each ciphertext chunk to `on_chunk`
+    pub fn build_encrypted_with<R, K, F>(
+        &self,
+        reader: R,
+        mut encryption: EncryptRef,
+        keys: &K,
+        on_chunk: F,
+    ) -> Result<ChunkedBlobBuild>
+    where
+        R: Read,
+        K: KeyProvider + ?Sized,
+        F: FnMut(u64, &[u8; 32], &[u8]) -> Result<()>,
+    {
+        let cipher = new_cipher(&encryption, keys)?;
+        encryption.nonce = Aes256Gcm::generate_nonce(&mut OsRng).to_vec();
+        let mut counter = CountingReader::new(reader);
+        let (mut build, hasher, plain_size) = {
+            let mut sealing = SealingReader {
+                inner: encode_reader(self.codec, &mut counter)?,
+                cipher,
+                nonce: encryption.nonce.clone(),
+                aad: encryption.aad.clone().unwrap_or_default(),
+                plain_chunk: self.plain_chunk_size()?,
+                index: 0,
+                buf: Vec::new(),
+                pos: 0,
+                hasher: Sha256::new(),
+                plain_size: 0,
+            };
+            let build = self.build_stream(&mut sealing, on_chunk)?;
+            (build, sealing.hasher, sealing.plain_size)
+        };
+
+        let mut params = BTreeMap::from([(PARAM_PLAIN_SIZE.to_string(), plain_size.to_string())]);
+        if self.codec != CODEC_RAW {
+            params.insert(PARAM_DECODED_SIZE.to_string(), counter.count().to_string());
+        }
+        if self.record_plain_hash {
+            let plain_hash = hasher.finalize().to_vec();
+            params.insert(PARAM_PLAIN_HASH.to_string(), hex::encode(&plain_hash));
+            encryption.plain_hash = Some(plain_hash);
+        }
+
+        build.blob_ref.codec = self.codec;
+        build.blob_ref.encryption = Some(encryption);
+        build.blob_ref.provenance = Some(build_provenance(params));
+        Ok(build)
+    }
+
+    fn plain_chunk_size(&self) -> Result<usize> {
+        if !(MIN_CHUNK_SIZE..=MAX_CHUNK_SIZE).contains(&self.chunk_size) {
+            return Err(Error::InvalidChunkSize(self.chunk_size));
+        }
+        Ok((self.chunk_size - AES_GCM_TAG_LEN as u64) as usize)
+    }
+}
+
+/// Reader that yields sealed ciphertext chunks for a plaintext stream
+struct SealingReader<R> {
+    inner: R,
+    cipher: Aes256Gcm,
+    nonce: Vec<u8>,
+    aad: Vec<u8>,
+    plain_chunk: usize,
+    index: u64,
+    buf: Vec<u8>,
+    pos: usize,
+    hasher: Sha256,
+    plain_size: u64,
+}
+
+impl<R: Read> SealingReader<R> {
+    fn fill(&mut self) -> Result<()> {
+        let mut plain = vec![0u8; self.plain_chunk];
+        let n = read_full(&mut self.inner, &mut plain)?;
+        self.buf.clear();
+        self.pos = 0;
+        if n == 0 {
+            return Ok(());
+        }
+
+        plain.truncate(n);
+        self.hasher.update(&plain);
+        self.plain_size += n as u64;
+
+        let nonce = chunk_nonce(&self.nonce, self.index)?;
+        self.buf = self
+            .cipher
+            .encrypt(
+                Nonce::from_slice(&nonce),
+                Payload {
+                    msg: &plain,
+                    aad: &self.aad,
+                },
+            )
+            .map_err(|_| Error::Encrypt(self.index))?;
+        self.index += 1;
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for SealingReader<R> {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        if self.pos == self.buf.len() {
+            self.fill().map_err(into_io)?;
+        }
+
+        let n = out.len().min(self.buf.len() - self.pos);
+        out[..n].copy_from_slice(&self.buf[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+/// Reader that verifies ciphertext against the DAG, then decrypts it
+///
+/// At end of stream the plaintext hash is checked against
+/// `EncryptRef.plain_hash` and the plaintext size against the recorded
+/// `plain_size`, if present. Encoded blobs yield encoded bytes; wrap them with
+/// `DecodingReader` (or use `DecryptingReader::open`) to decode.
+pub struct DecryptingReader<S, R> {
+    inner: VerifyingReader<S, R>,
+    cipher: Aes256Gcm,
+    nonce: Vec<u8>,
+    aad: Vec<u8>,
+    /// Plaintext size to enforce, if recorded
+    plain_size: Option<u64>,
+    plain_hash: Option<Vec<u8>>,
+    index: u64,
+    seen: u64,
+    hasher: Sha256,
+    cipher_buf: Vec<u8>,
+    buf: Vec<u8>,
+    pos: usize,
+    done: bool,
+    failed: bool,
+}
+
+impl<S: NodeSource, R: Read> DecryptingReader<S, R> {
+    /// Open the encrypted blob described by `blob_ref` over `data`
+    pub fn new<K: KeyProvider + ?Sized>(
+        blob_ref: &BlobRef,
+        source: S,
+        data: R,
+        keys: &K,
+    ) -> Result<Self> {
+        let encryption = blob_ref
+            .encryption
+            .as_ref()
+            .ok_or_else(|| Error::InvalidBlobRef("BlobRef has no encryption".into()))?;
+        let cipher = new_cipher(encryption, keys)?;
+
+        let root: [u8; 32] = blob_ref
+            .cid
+            .as_slice()
+            .try_into()
+            .map_err(|_| Error::InvalidHashLength(blob_ref.cid.len()))?;
+        let inner = VerifyingReader::new(&root, source, data)?;
+
+        if inner.chunk_size() <= AES_GCM_TAG_LEN as u64 {
+            return Err(Error::InvalidChunkSize(inner.chunk_size()));
+        }
+        let chunk_size = inner.chunk_size() as usize;
+
+        Ok(DecryptingReader {
+            inner,
+            cipher,
+            nonce: encryption.nonce.clone(),
+            aad: encryption.aad.clone().unwrap_or_default(),
+            plain_size: blob_ref.plain_size()?,
+            plain_hash: encryption.plain_hash.clone(),
+            index: 0,
+            seen: 0,
+            hasher: Sha256::new(),
+            cipher_buf: vec![0u8; chunk_size],
+            buf: Vec::new(),
+            pos: 0,
+            done: false,
+            failed: false,
+        })
+    }
+
+    /// Open `blob_ref`, decrypting and then undoing its codec
+    pub fn open<K: KeyProvider + ?Sized>(
+        blob_ref: &BlobRef,
+        source: S,
+        data: R,
+        keys: &K,
+    ) -> Result<DecodingReader<Self>> {
+        DecodingReader::new(blob_ref, Self::new(blob_ref, source, data, keys)?)
+    }
+
+    /// Decrypt the next verified ciphertext chunk into `buf`
+    fn fill(&mut self) -> Result<()> {
+        let n = read_full(&mut self.inner, &mut self.cipher_buf)?;
+        self.pos = 0;
+        if n == 0 {
+            self.buf.clear();
+            return self.finish();
+        }
+
+        let nonce = chunk_nonce(&self.nonce, self.index)?;
+        self.buf = self
+            .cipher
+            .decrypt(
+                Nonce::from_slice(&nonce),
+                Payload {
+                    msg: &self.cipher_buf[..n],
+                    aad: &self.aad,
+                },
+            )
+            .map_err(|_| Error::Decrypt(self.index))?;
+
+        self.seen += self.buf.len() as u64;
+        if let Some(expected) = self.plain_size {
+            if self.seen > expected {
+                return Err(Error::SizeMismatch {
+                    expected,
+                    actual: self.seen,
+                });
+            }
+        }
+        self.hasher.update(&self.buf);
+        self.index += 1;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.done = true;
+
+        if let Some(expected) = self.plain_size {
+            if self.seen != expected {
+                return Err(Error::SizeMismatch {
+                    expected,
+                    actual: self.seen,
+                });
+            }
+        }
+
+        if let Some(expected) = &self.plain_hash {
+            let computed = std::mem::take(&mut self.hasher).finalize();
+            if computed.as_slice() != expected.as_slice() {
+                return Err(Error::PlainHashMismatch {
+                    expected: hex::encode(expected),
+                    computed: hex::encode(computed),
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<S: NodeSource, R: Read> Read for DecryptingReader<S, R> {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        if self.failed {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "blob decryption already failed",
+            ));
+        }
+
+        while self.pos == self.buf.len() {
+            if self.done {
+                return Ok(0);
+            }
+            if let Err(e) = self.fill() {
+                self.failed = true;
+                return Err(into_io(e));
+            }
+        }
+
+        let n = out.len().min(self.buf.len() - self.pos);
+        out[..n].copy_from_slice(&self.buf[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+fn new_cipher<K: KeyProvider + ?Sized>(encryption: &EncryptRef, keys: &K) -> Result<Aes256Gcm> {
+    if encryption.scheme != SCHEME_AES_GCM {
+        return Err(Error::UnsupportedScheme(encryption.scheme));
+    }
+    if encryption.nonce.len() != AES_GCM_NONCE_LEN {
+        return Err(Error::InvalidNonceLength(encryption.nonce.len()));
+    }
+
+    let key = keys.resolve_key(&encryption.key_handle)?;
+    Ok(Aes256Gcm::new(&key.into()))
+}
@@ -1,12 +1,48 @@
 //! MYTHOS Blob Structures (RFC-0004 ChunkedBlob)
 //!
-//! Minimal implementation for BLOB_001 conformance.
+//! ChunkedBlob DAG construction and validation (ChunkLeaf and ChunkInternal),
+//! streaming and byte-range verification against a root CID, zstd and
+//! AES-GCM layers applied before chunking, and the BlobRef/EncryptRef/
+//! ProvenanceRef object model that points at a built DAG.
+//!
+//! The zstd codec and AES-GCM encryption sit behind the default `zstd` and
+//! `encryption` features; memory-mapped file builds behind `mmap`.
 
+mod blob_ref;
+mod build;
+mod codec;
+#[cfg(feature = "encryption")]
+mod encrypt;
+mod range;
+mod source;
 mod types;
 mod validation;
+mod verify;
 
-pub use types::{ChunkDesc, ChunkLeaf, ChunkedBlobNode};
-pub use validation::{compute_chunk_hashes, parse_chunked_blob_node, validate_chunk_leaf};
+pub use blob_ref::{
+    blob_ref_chunks, check_inline, BlobRef, EncryptRef, ProvenanceRef, CODEC_GZIP, CODEC_JPEG,
+    CODEC_JSONCBOR, CODEC_PARQUET, CODEC_PNG, CODEC_RAW, CODEC_ZSTD, DEFAULT_MEDIA,
+    INLINE_BLOB_LIMIT, PARAM_DECODED_SIZE, PARAM_PLAIN_HASH, PARAM_PLAIN_SIZE, SCHEME_AES_GCM,
+    SCHEME_AGE, SCHEME_KMS_ENVELOPE,
+};
+pub use build::{
+    encode_chunk_internal, encode_chunk_leaf, encode_chunked_blob_node, ChunkedBlobBuild,
+    ChunkedBlobBuilder,
+};
+pub use codec::{build_code_hash, DecodingReader, BUILD_TRANSFORM, ZSTD_LEVEL};
+#[cfg(feature = "encryption")]
+pub use encrypt::{chunk_nonce, DecryptingReader, KeyProvider, AES_GCM_NONCE_LEN, AES_GCM_TAG_LEN};
+pub use range::{read_range, RangeProof, RangeRead};
+pub use source::{fetch_verified, ChunkSource, NodeSource};
+pub use types::{
+    ChunkDesc, ChunkInternal, ChunkLeaf, ChunkNode, ChunkedBlobNode, DEFAULT_CHUNK_SIZE, FANOUT,
+    KIND_CHUNK_INTERNAL, KIND_CHUNK_LEAF, MAX_CHUNK_SIZE, MIN_CHUNK_SIZE,
+};
+pub use validation::{
+    compute_chunk_hashes, decode_chunk_node, parse_chunked_blob_node, validate_chunk_internal,
+    validate_chunk_leaf, Error, Result,
+};
+pub use verify::VerifyingReader;
 
 use sha2::{Digest, Sha256};
 
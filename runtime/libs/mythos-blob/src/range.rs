@@ -0,0 +1,461 @@
+/// Verified byte-range reads on chunked blobs
+///
+/// Backs `BLOB_GET` with a range and `Blob.Read(cid, range)`. `read_range`
+/// walks only the nodes on the paths to the chunks covering a byte range,
+/// fetches just those chunks and checks each one against its ChunkDesc.
+///
+/// The returned `RangeProof` carries the walked node bytes plus the parts
+/// of the first and last chunks that fall outside the range. Anyone who
+/// trusts the root CID can check the returned bytes against the proof
+/// without fetching the rest of the blob.
+use crate::cid_from_bytes;
+use crate::source::{fetch_verified, ChunkSource, NodeSource};
+use crate::types::*;
+use crate::validation::{decode_chunk_node, Error, Result};
+use mythos_can::Value;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ops::Range;
+
+/// Evidence that a byte range belongs to a blob root
+///
+/// MYTHOS-CAN encoding (local to this crate, not an RFC struct):
+/// ```text
+/// RangeProof {
+///   1: start (u64)
+///   2: end (u64)
+///   3: nodes (list(bytes)) - canonical node bytes, root first
+///   4: head (bytes)        - first chunk bytes before `start`
+///   5: tail (bytes)        - last chunk bytes after `end`
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct RangeProof {
+    pub start: u64,
+    pub end: u64,
+    pub nodes: Vec<Vec<u8>>,
+    pub head: Vec<u8>,
+    pub tail: Vec<u8>,
+}
+
+/// Bytes of a verified range read together with their proof
+#[derive(Debug, Clone)]
+pub struct RangeRead {
+    pub data: Vec<u8>,
+    pub proof: RangeProof,
+}
+
+/// Read `range` from the blob under `root`, verifying every touched node and chunk
+pub fn read_range<N, C>(
+    root: &[u8; 32],
+    range: Range<u64>,
+    nodes: &N,
+    chunks: &C,
+) -> Result<RangeRead>
+where
+    N: NodeSource + ?Sized,
+    C: ChunkSource + ?Sized,
+{
+    let recorder = Recorder {
+        inner: nodes,
+        seen: RefCell::new(Vec::new()),
+    };
+    let located = locate(root, &range, &recorder)?;
+
+    let mut covering = Vec::new();
+    for (i, desc) in located.descs.iter().enumerate() {
+        let index = located.first_index + i as u64;
+        let offset = index * located.chunk_size;
+
+        let hash = chunk_hash(desc)?;
+        let bytes = chunks.fetch_chunk(&hash)?;
+        check_chunk(&bytes, desc, index, offset)?;
+        covering.extend_from_slice(&bytes);
+    }
+
+    let head_len = (range.start - located.first_index * located.chunk_size) as usize;
+    let data_end = head_len + (range.end - range.start) as usize;
+    let tail = covering.split_off(data_end.min(covering.len()));
+    let data = covering.split_off(head_len.min(covering.len()));
+
+    Ok(RangeRead {
+        data,
+        proof: RangeProof {
+            start: range.start,
+            end: range.end,
+            nodes: recorder.seen.into_inner(),
+            head: covering,
+            tail,
+        },
+    })
+}
+
+impl RangeProof {
+    /// Check that `data` is exactly bytes `start..end` of the blob under `root`
+    pub fn verify(&self, root: &[u8; 32], data: &[u8]) -> Result<()> {
+        if self.start > self.end || data.len() as u64 != self.end - self.start {
+            return Err(Error::InvalidProof(format!(
+                "Data is {} bytes, range is {}..{}",
+                data.len(),
+                self.start,
+                self.end
+            )));
+        }
+
+        let nodes: HashMap<[u8; 32], Vec<u8>> = self
+            .nodes
+            .iter()
+            .map(|bytes| (cid_from_bytes(bytes), bytes.clone()))
+            .collect();
+        let located = locate(root, &(self.start..self.end), &nodes)?;
+
+        if located.descs.is_empty() {
+            if self.head.is_empty() && self.tail.is_empty() {
+                return Ok(());
+            }
+            return Err(Error::InvalidProof(
+                "Empty range carries chunk bytes".into(),
+            ));
+        }
+
+        let head_len = self.start - located.first_index * located.chunk_size;
+        if self.head.len() as u64 != head_len {
+            return Err(Error::InvalidProof(format!(
+                "Head is {} bytes, range start implies {}",
+                self.head.len(),
+                head_len
+            )));
+        }
+
+        let covered: u64 = located.descs.iter().map(|d| d.len).sum();
+        let supplied = (self.head.len() + data.len() + self.tail.len()) as u64;
+        if covered != supplied {
+            return Err(Error::InvalidProof(format!(
+                "Chunks cover {} bytes, proof supplies {}",
+                covered, supplied
+            )));
+        }
+
+        let covering = [self.head.as_slice(), data, self.tail.as_slice()].concat();
+        let mut pos = 0usize;
+        for (i, desc) in located.descs.iter().enumerate() {
+            let index = located.first_index + i as u64;
+            let offset = index * located.chunk_size;
+            let bytes = &covering[pos..pos + desc.len as usize];
+            check_chunk(bytes, desc, index, offset)?;
+            pos += desc.len as usize;
+        }
+
+        Ok(())
+    }
+
+    /// Encode as canonical MYTHOS-CAN bytes
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        let value = Value::Map(vec![
+            (Value::UVarint(1), Value::UVarint(self.start)),
+            (Value::UVarint(2), Value::UVarint(self.end)),
+            (
+                Value::UVarint(3),
+                Value::List(self.nodes.iter().cloned().map(Value::Bytes).collect()),
+            ),
+            (Value::UVarint(4), Value::Bytes(self.head.clone())),
+            (Value::UVarint(5), Value::Bytes(self.tail.clone())),
+        ]);
+
+        mythos_can::encode_value(&value)
+            .map_err(|e| Error::InvalidStructure(format!("Proof encode: {}", e)))
+    }
+
+    /// Decode from canonical MYTHOS-CAN bytes
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let decoded = mythos_can::decode_value_exact(bytes)
+            .map_err(|e| Error::InvalidProof(format!("Decode: {}", e)))?;
+
+        let fields = match decoded {
+            Value::Map(pairs) => pairs,
+            _ => return Err(Error::InvalidProof("RangeProof must be MAP".into())),
+        };
+
+        let get_field = |n: u64| {
+            fields
+                .iter()
+                .find(|(k, _)| matches!(k, Value::UVarint(x) if *x == n))
+                .map(|(_, v)| v)
+        };
+
+        let start = match get_field(1) {
+            Some(Value::UVarint(s)) => *s,
+            _ => return Err(Error::InvalidProof("Missing start".into())),
+        };
+
+        let end = match get_field(2) {
+            Some(Value::UVarint(e)) => *e,
+            _ => return Err(Error::InvalidProof("Missing end".into())),
+        };
+
+        let nodes = match get_field(3) {
+            Some(Value::List(items)) => items
+                .iter()
+                .map(|item| match item {
+                    Value::Bytes(b) => Ok(b.clone()),
+                    _ => Err(Error::InvalidProof("Node must be BYTES".into())),
+                })
+                .collect::<Result<Vec<_>>>()?,
+            _ => return Err(Error::InvalidProof("Missing nodes".into())),
+        };
+
+        let head = match get_field(4) {
+            Some(Value::Bytes(b)) => b.clone(),
+            _ => return Err(Error::InvalidProof("Missing head".into())),
+        };
+
+        let tail = match get_field(5) {
+            Some(Value::Bytes(b)) => b.clone(),
+            _ => return Err(Error::InvalidProof("Missing tail".into())),
+        };
+
+        Ok(RangeProof {
+            start,
+            end,
+            nodes,
+            head,
+            tail,
+        })
+    }
+}
+
+/// ChunkDescs covering a byte range, in order
+struct Located {
+    chunk_size: u64,
+    first_index: u64,
+    descs: Vec<ChunkDesc>,
+}
+
+/// Walk from the root to the ChunkDescs covering `range`
+fn locate<S: NodeSource + ?Sized>(
+    root: &[u8; 32],
+    range: &Range<u64>,
+    source: &S,
+) -> Result<Located> {
+    let node = load_node(source, root)?;
+
+    let chunk_size = node.chunk_size();
+    if chunk_size == 0 || chunk_size > MAX_CHUNK_SIZE {
+        return Err(Error::InvalidChunkSize(chunk_size));
+    }
+
+    let total_size = node.total_size();
+    let chunk_count = node.chunk_count();
+    if range.start > range.end || range.end > total_size {
+        return Err(Error::InvalidRange {
+            start: range.start,
+            end: range.end,
+            size: total_size,
+        });
+    }
+
+    // Every chunk but the last is full, so the root sizes must agree
+    let full = chunk_count.checked_mul(chunk_size);
+    if chunk_count == 0
+        || full.map_or(true, |f| total_size > f)
+        || total_size <= (chunk_count - 1) * chunk_size
+    {
+        return Err(Error::InvalidStructure(format!(
+            "total_size {} inconsistent with {} chunks of {}",
+            total_size, chunk_count, chunk_size
+        )));
+    }
+
+    let mut walker = RangeWalker {
+        source,
+        chunk_size,
+        total_size,
+        chunk_count,
+        first: range.start / chunk_size,
+        last: range.end.saturating_sub(1) / chunk_size,
+        descs: Vec::new(),
+    };
+
+    if range.start < range.end {
+        walker.walk(node, 0)?;
+    }
+
+    Ok(Located {
+        chunk_size,
+        first_index: walker.first,
+        descs: walker.descs,
+    })
+}
+
+struct RangeWalker<'a, S: ?Sized> {
+    source: &'a S,
+    chunk_size: u64,
+    total_size: u64,
+    chunk_count: u64,
+    /// Inclusive chunk index range to collect
+    first: u64,
+    last: u64,
+    descs: Vec<ChunkDesc>,
+}
+
+impl<S: NodeSource + ?Sized> RangeWalker<'_, S> {
+    /// Collect descs from `node`, whose first chunk has index `node_first`
+    fn walk(&mut self, node: ChunkNode, node_first: u64) -> Result<()> {
+        if node.chunk_size() != self.chunk_size {
+            return Err(Error::ChunkSizeMismatch {
+                expected: self.chunk_size,
+                actual: node.chunk_size(),
+            });
+        }
+
+        match node {
+            ChunkNode::Leaf(leaf) => {
+                let covered: u64 = leaf.chunks.iter().map(|c| c.len).sum();
+                if covered != leaf.total_size {
+                    return Err(Error::SizeMismatch {
+                        expected: leaf.total_size,
+                        actual: covered,
+                    });
+                }
+
+                for (i, desc) in leaf.chunks.into_iter().enumerate() {
+                    let index = node_first + i as u64;
+                    self.check_len(&desc, index)?;
+                    if index >= self.first && index <= self.last {
+                        self.descs.push(desc);
+                    }
+                }
+                Ok(())
+            }
+            ChunkNode::Internal(internal) => {
+                let span = child_span(&internal)?;
+                let n = internal.children.len() as u64;
+
+                for (j, child_cid) in internal.children.iter().enumerate() {
+                    let j = j as u64;
+                    let child_first = node_first + j * span;
+                    let child_count = if j + 1 < n {
+                        span
+                    } else {
+                        internal.chunk_count - (n - 1) * span
+                    };
+
+                    // Skip children entirely outside the requested chunks
+                    if child_first + child_count <= self.first || child_first > self.last {
+                        continue;
+                    }
+
+                    let cid: [u8; 32] = child_cid
+                        .as_slice()
+                        .try_into()
+                        .map_err(|_| Error::InvalidHashLength(child_cid.len()))?;
+                    let child = load_node(self.source, &cid)?;
+
+                    if child.chunk_count() != child_count {
+                        return Err(Error::ChunkCountMismatch {
+                            expected: child_count,
+                            actual: child.chunk_count(),
+                        });
+                    }
+
+                    let expected_size = if child_first + child_count == self.chunk_count {
+                        self.total_size - child_first * self.chunk_size
+                    } else {
+                        child_count * self.chunk_size
+                    };
+                    if child.total_size() != expected_size {
+                        return Err(Error::SizeMismatch {
+                            expected: expected_size,
+                            actual: child.total_size(),
+                        });
+                    }
+
+                    self.walk(child, child_first)?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Every chunk but the last must be exactly chunk_size
+    fn check_len(&self, desc: &ChunkDesc, index: u64) -> Result<()> {
+        let ok = if index + 1 == self.chunk_count {
+            desc.len > 0 && desc.len <= self.chunk_size
+        } else {
+            desc.len == self.chunk_size
+        };
+
+        if !ok {
+            return Err(Error::ChunkLengthMismatch {
+                index,
+                offset: index * self.chunk_size,
+                expected: self.chunk_size,
+                actual: desc.len,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Number of chunks covered by each full child of an internal node
+///
+/// The unique power of FANOUT with `(n - 1) * span < chunk_count <= n * span`.
+fn child_span(internal: &ChunkInternal) -> Result<u64> {
+    let n = internal.children.len() as u64;
+    let mut span = FANOUT as u64;
+    while n.saturating_mul(span) < internal.chunk_count {
+        span = span
+            .checked_mul(FANOUT as u64)
+            .ok_or_else(|| Error::InvalidStructure("chunk_count overflows tree height".into()))?;
+    }
+
+    if (n - 1) * span >= internal.chunk_count {
+        return Err(Error::InvalidStructure(format!(
+            "chunk_count {} inconsistent with {} children",
+            internal.chunk_count, n
+        )));
+    }
+
+    Ok(span)
+}
+
+fn check_chunk(bytes: &[u8], desc: &ChunkDesc, index: u64, offset: u64) -> Result<()> {
+    if bytes.len() as u64 != desc.len {
+        return Err(Error::ChunkLengthMismatch {
+            index,
+            offset,
+            expected: desc.len,
+            actual: bytes.len() as u64,
+        });
+    }
+    if cid_from_bytes(bytes).as_slice() != desc.hash.as_slice() {
+        return Err(Error::ChunkHashMismatch { index, offset });
+    }
+    Ok(())
+}
+
+fn chunk_hash(desc: &ChunkDesc) -> Result<[u8; 32]> {
+    desc.hash
+        .as_slice()
+        .try_into()
+        .map_err(|_| Error::InvalidHashLength(desc.hash.len()))
+}
+
+fn load_node<S: NodeSource + ?Sized>(source: &S, cid: &[u8; 32]) -> Result<ChunkNode> {
+    let bytes = fetch_verified(source, cid)?;
+    decode_chunk_node(&bytes)
+}
+
+/// Node source wrapper that keeps every node it hands out
+struct Recorder<'a, N: ?Sized> {
+    inner: &'a N,
+    seen: RefCell<Vec<Vec<u8>>>,
+}
+
+impl<N: NodeSource + ?Sized> NodeSource for Recorder<'_, N> {
+    fn fetch_node(&self, cid: &[u8; 32]) -> Result<Vec<u8>> {
+        let bytes = self.inner.fetch_node(cid)?;
+        self.seen.borrow_mut().push(bytes.clone());
+        Ok(bytes)
+    }
+}
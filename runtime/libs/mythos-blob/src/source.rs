@@ -0,0 +1,63 @@
+/// Node and chunk sources for ChunkedBlob traversal
+///
+/// A `NodeSource` returns canonical node bytes by CID and a `ChunkSource`
+/// returns chunk bytes by chunk hash. Sources are not trusted: every node
+/// and chunk is re-hashed before use.
+use crate::cid_from_bytes;
+use crate::validation::{Error, Result};
+use std::collections::HashMap;
+
+/// Fetch ChunkLeaf/ChunkInternal node bytes by CID
+pub trait NodeSource {
+    /// Return the bytes stored under `cid`, or `Error::NodeNotFound`
+    fn fetch_node(&self, cid: &[u8; 32]) -> Result<Vec<u8>>;
+}
+
+impl NodeSource for HashMap<[u8; 32], Vec<u8>> {
+    fn fetch_node(&self, cid: &[u8; 32]) -> Result<Vec<u8>> {
+        self.get(cid)
+            .cloned()
+            .ok_or_else(|| Error::NodeNotFound(hex::encode(cid)))
+    }
+}
+
+impl<S: NodeSource + ?Sized> NodeSource for &S {
+    fn fetch_node(&self, cid: &[u8; 32]) -> Result<Vec<u8>> {
+        (**self).fetch_node(cid)
+    }
+}
+
+/// Fetch chunk bytes by chunk hash
+pub trait ChunkSource {
+    /// Return the chunk stored under `hash`, or `Error::ChunkNotFound`
+    fn fetch_chunk(&self, hash: &[u8; 32]) -> Result<Vec<u8>>;
+}
+
+impl ChunkSource for HashMap<[u8; 32], Vec<u8>> {
+    fn fetch_chunk(&self, hash: &[u8; 32]) -> Result<Vec<u8>> {
+        self.get(hash)
+            .cloned()
+            .ok_or_else(|| Error::ChunkNotFound(hex::encode(hash)))
+    }
+}
+
+impl<S: ChunkSource + ?Sized> ChunkSource for &S {
+    fn fetch_chunk(&self, hash: &[u8; 32]) -> Result<Vec<u8>> {
+        (**self).fetch_chunk(hash)
+    }
+}
+
+/// Fetch a node and check that its bytes hash to the requested CID
+pub fn fetch_verified<S: NodeSource + ?Sized>(source: &S, cid: &[u8; 32]) -> Result<Vec<u8>> {
+    let bytes = source.fetch_node(cid)?;
+    let computed = cid_from_bytes(&bytes);
+
+    if &computed != cid {
+        return Err(Error::CidMismatch {
+            expected: hex::encode(cid),
+            computed: hex::encode(computed),
+        });
+    }
+
+    Ok(bytes)
+}
@@ -1,4 +1,4 @@
-/// ChunkedBlob types
+// ChunkedBlob types
 
 #[derive(Debug, Clone)]
 pub struct ChunkedBlobNode {
@@ -14,12 +14,57 @@ pub struct ChunkLeaf {
     pub total_size: u64,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct ChunkDesc {
     pub hash: Vec<u8>, // 32 bytes
     pub len: u64,
 }
 
+#[derive(Debug, Clone)]
+pub struct ChunkInternal {
+    pub chunk_size: u64,
+    pub children: Vec<Vec<u8>>, // Child node CIDs, 32 bytes each
+    pub total_size: u64,
+    pub chunk_count: u64,
+}
+
+/// Decoded ChunkedBlob node of either kind
+#[derive(Debug, Clone)]
+pub enum ChunkNode {
+    Leaf(ChunkLeaf),
+    Internal(ChunkInternal),
+}
+
+impl ChunkNode {
+    pub fn chunk_size(&self) -> u64 {
+        match self {
+            ChunkNode::Leaf(leaf) => leaf.chunk_size,
+            ChunkNode::Internal(internal) => internal.chunk_size,
+        }
+    }
+
+    pub fn total_size(&self) -> u64 {
+        match self {
+            ChunkNode::Leaf(leaf) => leaf.total_size,
+            ChunkNode::Internal(internal) => internal.total_size,
+        }
+    }
+
+    /// Number of chunks covered by this node
+    pub fn chunk_count(&self) -> u64 {
+        match self {
+            ChunkNode::Leaf(leaf) => leaf.chunks.len() as u64,
+            ChunkNode::Internal(internal) => internal.chunk_count,
+        }
+    }
+}
+
 pub const VERSION: u64 = 1;
-#[allow(dead_code)]
 pub const KIND_CHUNK_LEAF: u64 = 3;
+pub const KIND_CHUNK_INTERNAL: u64 = 4;
+pub const FANOUT: usize = 1024;
+
+// Chunk size limits (RFC-0004 §6.1)
+pub const MIN_CHUNK_SIZE: u64 = 256 * 1024;
+pub const MAX_CHUNK_SIZE: u64 = 16 * 1024 * 1024;
+pub const DEFAULT_CHUNK_SIZE: u64 = 4 * 1024 * 1024;
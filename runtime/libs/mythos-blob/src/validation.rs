@@ -1,3 +1,4 @@
+use crate::blob_ref::INLINE_BLOB_LIMIT;
 use crate::types::*;
 use mythos_can::Value;
 use sha2::{Digest, Sha256};
@@ -11,14 +12,100 @@ pub enum Error {
     #[error("Version must be 1, got {0}")]
     InvalidVersion(u64),
 
-    #[error("Kind must be 3 (ChunkLeaf), got {0}")]
+    #[error("Kind must be 3 (ChunkLeaf) or 4 (ChunkInternal), got {0}")]
     InvalidKind(u64),
 
     #[error("Hash must be 32 bytes, got {0}")]
     InvalidHashLength(usize),
+
+    #[error("ChunkLeaf must contain 1 to {FANOUT} chunks, got {0}")]
+    InvalidChunkCount(usize),
+
+    #[error("ChunkInternal must contain 2 to {FANOUT} children, got {0}")]
+    InvalidChildCount(usize),
+
+    #[error("Chunk size must be in [{MIN_CHUNK_SIZE}, {MAX_CHUNK_SIZE}], got {0}")]
+    InvalidChunkSize(u64),
+
+    #[error("Blob is empty; use an inline blob instead")]
+    EmptyBlob,
+
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Node not found: {0}")]
+    NodeNotFound(String),
+
+    #[error("Node bytes do not match CID: expected {expected}, computed {computed}")]
+    CidMismatch { expected: String, computed: String },
+
+    #[error("Node source error: {0}")]
+    Source(String),
+
+    #[error("Chunk {index} at offset {offset}: hash mismatch")]
+    ChunkHashMismatch { index: u64, offset: u64 },
+
+    #[error("Chunk {index} at offset {offset}: expected {expected} bytes, got {actual}")]
+    ChunkLengthMismatch {
+        index: u64,
+        offset: u64,
+        expected: u64,
+        actual: u64,
+    },
+
+    #[error("Chunk size mismatch: root declares {expected}, node has {actual}")]
+    ChunkSizeMismatch { expected: u64, actual: u64 },
+
+    #[error("Size mismatch: node declares {expected} bytes, children cover {actual}")]
+    SizeMismatch { expected: u64, actual: u64 },
+
+    #[error("Chunk count mismatch: node declares {expected}, children cover {actual}")]
+    ChunkCountMismatch { expected: u64, actual: u64 },
+
+    #[error("Data stream continues past total_size {0}")]
+    TrailingData(u64),
+
+    #[error("Chunk not found: {0}")]
+    ChunkNotFound(String),
+
+    #[error("Range {start}..{end} is outside blob of {size} bytes")]
+    InvalidRange { start: u64, end: u64, size: u64 },
+
+    #[error("Invalid range proof: {0}")]
+    InvalidProof(String),
+
+    #[error("Invalid BlobRef: {0}")]
+    InvalidBlobRef(String),
+
+    #[error("Inline blob is {0} bytes; limit is {INLINE_BLOB_LIMIT}")]
+    InlineTooLarge(usize),
+
+    #[error("Key not found: {0}")]
+    KeyNotFound(String),
+
+    #[error("Unsupported encryption scheme {0}")]
+    UnsupportedScheme(u8),
+
+    #[error("Nonce must be 12 bytes, got {0}")]
+    InvalidNonceLength(usize),
+
+    #[error("Chunk {0} failed to encrypt")]
+    Encrypt(u64),
+
+    #[error("Chunk {0} failed to decrypt")]
+    Decrypt(u64),
+
+    #[error("Unsupported codec {0}")]
+    UnsupportedCodec(u8),
+
+    #[error("Decoded blob exceeds declared size {0}")]
+    DecodedSizeExceeded(u64),
+
+    #[error("Plaintext hash mismatch: expected {expected}, computed {computed}")]
+    PlainHashMismatch { expected: String, computed: String },
 }
 
-type Result<T> = std::result::Result<T, Error>;
+pub type Result<T> = std::result::Result<T, Error>;
 
 pub fn parse_chunked_blob_node(decoded: &Value) -> Result<ChunkedBlobNode> {
     let fields = match decoded {
@@ -87,6 +174,10 @@ pub fn validate_chunk_leaf(payload: &[u8]) -> Result<ChunkLeaf> {
         _ => return Err(Error::InvalidStructure("Missing chunks list".into())),
     };
 
+    if chunks_list.is_empty() || chunks_list.len() > FANOUT {
+        return Err(Error::InvalidChunkCount(chunks_list.len()));
+    }
+
     let mut chunks = Vec::new();
     for (i, item) in chunks_list.iter().enumerate() {
         let chunk_desc = parse_chunk_desc(item)
@@ -107,6 +198,86 @@ pub fn validate_chunk_leaf(payload: &[u8]) -> Result<ChunkLeaf> {
     })
 }
 
+pub fn validate_chunk_internal(payload: &[u8]) -> Result<ChunkInternal> {
+    let decoded = mythos_can::decode_value_exact(payload)
+        .map_err(|e| Error::InvalidStructure(format!("Payload decode: {}", e)))?;
+
+    let fields = match decoded {
+        Value::Map(pairs) => pairs,
+        _ => return Err(Error::InvalidStructure("ChunkInternal must be MAP".into())),
+    };
+
+    let get_field = |n: u64| {
+        fields
+            .iter()
+            .find(|(k, _)| matches!(k, Value::UVarint(x) if *x == n))
+            .map(|(_, v)| v)
+    };
+
+    // Field 1: chunk_size
+    let chunk_size = match get_field(1) {
+        Some(Value::UVarint(s)) => *s,
+        _ => return Err(Error::InvalidStructure("Missing chunk_size".into())),
+    };
+
+    // Field 2: children (list of Hash)
+    let children_list = match get_field(2) {
+        Some(Value::List(items)) => items,
+        _ => return Err(Error::InvalidStructure("Missing children list".into())),
+    };
+
+    if children_list.len() < 2 || children_list.len() > FANOUT {
+        return Err(Error::InvalidChildCount(children_list.len()));
+    }
+
+    let mut children = Vec::with_capacity(children_list.len());
+    for (i, item) in children_list.iter().enumerate() {
+        let hash_map = match item {
+            Value::Map(hm) => hm,
+            _ => {
+                return Err(Error::InvalidStructure(format!(
+                    "children[{}]: Hash must be MAP",
+                    i
+                )))
+            }
+        };
+        children.push(parse_hash_bytes(hash_map)?);
+    }
+
+    // Field 3: total_size
+    let total_size = match get_field(3) {
+        Some(Value::UVarint(s)) => *s,
+        _ => return Err(Error::InvalidStructure("Missing total_size".into())),
+    };
+
+    // Field 4: chunk_count
+    let chunk_count = match get_field(4) {
+        Some(Value::UVarint(c)) => *c,
+        _ => return Err(Error::InvalidStructure("Missing chunk_count".into())),
+    };
+
+    Ok(ChunkInternal {
+        chunk_size,
+        children,
+        total_size,
+        chunk_count,
+    })
+}
+
+/// Decode canonical node bytes into a ChunkLeaf or ChunkInternal
+pub fn decode_chunk_node(bytes: &[u8]) -> Result<ChunkNode> {
+    let decoded = mythos_can::decode_value_exact(bytes)
+        .map_err(|e| Error::InvalidStructure(format!("Node decode: {}", e)))?;
+
+    let node = parse_chunked_blob_node(&decoded)?;
+
+    match node.kind {
+        KIND_CHUNK_LEAF => Ok(ChunkNode::Leaf(validate_chunk_leaf(&node.payload)?)),
+        KIND_CHUNK_INTERNAL => Ok(ChunkNode::Internal(validate_chunk_internal(&node.payload)?)),
+        other => Err(Error::InvalidKind(other)),
+    }
+}
+
 fn parse_chunk_desc(val: &Value) -> Result<ChunkDesc> {
     let fields = match val {
         Value::Map(pairs) => pairs,
@@ -140,7 +311,7 @@ fn parse_chunk_desc(val: &Value) -> Result<ChunkDesc> {
     })
 }
 
-fn parse_hash_bytes(hash_map: &[(Value, Value)]) -> Result<Vec<u8>> {
+pub(crate) fn parse_hash_bytes(hash_map: &[(Value, Value)]) -> Result<Vec<u8>> {
     let get_field = |n: u64| {
         hash_map
             .iter()
@@ -174,20 +345,27 @@ fn parse_hash_bytes(hash_map: &[(Value, Value)]) -> Result<Vec<u8>> {
 }
 
 /// Compute chunk hashes by splitting payload
+///
+/// With the `parallel` feature chunks are hashed on the rayon thread pool;
+/// the result is in chunk order either way.
+#[cfg(not(feature = "parallel"))]
 pub fn compute_chunk_hashes(payload: &[u8], chunk_size: usize) -> Vec<[u8; 32]> {
-    let mut hashes = Vec::new();
-    let mut pos = 0;
-
-    while pos < payload.len() {
-        let end = (pos + chunk_size).min(payload.len());
-        let chunk = &payload[pos..end];
+    payload.chunks(chunk_size).map(sha256_chunk).collect()
+}
 
-        let mut hasher = Sha256::new();
-        hasher.update(chunk);
-        hashes.push(hasher.finalize().into());
+/// Compute chunk hashes by splitting payload
+///
+/// With the `parallel` feature chunks are hashed on the rayon thread pool;
+/// the result is in chunk order either way.
+#[cfg(feature = "parallel")]
+pub fn compute_chunk_hashes(payload: &[u8], chunk_size: usize) -> Vec<[u8; 32]> {
+    use rayon::prelude::*;
 
-        pos = end;
-    }
+    payload.par_chunks(chunk_size).map(sha256_chunk).collect()
+}
 
-    hashes
+fn sha256_chunk(chunk: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(chunk);
+    hasher.finalize().into()
 }
@@ -0,0 +1,298 @@
+/// Streaming blob verification (RFC-0004 §7)
+///
+/// `VerifyingReader` wraps an untrusted data stream and walks the
+/// ChunkLeaf/ChunkInternal DAG under a root CID as it reads. Each chunk is
+/// buffered and hashed before any of its bytes are returned, so a bad chunk
+/// fails the read at that chunk instead of after the whole blob.
+///
+/// Verification errors surface as `io::ErrorKind::InvalidData` wrapping a
+/// crate `Error` that names the chunk index and byte offset.
+use crate::blob_ref::BlobRef;
+use crate::build::read_full;
+use crate::cid_from_bytes;
+use crate::codec::DecodingReader;
+use crate::source::{fetch_verified, NodeSource};
+use crate::types::*;
+use crate::validation::{decode_chunk_node, Error, Result};
+use std::io::{self, Read};
+
+/// Reader that yields blob bytes only after each chunk verifies
+pub struct VerifyingReader<S, R> {
+    source: S,
+    data: R,
+    chunk_size: u64,
+    total_size: u64,
+    chunk_count: u64,
+    /// Internal nodes on the path to the current leaf
+    stack: Vec<Frame>,
+    /// Remaining chunks of the current leaf
+    leaf: std::vec::IntoIter<ChunkDesc>,
+    next_index: u64,
+    next_offset: u64,
+    /// Verified bytes of the current chunk
+    buf: Vec<u8>,
+    pos: usize,
+    done: bool,
+    failed: bool,
+}
+
+struct Frame {
+    node: ChunkInternal,
+    next_child: usize,
+    size_seen: u64,
+    count_seen: u64,
+}
+
+impl<S: NodeSource, R: Read> VerifyingReader<S, R> {
+    /// Fetch and check the root node, then wrap `data`
+    pub fn new(root: &[u8; 32], source: S, data: R) -> Result<Self> {
+        let node = load_node(&source, root)?;
+
+        // Small chunk sizes appear in conformance vectors, but never allocate
+        // a buffer larger than the spec allows
+        if node.chunk_size() == 0 || node.chunk_size() > MAX_CHUNK_SIZE {
+            return Err(Error::InvalidChunkSize(node.chunk_size()));
+        }
+
+        let mut reader = VerifyingReader {
+            source,
+            data,
+            chunk_size: node.chunk_size(),
+            total_size: node.total_size(),
+            chunk_count: node.chunk_count(),
+            stack: Vec::new(),
+            leaf: Vec::new().into_iter(),
+            next_index: 0,
+            next_offset: 0,
+            buf: Vec::new(),
+            pos: 0,
+            done: false,
+            failed: false,
+        };
+        reader.enter(node)?;
+
+        Ok(reader)
+    }
+
+    /// Verify and decode the unencrypted blob `blob_ref` addresses
+    pub fn open(blob_ref: &BlobRef, source: S, data: R) -> Result<DecodingReader<Self>> {
+        if blob_ref.encryption.is_some() {
+            return Err(Error::InvalidBlobRef(
+                "Encrypted blob; use DecryptingReader::open".into(),
+            ));
+        }
+
+        let root: [u8; 32] = blob_ref
+            .cid
+            .as_slice()
+            .try_into()
+            .map_err(|_| Error::InvalidHashLength(blob_ref.cid.len()))?;
+        DecodingReader::new(blob_ref, Self::new(&root, source, data)?)
+    }
+
+    /// Chunk size declared by the root node
+    pub fn chunk_size(&self) -> u64 {
+        self.chunk_size
+    }
+
+    /// Total blob size declared by the root node
+    pub fn total_size(&self) -> u64 {
+        self.total_size
+    }
+
+    /// Chunk count declared by the root node
+    pub fn chunk_count(&self) -> u64 {
+        self.chunk_count
+    }
+
+    /// Descend into a freshly loaded node
+    fn enter(&mut self, node: ChunkNode) -> Result<()> {
+        if node.chunk_size() != self.chunk_size {
+            return Err(Error::ChunkSizeMismatch {
+                expected: self.chunk_size,
+                actual: node.chunk_size(),
+            });
+        }
+
+        match node {
+            ChunkNode::Leaf(leaf) => {
+                let covered: u64 = leaf.chunks.iter().map(|c| c.len).sum();
+                if covered != leaf.total_size {
+                    return Err(Error::SizeMismatch {
+                        expected: leaf.total_size,
+                        actual: covered,
+                    });
+                }
+                self.leaf = leaf.chunks.into_iter();
+            }
+            ChunkNode::Internal(internal) => {
+                self.stack.push(Frame {
+                    node: internal,
+                    next_child: 0,
+                    size_seen: 0,
+                    count_seen: 0,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Next ChunkDesc in blob order, fetching nodes as needed
+    fn next_desc(&mut self) -> Result<Option<ChunkDesc>> {
+        loop {
+            if let Some(desc) = self.leaf.next() {
+                return Ok(Some(desc));
+            }
+
+            let Some(frame) = self.stack.last_mut() else {
+                return Ok(None);
+            };
+
+            if frame.next_child == frame.node.children.len() {
+                // All children seen: their declared sizes must add up
+                let frame = self.stack.pop().expect("frame exists");
+                if frame.size_seen != frame.node.total_size {
+                    return Err(Error::SizeMismatch {
+                        expected: frame.node.total_size,
+                        actual: frame.size_seen,
+                    });
+                }
+                if frame.count_seen != frame.node.chunk_count {
+                    return Err(Error::ChunkCountMismatch {
+                        expected: frame.node.chunk_count,
+                        actual: frame.count_seen,
+                    });
+                }
+                continue;
+            }
+
+            let child_cid = &frame.node.children[frame.next_child];
+            let cid: [u8; 32] = child_cid
+                .as_slice()
+                .try_into()
+                .map_err(|_| Error::InvalidHashLength(child_cid.len()))?;
+            frame.next_child += 1;
+
+            let child = load_node(&self.source, &cid)?;
+            frame.size_seen += child.total_size();
+            frame.count_seen += child.chunk_count();
+            self.enter(child)?;
+        }
+    }
+
+    /// Read and verify the next chunk into `buf`
+    fn fill(&mut self) -> Result<()> {
+        let Some(desc) = self.next_desc()? else {
+            return self.finish();
+        };
+
+        let index = self.next_index;
+        let offset = self.next_offset;
+
+        if index >= self.chunk_count {
+            return Err(Error::ChunkCountMismatch {
+                expected: self.chunk_count,
+                actual: index + 1,
+            });
+        }
+
+        // Every chunk but the last must be exactly chunk_size
+        let is_last = index + 1 == self.chunk_count;
+        let len_ok = if is_last {
+            desc.len > 0 && desc.len <= self.chunk_size
+        } else {
+            desc.len == self.chunk_size
+        };
+        if !len_ok {
+            return Err(Error::ChunkLengthMismatch {
+                index,
+                offset,
+                expected: self.chunk_size,
+                actual: desc.len,
+            });
+        }
+
+        self.buf.resize(desc.len as usize, 0);
+        let n = read_full(&mut self.data, &mut self.buf)?;
+        if n as u64 != desc.len {
+            return Err(Error::ChunkLengthMismatch {
+                index,
+                offset,
+                expected: desc.len,
+                actual: n as u64,
+            });
+        }
+
+        if cid_from_bytes(&self.buf).as_slice() != desc.hash.as_slice() {
+            return Err(Error::ChunkHashMismatch { index, offset });
+        }
+
+        self.pos = 0;
+        self.next_index += 1;
+        self.next_offset += desc.len;
+        Ok(())
+    }
+
+    /// Check the stream ends exactly at total_size
+    fn finish(&mut self) -> Result<()> {
+        self.done = true;
+        self.buf.clear();
+        self.pos = 0;
+
+        if self.next_offset != self.total_size {
+            return Err(Error::SizeMismatch {
+                expected: self.total_size,
+                actual: self.next_offset,
+            });
+        }
+
+        let mut probe = [0u8; 1];
+        if read_full(&mut self.data, &mut probe)? != 0 {
+            return Err(Error::TrailingData(self.total_size));
+        }
+
+        Ok(())
+    }
+}
+
+impl<S: NodeSource, R: Read> Read for VerifyingReader<S, R> {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        if self.failed {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "blob verification already failed",
+            ));
+        }
+
+        if self.pos == self.buf.len() {
+            if self.done {
+                return Ok(0);
+            }
+            if let Err(e) = self.fill() {
+                self.failed = true;
+                return Err(into_io(e));
+            }
+            if self.pos == self.buf.len() {
+                return Ok(0);
+            }
+        }
+
+        let n = out.len().min(self.buf.len() - self.pos);
+        out[..n].copy_from_slice(&self.buf[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+fn load_node<S: NodeSource + ?Sized>(source: &S, cid: &[u8; 32]) -> Result<ChunkNode> {
+    let bytes = fetch_verified(source, cid)?;
+    decode_chunk_node(&bytes)
+}
+
+pub(crate) fn into_io(e: Error) -> io::Error {
+    match e {
+        Error::Io(io) => io,
+        other => io::Error::new(io::ErrorKind::InvalidData, other),
+    }
+}
@@ -0,0 +1,184 @@
+/// BlobRef, EncryptRef and ProvenanceRef tests
+use mythos_blob::{
+    check_inline, BlobRef, ChunkedBlobBuilder, EncryptRef, Error, ProvenanceRef, CODEC_ZSTD,
+    INLINE_BLOB_LIMIT, MIN_CHUNK_SIZE, SCHEME_AES_GCM,
+};
+use mythos_can::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::io::{self, Read};
+
+fn full_ref() -> BlobRef {
+    BlobRef {
+        cid: vec![0x11; 32],
+        size: 1234,
+        media: "application/x-trace".into(),
+        codec: CODEC_ZSTD,
+        chunks: 1,
+        encryption: Some(EncryptRef {
+            scheme: SCHEME_AES_GCM,
+            key_handle: "kms://tenant/key-1".into(),
+            nonce: vec![7; 12],
+            aad: Some(b"episode".to_vec()),
+            plain_hash: Some(vec![0x22; 32]),
+        }),
+        provenance: Some(ProvenanceRef {
+            parents: vec![vec![0x33; 32], vec![0x44; 32]],
+            transform: "redact".into(),
+            params: BTreeMap::from([
+                ("policy".to_string(), "pii-v2".to_string()),
+                ("mode".to_string(), "strict".to_string()),
+            ]),
+            code_hash: vec![0x55; 32],
+            time_observed: Some(1_700_000_000_000_000),
+        }),
+    }
+}
+
+#[test]
+fn test_roundtrip_all_fields() {
+    let blob_ref = full_ref();
+    let bytes = blob_ref.to_bytes().unwrap();
+    let decoded = BlobRef::from_bytes(&bytes).unwrap();
+
+    assert_eq!(decoded, blob_ref);
+    assert_eq!(decoded.to_bytes().unwrap(), bytes);
+}
+
+#[test]
+fn test_optional_fields_omitted() {
+    let blob_ref = BlobRef {
+        encryption: None,
+        provenance: None,
+        ..full_ref()
+    };
+
+    let value = mythos_can::decode_value_exact(&blob_ref.to_bytes().unwrap()).unwrap();
+    match value {
+        Value::Map(pairs) => {
+            let keys: Vec<_> = pairs.iter().map(|(k, _)| k.clone()).collect();
+            assert_eq!(keys, (1..=5).map(Value::UVarint).collect::<Vec<_>>());
+        }
+        _ => panic!("Expected MAP"),
+    }
+}
+
+#[test]
+fn test_from_build() {
+    let build = ChunkedBlobBuilder::new()
+        .chunk_size(MIN_CHUNK_SIZE)
+        .build(io::repeat(1).take(MIN_CHUNK_SIZE * 2 + 1))
+        .unwrap();
+
+    let blob_ref = BlobRef::from_build(&build, "text/plain");
+    assert_eq!(blob_ref.cid, build.root.to_vec());
+    assert_eq!(blob_ref.size, MIN_CHUNK_SIZE * 2 + 1);
+    assert_eq!(blob_ref.chunks, 3);
+    assert_eq!(blob_ref.media, "text/plain");
+    assert!(blob_ref.encryption.is_none());
+    blob_ref.validate().unwrap();
+}
+
+#[test]
+fn test_check_dag() {
+    let build = ChunkedBlobBuilder::new()
+        .chunk_size(MIN_CHUNK_SIZE)
+        .build(io::repeat(1).take(MIN_CHUNK_SIZE * 2 + 1))
+        .unwrap();
+    let nodes: HashMap<_, _> = build.nodes.iter().cloned().collect();
+
+    build.blob_ref.check_dag(&nodes).unwrap();
+
+    let wrong_chunks = BlobRef {
+        chunks: 2,
+        ..build.blob_ref.clone()
+    };
+    assert!(matches!(
+        wrong_chunks.check_dag(&nodes),
+        Err(Error::ChunkCountMismatch {
+            expected: 3,
+            actual: 2
+        })
+    ));
+
+    let wrong_size = BlobRef {
+        size: 5,
+        ..build.blob_ref.clone()
+    };
+    assert!(matches!(
+        wrong_size.check_dag(&nodes),
+        Err(Error::SizeMismatch { actual: 5, .. })
+    ));
+
+    // With a codec applied, size is still the DAG's total_size
+    let compressed = BlobRef {
+        size: 5,
+        codec: CODEC_ZSTD,
+        ..build.blob_ref.clone()
+    };
+    assert!(matches!(
+        compressed.check_dag(&nodes),
+        Err(Error::SizeMismatch { actual: 5, .. })
+    ));
+}
+
+#[test]
+fn test_validation_rejects_bad_fields() {
+    let short_cid = BlobRef {
+        cid: vec![0; 31],
+        ..full_ref()
+    };
+    assert!(matches!(
+        short_cid.validate(),
+        Err(Error::InvalidHashLength(31))
+    ));
+
+    let bad_codec = BlobRef {
+        codec: 7,
+        ..full_ref()
+    };
+    assert!(matches!(
+        bad_codec.validate(),
+        Err(Error::InvalidBlobRef(_))
+    ));
+
+    let mut bad_scheme = full_ref();
+    bad_scheme.encryption.as_mut().unwrap().scheme = 9;
+    assert!(matches!(
+        bad_scheme.validate(),
+        Err(Error::InvalidBlobRef(_))
+    ));
+
+    let mut bad_parent = full_ref();
+    bad_parent.provenance.as_mut().unwrap().parents[1] = vec![0; 8];
+    assert!(matches!(
+        bad_parent.validate(),
+        Err(Error::InvalidHashLength(8))
+    ));
+}
+
+#[test]
+fn test_decode_rejects_out_of_range_chunks() {
+    let mut value = full_ref().to_value();
+    if let Value::Map(pairs) = &mut value {
+        for (k, v) in pairs.iter_mut() {
+            if *k == Value::UVarint(5) {
+                *v = Value::UVarint(u32::MAX as u64 + 1);
+            }
+        }
+    }
+
+    let bytes = mythos_can::encode_value(&value).unwrap();
+    assert!(matches!(
+        BlobRef::from_bytes(&bytes),
+        Err(Error::InvalidBlobRef(_))
+    ));
+}
+
+#[test]
+fn test_inline_limit() {
+    check_inline(&vec![0; INLINE_BLOB_LIMIT]).unwrap();
+    assert!(matches!(
+        check_inline(&vec![0; INLINE_BLOB_LIMIT + 1]),
+        Err(Error::InlineTooLarge(n)) if n == INLINE_BLOB_LIMIT + 1
+    ));
+}
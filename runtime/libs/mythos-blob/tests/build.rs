@@ -0,0 +1,138 @@
+/// ChunkedBlobBuilder tests
+use mythos_blob::{
+    decode_chunk_node, encode_chunk_leaf, parse_chunked_blob_node, validate_chunk_leaf, ChunkNode,
+    ChunkedBlobBuilder, Error, DEFAULT_CHUNK_SIZE, MAX_CHUNK_SIZE, MIN_CHUNK_SIZE,
+};
+use std::fs;
+use std::io::{self, Read};
+
+const VECTORS_PATH: &str = "../../../mythos-v0.2-conformance/vectors/blob";
+
+fn pattern(len: usize) -> Vec<u8> {
+    (0..len).map(|i| (i * 31 % 251) as u8).collect()
+}
+
+/// Reader that returns at most 7 bytes per call
+struct Trickle<R>(R);
+
+impl<R: Read> Read for Trickle<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = buf.len().min(7);
+        self.0.read(&mut buf[..n])
+    }
+}
+
+#[test]
+fn test_leaf_encoding_matches_blob_001() {
+    let rootnode = fs::read(format!("{}/chunkedblob_001_rootnode.bin", VECTORS_PATH))
+        .expect("Failed to read rootnode");
+
+    let decoded = mythos_can::decode_value_exact(&rootnode).unwrap();
+    let node = parse_chunked_blob_node(&decoded).unwrap();
+    let leaf = validate_chunk_leaf(&node.payload).unwrap();
+
+    let encoded = encode_chunk_leaf(leaf.chunk_size, &leaf.chunks).unwrap();
+    assert_eq!(encoded, rootnode, "Leaf encoding must match BLOB_001 bytes");
+}
+
+#[test]
+fn test_chunk_size_range_enforced() {
+    for size in [0, MIN_CHUNK_SIZE - 1, MAX_CHUNK_SIZE + 1] {
+        let result = ChunkedBlobBuilder::new()
+            .chunk_size(size)
+            .build(&b"data"[..]);
+        assert!(matches!(result, Err(Error::InvalidChunkSize(s)) if s == size));
+    }
+
+    for size in [MIN_CHUNK_SIZE, MAX_CHUNK_SIZE] {
+        assert!(ChunkedBlobBuilder::new()
+            .chunk_size(size)
+            .build(&b"data"[..])
+            .is_ok());
+    }
+}
+
+#[test]
+fn test_single_leaf_blob() {
+    let data = pattern(DEFAULT_CHUNK_SIZE as usize * 2 + 1000);
+    let mut streamed = Vec::new();
+
+    let build = ChunkedBlobBuilder::new()
+        .media("application/x-test")
+        .build_with(&data[..], |index, hash, bytes| {
+            assert_eq!(index as usize, streamed.len() / DEFAULT_CHUNK_SIZE as usize);
+            assert_eq!(hash, &mythos_blob::cid_from_bytes(bytes));
+            streamed.extend_from_slice(bytes);
+            Ok(())
+        })
+        .unwrap();
+
+    assert_eq!(streamed, data);
+    assert_eq!(build.chunk_count, 3);
+    assert_eq!(build.total_size, data.len() as u64);
+    assert_eq!(build.nodes.len(), 1);
+    assert_eq!(build.nodes[0].0, build.root);
+
+    let blob_ref = &build.blob_ref;
+    assert_eq!(blob_ref.cid, build.root.to_vec());
+    assert_eq!(blob_ref.size, data.len() as u64);
+    assert_eq!(blob_ref.media, "application/x-test");
+    assert_eq!(blob_ref.codec, 0);
+    assert_eq!(blob_ref.chunks, 3);
+
+    match decode_chunk_node(&build.nodes[0].1).unwrap() {
+        ChunkNode::Leaf(leaf) => {
+            assert_eq!(leaf.chunk_size, DEFAULT_CHUNK_SIZE);
+            assert_eq!(leaf.total_size, data.len() as u64);
+            let lens: Vec<u64> = leaf.chunks.iter().map(|c| c.len).collect();
+            assert_eq!(lens, vec![DEFAULT_CHUNK_SIZE, DEFAULT_CHUNK_SIZE, 1000]);
+        }
+        ChunkNode::Internal(_) => panic!("Expected ChunkLeaf root"),
+    }
+}
+
+#[test]
+fn test_short_reads_do_not_change_root() {
+    let data = pattern(MIN_CHUNK_SIZE as usize * 3 + 17);
+    let builder = ChunkedBlobBuilder::new().chunk_size(MIN_CHUNK_SIZE);
+
+    let direct = builder.build(&data[..]).unwrap();
+    let trickled = builder.build(Trickle(&data[..])).unwrap();
+
+    assert_eq!(direct.root, trickled.root);
+    assert_eq!(direct.chunk_count, 4);
+}
+
+#[test]
+fn test_internal_node_above_fanout() {
+    // 1026 chunks: one full leaf, one leaf of 2, under a ChunkInternal root
+    let size = MIN_CHUNK_SIZE * 1025 + 10;
+    let build = ChunkedBlobBuilder::new()
+        .chunk_size(MIN_CHUNK_SIZE)
+        .build(io::repeat(0x5a).take(size))
+        .unwrap();
+
+    assert_eq!(build.chunk_count, 1026);
+    assert_eq!(build.total_size, size);
+    assert_eq!(build.nodes.len(), 3);
+    assert_eq!(build.nodes.last().unwrap().0, build.root);
+
+    match decode_chunk_node(&build.nodes[2].1).unwrap() {
+        ChunkNode::Internal(internal) => {
+            assert_eq!(internal.chunk_size, MIN_CHUNK_SIZE);
+            assert_eq!(internal.total_size, size);
+            assert_eq!(internal.chunk_count, 1026);
+            assert_eq!(
+                internal.children,
+                vec![build.nodes[0].0.to_vec(), build.nodes[1].0.to_vec()]
+            );
+        }
+        ChunkNode::Leaf(_) => panic!("Expected ChunkInternal root"),
+    }
+}
+
+#[test]
+fn test_empty_blob_rejected() {
+    let result = ChunkedBlobBuilder::new().build(io::empty());
+    assert!(matches!(result, Err(Error::EmptyBlob)));
+}
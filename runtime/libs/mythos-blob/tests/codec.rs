@@ -0,0 +1,251 @@
+/// Blob codec tests (zstd, decompression limits)
+use mythos_blob::{
+    build_code_hash, BlobRef, ChunkedBlobBuild, ChunkedBlobBuilder, DecodingReader,
+    DecryptingReader, EncryptRef, Error, VerifyingReader, BUILD_TRANSFORM, CODEC_GZIP, CODEC_RAW,
+    CODEC_ZSTD, MIN_CHUNK_SIZE, PARAM_DECODED_SIZE, SCHEME_AES_GCM,
+};
+use std::collections::HashMap;
+use std::io::{self, Read};
+
+const S: u64 = MIN_CHUNK_SIZE;
+
+fn text(len: usize) -> Vec<u8> {
+    b"episode trace line\n"
+        .iter()
+        .cycle()
+        .take(len)
+        .copied()
+        .collect()
+}
+
+/// Overwrite the decoded size a built BlobRef declares
+fn set_decoded_size(blob_ref: &mut BlobRef, size: u64) {
+    let provenance = blob_ref
+        .provenance
+        .as_mut()
+        .expect("coded blob has provenance");
+    provenance
+        .params
+        .insert(PARAM_DECODED_SIZE.to_string(), size.to_string());
+}
+
+/// Build with `builder`, collecting the stored (post-codec) bytes
+fn build(builder: ChunkedBlobBuilder, data: &[u8]) -> (ChunkedBlobBuild, Vec<u8>) {
+    let mut stored = Vec::new();
+    let build = builder
+        .build_with(data, |_, _, bytes| {
+            stored.extend_from_slice(bytes);
+            Ok(())
+        })
+        .unwrap();
+    (build, stored)
+}
+
+fn read_err<R: Read>(mut reader: R) -> Error {
+    let err = reader.read_to_end(&mut Vec::new()).unwrap_err();
+    *err.into_inner()
+        .expect("wrapped error")
+        .downcast::<Error>()
+        .expect("blob error")
+}
+
+#[test]
+fn test_zstd_roundtrip() {
+    let data = text(S as usize * 3);
+    let (build, stored) = build(
+        ChunkedBlobBuilder::new().chunk_size(S).codec(CODEC_ZSTD),
+        &data,
+    );
+    let nodes: HashMap<_, _> = build.nodes.into_iter().collect();
+
+    assert_eq!(build.blob_ref.codec, CODEC_ZSTD);
+    assert_eq!(build.blob_ref.size, stored.len() as u64);
+    assert_eq!(
+        build.blob_ref.decoded_size().unwrap(),
+        Some(data.len() as u64)
+    );
+    assert_eq!(build.total_size, stored.len() as u64);
+    assert!(stored.len() < data.len() / 10);
+    build.blob_ref.check_dag(&nodes).unwrap();
+
+    let provenance = build.blob_ref.provenance.as_ref().unwrap();
+    assert_eq!(provenance.transform, BUILD_TRANSFORM);
+    assert_eq!(provenance.code_hash, build_code_hash().to_vec());
+
+    // The DAG covers the compressed bytes
+    assert_eq!(zstd::decode_all(&stored[..]).unwrap(), data);
+
+    let mut out = Vec::new();
+    VerifyingReader::open(&build.blob_ref, &nodes, &stored[..])
+        .unwrap()
+        .read_to_end(&mut out)
+        .unwrap();
+    assert_eq!(out, data);
+}
+
+#[test]
+fn test_zstd_build_is_deterministic() {
+    let data = text(S as usize + 77);
+    let builder = ChunkedBlobBuilder::new().chunk_size(S).codec(CODEC_ZSTD);
+    let (a, _) = build(builder.clone(), &data);
+    let (b, _) = build(builder, &data);
+    assert_eq!(a.root, b.root);
+
+    let (raw, _) = build(ChunkedBlobBuilder::new().chunk_size(S), &data);
+    assert_ne!(a.root, raw.root);
+}
+
+#[test]
+fn test_raw_open_passes_through() {
+    let data = text(1000);
+    let (build, stored) = build(ChunkedBlobBuilder::new().chunk_size(S), &data);
+    let nodes: HashMap<_, _> = build.nodes.into_iter().collect();
+
+    assert_eq!(build.blob_ref.codec, CODEC_RAW);
+    assert_eq!(stored, data);
+
+    let mut out = Vec::new();
+    VerifyingReader::open(&build.blob_ref, &nodes, &stored[..])
+        .unwrap()
+        .read_to_end(&mut out)
+        .unwrap();
+    assert_eq!(out, data);
+}
+
+#[test]
+fn test_decompression_bomb_limited() {
+    // 64 MiB of zeros compresses to a few KiB
+    let bomb_size = 64 * 1024 * 1024;
+    let (build, stored) = build(
+        ChunkedBlobBuilder::new().chunk_size(S).codec(CODEC_ZSTD),
+        &vec![0u8; bomb_size],
+    );
+    let nodes: HashMap<_, _> = build.nodes.into_iter().collect();
+    assert!(stored.len() < S as usize);
+
+    // A BlobRef that understates the decoded size stops at the declared size
+    let mut blob_ref = build.blob_ref;
+    set_decoded_size(&mut blob_ref, 1024);
+    let mut reader = VerifyingReader::open(&blob_ref, &nodes, &stored[..]).unwrap();
+    let mut buf = vec![0u8; 1 << 20];
+    let mut total = 0;
+    let err = loop {
+        match reader.read(&mut buf) {
+            Ok(n) => total += n,
+            Err(e) => break e,
+        }
+    };
+
+    assert_eq!(total, 1024);
+    assert!(matches!(
+        *err.into_inner().unwrap().downcast::<Error>().unwrap(),
+        Error::DecodedSizeExceeded(1024)
+    ));
+}
+
+#[test]
+fn test_decoded_size_overstated() {
+    let data = text(5000);
+    let (build, stored) = build(
+        ChunkedBlobBuilder::new().chunk_size(S).codec(CODEC_ZSTD),
+        &data,
+    );
+    let nodes: HashMap<_, _> = build.nodes.into_iter().collect();
+
+    let mut blob_ref = build.blob_ref;
+    set_decoded_size(&mut blob_ref, 5001);
+    let reader = VerifyingReader::open(&blob_ref, &nodes, &stored[..]).unwrap();
+    assert!(matches!(
+        read_err(reader),
+        Error::SizeMismatch {
+            expected: 5001,
+            actual: 5000
+        }
+    ));
+}
+
+#[test]
+fn test_zstd_with_encryption() {
+    let keys = HashMap::from([("k".to_string(), [4u8; 32])]);
+    let encryption = EncryptRef {
+        scheme: SCHEME_AES_GCM,
+        key_handle: "k".into(),
+        nonce: vec![1; 12],
+        aad: None,
+        plain_hash: None,
+    };
+    let data = text(S as usize * 2);
+
+    let mut stored = Vec::new();
+    let build = ChunkedBlobBuilder::new()
+        .chunk_size(S)
+        .codec(CODEC_ZSTD)
+        .build_encrypted_with(&data[..], encryption, &keys, |_, _, bytes| {
+            stored.extend_from_slice(bytes);
+            Ok(())
+        })
+        .unwrap();
+    let nodes: HashMap<_, _> = build.nodes.into_iter().collect();
+
+    assert_eq!(build.blob_ref.codec, CODEC_ZSTD);
+    assert_eq!(
+        build.blob_ref.decoded_size().unwrap(),
+        Some(data.len() as u64)
+    );
+    assert!(stored.len() < data.len() / 10);
+
+    let mut out = Vec::new();
+    DecryptingReader::open(&build.blob_ref, &nodes, &stored[..], &keys)
+        .unwrap()
+        .read_to_end(&mut out)
+        .unwrap();
+    assert_eq!(out, data);
+
+    // Encrypted blobs must go through the decrypting path
+    assert!(matches!(
+        VerifyingReader::open(&build.blob_ref, &nodes, &stored[..]),
+        Err(Error::InvalidBlobRef(_))
+    ));
+}
+
+#[test]
+fn test_unsupported_codec() {
+    let result = ChunkedBlobBuilder::new()
+        .codec(CODEC_GZIP)
+        .build(&b"data"[..]);
+    assert!(matches!(result, Err(Error::UnsupportedCodec(2))));
+
+    let (build, _) = build(ChunkedBlobBuilder::new().chunk_size(S), b"data");
+    let mut blob_ref = build.blob_ref;
+    blob_ref.codec = CODEC_GZIP;
+    assert!(matches!(
+        DecodingReader::new(&blob_ref, io::empty()),
+        Err(Error::UnsupportedCodec(2))
+    ));
+}
+
+#[test]
+fn test_zstd_requires_decoded_size() {
+    let (build, _) = build(
+        ChunkedBlobBuilder::new().chunk_size(S).codec(CODEC_ZSTD),
+        b"data",
+    );
+    let mut blob_ref = build.blob_ref.clone();
+    blob_ref.provenance = None;
+    assert!(matches!(
+        DecodingReader::new(&blob_ref, io::empty()),
+        Err(Error::InvalidBlobRef(_))
+    ));
+
+    let mut malformed = build.blob_ref.clone();
+    malformed
+        .provenance
+        .as_mut()
+        .unwrap()
+        .params
+        .insert(PARAM_DECODED_SIZE.to_string(), "four".to_string());
+    assert!(matches!(
+        DecodingReader::new(&malformed, io::empty()),
+        Err(Error::InvalidBlobRef(_))
+    ));
+}
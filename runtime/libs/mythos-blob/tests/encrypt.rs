@@ -0,0 +1,219 @@
+/// Blob encryption tests (RFC-0001 §13.6)
+use mythos_blob::{
+    chunk_nonce, cid_from_bytes, ChunkedBlobBuild, ChunkedBlobBuilder, DecryptingReader,
+    EncryptRef, Error, AES_GCM_TAG_LEN, MIN_CHUNK_SIZE, PARAM_PLAIN_HASH, PARAM_PLAIN_SIZE,
+    SCHEME_AES_GCM, SCHEME_AGE,
+};
+use std::collections::HashMap;
+use std::io::Read;
+
+const S: u64 = MIN_CHUNK_SIZE;
+const HANDLE: &str = "local://test-key";
+
+fn pattern(len: usize) -> Vec<u8> {
+    (0..len).map(|i| (i * 31 % 251) as u8).collect()
+}
+
+fn keys() -> HashMap<String, [u8; 32]> {
+    HashMap::from([(HANDLE.to_string(), [9u8; 32])])
+}
+
+fn encrypt_ref() -> EncryptRef {
+    EncryptRef {
+        scheme: SCHEME_AES_GCM,
+        key_handle: HANDLE.into(),
+        nonce: vec![3; 12],
+        aad: Some(b"trace".to_vec()),
+        plain_hash: None,
+    }
+}
+
+/// Build an encrypted blob, returning the build and the ciphertext stream
+fn encrypt(builder: ChunkedBlobBuilder, data: &[u8]) -> (ChunkedBlobBuild, Vec<u8>) {
+    let mut ciphertext = Vec::new();
+    let build = builder
+        .build_encrypted_with(data, encrypt_ref(), &keys(), |_, _, bytes| {
+            ciphertext.extend_from_slice(bytes);
+            Ok(())
+        })
+        .unwrap();
+    (build, ciphertext)
+}
+
+fn read_err<R: Read>(mut reader: R) -> Error {
+    let err = reader.read_to_end(&mut Vec::new()).unwrap_err();
+    *err.into_inner()
+        .expect("wrapped error")
+        .downcast::<Error>()
+        .expect("blob error")
+}
+
+#[test]
+fn test_roundtrip() {
+    let data = pattern(S as usize * 2 + 500);
+    let (build, ciphertext) = encrypt(ChunkedBlobBuilder::new().chunk_size(S), &data);
+    let nodes: HashMap<_, _> = build.nodes.into_iter().collect();
+
+    // Ciphertext chunks are full-size, so there is one extra tag per chunk
+    assert_eq!(build.chunk_count, 3);
+    assert_eq!(ciphertext.len(), data.len() + 3 * AES_GCM_TAG_LEN);
+    assert_eq!(build.total_size, ciphertext.len() as u64);
+    assert_eq!(build.blob_ref.size, ciphertext.len() as u64);
+    assert_eq!(
+        build.blob_ref.plain_size().unwrap(),
+        Some(data.len() as u64)
+    );
+    assert_eq!(
+        build.blob_ref.decoded_size().unwrap(),
+        Some(data.len() as u64)
+    );
+    let encryption = build.blob_ref.encryption.clone().unwrap();
+    assert_eq!(encryption.nonce.len(), 12);
+    assert_eq!(
+        encryption,
+        EncryptRef {
+            nonce: encryption.nonce.clone(),
+            ..encrypt_ref()
+        }
+    );
+    build.blob_ref.check_dag(&nodes).unwrap();
+    assert_ne!(&ciphertext[..data.len()], &data[..]);
+
+    let mut out = Vec::new();
+    DecryptingReader::new(&build.blob_ref, &nodes, &ciphertext[..], &keys())
+        .unwrap()
+        .read_to_end(&mut out)
+        .unwrap();
+    assert_eq!(out, data);
+}
+
+#[test]
+fn test_fresh_nonce_per_blob() {
+    let data = pattern(S as usize + 1);
+    let (a, _) = encrypt(ChunkedBlobBuilder::new().chunk_size(S), &data);
+    let (b, _) = encrypt(ChunkedBlobBuilder::new().chunk_size(S), &data);
+    let nonce = |build: &ChunkedBlobBuild| build.blob_ref.encryption.clone().unwrap().nonce;
+    assert_ne!(nonce(&a), encrypt_ref().nonce);
+    assert_ne!(nonce(&a), nonce(&b));
+    assert_ne!(a.root, b.root);
+
+    let plain = ChunkedBlobBuilder::new()
+        .chunk_size(S)
+        .build(&data[..])
+        .unwrap();
+    assert_ne!(a.root, plain.root);
+}
+
+#[test]
+fn test_chunk_nonces_differ() {
+    let base = [3u8; 12];
+    assert_eq!(chunk_nonce(&base, 0).unwrap(), base);
+    assert_ne!(
+        chunk_nonce(&base, 1).unwrap(),
+        chunk_nonce(&base, 2).unwrap()
+    );
+    assert!(matches!(
+        chunk_nonce(&[0; 8], 0),
+        Err(Error::InvalidNonceLength(8))
+    ));
+}
+
+#[test]
+fn test_plain_hash_opt_in() {
+    let data = pattern(1000);
+    let (without, _) = encrypt(ChunkedBlobBuilder::new().chunk_size(S), &data);
+    let params = &without.blob_ref.provenance.as_ref().unwrap().params;
+    assert!(!params.contains_key(PARAM_PLAIN_HASH));
+    assert!(without.blob_ref.encryption.unwrap().plain_hash.is_none());
+
+    let (with, ciphertext) = encrypt(
+        ChunkedBlobBuilder::new().chunk_size(S).plain_hash(true),
+        &data,
+    );
+    let encryption = with.blob_ref.encryption.as_ref().unwrap();
+    assert_eq!(
+        encryption.plain_hash.as_deref(),
+        Some(&cid_from_bytes(&data)[..])
+    );
+    let params = &with.blob_ref.provenance.as_ref().unwrap().params;
+    assert_eq!(
+        params.get(PARAM_PLAIN_HASH),
+        Some(&hex::encode(cid_from_bytes(&data)))
+    );
+
+    let nodes: HashMap<_, _> = with.nodes.iter().cloned().collect();
+    let mut forged = with.blob_ref.clone();
+    forged.encryption.as_mut().unwrap().plain_hash = Some(vec![0; 32]);
+    let reader = DecryptingReader::new(&forged, &nodes, &ciphertext[..], &keys()).unwrap();
+    assert!(matches!(read_err(reader), Error::PlainHashMismatch { .. }));
+}
+
+#[test]
+fn test_tampered_ciphertext_fails_cid_check() {
+    let data = pattern(S as usize * 2);
+    let (build, mut ciphertext) = encrypt(ChunkedBlobBuilder::new().chunk_size(S), &data);
+    let nodes: HashMap<_, _> = build.nodes.into_iter().collect();
+
+    ciphertext[S as usize + 3] ^= 1;
+    let reader = DecryptingReader::new(&build.blob_ref, &nodes, &ciphertext[..], &keys()).unwrap();
+
+    // Caught by the DAG hash before the cipher sees the chunk
+    assert!(matches!(
+        read_err(reader),
+        Error::ChunkHashMismatch { index: 1, .. }
+    ));
+}
+
+#[test]
+fn test_wrong_key_fails_to_decrypt() {
+    let data = pattern(100);
+    let (build, ciphertext) = encrypt(ChunkedBlobBuilder::new().chunk_size(S), &data);
+    let nodes: HashMap<_, _> = build.nodes.into_iter().collect();
+
+    let wrong = HashMap::from([(HANDLE.to_string(), [1u8; 32])]);
+    let reader = DecryptingReader::new(&build.blob_ref, &nodes, &ciphertext[..], &wrong).unwrap();
+    assert!(matches!(read_err(reader), Error::Decrypt(0)));
+}
+
+#[test]
+fn test_declared_size_checked() {
+    let data = pattern(100);
+    let (build, ciphertext) = encrypt(ChunkedBlobBuilder::new().chunk_size(S), &data);
+    let nodes: HashMap<_, _> = build.nodes.into_iter().collect();
+
+    let mut blob_ref = build.blob_ref;
+    let provenance = blob_ref.provenance.as_mut().unwrap();
+    provenance
+        .params
+        .insert(PARAM_PLAIN_SIZE.to_string(), "99".to_string());
+    let reader = DecryptingReader::new(&blob_ref, &nodes, &ciphertext[..], &keys()).unwrap();
+    assert!(matches!(
+        read_err(reader),
+        Error::SizeMismatch {
+            expected: 99,
+            actual: 100
+        }
+    ));
+}
+
+#[test]
+fn test_key_and_scheme_errors() {
+    let data = pattern(100);
+    let empty: HashMap<String, [u8; 32]> = HashMap::new();
+    let result = ChunkedBlobBuilder::new().build_encrypted(&data[..], encrypt_ref(), &empty);
+    assert!(matches!(result, Err(Error::KeyNotFound(h)) if h == HANDLE));
+
+    let age = EncryptRef {
+        scheme: SCHEME_AGE,
+        ..encrypt_ref()
+    };
+    let result = ChunkedBlobBuilder::new().build_encrypted(&data[..], age, &keys());
+    assert!(matches!(result, Err(Error::UnsupportedScheme(1))));
+
+    let (build, ciphertext) = encrypt(ChunkedBlobBuilder::new().chunk_size(S), &data);
+    let nodes: HashMap<_, _> = build.nodes.into_iter().collect();
+    let mut plain_ref = build.blob_ref;
+    plain_ref.encryption = None;
+    let result = DecryptingReader::new(&plain_ref, &nodes, &ciphertext[..], &keys());
+    assert!(matches!(result, Err(Error::InvalidBlobRef(_))));
+}
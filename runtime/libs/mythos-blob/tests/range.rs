@@ -0,0 +1,201 @@
+/// Verified byte-range read tests
+use mythos_blob::{
+    cid_from_bytes, read_range, ChunkSource, ChunkedBlobBuilder, Error, NodeSource, RangeProof,
+    Result, MIN_CHUNK_SIZE,
+};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::io::{self, Read};
+
+const S: u64 = MIN_CHUNK_SIZE;
+
+fn pattern(len: usize) -> Vec<u8> {
+    (0..len).map(|i| (i * 31 % 251) as u8).collect()
+}
+
+type Store = HashMap<[u8; 32], Vec<u8>>;
+
+fn build<R: Read>(data: R) -> ([u8; 32], Store, Store) {
+    let mut chunks = HashMap::new();
+    let build = ChunkedBlobBuilder::new()
+        .chunk_size(S)
+        .build_with(data, |_, hash, bytes| {
+            chunks.insert(*hash, bytes.to_vec());
+            Ok(())
+        })
+        .unwrap();
+    (build.root, build.nodes.into_iter().collect(), chunks)
+}
+
+/// Source that records every CID requested
+struct Counting<'a> {
+    inner: &'a Store,
+    fetched: RefCell<Vec<[u8; 32]>>,
+}
+
+impl<'a> Counting<'a> {
+    fn new(inner: &'a Store) -> Self {
+        Counting {
+            inner,
+            fetched: RefCell::new(Vec::new()),
+        }
+    }
+
+    fn count(&self) -> usize {
+        self.fetched.borrow().len()
+    }
+}
+
+impl NodeSource for Counting<'_> {
+    fn fetch_node(&self, cid: &[u8; 32]) -> Result<Vec<u8>> {
+        self.fetched.borrow_mut().push(*cid);
+        self.inner.fetch_node(cid)
+    }
+}
+
+impl ChunkSource for Counting<'_> {
+    fn fetch_chunk(&self, hash: &[u8; 32]) -> Result<Vec<u8>> {
+        self.fetched.borrow_mut().push(*hash);
+        self.inner.fetch_chunk(hash)
+    }
+}
+
+#[test]
+fn test_range_within_one_chunk() {
+    let data = pattern(S as usize * 3 + 100);
+    let (root, nodes, chunks) = build(&data[..]);
+
+    let start = S + 10;
+    let end = S + 5000;
+    let chunk_source = Counting::new(&chunks);
+    let read = read_range(&root, start..end, &nodes, &chunk_source).unwrap();
+
+    assert_eq!(read.data, data[start as usize..end as usize]);
+    assert_eq!(chunk_source.count(), 1);
+    assert_eq!(read.proof.head.len(), 10);
+    assert_eq!(read.proof.tail.len(), (2 * S - end) as usize);
+    read.proof.verify(&root, &read.data).unwrap();
+}
+
+#[test]
+fn test_range_spanning_chunks() {
+    let data = pattern(S as usize * 3 + 100);
+    let (root, nodes, chunks) = build(&data[..]);
+
+    let start = S - 7;
+    let end = data.len() as u64;
+    let chunk_source = Counting::new(&chunks);
+    let read = read_range(&root, start..end, &nodes, &chunk_source).unwrap();
+
+    assert_eq!(read.data, data[start as usize..]);
+    assert_eq!(chunk_source.count(), 4);
+    assert!(read.proof.tail.is_empty());
+    read.proof.verify(&root, &read.data).unwrap();
+}
+
+#[test]
+fn test_range_fetches_only_covering_nodes() {
+    // 1026 chunks: root ChunkInternal over a full leaf and a leaf of 2
+    let size = S * 1025 + 10;
+    let (root, nodes, chunks) = build(io::repeat(0x5a).take(size));
+    assert_eq!(nodes.len(), 3);
+
+    let node_source = Counting::new(&nodes);
+    let chunk_source = Counting::new(&chunks);
+    let read = read_range(&root, S * 1024 + 3..size, &node_source, &chunk_source).unwrap();
+
+    assert_eq!(read.data, vec![0x5a; (S + 7) as usize]);
+    // Root and the second leaf only; the full first leaf is never fetched
+    assert_eq!(node_source.count(), 2);
+    assert_eq!(read.proof.nodes.len(), 2);
+    // Chunks 1024 and 1025
+    assert_eq!(chunk_source.count(), 2);
+    read.proof.verify(&root, &read.data).unwrap();
+}
+
+#[test]
+fn test_proof_roundtrip_and_third_party_verify() {
+    let data = pattern(S as usize * 2 + 1);
+    let (root, nodes, chunks) = build(&data[..]);
+
+    let read = read_range(&root, 100..S + 100, &nodes, &chunks).unwrap();
+    let bytes = read.proof.to_bytes().unwrap();
+    let decoded = RangeProof::from_bytes(&bytes).unwrap();
+
+    assert_eq!(decoded, read.proof);
+    assert_eq!(decoded.to_bytes().unwrap(), bytes);
+    decoded.verify(&root, &read.data).unwrap();
+}
+
+#[test]
+fn test_proof_rejects_tampered_data() {
+    let data = pattern(S as usize * 2);
+    let (root, nodes, chunks) = build(&data[..]);
+
+    let read = read_range(&root, 10..S + 10, &nodes, &chunks).unwrap();
+    let mut tampered = read.data.clone();
+    tampered[S as usize - 1] ^= 1;
+
+    let result = read.proof.verify(&root, &tampered);
+    assert!(matches!(result, Err(Error::ChunkHashMismatch { index: 1, offset }) if offset == S));
+
+    let short = read.proof.verify(&root, &read.data[1..]);
+    assert!(matches!(short, Err(Error::InvalidProof(_))));
+}
+
+#[test]
+fn test_proof_rejects_other_root() {
+    let data = pattern(S as usize + 10);
+    let (root, nodes, chunks) = build(&data[..]);
+    let (other, _, _) = build(&pattern(S as usize + 11)[..]);
+
+    let read = read_range(&root, 0..10, &nodes, &chunks).unwrap();
+    let result = read.proof.verify(&other, &read.data);
+    assert!(matches!(result, Err(Error::NodeNotFound(_))));
+}
+
+#[test]
+fn test_bad_chunk_from_source_rejected() {
+    let data = pattern(S as usize * 2);
+    let (root, nodes, mut chunks) = build(&data[..]);
+
+    let hash = cid_from_bytes(&data[S as usize..]);
+    chunks.get_mut(&hash).unwrap()[0] ^= 1;
+
+    let result = read_range(&root, S..S + 1, &nodes, &chunks);
+    assert!(matches!(
+        result,
+        Err(Error::ChunkHashMismatch { index: 1, .. })
+    ));
+}
+
+#[test]
+fn test_range_outside_blob_rejected() {
+    let data = pattern(100);
+    let (root, nodes, chunks) = build(&data[..]);
+
+    let result = read_range(&root, 50..101, &nodes, &chunks);
+    assert!(matches!(
+        result,
+        Err(Error::InvalidRange {
+            start: 50,
+            end: 101,
+            size: 100
+        })
+    ));
+
+    #[allow(clippy::reversed_empty_ranges)]
+    let reversed = read_range(&root, 60..50, &nodes, &chunks);
+    assert!(matches!(reversed, Err(Error::InvalidRange { .. })));
+}
+
+#[test]
+fn test_empty_range() {
+    let data = pattern(100);
+    let (root, nodes, chunks) = build(&data[..]);
+
+    let read = read_range(&root, 100..100, &nodes, &chunks).unwrap();
+    assert!(read.data.is_empty());
+    assert_eq!(read.proof.nodes.len(), 1);
+    read.proof.verify(&root, &[]).unwrap();
+}
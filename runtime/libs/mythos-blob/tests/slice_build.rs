@@ -0,0 +1,125 @@
+/// Slice and file builds (sequential or `parallel` feature)
+use mythos_blob::{
+    cid_from_bytes, compute_chunk_hashes, ChunkedBlobBuilder, Error, MIN_CHUNK_SIZE,
+};
+use std::fs;
+use std::path::PathBuf;
+
+const S: u64 = MIN_CHUNK_SIZE;
+
+fn pattern(len: usize) -> Vec<u8> {
+    (0..len).map(|i| (i * 31 % 251) as u8).collect()
+}
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("mythos-blob-{}-{}", std::process::id(), name))
+}
+
+#[test]
+fn test_chunk_hashes_in_order() {
+    let data = pattern(10_000);
+    let hashes = compute_chunk_hashes(&data, 1000);
+
+    assert_eq!(hashes.len(), 10);
+    for (i, hash) in hashes.iter().enumerate() {
+        assert_eq!(*hash, cid_from_bytes(&data[i * 1000..(i + 1) * 1000]));
+    }
+}
+
+#[test]
+fn test_slice_build_matches_streaming() {
+    let builder = ChunkedBlobBuilder::new().chunk_size(S);
+
+    for len in [1, S as usize, S as usize * 5 + 3] {
+        let data = pattern(len);
+        let streaming = builder.build(&data[..]).unwrap();
+        let slice = builder.build_slice(&data).unwrap();
+
+        assert_eq!(slice.root, streaming.root);
+        assert_eq!(slice.nodes, streaming.nodes);
+        assert_eq!(slice.blob_ref, streaming.blob_ref);
+    }
+}
+
+#[test]
+fn test_slice_build_above_fanout() {
+    // 1026 chunks, so the slice path also builds a ChunkInternal root
+    let data = pattern(S as usize * 1025 + 10);
+    let builder = ChunkedBlobBuilder::new().chunk_size(S);
+
+    let mut seen = Vec::new();
+    let slice = builder
+        .build_slice_with(&data, |index, _, bytes| {
+            seen.push((index, bytes.len()));
+            Ok(())
+        })
+        .unwrap();
+    let streaming = builder.build(&data[..]).unwrap();
+
+    assert_eq!(slice.nodes, streaming.nodes);
+    assert_eq!(seen.len(), 1026);
+    assert!(seen
+        .iter()
+        .enumerate()
+        .all(|(i, (index, _))| i as u64 == *index));
+    assert_eq!(seen.last(), Some(&(1025, 10)));
+}
+
+#[cfg(feature = "zstd")]
+#[test]
+fn test_slice_build_with_codec_falls_back() {
+    use mythos_blob::CODEC_ZSTD;
+
+    let data = pattern(S as usize * 2);
+    let builder = ChunkedBlobBuilder::new().chunk_size(S).codec(CODEC_ZSTD);
+
+    let slice = builder.build_slice(&data).unwrap();
+    let streaming = builder.build(&data[..]).unwrap();
+    assert_eq!(slice.root, streaming.root);
+    assert_eq!(slice.blob_ref.codec, CODEC_ZSTD);
+}
+
+#[test]
+fn test_file_build_matches_streaming() {
+    let data = pattern(S as usize * 3 + 17);
+    let path = temp_path("file-build");
+    fs::write(&path, &data).unwrap();
+
+    let builder = ChunkedBlobBuilder::new()
+        .chunk_size(S)
+        .media("model/weights");
+    let file = builder.build_file(&path);
+    fs::remove_file(&path).unwrap();
+
+    let file = file.unwrap();
+    let streaming = builder.build(&data[..]).unwrap();
+    assert_eq!(file.root, streaming.root);
+    assert_eq!(file.blob_ref, streaming.blob_ref);
+}
+
+#[test]
+fn test_file_build_errors() {
+    let path = temp_path("empty");
+    fs::write(&path, b"").unwrap();
+    let empty = ChunkedBlobBuilder::new().build_file(&path);
+    fs::remove_file(&path).unwrap();
+    assert!(matches!(empty, Err(Error::EmptyBlob)));
+
+    let missing = ChunkedBlobBuilder::new().build_file(temp_path("missing"));
+    assert!(matches!(missing, Err(Error::Io(_))));
+}
+
+#[cfg(feature = "mmap")]
+#[test]
+fn test_mmap_build_matches_streaming() {
+    let data = pattern(S as usize * 2 + 5);
+    let path = temp_path("mmap-build");
+    fs::write(&path, &data).unwrap();
+
+    let builder = ChunkedBlobBuilder::new().chunk_size(S);
+    // SAFETY: the file is private to this test and unchanged until removed
+    let mapped = unsafe { builder.build_file_mmap(&path) };
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(mapped.unwrap().root, builder.build(&data[..]).unwrap().root);
+}
@@ -0,0 +1,200 @@
+/// Streaming verification tests (RFC-0004 §7)
+use mythos_blob::{
+    cid_from_bytes, encode_chunk_internal, encode_chunk_leaf, ChunkDesc, ChunkedBlobBuilder, Error,
+    VerifyingReader, MIN_CHUNK_SIZE,
+};
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, Read};
+
+const VECTORS_PATH: &str = "../../../mythos-v0.2-conformance/vectors/blob";
+const S: u64 = MIN_CHUNK_SIZE;
+
+fn pattern(len: usize) -> Vec<u8> {
+    (0..len).map(|i| (i * 31 % 251) as u8).collect()
+}
+
+fn build(data: &[u8]) -> ([u8; 32], HashMap<[u8; 32], Vec<u8>>) {
+    let build = ChunkedBlobBuilder::new().chunk_size(S).build(data).unwrap();
+    (build.root, build.nodes.into_iter().collect())
+}
+
+fn desc(bytes: &[u8]) -> ChunkDesc {
+    ChunkDesc {
+        hash: cid_from_bytes(bytes).to_vec(),
+        len: bytes.len() as u64,
+    }
+}
+
+/// Read until error, returning bytes delivered and the verification error
+fn read_until_error<R: Read>(mut reader: R) -> (Vec<u8>, Error) {
+    let mut out = Vec::new();
+    let mut buf = [0u8; 8192];
+    loop {
+        match reader.read(&mut buf) {
+            Ok(0) => panic!("Expected verification failure"),
+            Ok(n) => out.extend_from_slice(&buf[..n]),
+            Err(e) => {
+                let inner = e
+                    .into_inner()
+                    .expect("wrapped error")
+                    .downcast::<Error>()
+                    .expect("blob error");
+                return (out, *inner);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_roundtrip_single_leaf() {
+    let data = pattern(S as usize * 3 + 123);
+    let (root, nodes) = build(&data);
+
+    let mut reader = VerifyingReader::new(&root, &nodes, &data[..]).unwrap();
+    assert_eq!(reader.total_size(), data.len() as u64);
+    assert_eq!(reader.chunk_count(), 4);
+
+    let mut out = Vec::new();
+    reader.read_to_end(&mut out).unwrap();
+    assert_eq!(out, data);
+}
+
+#[test]
+fn test_roundtrip_with_internal_nodes() {
+    let size = S * 1025 + 10;
+    let build = ChunkedBlobBuilder::new()
+        .chunk_size(S)
+        .build(io::repeat(0x5a).take(size))
+        .unwrap();
+    let nodes: HashMap<_, _> = build.nodes.into_iter().collect();
+
+    let reader = VerifyingReader::new(&build.root, &nodes, io::repeat(0x5a).take(size)).unwrap();
+    let copied = io::copy(&mut { reader }, &mut io::sink()).unwrap();
+    assert_eq!(copied, size);
+}
+
+#[test]
+fn test_blob_001_payload_verifies() {
+    let rootnode = fs::read(format!("{}/chunkedblob_001_rootnode.bin", VECTORS_PATH)).unwrap();
+    let payload = fs::read(format!("{}/chunkedblob_001_payload.bin", VECTORS_PATH)).unwrap();
+    let root = cid_from_bytes(&rootnode);
+    let nodes = HashMap::from([(root, rootnode)]);
+
+    let mut out = Vec::new();
+    VerifyingReader::new(&root, &nodes, &payload[..])
+        .unwrap()
+        .read_to_end(&mut out)
+        .unwrap();
+    assert_eq!(out, payload);
+}
+
+#[test]
+fn test_bad_chunk_fails_at_that_chunk() {
+    let data = pattern(S as usize * 4);
+    let (root, nodes) = build(&data);
+
+    let mut corrupted = data.clone();
+    corrupted[S as usize * 2 + 5] ^= 0xff;
+
+    let reader = VerifyingReader::new(&root, &nodes, &corrupted[..]).unwrap();
+    let (delivered, err) = read_until_error(reader);
+
+    // Chunks 0 and 1 verified and were delivered; nothing from chunk 2
+    assert_eq!(delivered, data[..S as usize * 2]);
+    assert!(matches!(
+        err,
+        Error::ChunkHashMismatch { index: 2, offset } if offset == 2 * S
+    ));
+}
+
+#[test]
+fn test_truncated_stream_fails() {
+    let data = pattern(S as usize * 2 + 50);
+    let (root, nodes) = build(&data);
+
+    let reader = VerifyingReader::new(&root, &nodes, &data[..data.len() - 1]).unwrap();
+    let (_, err) = read_until_error(reader);
+
+    assert!(matches!(
+        err,
+        Error::ChunkLengthMismatch {
+            index: 2,
+            expected: 50,
+            actual: 49,
+            ..
+        }
+    ));
+}
+
+#[test]
+fn test_trailing_data_fails() {
+    let data = pattern(S as usize + 50);
+    let (root, nodes) = build(&data);
+
+    let mut extended = data.clone();
+    extended.push(0);
+
+    let reader = VerifyingReader::new(&root, &nodes, &extended[..]).unwrap();
+    let (delivered, err) = read_until_error(reader);
+
+    assert_eq!(delivered, data);
+    assert!(matches!(err, Error::TrailingData(_)));
+}
+
+#[test]
+fn test_short_middle_chunk_rejected() {
+    // A leaf whose middle chunk is shorter than chunk_size is not canonical
+    let chunks = [pattern(S as usize), pattern(S as usize - 1), pattern(10)];
+    let leaf = encode_chunk_leaf(S, &chunks.iter().map(|c| desc(c)).collect::<Vec<_>>()).unwrap();
+    let root = cid_from_bytes(&leaf);
+    let nodes = HashMap::from([(root, leaf)]);
+    let data = chunks.concat();
+
+    let reader = VerifyingReader::new(&root, &nodes, &data[..]).unwrap();
+    let (_, err) = read_until_error(reader);
+
+    assert!(matches!(
+        err,
+        Error::ChunkLengthMismatch { index: 1, offset, .. } if offset == S
+    ));
+}
+
+#[test]
+fn test_internal_total_size_checked() {
+    let a = pattern(S as usize);
+    let b = pattern(10);
+    let leaf_a = encode_chunk_leaf(S, &[desc(&a)]).unwrap();
+    let leaf_b = encode_chunk_leaf(S, &[desc(&b)]).unwrap();
+    let children = [cid_from_bytes(&leaf_a), cid_from_bytes(&leaf_b)];
+
+    // Declares one byte more than its children cover
+    let internal = encode_chunk_internal(S, &children, S + 11, 2).unwrap();
+    let root = cid_from_bytes(&internal);
+    let nodes = HashMap::from([
+        (children[0], leaf_a),
+        (children[1], leaf_b),
+        (root, internal),
+    ]);
+    let data = [a, b].concat();
+
+    let reader = VerifyingReader::new(&root, &nodes, &data[..]).unwrap();
+    let (_, err) = read_until_error(reader);
+
+    assert!(matches!(
+        err,
+        Error::SizeMismatch { expected, actual } if expected == S + 11 && actual == S + 10
+    ));
+}
+
+#[test]
+fn test_tampered_node_rejected() {
+    let data = pattern(100);
+    let root_a = build(&pattern(S as usize + 1)).0;
+    let (_, nodes) = build(&data);
+    let bytes = nodes.values().next().unwrap().clone();
+    let tampered = HashMap::from([(root_a, bytes)]);
+
+    let result = VerifyingReader::new(&root_a, &tampered, &data[..]);
+    assert!(matches!(result, Err(Error::CidMismatch { .. })));
+}
@@ -18,7 +18,7 @@ pub fn decode_value(bytes: &[u8]) -> Result<Value> {
 /// This is the strict decoding mode for test vectors and validation.
 /// Use this when decoding a complete buffer that should contain exactly one value.
 pub fn decode_value_exact(bytes: &[u8]) -> Result<Value> {
-    let mut reader = &bytes[..];
+    let mut reader = bytes;
     let value = decode_value_from(&mut reader)?;
 
     // Check for trailing bytes
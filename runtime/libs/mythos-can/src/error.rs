@@ -31,6 +31,12 @@ pub enum Error {
 
     #[error("Trailing bytes after value: {0} bytes remaining")]
     TrailingBytes(usize),
+
+    #[error("Invalid structure: {0}")]
+    InvalidStructure(String),
+
+    #[error("Hash must be SHA-256 with 32 bytes: {0}")]
+    InvalidHash(String),
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -19,10 +19,13 @@
 //! - `0x06`: TEXT (UTF-8 string)
 //! - `0x07`: LIST (ordered list of values)
 //! - `0x08`: MAP (key-value pairs, sorted by encoded key bytes)
+//!
+//! `structs` holds the field helpers typed structs are decoded with.
 
 mod decoder;
 mod encoder;
 mod error;
+pub mod structs;
 mod value;
 pub mod varint;
 
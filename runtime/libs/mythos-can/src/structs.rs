@@ -0,0 +1,164 @@
+/// Helpers for typed structs encoded as MYTHOS-CAN MAPs
+///
+/// Structs are MAPs keyed by UVARINT field numbers; a Hash is the struct
+/// `{ 1: alg=1, 2: 32 bytes }`. Crates defining typed structs decode them
+/// with these helpers and convert the `InvalidStructure` and `InvalidHash`
+/// errors into their own.
+use crate::{Error, Result, Value};
+
+pub fn struct_fields<'a>(value: &'a Value, name: &str) -> Result<&'a [(Value, Value)]> {
+    match value {
+        Value::Map(pairs) => Ok(pairs),
+        _ => Err(Error::InvalidStructure(format!("{} must be MAP", name))),
+    }
+}
+
+pub fn field(fields: &[(Value, Value)], n: u64) -> Option<&Value> {
+    fields
+        .iter()
+        .find(|(k, _)| matches!(k, Value::UVarint(x) if *x == n))
+        .map(|(_, v)| v)
+}
+
+/// Reject field numbers a struct does not define
+pub fn known_fields(fields: &[(Value, Value)], max: u64, name: &str) -> Result<()> {
+    for (k, _) in fields {
+        match k {
+            Value::UVarint(n) if (1..=max).contains(n) => {}
+            other => {
+                return Err(Error::InvalidStructure(format!(
+                    "{} has unknown field {:?}",
+                    name, other
+                )))
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The struct without the `excluded` fields, e.g. to compute an id over
+pub fn without_fields(fields: Vec<(Value, Value)>, excluded: &[u64]) -> Value {
+    Value::Map(
+        fields
+            .into_iter()
+            .filter(|(k, _)| !matches!(k, Value::UVarint(n) if excluded.contains(n)))
+            .collect(),
+    )
+}
+
+pub fn required<'a>(value: Option<&'a Value>, name: &str) -> Result<&'a Value> {
+    value.ok_or_else(|| Error::InvalidStructure(format!("Missing {}", name)))
+}
+
+pub fn hash_value(bytes: &[u8; 32]) -> Value {
+    Value::Map(vec![
+        (Value::UVarint(1), Value::UVarint(1)),
+        (Value::UVarint(2), Value::Bytes(bytes.to_vec())),
+    ])
+}
+
+pub fn required_hash(value: Option<&Value>, name: &str) -> Result<[u8; 32]> {
+    let fields = match value {
+        Some(Value::Map(pairs)) => pairs,
+        Some(_) => {
+            return Err(Error::InvalidHash(format!(
+                "{} must be a Hash struct",
+                name
+            )))
+        }
+        None => return Err(Error::InvalidStructure(format!("Missing {}", name))),
+    };
+    if fields.len() != 2 || !matches!(field(fields, 1), Some(Value::UVarint(1))) {
+        return Err(Error::InvalidHash(format!("{} must have alg 1", name)));
+    }
+    match field(fields, 2) {
+        Some(Value::Bytes(bytes)) => bytes
+            .as_slice()
+            .try_into()
+            .map_err(|_| Error::InvalidHash(format!("{} has {} bytes", name, bytes.len()))),
+        _ => Err(Error::InvalidHash(format!("{} missing bytes", name))),
+    }
+}
+
+pub fn required_uint(value: Option<&Value>, name: &str, max: u64) -> Result<u64> {
+    match value {
+        Some(Value::UVarint(n)) if *n <= max => Ok(*n),
+        Some(Value::UVarint(n)) => Err(Error::InvalidStructure(format!(
+            "{} out of range: {}",
+            name, n
+        ))),
+        Some(_) => Err(Error::InvalidStructure(format!("{} must be UVARINT", name))),
+        None => Err(Error::InvalidStructure(format!("Missing {}", name))),
+    }
+}
+
+pub fn optional_list<'a>(value: Option<&'a Value>, name: &str) -> Result<Option<&'a [Value]>> {
+    match value {
+        None => Ok(None),
+        Some(Value::List(items)) => Ok(Some(items)),
+        Some(_) => Err(Error::InvalidStructure(format!("{} must be LIST", name))),
+    }
+}
+
+pub fn required_list<'a>(value: Option<&'a Value>, name: &str) -> Result<&'a [Value]> {
+    optional_list(value, name)?.ok_or_else(|| Error::InvalidStructure(format!("Missing {}", name)))
+}
+
+pub fn required_bytes<'a>(value: Option<&'a Value>, name: &str) -> Result<&'a [u8]> {
+    match value {
+        Some(Value::Bytes(bytes)) => Ok(bytes),
+        Some(_) => Err(Error::InvalidStructure(format!("{} must be BYTES", name))),
+        None => Err(Error::InvalidStructure(format!("Missing {}", name))),
+    }
+}
+
+/// Time (RFC-0001 A.2): IVARINT microseconds since epoch
+pub fn required_time(value: Option<&Value>, name: &str) -> Result<i64> {
+    match value {
+        Some(Value::IVarint(t)) => Ok(*t),
+        Some(_) => Err(Error::InvalidStructure(format!("{} must be IVARINT", name))),
+        None => Err(Error::InvalidStructure(format!("Missing {}", name))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hash_roundtrip() {
+        let value = hash_value(&[7; 32]);
+        assert_eq!(required_hash(Some(&value), "h").unwrap(), [7; 32]);
+
+        let short = Value::Map(vec![
+            (Value::UVarint(1), Value::UVarint(1)),
+            (Value::UVarint(2), Value::Bytes(vec![7; 31])),
+        ]);
+        assert!(matches!(
+            required_hash(Some(&short), "h"),
+            Err(Error::InvalidHash(_))
+        ));
+        assert!(matches!(
+            required_hash(None, "h"),
+            Err(Error::InvalidStructure(_))
+        ));
+    }
+
+    #[test]
+    fn test_fields() {
+        let value = Value::Map(vec![
+            (Value::UVarint(1), Value::UVarint(5)),
+            (Value::UVarint(2), Value::List(vec![])),
+        ]);
+        let fields = struct_fields(&value, "S").unwrap();
+        assert_eq!(required_uint(field(fields, 1), "S.a", 5).unwrap(), 5);
+        assert!(required_uint(field(fields, 1), "S.a", 4).is_err());
+        assert!(optional_list(field(fields, 3), "S.c").unwrap().is_none());
+        assert!(known_fields(fields, 2, "S").is_ok());
+        assert!(known_fields(fields, 1, "S").is_err());
+        assert_eq!(
+            without_fields(fields.to_vec(), &[2]),
+            Value::Map(vec![(Value::UVarint(1), Value::UVarint(5))])
+        );
+    }
+}
@@ -81,7 +81,8 @@ pub fn decode_uvarint(reader: &mut impl Read) -> Result<u64> {
 /// - 1 → 2
 /// - -2 → 3
 /// - 2 → 4
-/// ...
+///
+/// and so on.
 ///
 /// # Example
 /// ```
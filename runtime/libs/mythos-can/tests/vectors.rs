@@ -6,7 +6,7 @@ use mythos_can::{decode_value, decode_value_exact, encode_value, Value};
 use sha2::{Digest, Sha256};
 use std::ffi::OsStr;
 use std::fs;
-use std::path::{Path, PathBuf};
+use std::path::Path;
 
 const VECTORS_PATH: &str = "../../../mythos-v0.2-conformance/vectors/can";
 
@@ -160,7 +160,7 @@ fn test_all_can_vectors_data_driven() {
 
             tested += 1;
 
-            match test_single_vector(&stem) {
+            match test_single_vector(stem) {
                 Ok(()) => {
                     println!("✅ PASS: {}", stem);
                     passed += 1;
@@ -195,7 +195,7 @@ fn test_single_vector(name: &str) -> Result<(), String> {
             fs::read_to_string(&sha_path).map_err(|e| format!("Failed to read .sha256: {}", e))?;
 
         if !verify_sha256(&bin_data, &expected_sha) {
-            return Err(format!("SHA256 mismatch"));
+            return Err("SHA256 mismatch".to_string());
         }
     }
 
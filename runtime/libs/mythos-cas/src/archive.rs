@@ -0,0 +1,226 @@
+/// Object archives (CAR-style import/export)
+///
+/// One stream holding a set of roots and every object reachable from them,
+/// for moving DAGs between stores that share no network. Each frame is a
+/// UVARINT byte length followed by that many bytes of canonical
+/// MYTHOS-CAN:
+///
+/// ```text
+/// header := { 1: version=1, 2: [Hash, ...] roots }
+/// record := { 1: Hash cid, 2: bytes }
+/// archive := frame(header) frame(record)*
+/// ```
+///
+/// Export writes each object `walk` reaches once, parents before children.
+/// Import re-hashes every record before it is stored and fails if a
+/// claimed root never appears. Objects stored before such a failure are
+/// left unpinned for GC to reclaim.
+use crate::error::{Error, Result};
+use crate::registry::Registry;
+use crate::walk::{walk, WalkOptions};
+use crate::{BlobStore, Cid};
+use mythos_can::Value;
+use std::collections::HashSet;
+use std::io::{self, Read, Write};
+
+pub const ARCHIVE_VERSION: u64 = 1;
+
+/// Largest frame export will write and import will buffer (chunks are at
+/// most 16 MiB)
+pub const MAX_FRAME_LEN: u64 = 32 * 1024 * 1024;
+
+/// What an export or import moved
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ArchiveSummary {
+    pub roots: Vec<Cid>,
+    pub objects: u64,
+    pub bytes: u64,
+}
+
+/// Write `roots` and every object reachable from them to `writer`
+///
+/// Fails with `Error::NotFound` if any required reachable object is
+/// missing, so an archive is never silently incomplete, and with
+/// `Error::InvalidArchive` if an object does not fit in one frame of
+/// `MAX_FRAME_LEN`, which import would refuse. Optional references (see
+/// `Reference::optional`) are included when present.
+pub fn export_archive<S, W>(store: &S, roots: &[Cid], writer: &mut W) -> Result<ArchiveSummary>
+where
+    S: BlobStore + ?Sized,
+    W: Write,
+{
+    export_archive_with(store, Registry::builtin(), roots, writer)
+}
+
+/// `export_archive` following the references `registry` extracts
+pub fn export_archive_with<S, W>(
+    store: &S,
+    registry: &Registry,
+    roots: &[Cid],
+    writer: &mut W,
+) -> Result<ArchiveSummary>
+where
+    S: BlobStore + ?Sized,
+    W: Write,
+{
+    let header = Value::Map(vec![
+        (Value::UVarint(1), Value::UVarint(ARCHIVE_VERSION)),
+        (
+            Value::UVarint(2),
+            Value::List(roots.iter().map(hash_value).collect()),
+        ),
+    ]);
+    write_frame(writer, &header)?;
+
+    let mut summary = ArchiveSummary {
+        roots: roots.to_vec(),
+        ..ArchiveSummary::default()
+    };
+    let walked = walk(store, registry, roots, &WalkOptions::default(), |visit| {
+        summary.objects += 1;
+        summary.bytes += visit.bytes.len() as u64;
+        let record = Value::Map(vec![
+            (Value::UVarint(1), hash_value(&visit.cid)),
+            (Value::UVarint(2), Value::Bytes(visit.bytes.to_vec())),
+        ]);
+        write_frame(writer, &record)
+    })?;
+    if let Some(missing) = walked.missing.first() {
+        return Err(Error::NotFound(hex::encode(missing)));
+    }
+
+    writer.flush()?;
+    Ok(summary)
+}
+
+/// Verify and store every object in an archive read from `reader`
+pub fn import_archive<S, R>(store: &S, reader: &mut R) -> Result<ArchiveSummary>
+where
+    S: BlobStore + ?Sized,
+    R: Read,
+{
+    let header =
+        read_frame(reader)?.ok_or_else(|| Error::InvalidArchive("missing header".into()))?;
+    let fields = map_fields(&header, "header")?;
+
+    match field(fields, 1) {
+        Some(Value::UVarint(ARCHIVE_VERSION)) => {}
+        Some(Value::UVarint(v)) => {
+            return Err(Error::InvalidArchive(format!("unsupported version {}", v)))
+        }
+        _ => return Err(Error::InvalidArchive("missing version".into())),
+    }
+    let roots = match field(fields, 2) {
+        Some(Value::List(roots)) => roots.iter().map(parse_hash).collect::<Result<Vec<_>>>()?,
+        _ => return Err(Error::InvalidArchive("missing roots".into())),
+    };
+
+    let mut summary = ArchiveSummary {
+        roots: roots.clone(),
+        ..ArchiveSummary::default()
+    };
+    let mut seen = HashSet::new();
+
+    while let Some(record) = read_frame(reader)? {
+        let fields = map_fields(&record, "record")?;
+        let cid = match field(fields, 1) {
+            Some(hash) => parse_hash(hash)?,
+            None => return Err(Error::InvalidArchive("record missing cid".into())),
+        };
+        let bytes = match field(fields, 2) {
+            Some(Value::Bytes(bytes)) => bytes,
+            _ => return Err(Error::InvalidArchive("record missing bytes".into())),
+        };
+
+        // put_with_cid re-hashes before anything becomes visible
+        store.put_with_cid(&cid, bytes)?;
+        if seen.insert(cid) {
+            summary.objects += 1;
+            summary.bytes += bytes.len() as u64;
+        }
+    }
+
+    if let Some(missing) = roots.iter().find(|root| !seen.contains(*root)) {
+        return Err(Error::MissingRoot(hex::encode(missing)));
+    }
+    Ok(summary)
+}
+
+fn hash_value(cid: &Cid) -> Value {
+    Value::Map(vec![
+        (Value::UVarint(1), Value::UVarint(1)),
+        (Value::UVarint(2), Value::Bytes(cid.to_vec())),
+    ])
+}
+
+fn parse_hash(value: &Value) -> Result<Cid> {
+    let fields = map_fields(value, "Hash")?;
+    if !matches!(field(fields, 1), Some(Value::UVarint(1))) {
+        return Err(Error::InvalidArchive("Hash must be SHA-256".into()));
+    }
+    match field(fields, 2) {
+        Some(Value::Bytes(bytes)) => Cid::try_from(&bytes[..]).map_err(|_| {
+            Error::InvalidArchive(format!("Hash must be 32 bytes, got {}", bytes.len()))
+        }),
+        _ => Err(Error::InvalidArchive("Hash missing bytes".into())),
+    }
+}
+
+fn map_fields<'a>(value: &'a Value, what: &str) -> Result<&'a [(Value, Value)]> {
+    match value {
+        Value::Map(pairs) => Ok(pairs),
+        _ => Err(Error::InvalidArchive(format!("{} must be MAP", what))),
+    }
+}
+
+fn field(fields: &[(Value, Value)], n: u64) -> Option<&Value> {
+    fields
+        .iter()
+        .find(|(k, _)| matches!(k, Value::UVarint(x) if *x == n))
+        .map(|(_, v)| v)
+}
+
+fn write_frame<W: Write>(writer: &mut W, value: &Value) -> Result<()> {
+    let bytes = mythos_can::encode_value(value)
+        .map_err(|e| Error::InvalidArchive(format!("encode failed: {}", e)))?;
+    if bytes.len() as u64 > MAX_FRAME_LEN {
+        return Err(Error::InvalidArchive(format!(
+            "frame too large: {}",
+            bytes.len()
+        )));
+    }
+    let mut frame = Vec::with_capacity(bytes.len() + 10);
+    mythos_can::encode_uvarint(&mut frame, bytes.len() as u64)
+        .map_err(|e| Error::InvalidArchive(format!("frame length: {}", e)))?;
+    frame.extend_from_slice(&bytes);
+    writer.write_all(&frame)?;
+    Ok(())
+}
+
+/// Read one frame, or `None` at a clean end of stream
+fn read_frame<R: Read>(reader: &mut R) -> Result<Option<Value>> {
+    let mut first = [0u8; 1];
+    loop {
+        match reader.read(&mut first) {
+            Ok(0) => return Ok(None),
+            Ok(_) => break,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e.into()),
+        }
+    }
+
+    let len = mythos_can::decode_uvarint(&mut (&first[..]).chain(&mut *reader))
+        .map_err(|e| Error::InvalidArchive(format!("frame length: {}", e)))?;
+    if len > MAX_FRAME_LEN {
+        return Err(Error::InvalidArchive(format!("frame too large: {}", len)));
+    }
+
+    let mut bytes = vec![0u8; len as usize];
+    reader.read_exact(&mut bytes).map_err(|e| match e.kind() {
+        io::ErrorKind::UnexpectedEof => Error::InvalidArchive("truncated frame".into()),
+        _ => e.into(),
+    })?;
+    let value = mythos_can::decode_value_exact(&bytes)
+        .map_err(|e| Error::InvalidArchive(format!("frame decode failed: {}", e)))?;
+    Ok(Some(value))
+}
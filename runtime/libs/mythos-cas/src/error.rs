@@ -0,0 +1,36 @@
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Object not found: {0}")]
+    NotFound(String),
+
+    #[error("Bytes do not match CID: expected {expected}, computed {computed}")]
+    CidMismatch { expected: String, computed: String },
+
+    #[error("Stored object is corrupt: {0}")]
+    Corrupt(String),
+
+    #[error("Object too large for a pack segment: {0} bytes")]
+    ObjectTooLarge(usize),
+
+    #[error("Store is locked by another process: {0}")]
+    Locked(String),
+
+    #[error("Invalid archive: {0}")]
+    InvalidArchive(String),
+
+    #[error("Archive root not present: {0}")]
+    MissingRoot(String),
+
+    #[error("Invalid pin record: {0}")]
+    InvalidPin(String),
+
+    #[error("Invalid lineage object: {0}")]
+    InvalidLineage(String),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
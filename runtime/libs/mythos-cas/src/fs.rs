@@ -0,0 +1,306 @@
+/// Filesystem BlobStore
+///
+/// Layout under the store root:
+///
+/// ```text
+/// objects/<first two hex chars>/<remaining 62 hex chars>
+/// pins/<64 hex chars>
+/// quarantine/<64 hex chars>
+/// tmp/<unique name>
+/// ```
+///
+/// Every write goes to a file in `tmp/`, is fsynced, checked against its
+/// CID and only then renamed into `objects/`; the shard directory is
+/// fsynced after the rename. A crash can leave stray temp files but never
+/// a partial object under a CID. Pin records are written the same way.
+///
+/// Putting an object that already exists bumps its mtime instead of
+/// rewriting it, which is what keeps re-ingested objects out of GC.
+/// `delete_if_older` first renames the object into `tmp/` and only then
+/// checks its mtime, putting it back if a put touched it; a put checks
+/// that the object is still in place after touching it and writes it
+/// afresh if not. Either way a put that returns Ok leaves the object
+/// stored.
+use crate::error::{Error, Result};
+use crate::pin::{Pin, PinDir, PinStore};
+use crate::{cid_from_bytes, verify_cid, BlobStore, Cid, ObjectStat};
+use sha2::{Digest, Sha256};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime};
+
+static TEMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Sharded-directory store rooted at a local path
+#[derive(Debug, Clone)]
+pub struct FsStore {
+    root: PathBuf,
+}
+
+impl FsStore {
+    /// Open the store at `root`, creating its directories if needed
+    pub fn open<P: AsRef<Path>>(root: P) -> Result<Self> {
+        let root = root.as_ref().to_path_buf();
+        fs::create_dir_all(root.join("objects"))?;
+        fs::create_dir_all(root.join("pins"))?;
+        fs::create_dir_all(root.join("tmp"))?;
+        Ok(FsStore { root })
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Path an object with `cid` is stored at
+    pub fn object_path(&self, cid: &Cid) -> PathBuf {
+        let hex = hex::encode(cid);
+        self.root.join("objects").join(&hex[..2]).join(&hex[2..])
+    }
+
+    /// Where `quarantine` moves a corrupt object
+    pub fn quarantine_path(&self, cid: &Cid) -> PathBuf {
+        self.root.join("quarantine").join(hex::encode(cid))
+    }
+
+    fn tmp_dir(&self) -> PathBuf {
+        self.root.join("tmp")
+    }
+
+    fn pins_dir(&self) -> PinDir {
+        PinDir::new(self.root.join("pins"), self.tmp_dir())
+    }
+
+    /// Write `bytes` under `cid` unless the object already exists
+    fn write_object(&self, cid: &Cid, bytes: &[u8]) -> Result<()> {
+        let dest = self.object_path(cid);
+        if touch(&dest)? {
+            return Ok(());
+        }
+        write_atomic(&self.tmp_dir(), &dest, bytes)
+    }
+}
+
+impl BlobStore for FsStore {
+    fn put(&self, bytes: &[u8]) -> Result<Cid> {
+        let cid = cid_from_bytes(bytes);
+        self.write_object(&cid, bytes)?;
+        Ok(cid)
+    }
+
+    fn put_with_cid(&self, cid: &Cid, bytes: &[u8]) -> Result<()> {
+        verify_cid(cid, bytes)?;
+        self.write_object(cid, bytes)
+    }
+
+    fn put_stream(&self, reader: &mut dyn Read) -> Result<(Cid, u64)> {
+        let (temp, mut file) = temp_file(&self.tmp_dir())?;
+
+        let result = (|| {
+            let (cid, size) = spool(reader, &mut file)?;
+            file.sync_all()?;
+
+            let dest = self.object_path(&cid);
+            if touch(&dest)? {
+                fs::remove_file(&temp)?;
+            } else {
+                commit(&temp, &dest)?;
+            }
+            Ok((cid, size))
+        })();
+
+        if result.is_err() {
+            let _ = fs::remove_file(&temp);
+        }
+        result
+    }
+
+    fn get(&self, cid: &Cid) -> Result<Vec<u8>> {
+        let bytes = match fs::read(self.object_path(cid)) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                return Err(Error::NotFound(hex::encode(cid)))
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        verify_cid(cid, &bytes).map_err(|_| Error::Corrupt(hex::encode(cid)))?;
+        Ok(bytes)
+    }
+
+    fn has(&self, cid: &Cid) -> Result<bool> {
+        Ok(self.stat(cid)?.is_some())
+    }
+
+    fn stat(&self, cid: &Cid) -> Result<Option<ObjectStat>> {
+        match fs::metadata(self.object_path(cid)) {
+            Ok(meta) => Ok(Some(ObjectStat {
+                size: meta.len(),
+                modified: meta.modified()?,
+            })),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn delete(&self, cid: &Cid) -> Result<bool> {
+        match fs::remove_file(self.object_path(cid)) {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn delete_if_older(&self, cid: &Cid, cutoff: SystemTime) -> Result<bool> {
+        let path = self.object_path(cid);
+        let trash = temp_path(&self.tmp_dir());
+        match fs::rename(&path, &trash) {
+            Ok(()) => {}
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(false),
+            Err(e) => return Err(e.into()),
+        }
+
+        // A put that touched the object before the rename shows up here
+        if fs::metadata(&trash)?.modified()? >= cutoff {
+            commit(&trash, &path)?;
+            return Ok(false);
+        }
+        fs::remove_file(&trash)?;
+        Ok(true)
+    }
+
+    fn list(&self) -> Result<Vec<Cid>> {
+        let mut cids = Vec::new();
+        for shard in fs::read_dir(self.root.join("objects"))? {
+            let shard = shard?;
+            if !shard.file_type()?.is_dir() {
+                continue;
+            }
+            let prefix = shard.file_name().to_string_lossy().into_owned();
+            for entry in fs::read_dir(shard.path())? {
+                let name = entry?.file_name();
+                if let Some(cid) = parse_cid(&format!("{}{}", prefix, name.to_string_lossy())) {
+                    cids.push(cid);
+                }
+            }
+        }
+        Ok(cids)
+    }
+
+    fn quarantine(&self, cid: &Cid) -> Result<bool> {
+        let dest = self.quarantine_path(cid);
+        match commit(&self.object_path(cid), &dest) {
+            Ok(()) => Ok(true),
+            Err(Error::Io(e)) if e.kind() == io::ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+impl PinStore for FsStore {
+    fn pin(&self, cid: &Cid, ttl: Option<Duration>) -> Result<Pin> {
+        if !self.has(cid)? {
+            return Err(Error::NotFound(hex::encode(cid)));
+        }
+        self.pins_dir().pin(cid, ttl)
+    }
+
+    fn unpin(&self, cid: &Cid) -> Result<bool> {
+        self.pins_dir().unpin(cid)
+    }
+
+    fn pins(&self) -> Result<Vec<Pin>> {
+        self.pins_dir().pins()
+    }
+}
+
+/// A unique name in `tmp_dir`
+fn temp_path(tmp_dir: &Path) -> PathBuf {
+    tmp_dir.join(format!(
+        "{}-{}",
+        std::process::id(),
+        TEMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ))
+}
+
+/// Create a uniquely named temp file in `tmp_dir`
+pub(crate) fn temp_file(tmp_dir: &Path) -> Result<(PathBuf, File)> {
+    let path = temp_path(tmp_dir);
+    let file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create_new(true)
+        .open(&path)?;
+    Ok((path, file))
+}
+
+/// Copy `reader` into `file`, returning the CID and size of what was read
+pub(crate) fn spool(reader: &mut dyn Read, file: &mut File) -> Result<(Cid, u64)> {
+    let mut hasher = Sha256::new();
+    let mut size = 0u64;
+    let mut buf = vec![0u8; 64 * 1024];
+    loop {
+        let n = match reader.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e.into()),
+        };
+        hasher.update(&buf[..n]);
+        file.write_all(&buf[..n])?;
+        size += n as u64;
+    }
+    Ok((hasher.finalize().into(), size))
+}
+
+/// Move a synced file to `dest` and persist the rename
+pub(crate) fn commit(temp: &Path, dest: &Path) -> Result<()> {
+    let dir = dest.parent().expect("store paths have a parent directory");
+    fs::create_dir_all(dir)?;
+    fs::rename(temp, dest)?;
+    sync_dir(dir)
+}
+
+/// Atomically write `bytes` to `dest` via a temp file in `tmp_dir`
+pub(crate) fn write_atomic(tmp_dir: &Path, dest: &Path, bytes: &[u8]) -> Result<()> {
+    let (temp, mut file) = temp_file(tmp_dir)?;
+    let result = file
+        .write_all(bytes)
+        .and_then(|_| file.sync_all())
+        .map_err(Error::from)
+        .and_then(|_| commit(&temp, dest));
+
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    result
+}
+
+/// Parse a 64-char hex CID, ignoring anything else in the store directories
+pub(crate) fn parse_cid(hex: &str) -> Option<Cid> {
+    let bytes = hex::decode(hex).ok()?;
+    Cid::try_from(&bytes[..]).ok()
+}
+
+/// Bump the mtime of an existing file, returning false if it is absent
+/// or was moved away by `delete_if_older` meanwhile
+fn touch(path: &Path) -> Result<bool> {
+    match OpenOptions::new().write(true).open(path) {
+        Ok(file) => {
+            file.set_modified(SystemTime::now())?;
+            Ok(path.try_exists()?)
+        }
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(false),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Persist a rename by syncing its directory (no-op where unsupported)
+pub(crate) fn sync_dir(dir: &Path) -> Result<()> {
+    #[cfg(unix)]
+    File::open(dir)?.sync_all()?;
+    #[cfg(not(unix))]
+    let _ = dir;
+    Ok(())
+}
@@ -0,0 +1,197 @@
+/// Mark-and-sweep garbage collection
+///
+/// Mark walks every object reachable from a live pin, following the
+/// references `GcOptions::registry` extracts (see `refs`); an object
+/// reached both by shape and through a routed media type is followed both
+/// ways, as in `walk`. Sweep deletes unmarked objects last written before
+/// `started - grace`.
+///
+/// If any reachable object cannot be read or decoded, nothing is swept:
+/// what it references is unknown, and deleting it would leave the DAG
+/// unrecoverable even once the bad object is restored from a replica.
+///
+/// Concurrent puts are safe as long as writers pin their roots within the
+/// grace period:
+/// - a put of an object that already exists refreshes its mtime, so a DAG
+///   re-ingested during GC is never older than the cutoff;
+/// - pins are re-read after marking, and pins added meanwhile are marked
+///   before anything is swept;
+/// - each object is deleted with `BlobStore::delete_if_older`, which the
+///   store makes atomic with respect to puts, so an object re-put after
+///   its stat is kept.
+///
+/// Expired pins are ignored but left in place; callers remove them with
+/// `unpin`.
+use crate::error::{Error, Result};
+use crate::pin::PinStore;
+use crate::refs::{ObjectKind, Reference};
+use crate::registry::Registry;
+use crate::Cid;
+use std::collections::HashSet;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Default grace period before an unreferenced object may be swept
+pub const DEFAULT_GC_GRACE: Duration = Duration::from_secs(60 * 60);
+
+#[derive(Debug, Clone)]
+pub struct GcOptions<'a> {
+    /// Minimum age of an unreferenced object before it is swept
+    pub grace: Duration,
+    /// Report what would be swept without deleting anything
+    pub dry_run: bool,
+    /// Types whose references are followed while marking
+    pub registry: &'a Registry,
+}
+
+impl Default for GcOptions<'static> {
+    fn default() -> Self {
+        GcOptions {
+            grace: DEFAULT_GC_GRACE,
+            dry_run: false,
+            registry: Registry::builtin(),
+        }
+    }
+}
+
+/// Outcome of a GC run
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GcReport {
+    pub dry_run: bool,
+    /// Objects reachable from live pins
+    pub marked: u64,
+    /// Objects deleted (or, on a dry run, that would be)
+    pub swept: Vec<Cid>,
+    pub swept_bytes: u64,
+    /// Unreferenced objects kept because they are inside the grace period
+    pub kept_recent: u64,
+    /// Pins past their TTL, not used as roots
+    pub expired_pins: Vec<Cid>,
+    /// Objects required by a reference but not in the store
+    pub missing: Vec<Cid>,
+    /// Reachable objects that could not be read or decoded; their
+    /// references are not followed, so if any are listed the sweep is
+    /// skipped
+    pub unreadable: Vec<Cid>,
+}
+
+/// Run one GC pass over `store`
+pub fn collect_garbage<S: PinStore + ?Sized>(store: &S, options: &GcOptions) -> Result<GcReport> {
+    let started = SystemTime::now();
+    let cutoff = started.checked_sub(options.grace).unwrap_or(UNIX_EPOCH);
+
+    let mut report = GcReport {
+        dry_run: options.dry_run,
+        ..GcReport::default()
+    };
+    let mut marked = HashSet::new();
+    let mut followed = HashSet::new();
+
+    let pins = store.pins()?;
+    let mut roots = Vec::new();
+    for pin in &pins {
+        if pin.is_live(started) {
+            roots.push(pin.cid);
+        } else {
+            report.expired_pins.push(pin.cid);
+        }
+    }
+    mark(
+        store,
+        options.registry,
+        roots,
+        &mut marked,
+        &mut followed,
+        &mut report,
+    )?;
+
+    // Pins taken while marking
+    let late: Vec<Cid> = store
+        .pins()?
+        .into_iter()
+        .filter(|pin| pin.is_live(started) && !marked.contains(&pin.cid))
+        .map(|pin| pin.cid)
+        .collect();
+    mark(
+        store,
+        options.registry,
+        late,
+        &mut marked,
+        &mut followed,
+        &mut report,
+    )?;
+
+    report.marked = marked.len() as u64;
+    if !report.unreadable.is_empty() {
+        return Ok(report);
+    }
+
+    for cid in store.list()? {
+        if marked.contains(&cid) {
+            continue;
+        }
+        let stat = match store.stat(&cid)? {
+            Some(stat) => stat,
+            None => continue,
+        };
+        if stat.modified >= cutoff {
+            report.kept_recent += 1;
+            continue;
+        }
+
+        if options.dry_run || store.delete_if_older(&cid, cutoff)? {
+            report.swept.push(cid);
+            report.swept_bytes += stat.size;
+        }
+    }
+
+    Ok(report)
+}
+
+fn mark<S: PinStore + ?Sized>(
+    store: &S,
+    registry: &Registry,
+    roots: Vec<Cid>,
+    marked: &mut HashSet<Cid>,
+    followed: &mut HashSet<(Cid, bool)>,
+    report: &mut GcReport,
+) -> Result<()> {
+    let mut stack: Vec<Reference> = roots
+        .into_iter()
+        .map(|cid| Reference::new(cid, ObjectKind::Unknown))
+        .collect();
+
+    while let Some(reference) = stack.pop() {
+        let key = (reference.cid, registry.routes(&reference));
+        if followed.contains(&key) {
+            continue;
+        }
+        // Not recorded as followed, so a later required reference to an
+        // object first reached optionally is still reported missing
+        if !store.has(&reference.cid)? {
+            if !reference.optional && !report.missing.contains(&reference.cid) {
+                report.missing.push(reference.cid);
+            }
+            continue;
+        }
+        followed.insert(key);
+        marked.insert(reference.cid);
+
+        if reference.kind == ObjectKind::Chunk {
+            continue;
+        }
+        let refs = match store.get(&reference.cid) {
+            Ok(bytes) => registry.references(&bytes, &reference),
+            Err(e) => Err(e),
+        };
+        match refs {
+            Ok(refs) => stack.extend(refs),
+            Err(Error::NotFound(_)) | Err(Error::Corrupt(_)) => {
+                if !report.unreadable.contains(&reference.cid) {
+                    report.unreadable.push(reference.cid)
+                }
+            }
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(())
+}
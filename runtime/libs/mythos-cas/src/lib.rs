@@ -0,0 +1,164 @@
+//! MYTHOS Content-Addressed Store
+//!
+//! `BlobStore` keeps immutable objects under the SHA-256 of their bytes.
+//! Chunks, MerkleNode and ChunkedBlob node bytes, and encoded BlobRefs all
+//! go through the same trait; `StoreSource` adapts a store to the node and
+//! chunk sources used by mythos-merkle and mythos-blob.
+//!
+//! `FsStore` keeps one file per object; `PackStore` appends small objects
+//! to segment files. Both have the same `BlobStore` semantics.
+//!
+//! Pins (`PinStore`) mark GC roots; `collect_garbage` sweeps everything
+//! not reachable from a live pin. `scrub` re-hashes stored objects and
+//! checks their references, quarantining corrupt ones. Archives move a
+//! DAG and its roots between stores as a single stream.
+//!
+//! Objects carry no type tag; a `Registry` recognises them by shape and
+//! lists their references, and `walk` traverses the resulting graph.
+//! New object types plug in by registering an `Extractor`.
+//! `LineageIndex` answers provenance queries over the BlobRefs in a store.
+
+mod archive;
+mod error;
+mod fs;
+mod gc;
+mod lineage;
+mod pack;
+mod pin;
+mod refs;
+mod registry;
+mod scrub;
+mod source;
+mod walk;
+
+pub use archive::{
+    export_archive, export_archive_with, import_archive, ArchiveSummary, ARCHIVE_VERSION,
+    MAX_FRAME_LEN,
+};
+pub use error::{Error, Result};
+pub use fs::FsStore;
+pub use gc::{collect_garbage, GcOptions, GcReport, DEFAULT_GC_GRACE};
+pub use lineage::{Lineage, LineageEdge, LineageIndex, LineageNode};
+pub use pack::{CompactReport, PackOptions, PackStore, DEFAULT_SEGMENT_SIZE};
+pub use pin::{Pin, PinStore};
+pub use refs::{references, ObjectKind, Reference};
+pub use registry::{Extractor, ObjectInfo, Registry};
+pub use scrub::{scrub, MissingRef, ScrubOptions, ScrubReport};
+pub use source::{put_nodes, StoreSource};
+pub use walk::{walk, Visit, WalkOptions, WalkReport};
+
+use sha2::{Digest, Sha256};
+use std::io::Read;
+use std::time::SystemTime;
+
+/// Object identifier: SHA-256 of the stored bytes
+pub type Cid = [u8; 32];
+
+/// Metadata for a stored object
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObjectStat {
+    pub size: u64,
+    /// When the object was last written (or re-put)
+    pub modified: SystemTime,
+}
+
+/// Content-addressed object store
+///
+/// Stores never hold bytes under a CID they do not hash to: writes with a
+/// caller-supplied CID are checked before they become visible.
+pub trait BlobStore {
+    /// Store `bytes`, returning their CID
+    fn put(&self, bytes: &[u8]) -> Result<Cid>;
+
+    /// Store `bytes` under `cid`, refusing them if they hash to anything else
+    fn put_with_cid(&self, cid: &Cid, bytes: &[u8]) -> Result<()>;
+
+    /// Store everything `reader` yields, returning the CID and size
+    fn put_stream(&self, reader: &mut dyn Read) -> Result<(Cid, u64)>;
+
+    /// Fetch and re-verify the bytes under `cid`, or `Error::NotFound`
+    fn get(&self, cid: &Cid) -> Result<Vec<u8>>;
+
+    fn has(&self, cid: &Cid) -> Result<bool>;
+
+    /// Object metadata, or `None` if absent
+    fn stat(&self, cid: &Cid) -> Result<Option<ObjectStat>>;
+
+    /// Remove the object, returning whether it existed
+    fn delete(&self, cid: &Cid) -> Result<bool>;
+
+    /// Remove the object unless it was written or re-put at or after
+    /// `cutoff`, returning whether it was removed
+    ///
+    /// Atomic with respect to puts: once a put has refreshed the object at
+    /// or after `cutoff` and returned Ok, the object stays stored.
+    fn delete_if_older(&self, cid: &Cid, cutoff: SystemTime) -> Result<bool>;
+
+    /// CIDs of every stored object, in no particular order
+    fn list(&self) -> Result<Vec<Cid>>;
+
+    /// Move an object out of the store, keeping its bytes for inspection;
+    /// returns whether it existed
+    fn quarantine(&self, cid: &Cid) -> Result<bool>;
+}
+
+impl<S: BlobStore + ?Sized> BlobStore for &S {
+    fn put(&self, bytes: &[u8]) -> Result<Cid> {
+        (**self).put(bytes)
+    }
+
+    fn put_with_cid(&self, cid: &Cid, bytes: &[u8]) -> Result<()> {
+        (**self).put_with_cid(cid, bytes)
+    }
+
+    fn put_stream(&self, reader: &mut dyn Read) -> Result<(Cid, u64)> {
+        (**self).put_stream(reader)
+    }
+
+    fn get(&self, cid: &Cid) -> Result<Vec<u8>> {
+        (**self).get(cid)
+    }
+
+    fn has(&self, cid: &Cid) -> Result<bool> {
+        (**self).has(cid)
+    }
+
+    fn stat(&self, cid: &Cid) -> Result<Option<ObjectStat>> {
+        (**self).stat(cid)
+    }
+
+    fn delete(&self, cid: &Cid) -> Result<bool> {
+        (**self).delete(cid)
+    }
+
+    fn delete_if_older(&self, cid: &Cid, cutoff: SystemTime) -> Result<bool> {
+        (**self).delete_if_older(cid, cutoff)
+    }
+
+    fn list(&self) -> Result<Vec<Cid>> {
+        (**self).list()
+    }
+
+    fn quarantine(&self, cid: &Cid) -> Result<bool> {
+        (**self).quarantine(cid)
+    }
+}
+
+/// Compute CID from canonical bytes
+pub fn cid_from_bytes(bytes: &[u8]) -> Cid {
+    let mut hasher = Sha256::new();
+    hasher.update(bytes);
+    hasher.finalize().into()
+}
+
+/// Check that `bytes` hash to `cid`
+pub fn verify_cid(cid: &Cid, bytes: &[u8]) -> Result<()> {
+    let computed = cid_from_bytes(bytes);
+    if &computed != cid {
+        return Err(Error::CidMismatch {
+            expected: hex::encode(cid),
+            computed: hex::encode(computed),
+        });
+    }
+    Ok(())
+}
@@ -0,0 +1,431 @@
+/// Provenance lineage (RFC-0001 §13.7)
+///
+/// A derived blob's BlobRef carries a ProvenanceRef naming its parent CIDs,
+/// the transform, its parameters and the transform's code hash. The
+/// `LineageIndex` collects those records from every object in a store,
+/// including BlobRefs embedded in larger objects (a DatasetRef manifest, a
+/// TraceRef), and answers ancestor and descendant queries over the blob
+/// CIDs they describe.
+///
+/// A query result (`Lineage`) exports as Graphviz DOT or as a canonical
+/// MYTHOS-CAN object:
+///
+/// ```text
+/// Lineage := { 1: Hash root, 2: [Node], 3: [Edge] }
+/// Node    := { 1: Hash cid, 2: u32 depth }
+/// Edge    := { 1: Hash child, 2: Hash parent, 3: text transform,
+///              4: map(text, text) params, 5: Hash code_hash,
+///              6: Hash record }
+/// ```
+use crate::error::{Error, Result};
+use crate::{BlobStore, Cid};
+use mythos_blob::BlobRef;
+use mythos_can::Value;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::Write;
+
+/// One parent-to-child derivation step
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct LineageEdge {
+    /// Derived blob
+    pub child: Cid,
+    /// Blob it was derived from
+    pub parent: Cid,
+    pub transform: String,
+    pub params: BTreeMap<String, String>,
+    pub code_hash: Cid,
+    /// Stored object holding the ProvenanceRef
+    pub record: Cid,
+}
+
+/// A blob reached by a lineage query, `depth` steps from the root
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LineageNode {
+    pub cid: Cid,
+    pub depth: u32,
+}
+
+/// Lineage subgraph around one blob
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Lineage {
+    pub root: Cid,
+    /// Reached blobs, excluding the root, nearest first
+    pub nodes: Vec<LineageNode>,
+    /// Edges followed, sorted
+    pub edges: Vec<LineageEdge>,
+    /// The depth limit cut off further lineage
+    pub truncated: bool,
+}
+
+/// Derivation edges indexed both ways
+#[derive(Debug, Clone, Default)]
+pub struct LineageIndex {
+    /// child -> edges to its parents
+    up: HashMap<Cid, Vec<LineageEdge>>,
+    /// parent -> edges to its children
+    down: HashMap<Cid, Vec<LineageEdge>>,
+    seen: HashSet<LineageEdge>,
+    /// Objects that could not be read while building
+    pub unreadable: Vec<Cid>,
+}
+
+#[derive(Clone, Copy)]
+enum Direction {
+    Up,
+    Down,
+}
+
+impl LineageIndex {
+    pub fn new() -> Self {
+        LineageIndex::default()
+    }
+
+    /// Index the provenance held by every object in `store`
+    pub fn build<S: BlobStore + ?Sized>(store: &S) -> Result<Self> {
+        let mut index = LineageIndex::new();
+        let mut cids = store.list()?;
+        cids.sort_unstable();
+
+        for cid in cids {
+            match store.get(&cid) {
+                Ok(bytes) => index.add_object(&cid, &bytes),
+                // Deleted since listing
+                Err(Error::NotFound(_)) => {}
+                Err(Error::Corrupt(_)) => index.unreadable.push(cid),
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(index)
+    }
+
+    /// Index the provenance in one stored object, e.g. right after a put
+    ///
+    /// Objects that are not MYTHOS-CAN or hold no ProvenanceRef add nothing.
+    pub fn add_object(&mut self, cid: &Cid, bytes: &[u8]) {
+        if let Ok(value) = mythos_can::decode_value_exact(bytes) {
+            self.add_value(cid, &value);
+        }
+    }
+
+    fn add_value(&mut self, record: &Cid, value: &Value) {
+        match value {
+            Value::Map(pairs) => {
+                if let Ok(blob_ref) = BlobRef::from_value(value) {
+                    self.add_blob_ref(record, &blob_ref);
+                    return;
+                }
+                for (_, v) in pairs {
+                    self.add_value(record, v);
+                }
+            }
+            Value::List(items) => {
+                for item in items {
+                    self.add_value(record, item);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Index the provenance of `blob_ref`, found in the object `record`
+    pub fn add_blob_ref(&mut self, record: &Cid, blob_ref: &BlobRef) {
+        let (Some(provenance), Ok(child)) =
+            (&blob_ref.provenance, Cid::try_from(&blob_ref.cid[..]))
+        else {
+            return;
+        };
+        let Ok(code_hash) = Cid::try_from(&provenance.code_hash[..]) else {
+            return;
+        };
+
+        for parent in &provenance.parents {
+            let Ok(parent) = Cid::try_from(&parent[..]) else {
+                continue;
+            };
+            let edge = LineageEdge {
+                child,
+                parent,
+                transform: provenance.transform.clone(),
+                params: provenance.params.clone(),
+                code_hash,
+                record: *record,
+            };
+
+            // The same BlobRef may be stored more than once
+            let key = LineageEdge {
+                record: [0; 32],
+                ..edge.clone()
+            };
+            if !self.seen.insert(key) {
+                continue;
+            }
+            self.up.entry(child).or_default().push(edge.clone());
+            self.down.entry(parent).or_default().push(edge);
+        }
+    }
+
+    /// Number of distinct derivation edges
+    pub fn edge_count(&self) -> usize {
+        self.seen.len()
+    }
+
+    /// Direct parents of `cid`
+    pub fn parents(&self, cid: &Cid) -> &[LineageEdge] {
+        self.up.get(cid).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// Direct children of `cid`
+    pub fn children(&self, cid: &Cid) -> &[LineageEdge] {
+        self.down.get(cid).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// Everything `cid` was derived from, up to `max_depth` steps back
+    pub fn ancestors(&self, cid: &Cid, max_depth: Option<u32>) -> Lineage {
+        self.query(cid, max_depth, Direction::Up)
+    }
+
+    /// Everything derived from `cid`, up to `max_depth` steps forward
+    pub fn descendants(&self, cid: &Cid, max_depth: Option<u32>) -> Lineage {
+        self.query(cid, max_depth, Direction::Down)
+    }
+
+    fn query(&self, root: &Cid, max_depth: Option<u32>, direction: Direction) -> Lineage {
+        let next = |cid: &Cid| match direction {
+            Direction::Up => self.parents(cid),
+            Direction::Down => self.children(cid),
+        };
+        let far_end = |edge: &LineageEdge| match direction {
+            Direction::Up => edge.parent,
+            Direction::Down => edge.child,
+        };
+
+        let mut lineage = Lineage {
+            root: *root,
+            ..Lineage::default()
+        };
+        let mut reached = HashSet::from([*root]);
+        let mut frontier = vec![*root];
+        let mut depth = 0;
+
+        // Breadth-first, so each node gets its shortest distance
+        while !frontier.is_empty() {
+            if max_depth.is_some_and(|max| depth >= max) {
+                lineage.truncated = frontier.iter().any(|cid| !next(cid).is_empty());
+                break;
+            }
+            depth += 1;
+
+            let mut layer = Vec::new();
+            for cid in &frontier {
+                for edge in next(cid) {
+                    lineage.edges.push(edge.clone());
+                    let target = far_end(edge);
+                    if reached.insert(target) {
+                        layer.push(target);
+                    }
+                }
+            }
+            layer.sort_unstable();
+            lineage
+                .nodes
+                .extend(layer.iter().map(|cid| LineageNode { cid: *cid, depth }));
+            frontier = layer;
+        }
+
+        lineage.edges.sort();
+        lineage
+    }
+}
+
+impl Lineage {
+    /// Graphviz DOT, edges pointing from parent to child
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph lineage {\n    rankdir=LR;\n");
+        let _ = writeln!(
+            dot,
+            "    \"{}\" [label=\"{}\", shape=box, style=bold];",
+            hex::encode(self.root),
+            short(&self.root)
+        );
+        for node in &self.nodes {
+            let _ = writeln!(
+                dot,
+                "    \"{}\" [label=\"{}\"];",
+                hex::encode(node.cid),
+                short(&node.cid)
+            );
+        }
+        for edge in &self.edges {
+            let _ = writeln!(
+                dot,
+                "    \"{}\" -> \"{}\" [label=\"{}\"];",
+                hex::encode(edge.parent),
+                hex::encode(edge.child),
+                escape(&edge.transform)
+            );
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    pub fn to_value(&self) -> Value {
+        let nodes = self
+            .nodes
+            .iter()
+            .map(|node| {
+                Value::Map(vec![
+                    (Value::UVarint(1), hash_value(&node.cid)),
+                    (Value::UVarint(2), Value::UVarint(node.depth as u64)),
+                ])
+            })
+            .collect();
+        let edges = self
+            .edges
+            .iter()
+            .map(|edge| {
+                Value::Map(vec![
+                    (Value::UVarint(1), hash_value(&edge.child)),
+                    (Value::UVarint(2), hash_value(&edge.parent)),
+                    (Value::UVarint(3), Value::Text(edge.transform.clone())),
+                    (
+                        Value::UVarint(4),
+                        Value::Map(
+                            edge.params
+                                .iter()
+                                .map(|(k, v)| (Value::Text(k.clone()), Value::Text(v.clone())))
+                                .collect(),
+                        ),
+                    ),
+                    (Value::UVarint(5), hash_value(&edge.code_hash)),
+                    (Value::UVarint(6), hash_value(&edge.record)),
+                ])
+            })
+            .collect();
+
+        Value::Map(vec![
+            (Value::UVarint(1), hash_value(&self.root)),
+            (Value::UVarint(2), Value::List(nodes)),
+            (Value::UVarint(3), Value::List(edges)),
+            (Value::UVarint(4), Value::Bool(self.truncated)),
+        ])
+    }
+
+    /// Canonical bytes, ready to `put` into a store
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        mythos_can::encode_value(&self.to_value())
+            .map_err(|e| Error::InvalidLineage(format!("encode failed: {}", e)))
+    }
+
+    pub fn from_value(value: &Value) -> Result<Self> {
+        let root = parse_hash(field(value, 1), "Lineage.root")?;
+
+        let nodes = list(field(value, 2), "Lineage.nodes")?
+            .iter()
+            .map(|node| {
+                let depth = match field(node, 2) {
+                    Some(Value::UVarint(d)) if *d <= u32::MAX as u64 => *d as u32,
+                    _ => return Err(Error::InvalidLineage("Node.depth must be u32".into())),
+                };
+                Ok(LineageNode {
+                    cid: parse_hash(field(node, 1), "Node.cid")?,
+                    depth,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let edges = list(field(value, 3), "Lineage.edges")?
+            .iter()
+            .map(|edge| {
+                let transform = match field(edge, 3) {
+                    Some(Value::Text(t)) => t.clone(),
+                    _ => return Err(Error::InvalidLineage("Edge.transform must be TEXT".into())),
+                };
+                let params = match field(edge, 4) {
+                    Some(Value::Map(pairs)) => pairs
+                        .iter()
+                        .map(|pair| match pair {
+                            (Value::Text(k), Value::Text(v)) => Ok((k.clone(), v.clone())),
+                            _ => Err(Error::InvalidLineage(
+                                "Edge.params must be map(text, text)".into(),
+                            )),
+                        })
+                        .collect::<Result<BTreeMap<_, _>>>()?,
+                    _ => return Err(Error::InvalidLineage("Edge.params must be MAP".into())),
+                };
+                Ok(LineageEdge {
+                    child: parse_hash(field(edge, 1), "Edge.child")?,
+                    parent: parse_hash(field(edge, 2), "Edge.parent")?,
+                    transform,
+                    params,
+                    code_hash: parse_hash(field(edge, 5), "Edge.code_hash")?,
+                    record: parse_hash(field(edge, 6), "Edge.record")?,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let truncated = match field(value, 4) {
+            Some(Value::Bool(b)) => *b,
+            _ => {
+                return Err(Error::InvalidLineage(
+                    "Lineage.truncated must be BOOL".into(),
+                ))
+            }
+        };
+
+        Ok(Lineage {
+            root,
+            nodes,
+            edges,
+            truncated,
+        })
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let value = mythos_can::decode_value_exact(bytes)
+            .map_err(|e| Error::InvalidLineage(format!("decode failed: {}", e)))?;
+        Lineage::from_value(&value)
+    }
+}
+
+fn short(cid: &Cid) -> String {
+    hex::encode(&cid[..6])
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn hash_value(cid: &Cid) -> Value {
+    Value::Map(vec![
+        (Value::UVarint(1), Value::UVarint(1)),
+        (Value::UVarint(2), Value::Bytes(cid.to_vec())),
+    ])
+}
+
+fn field(value: &Value, n: u64) -> Option<&Value> {
+    match value {
+        Value::Map(pairs) => pairs
+            .iter()
+            .find(|(k, _)| matches!(k, Value::UVarint(x) if *x == n))
+            .map(|(_, v)| v),
+        _ => None,
+    }
+}
+
+fn list<'a>(value: Option<&'a Value>, name: &str) -> Result<&'a [Value]> {
+    match value {
+        Some(Value::List(items)) => Ok(items),
+        _ => Err(Error::InvalidLineage(format!("{} must be LIST", name))),
+    }
+}
+
+fn parse_hash(value: Option<&Value>, name: &str) -> Result<Cid> {
+    match value.map(|v| (field(v, 1), field(v, 2))) {
+        Some((Some(Value::UVarint(1)), Some(Value::Bytes(bytes)))) => Cid::try_from(&bytes[..])
+            .map_err(|_| Error::InvalidLineage(format!("{} must be 32 bytes", name))),
+        _ => Err(Error::InvalidLineage(format!(
+            "{} must be a SHA-256 Hash",
+            name
+        ))),
+    }
+}
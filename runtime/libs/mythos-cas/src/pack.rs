@@ -0,0 +1,631 @@
+/// Pack-file BlobStore for small objects
+///
+/// Objects are appended to segment files instead of getting a file each:
+///
+/// ```text
+/// LOCK
+/// packs/<segment id, 16 hex digits>.pack
+/// pins/<64 hex chars>
+/// quarantine/<64 hex chars>
+/// tmp/<unique name>
+/// ```
+///
+/// A segment is the 8-byte magic `MYTHPK01` followed by records:
+///
+/// ```text
+/// cid (32) | op (u8) | written (u64 BE, µs since epoch) | len (u32 BE) | bytes (len)
+/// ```
+///
+/// `op` is PUT, DELETE (tombstone, len 0) or TOUCH (mtime refresh, len 0).
+/// Records are appended with a single write, except that `put_stream`
+/// spools its input to `tmp/` and copies it in after the header, and are
+/// synced before the writing call returns. An object must be under 4 GiB
+/// to fit `len`; `put_stream` fails with `Error::ObjectTooLarge` before
+/// appending anything if it is not.
+///
+/// The CID-to-offset index lives in memory and is rebuilt on open by
+/// replaying record headers in segment order; a torn record at the end of
+/// the newest segment is truncated away, and a newest segment cut off
+/// before its magic was written is given the magic again.
+///
+/// `compact` rewrites live objects into fresh segments and removes the
+/// old ones, dropping deleted objects and tombstones.
+///
+/// The in-memory index and offsets belong to one `PackStore`, so only one
+/// may have a directory open at a time: `open` takes an exclusive lock on
+/// `LOCK` in the store root, held until the store is dropped, and fails
+/// with `Error::Locked` if another process (or another `PackStore` in this
+/// one) holds it.
+use crate::error::{Error, Result};
+use crate::fs::{commit, spool, sync_dir, temp_file};
+use crate::pin::{Pin, PinDir, PinStore};
+use crate::{cid_from_bytes, verify_cid, BlobStore, Cid, ObjectStat};
+use fs2::FileExt;
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const MAGIC: &[u8; 8] = b"MYTHPK01";
+const HEADER_LEN: u64 = 32 + 1 + 8 + 4;
+
+const OP_PUT: u8 = 0;
+const OP_DELETE: u8 = 1;
+const OP_TOUCH: u8 = 2;
+
+/// Default size at which the active segment is sealed
+pub const DEFAULT_SEGMENT_SIZE: u64 = 64 * 1024 * 1024;
+
+#[derive(Debug, Clone)]
+pub struct PackOptions {
+    /// Start a new segment once the active one reaches this size
+    pub segment_size: u64,
+}
+
+impl Default for PackOptions {
+    fn default() -> Self {
+        PackOptions {
+            segment_size: DEFAULT_SEGMENT_SIZE,
+        }
+    }
+}
+
+/// Outcome of `PackStore::compact`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CompactReport {
+    pub live_objects: u64,
+    pub segments_removed: u64,
+    pub bytes_before: u64,
+    pub bytes_after: u64,
+}
+
+/// Where an object's bytes live
+#[derive(Debug, Clone, Copy)]
+struct Location {
+    segment: u64,
+    /// Offset of the object bytes (after the record header)
+    offset: u64,
+    len: u32,
+    written: SystemTime,
+}
+
+struct Inner {
+    index: HashMap<Cid, Location>,
+    active: File,
+    active_id: u64,
+    active_len: u64,
+}
+
+/// Append-only segment store for small objects
+pub struct PackStore {
+    root: PathBuf,
+    options: PackOptions,
+    inner: Mutex<Inner>,
+    /// Holds the exclusive directory lock while open
+    _lock: File,
+}
+
+impl std::fmt::Debug for PackStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PackStore")
+            .field("root", &self.root)
+            .field("options", &self.options)
+            .finish()
+    }
+}
+
+impl PackStore {
+    /// Open the store at `root` with default options
+    pub fn open<P: AsRef<Path>>(root: P) -> Result<Self> {
+        Self::open_with(root, PackOptions::default())
+    }
+
+    /// Open the store at `root`, replaying every segment into the index
+    pub fn open_with<P: AsRef<Path>>(root: P, options: PackOptions) -> Result<Self> {
+        let root = root.as_ref().to_path_buf();
+        for dir in ["packs", "pins", "tmp"] {
+            fs::create_dir_all(root.join(dir))?;
+        }
+        let lock = lock_root(&root)?;
+
+        let segments = list_segments(&root.join("packs"))?;
+        let mut index = HashMap::new();
+        for (i, id) in segments.iter().enumerate() {
+            let newest = i + 1 == segments.len();
+            replay_segment(&segment_path(&root, *id), *id, newest, &mut index)?;
+        }
+
+        let (active_id, active, active_len) = match segments.last() {
+            Some(id) => {
+                let active = OpenOptions::new()
+                    .append(true)
+                    .open(segment_path(&root, *id))?;
+                let len = active.metadata()?.len();
+                (*id, active, len)
+            }
+            None => {
+                let (active, len) = create_segment(&root, 0)?;
+                (0, active, len)
+            }
+        };
+
+        Ok(PackStore {
+            root,
+            options,
+            inner: Mutex::new(Inner {
+                index,
+                active,
+                active_id,
+                active_len,
+            }),
+            _lock: lock,
+        })
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Where `quarantine` copies a corrupt object
+    pub fn quarantine_path(&self, cid: &Cid) -> PathBuf {
+        self.root.join("quarantine").join(hex::encode(cid))
+    }
+
+    /// Number of segment files on disk
+    pub fn segment_count(&self) -> Result<usize> {
+        Ok(list_segments(&self.root.join("packs"))?.len())
+    }
+
+    /// Rewrite live objects into new segments and remove the old ones
+    ///
+    /// Holds the store lock throughout, so concurrent calls wait. Old
+    /// segments are only removed once the new ones are synced, oldest
+    /// first, so a crash part-way never resurrects a deleted object.
+    pub fn compact(&self) -> Result<CompactReport> {
+        let mut inner = self.lock();
+        let old = list_segments(&self.root.join("packs"))?;
+        let mut report = CompactReport {
+            live_objects: inner.index.len() as u64,
+            ..CompactReport::default()
+        };
+        for id in &old {
+            report.bytes_before += fs::metadata(segment_path(&self.root, *id))?.len();
+        }
+
+        let mut live: Vec<(Cid, Location)> = inner.index.iter().map(|(c, l)| (*c, *l)).collect();
+        live.sort_by_key(|(_, loc)| (loc.segment, loc.offset));
+
+        self.roll(&mut inner)?;
+        let first_new = inner.active_id;
+        for (cid, loc) in live {
+            let bytes = self.read_at(&loc)?;
+            self.append(&mut inner, &cid, OP_PUT, loc.written, &bytes)?;
+        }
+        inner.active.sync_data()?;
+
+        for id in old.iter().filter(|id| **id < first_new) {
+            fs::remove_file(segment_path(&self.root, *id))?;
+            report.segments_removed += 1;
+        }
+        sync_dir(&self.root.join("packs"))?;
+
+        for id in list_segments(&self.root.join("packs"))? {
+            report.bytes_after += fs::metadata(segment_path(&self.root, id))?.len();
+        }
+        Ok(report)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Inner> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn pins_dir(&self) -> PinDir {
+        PinDir::new(self.root.join("pins"), self.root.join("tmp"))
+    }
+
+    /// Seal the active segment and start a new one
+    fn roll(&self, inner: &mut Inner) -> Result<()> {
+        inner.active.sync_data()?;
+        let id = inner.active_id + 1;
+        let (active, len) = create_segment(&self.root, id)?;
+        inner.active = active;
+        inner.active_id = id;
+        inner.active_len = len;
+        Ok(())
+    }
+
+    /// Append one record to the active segment and index it; the caller
+    /// syncs
+    fn append(
+        &self,
+        inner: &mut Inner,
+        cid: &Cid,
+        op: u8,
+        written: SystemTime,
+        bytes: &[u8],
+    ) -> Result<()> {
+        let len = u32::try_from(bytes.len()).map_err(|_| Error::ObjectTooLarge(bytes.len()))?;
+        self.append_with(inner, cid, op, written, len, |file, header| {
+            let mut record = Vec::with_capacity(HEADER_LEN as usize + bytes.len());
+            record.extend_from_slice(header);
+            record.extend_from_slice(bytes);
+            file.write_all(&record)
+        })
+    }
+
+    /// Append a record whose header and `len` object bytes `write` puts
+    /// into the active segment, and index it; the caller syncs
+    fn append_with<F>(
+        &self,
+        inner: &mut Inner,
+        cid: &Cid,
+        op: u8,
+        written: SystemTime,
+        len: u32,
+        write: F,
+    ) -> Result<()>
+    where
+        F: FnOnce(&mut File, &[u8]) -> io::Result<()>,
+    {
+        if inner.active_len >= self.options.segment_size {
+            self.roll(inner)?;
+        }
+
+        let mut header = Vec::with_capacity(HEADER_LEN as usize);
+        header.extend_from_slice(cid);
+        header.push(op);
+        header.extend_from_slice(&to_micros(written).to_be_bytes());
+        header.extend_from_slice(&len.to_be_bytes());
+
+        if let Err(e) = write(&mut inner.active, &header) {
+            // Drop a partial record so later appends stay aligned
+            let _ = inner.active.set_len(inner.active_len);
+            return Err(e.into());
+        }
+
+        let offset = inner.active_len + HEADER_LEN;
+        inner.active_len += HEADER_LEN + len as u64;
+        let segment = inner.active_id;
+        apply(&mut inner.index, cid, op, segment, offset, len, written);
+        Ok(())
+    }
+
+    fn read_at(&self, loc: &Location) -> Result<Vec<u8>> {
+        let mut file = File::open(segment_path(&self.root, loc.segment))?;
+        file.seek(SeekFrom::Start(loc.offset))?;
+        let mut bytes = vec![0u8; loc.len as usize];
+        file.read_exact(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    /// Store `bytes` under `cid`, or refresh the object if already present
+    fn write_object(&self, cid: &Cid, bytes: &[u8]) -> Result<()> {
+        let mut inner = self.lock();
+        if inner.index.contains_key(cid) {
+            self.append(&mut inner, cid, OP_TOUCH, SystemTime::now(), &[])?;
+        } else {
+            self.append(&mut inner, cid, OP_PUT, SystemTime::now(), bytes)?;
+        }
+        Ok(inner.active.sync_data()?)
+    }
+}
+
+impl BlobStore for PackStore {
+    fn put(&self, bytes: &[u8]) -> Result<Cid> {
+        let cid = cid_from_bytes(bytes);
+        self.write_object(&cid, bytes)?;
+        Ok(cid)
+    }
+
+    fn put_with_cid(&self, cid: &Cid, bytes: &[u8]) -> Result<()> {
+        verify_cid(cid, bytes)?;
+        self.write_object(cid, bytes)
+    }
+
+    /// Spools the stream to a temp file while hashing it, then copies it
+    /// into the segment under the store lock
+    fn put_stream(&self, reader: &mut dyn Read) -> Result<(Cid, u64)> {
+        let (temp, mut file) = temp_file(&self.root.join("tmp"))?;
+
+        let result = (|| {
+            let (cid, size) = spool(reader, &mut file)?;
+            let len = u32::try_from(size).map_err(|_| Error::ObjectTooLarge(size as usize))?;
+
+            let mut inner = self.lock();
+            if inner.index.contains_key(&cid) {
+                self.append(&mut inner, &cid, OP_TOUCH, SystemTime::now(), &[])?;
+            } else {
+                file.seek(SeekFrom::Start(0))?;
+                self.append_with(
+                    &mut inner,
+                    &cid,
+                    OP_PUT,
+                    SystemTime::now(),
+                    len,
+                    |active, header| {
+                        active.write_all(header)?;
+                        let copied = io::copy(&mut (&mut file).take(size), active)?;
+                        if copied < size {
+                            return Err(io::ErrorKind::UnexpectedEof.into());
+                        }
+                        Ok(())
+                    },
+                )?;
+            }
+            inner.active.sync_data()?;
+            Ok((cid, size))
+        })();
+
+        let _ = fs::remove_file(&temp);
+        result
+    }
+
+    fn get(&self, cid: &Cid) -> Result<Vec<u8>> {
+        let loc = self.lock().index.get(cid).copied();
+        let loc = loc.ok_or_else(|| Error::NotFound(hex::encode(cid)))?;
+
+        let bytes = match self.read_at(&loc) {
+            Ok(bytes) => bytes,
+            // Compacted away between the index lookup and the read
+            Err(Error::Io(e)) if e.kind() == io::ErrorKind::NotFound => {
+                let loc = self.lock().index.get(cid).copied();
+                self.read_at(&loc.ok_or_else(|| Error::NotFound(hex::encode(cid)))?)?
+            }
+            Err(e) => return Err(e),
+        };
+
+        verify_cid(cid, &bytes).map_err(|_| Error::Corrupt(hex::encode(cid)))?;
+        Ok(bytes)
+    }
+
+    fn has(&self, cid: &Cid) -> Result<bool> {
+        Ok(self.lock().index.contains_key(cid))
+    }
+
+    fn stat(&self, cid: &Cid) -> Result<Option<ObjectStat>> {
+        Ok(self.lock().index.get(cid).map(|loc| ObjectStat {
+            size: loc.len as u64,
+            modified: loc.written,
+        }))
+    }
+
+    fn delete(&self, cid: &Cid) -> Result<bool> {
+        let mut inner = self.lock();
+        if !inner.index.contains_key(cid) {
+            return Ok(false);
+        }
+        self.append(&mut inner, cid, OP_DELETE, SystemTime::now(), &[])?;
+        inner.active.sync_data()?;
+        Ok(true)
+    }
+
+    /// Checked and tombstoned under the store lock, which puts also take
+    fn delete_if_older(&self, cid: &Cid, cutoff: SystemTime) -> Result<bool> {
+        let mut inner = self.lock();
+        match inner.index.get(cid) {
+            Some(loc) if loc.written < cutoff => {}
+            _ => return Ok(false),
+        }
+        self.append(&mut inner, cid, OP_DELETE, SystemTime::now(), &[])?;
+        inner.active.sync_data()?;
+        Ok(true)
+    }
+
+    fn list(&self) -> Result<Vec<Cid>> {
+        Ok(self.lock().index.keys().copied().collect())
+    }
+
+    fn quarantine(&self, cid: &Cid) -> Result<bool> {
+        let loc = match self.lock().index.get(cid).copied() {
+            Some(loc) => loc,
+            None => return Ok(false),
+        };
+
+        let bytes = self.read_at(&loc)?;
+        let (temp, mut file) = temp_file(&self.root.join("tmp"))?;
+        file.write_all(&bytes)?;
+        file.sync_all()?;
+        commit(&temp, &self.quarantine_path(cid))?;
+
+        self.delete(cid)
+    }
+}
+
+impl PinStore for PackStore {
+    fn pin(&self, cid: &Cid, ttl: Option<Duration>) -> Result<Pin> {
+        if !self.has(cid)? {
+            return Err(Error::NotFound(hex::encode(cid)));
+        }
+        self.pins_dir().pin(cid, ttl)
+    }
+
+    fn unpin(&self, cid: &Cid) -> Result<bool> {
+        self.pins_dir().unpin(cid)
+    }
+
+    fn pins(&self) -> Result<Vec<Pin>> {
+        self.pins_dir().pins()
+    }
+}
+
+/// Take the exclusive lock on `root/LOCK`, released when the file closes
+fn lock_root(root: &Path) -> Result<File> {
+    let path = root.join("LOCK");
+    let file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(&path)?;
+    match file.try_lock_exclusive() {
+        Ok(()) => Ok(file),
+        Err(e) if e.kind() == fs2::lock_contended_error().kind() => {
+            Err(Error::Locked(path.display().to_string()))
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn segment_path(root: &Path, id: u64) -> PathBuf {
+    root.join("packs").join(format!("{:016x}.pack", id))
+}
+
+/// Segment ids on disk, ascending
+fn list_segments(dir: &Path) -> Result<Vec<u64>> {
+    let mut ids = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let name = entry?.file_name();
+        let name = name.to_string_lossy();
+        if let Some(id) = name
+            .strip_suffix(".pack")
+            .and_then(|stem| u64::from_str_radix(stem, 16).ok())
+        {
+            ids.push(id);
+        }
+    }
+    ids.sort_unstable();
+    Ok(ids)
+}
+
+/// Create an empty segment holding only the magic
+fn create_segment(root: &Path, id: u64) -> Result<(File, u64)> {
+    let path = segment_path(root, id);
+    let mut file = OpenOptions::new()
+        .append(true)
+        .create_new(true)
+        .open(&path)?;
+    file.write_all(MAGIC)?;
+    file.sync_all()?;
+    sync_dir(&root.join("packs"))?;
+    Ok((file, MAGIC.len() as u64))
+}
+
+/// Replay a segment's record headers into `index`
+///
+/// A torn record is truncated away in the newest segment (an interrupted
+/// append) and is an error anywhere else; likewise a newest segment holding
+/// only part of the magic (an interrupted create) gets the full magic.
+fn replay_segment(
+    path: &Path,
+    segment: u64,
+    newest: bool,
+    index: &mut HashMap<Cid, Location>,
+) -> Result<()> {
+    let mut file = File::open(path)?;
+    let file_len = file.metadata()?.len();
+    if file_len < MAGIC.len() as u64 {
+        // Only create_segment writes a segment this short: if it was cut
+        // off before the magic landed, write the magic again
+        let mut head = Vec::new();
+        file.read_to_end(&mut head)?;
+        if !newest || !MAGIC.starts_with(&head) {
+            return Err(Error::Corrupt(format!(
+                "truncated pack magic: {}",
+                path.display()
+            )));
+        }
+        let mut file = OpenOptions::new().write(true).open(path)?;
+        file.set_len(0)?;
+        file.write_all(MAGIC)?;
+        file.sync_all()?;
+        return Ok(());
+    }
+    let mut reader = BufReader::new(file);
+
+    let mut magic = [0u8; 8];
+    reader.read_exact(&mut magic)?;
+    if &magic != MAGIC {
+        return Err(Error::Corrupt(format!(
+            "bad pack magic: {}",
+            path.display()
+        )));
+    }
+
+    let mut pos = MAGIC.len() as u64;
+    let mut header = [0u8; HEADER_LEN as usize];
+    while pos < file_len {
+        let data_end = match reader.read_exact(&mut header) {
+            Ok(()) => {
+                let len = u32::from_be_bytes(header[41..45].try_into().unwrap());
+                pos + HEADER_LEN + len as u64
+            }
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => file_len + 1,
+            Err(e) => return Err(e.into()),
+        };
+
+        if data_end > file_len {
+            if !newest {
+                return Err(Error::Corrupt(format!(
+                    "truncated record at {} in {}",
+                    pos,
+                    path.display()
+                )));
+            }
+            OpenOptions::new().write(true).open(path)?.set_len(pos)?;
+            break;
+        }
+
+        let cid: Cid = header[..32].try_into().unwrap();
+        let op = header[32];
+        let written = from_micros(u64::from_be_bytes(header[33..41].try_into().unwrap()));
+        let len = u32::from_be_bytes(header[41..45].try_into().unwrap());
+        if op > OP_TOUCH {
+            return Err(Error::Corrupt(format!(
+                "unknown pack op {} at {} in {}",
+                op,
+                pos,
+                path.display()
+            )));
+        }
+
+        apply(index, &cid, op, segment, pos + HEADER_LEN, len, written);
+        reader.seek(SeekFrom::Current(len as i64))?;
+        pos = data_end;
+    }
+    Ok(())
+}
+
+/// Update the index for one record
+fn apply(
+    index: &mut HashMap<Cid, Location>,
+    cid: &Cid,
+    op: u8,
+    segment: u64,
+    offset: u64,
+    len: u32,
+    written: SystemTime,
+) {
+    match op {
+        OP_PUT => {
+            index.insert(
+                *cid,
+                Location {
+                    segment,
+                    offset,
+                    len,
+                    written,
+                },
+            );
+        }
+        OP_DELETE => {
+            index.remove(cid);
+        }
+        _ => {
+            if let Some(loc) = index.get_mut(cid) {
+                loc.written = written;
+            }
+        }
+    }
+}
+
+fn to_micros(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_micros() as u64)
+        .unwrap_or(0)
+}
+
+fn from_micros(micros: u64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_micros(micros)
+}
@@ -0,0 +1,185 @@
+/// Pins (RFC-0001 §13.4 `Blob.Pin(cid, ttl)`)
+///
+/// A pin keeps an object and everything reachable from it alive through
+/// garbage collection until it is removed or its TTL runs out. Pin records
+/// are canonical MYTHOS-CAN maps:
+///
+/// ```text
+/// { 1: Hash { 1: alg=1, 2: cid }, 2: expires_at (u64 µs since epoch, optional) }
+/// ```
+use crate::error::{Error, Result};
+use crate::fs::{parse_cid, write_atomic};
+use crate::{BlobStore, Cid};
+use mythos_can::Value;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// A GC root, optionally expiring
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pin {
+    pub cid: Cid,
+    /// `None` pins until explicitly removed
+    pub expires_at: Option<SystemTime>,
+}
+
+impl Pin {
+    pub fn new(cid: Cid, ttl: Option<Duration>) -> Self {
+        Pin {
+            cid,
+            expires_at: ttl.map(|ttl| SystemTime::now() + ttl),
+        }
+    }
+
+    /// Whether the pin still holds at `now`
+    pub fn is_live(&self, now: SystemTime) -> bool {
+        self.expires_at.map_or(true, |expires| now < expires)
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        let mut fields = vec![(
+            Value::UVarint(1),
+            Value::Map(vec![
+                (Value::UVarint(1), Value::UVarint(1)),
+                (Value::UVarint(2), Value::Bytes(self.cid.to_vec())),
+            ]),
+        )];
+        if let Some(expires) = self.expires_at {
+            let micros = expires
+                .duration_since(UNIX_EPOCH)
+                .map_err(|_| Error::InvalidPin("expiry before epoch".into()))?
+                .as_micros();
+            let micros = u64::try_from(micros)
+                .map_err(|_| Error::InvalidPin("expiry out of range".into()))?;
+            fields.push((Value::UVarint(2), Value::UVarint(micros)));
+        }
+
+        mythos_can::encode_value(&Value::Map(fields))
+            .map_err(|e| Error::InvalidPin(format!("encode failed: {}", e)))
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let decoded = mythos_can::decode_value_exact(bytes)
+            .map_err(|e| Error::InvalidPin(format!("decode failed: {}", e)))?;
+        let fields = match &decoded {
+            Value::Map(pairs) => pairs,
+            _ => return Err(Error::InvalidPin("Pin must be MAP".into())),
+        };
+
+        let get_field = |n: u64| {
+            fields
+                .iter()
+                .find(|(k, _)| matches!(k, Value::UVarint(x) if *x == n))
+                .map(|(_, v)| v)
+        };
+
+        // Field 1: Hash
+        let hash = match get_field(1) {
+            Some(Value::Map(hash)) => hash,
+            _ => return Err(Error::InvalidPin("Missing cid".into())),
+        };
+        let alg = hash
+            .iter()
+            .find(|(k, _)| matches!(k, Value::UVarint(1)))
+            .map(|(_, v)| v);
+        if !matches!(alg, Some(Value::UVarint(1))) {
+            return Err(Error::InvalidPin("cid must be SHA-256".into()));
+        }
+        let cid = match hash.iter().find(|(k, _)| matches!(k, Value::UVarint(2))) {
+            Some((_, Value::Bytes(b))) => Cid::try_from(&b[..])
+                .map_err(|_| Error::InvalidPin(format!("cid must be 32 bytes, got {}", b.len())))?,
+            _ => return Err(Error::InvalidPin("Missing cid bytes".into())),
+        };
+
+        // Field 2: expires_at, optional
+        let expires_at = match get_field(2) {
+            Some(Value::UVarint(micros)) => Some(UNIX_EPOCH + Duration::from_micros(*micros)),
+            None => None,
+            _ => return Err(Error::InvalidPin("expires_at must be UVARINT".into())),
+        };
+
+        Ok(Pin { cid, expires_at })
+    }
+}
+
+/// Store that also keeps pin records
+pub trait PinStore: BlobStore {
+    /// Pin an existing object, replacing any earlier pin on it
+    fn pin(&self, cid: &Cid, ttl: Option<Duration>) -> Result<Pin>;
+
+    /// Remove a pin, returning whether it existed
+    fn unpin(&self, cid: &Cid) -> Result<bool>;
+
+    /// Every pin record, including expired ones
+    fn pins(&self) -> Result<Vec<Pin>>;
+}
+
+impl<S: PinStore + ?Sized> PinStore for &S {
+    fn pin(&self, cid: &Cid, ttl: Option<Duration>) -> Result<Pin> {
+        (**self).pin(cid, ttl)
+    }
+
+    fn unpin(&self, cid: &Cid) -> Result<bool> {
+        (**self).unpin(cid)
+    }
+
+    fn pins(&self) -> Result<Vec<Pin>> {
+        (**self).pins()
+    }
+}
+
+/// Directory of pin records, one file per pinned CID, shared by the
+/// filesystem backends
+pub(crate) struct PinDir {
+    dir: PathBuf,
+    tmp_dir: PathBuf,
+}
+
+impl PinDir {
+    pub(crate) fn new(dir: PathBuf, tmp_dir: PathBuf) -> Self {
+        PinDir { dir, tmp_dir }
+    }
+
+    pub(crate) fn pin(&self, cid: &Cid, ttl: Option<Duration>) -> Result<Pin> {
+        // Round-trip so the returned expiry has the stored precision
+        let bytes = Pin::new(*cid, ttl).to_bytes()?;
+        write_atomic(&self.tmp_dir, &self.dir.join(hex::encode(cid)), &bytes)?;
+        Pin::from_bytes(&bytes)
+    }
+
+    pub(crate) fn unpin(&self, cid: &Cid) -> Result<bool> {
+        match fs::remove_file(self.dir.join(hex::encode(cid))) {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub(crate) fn pins(&self) -> Result<Vec<Pin>> {
+        let mut pins = Vec::new();
+        for entry in fs::read_dir(&self.dir)? {
+            let entry = entry?;
+            let cid = match parse_cid(&entry.file_name().to_string_lossy()) {
+                Some(cid) => cid,
+                None => continue,
+            };
+            let bytes = match fs::read(entry.path()) {
+                Ok(bytes) => bytes,
+                // Unpinned since the directory was read
+                Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(e.into()),
+            };
+            let pin = Pin::from_bytes(&bytes)?;
+            if pin.cid != cid {
+                return Err(Error::InvalidPin(format!(
+                    "record for {} names {}",
+                    hex::encode(cid),
+                    hex::encode(pin.cid)
+                )));
+            }
+            pins.push(pin);
+        }
+        Ok(pins)
+    }
+}
@@ -0,0 +1,97 @@
+/// References between stored objects
+///
+/// Objects carry no type tag, so a reference records what the referrer
+/// knows about its target. Roots and most hash fields are `Unknown` and
+/// are classified by the `Registry` once fetched. Chunks are never
+/// decoded, since chunk bytes are arbitrary data.
+///
+/// Optional references are hashes that may name something kept outside
+/// the store (request hashes, ids); they are followed when present but a
+/// missing target is not an error.
+///
+/// A reference taken from a BlobRef also carries the blob's media type, so
+/// a `Registry` can route the target to the extractor registered for it.
+use crate::error::{Error, Result};
+use crate::registry::Registry;
+use crate::Cid;
+use mythos_blob::ChunkNode;
+
+/// What a reference says about its target
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ObjectKind {
+    /// Root or hash field: classify by shape once fetched
+    Unknown,
+    /// ChunkLeaf or ChunkInternal node
+    ChunkNode,
+    /// Blob chunk bytes
+    Chunk,
+}
+
+/// Edge from a stored object to another
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Reference {
+    pub cid: Cid,
+    pub kind: ObjectKind,
+    /// The target may legitimately be absent from the store
+    pub optional: bool,
+    /// Media type of the target, when the referrer records one
+    pub media: Option<String>,
+}
+
+impl Reference {
+    /// Reference to an object that must be in the store
+    pub fn new(cid: Cid, kind: ObjectKind) -> Self {
+        Reference {
+            cid,
+            kind,
+            optional: false,
+            media: None,
+        }
+    }
+
+    /// Reference to an object that may live elsewhere
+    pub fn optional(cid: Cid, kind: ObjectKind) -> Self {
+        Reference {
+            cid,
+            kind,
+            optional: true,
+            media: None,
+        }
+    }
+
+    /// The same reference with the target's media type recorded
+    pub fn with_media(mut self, media: impl Into<String>) -> Self {
+        self.media = Some(media.into());
+        self
+    }
+}
+
+/// Objects `bytes` refers to, classified with the built-in registry
+///
+/// Fails with `Error::Corrupt` when the bytes claim to be a node (by
+/// reference or by their shape) but do not validate. Use
+/// `Registry::references` to classify with registered extractors too.
+pub fn references(bytes: &[u8], kind: ObjectKind) -> Result<Vec<Reference>> {
+    Registry::builtin().references(bytes, &Reference::new([0; 32], kind))
+}
+
+/// References held by a ChunkLeaf or ChunkInternal
+pub(crate) fn chunk_node_refs(bytes: &[u8]) -> Result<Vec<Reference>> {
+    let node = mythos_blob::decode_chunk_node(bytes)
+        .map_err(|e| Error::Corrupt(format!("ChunkedBlob node: {}", e)))?;
+
+    Ok(match node {
+        ChunkNode::Leaf(leaf) => leaf
+            .chunks
+            .iter()
+            .filter_map(|c| Cid::try_from(&c.hash[..]).ok())
+            .map(|cid| Reference::new(cid, ObjectKind::Chunk))
+            .collect(),
+        ChunkNode::Internal(internal) => internal
+            .children
+            .iter()
+            .filter_map(|c| Cid::try_from(&c[..]).ok())
+            .map(|cid| Reference::new(cid, ObjectKind::ChunkNode))
+            .collect(),
+    })
+}
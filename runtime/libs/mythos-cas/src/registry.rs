@@ -0,0 +1,539 @@
+/// Object type registry
+///
+/// MYTHOS objects are untagged MYTHOS-CAN values, so their type is
+/// recovered from shape: which field numbers are present and what value
+/// types they hold. Each `Extractor` recognises one type and lists the
+/// references it holds. Extractors are tried in registration order and the
+/// first match wins, so more specific shapes must be registered first;
+/// `Registry::register` appends, keeping the built-ins in front.
+///
+/// When the media type is known (e.g. from a BlobRef), `classify_media`
+/// looks the extractor up directly instead of sniffing. References taken
+/// from a BlobRef carry its media type, so `references` routes their
+/// targets the same way.
+///
+/// Built-in types (RFC-0001 Appendix A, RFC-0004):
+///
+/// | type | refs followed |
+/// |------|---------------|
+/// | MerkleList node | children; values (optional) |
+/// | ChunkedBlob node | child nodes / chunks |
+/// | BlobRef | cid; provenance parents and code_hash (optional) |
+/// | Receipt | request/response hashes, evidence (optional) |
+/// | DatasetDef | corpus_roots |
+/// | DatasetRef | manifest BlobRef; def and receipt ids (optional) |
+/// | TraceRef | trace BlobRef; receipt_ids (optional) |
+/// | EpisodeRef | TraceRef; context/outcome hashes (optional) |
+/// | ModelRef | weights and config BlobRefs; code hash and parent (optional) |
+/// | IRBundle | Hash constants (optional) |
+use crate::error::{Error, Result};
+use crate::refs::{chunk_node_refs, ObjectKind, Reference};
+use crate::Cid;
+use mythos_blob::{BlobRef, KIND_CHUNK_INTERNAL, KIND_CHUNK_LEAF};
+use mythos_can::Value;
+use mythos_merkle::{MerkleListNode, KIND_MERKLE_LIST_INTERNAL, KIND_MERKLE_LIST_LEAF};
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+/// Recognises one object type and extracts its references
+pub trait Extractor: Send + Sync {
+    /// Type name reported by `classify`
+    fn name(&self) -> &'static str;
+
+    /// Whether `value` has this type's shape
+    fn matches(&self, value: &Value) -> bool;
+
+    /// Outgoing references of a matching value
+    ///
+    /// Fails with `Error::Corrupt` if the value has the shape but not the
+    /// content of this type.
+    fn references(&self, value: &Value) -> Result<Vec<Reference>>;
+}
+
+/// A classified object
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObjectInfo {
+    pub type_name: &'static str,
+    pub refs: Vec<Reference>,
+}
+
+/// Ordered set of extractors, with an optional media type index
+pub struct Registry {
+    extractors: Vec<Box<dyn Extractor>>,
+    by_media: HashMap<String, usize>,
+}
+
+impl std::fmt::Debug for Registry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let names: Vec<_> = self.extractors.iter().map(|e| e.name()).collect();
+        f.debug_struct("Registry")
+            .field("extractors", &names)
+            .field("media", &self.by_media.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl Default for Registry {
+    /// Registry holding the built-in MYTHOS types
+    fn default() -> Self {
+        let mut registry = Registry::empty();
+        registry.register(MerkleListExtractor);
+        registry.register(ChunkNodeExtractor);
+        registry.register(BlobRefExtractor);
+        registry.register(ReceiptExtractor);
+        registry.register(DatasetDefExtractor);
+        registry.register(DatasetRefExtractor);
+        registry.register(EpisodeRefExtractor);
+        registry.register(TraceRefExtractor);
+        registry.register(ModelRefExtractor);
+        registry.register(IrBundleExtractor);
+        registry
+    }
+}
+
+impl Registry {
+    /// Registry with no extractors
+    pub fn empty() -> Self {
+        Registry {
+            extractors: Vec::new(),
+            by_media: HashMap::new(),
+        }
+    }
+
+    /// Shared registry of the built-in types
+    pub fn builtin() -> &'static Registry {
+        static BUILTIN: OnceLock<Registry> = OnceLock::new();
+        BUILTIN.get_or_init(Registry::default)
+    }
+
+    /// Add an extractor after those already registered
+    pub fn register<E: Extractor + 'static>(&mut self, extractor: E) -> &mut Self {
+        self.extractors.push(Box::new(extractor));
+        self
+    }
+
+    /// Add an extractor and route `media` straight to it
+    pub fn register_media<E: Extractor + 'static>(
+        &mut self,
+        media: &str,
+        extractor: E,
+    ) -> &mut Self {
+        self.by_media
+            .insert(media.to_string(), self.extractors.len());
+        self.register(extractor)
+    }
+
+    /// Names of registered types, in match order
+    pub fn type_names(&self) -> Vec<&'static str> {
+        self.extractors.iter().map(|e| e.name()).collect()
+    }
+
+    /// Classify `value` by shape, or `None` if no extractor matches
+    pub fn classify(&self, value: &Value) -> Result<Option<ObjectInfo>> {
+        match self.extractors.iter().find(|e| e.matches(value)) {
+            Some(extractor) => info(extractor.as_ref(), value).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Classify `value` whose media type is known, falling back to shape
+    pub fn classify_media(&self, media: &str, value: &Value) -> Result<Option<ObjectInfo>> {
+        match self.by_media.get(media) {
+            Some(index) => info(self.extractors[*index].as_ref(), value).map(Some),
+            None => self.classify(value),
+        }
+    }
+
+    /// Whether `references` hands the target of `reference` to an extractor
+    /// registered for its media type rather than classifying it by shape
+    ///
+    /// An object reached both ways has to be followed both ways.
+    pub(crate) fn routes(&self, reference: &Reference) -> bool {
+        reference.kind == ObjectKind::Unknown
+            && reference
+                .media
+                .as_ref()
+                .is_some_and(|media| self.by_media.contains_key(media))
+    }
+
+    /// Objects `bytes` refers to, given the reference that reached it
+    ///
+    /// A target whose media type was registered with `register_media` is
+    /// handed to that extractor, and must decode as MYTHOS-CAN; otherwise
+    /// the target's kind decides, with unknown objects classified by shape.
+    pub fn references(&self, bytes: &[u8], target: &Reference) -> Result<Vec<Reference>> {
+        match target.kind {
+            ObjectKind::Chunk => Ok(Vec::new()),
+            ObjectKind::ChunkNode => chunk_node_refs(bytes),
+            ObjectKind::Unknown => {
+                let routed = target
+                    .media
+                    .as_ref()
+                    .and_then(|media| self.by_media.get(media));
+                if let Some(index) = routed {
+                    let value = mythos_can::decode_value_exact(bytes).map_err(|e| {
+                        Error::Corrupt(format!("{}: {}", self.extractors[*index].name(), e))
+                    })?;
+                    return Ok(info(self.extractors[*index].as_ref(), &value)?.refs);
+                }
+
+                // Not MYTHOS-CAN: opaque data
+                let value = match mythos_can::decode_value_exact(bytes) {
+                    Ok(value) => value,
+                    Err(_) => return Ok(Vec::new()),
+                };
+                Ok(self.classify(&value)?.map(|i| i.refs).unwrap_or_default())
+            }
+        }
+    }
+}
+
+fn info(extractor: &dyn Extractor, value: &Value) -> Result<ObjectInfo> {
+    Ok(ObjectInfo {
+        type_name: extractor.name(),
+        refs: extractor.references(value)?,
+    })
+}
+
+// Shape helpers
+
+fn fields(value: &Value) -> Option<&[(Value, Value)]> {
+    match value {
+        Value::Map(pairs) => Some(pairs),
+        _ => None,
+    }
+}
+
+fn field(value: &Value, n: u64) -> Option<&Value> {
+    fields(value)?
+        .iter()
+        .find(|(k, _)| matches!(k, Value::UVarint(x) if *x == n))
+        .map(|(_, v)| v)
+}
+
+/// `Hash { 1: alg, 2: bytes }`
+fn is_hash(value: Option<&Value>) -> bool {
+    let Some(value) = value else {
+        return false;
+    };
+    fields(value).is_some_and(|pairs| pairs.len() == 2)
+        && matches!(field(value, 1), Some(Value::UVarint(_)))
+        && matches!(field(value, 2), Some(Value::Bytes(_)))
+}
+
+/// CID of a SHA-256 Hash value
+fn hash_cid(value: Option<&Value>) -> Option<Cid> {
+    let value = value?;
+    match (field(value, 1), field(value, 2)) {
+        (Some(Value::UVarint(1)), Some(Value::Bytes(bytes))) => Cid::try_from(&bytes[..]).ok(),
+        _ => None,
+    }
+}
+
+fn is_map(value: Option<&Value>) -> bool {
+    matches!(value, Some(Value::Map(_)))
+}
+
+fn is_blob_ref(value: Option<&Value>) -> bool {
+    value.is_some_and(|v| BlobRef::from_value(v).is_ok())
+}
+
+fn optional_hash(refs: &mut Vec<Reference>, value: Option<&Value>) {
+    if let Some(cid) = hash_cid(value) {
+        refs.push(Reference::optional(cid, ObjectKind::Unknown));
+    }
+}
+
+fn optional_hash_list(refs: &mut Vec<Reference>, value: Option<&Value>) {
+    if let Some(Value::List(items)) = value {
+        for item in items {
+            optional_hash(refs, Some(item));
+        }
+    }
+}
+
+/// References held by an embedded or stored BlobRef
+fn blob_ref_refs(blob_ref: &BlobRef) -> Vec<Reference> {
+    let mut refs = Vec::new();
+
+    // A zero chunk count is also used for counts above u32::MAX, so only a
+    // non-zero count pins down the target as a DAG root
+    let kind = if blob_ref.chunks > 0 {
+        ObjectKind::ChunkNode
+    } else {
+        ObjectKind::Unknown
+    };
+    if let Ok(cid) = Cid::try_from(&blob_ref.cid[..]) {
+        refs.push(Reference::new(cid, kind).with_media(blob_ref.media.as_str()));
+    }
+
+    if let Some(provenance) = &blob_ref.provenance {
+        for hash in provenance
+            .parents
+            .iter()
+            .chain(std::iter::once(&provenance.code_hash))
+        {
+            if let Ok(cid) = Cid::try_from(&hash[..]) {
+                refs.push(Reference::optional(cid, ObjectKind::Unknown));
+            }
+        }
+    }
+    refs
+}
+
+fn embedded_blob_ref(value: Option<&Value>) -> Result<Vec<Reference>> {
+    let value = value.ok_or_else(|| Error::Corrupt("missing BlobRef".into()))?;
+    let blob_ref =
+        BlobRef::from_value(value).map_err(|e| Error::Corrupt(format!("BlobRef: {}", e)))?;
+    Ok(blob_ref_refs(&blob_ref))
+}
+
+// Built-in extractors
+
+/// MerkleListLeaf / MerkleListInternal (RFC-0004 §5)
+///
+/// Leaf values are hashes whose meaning depends on the list (episode ids,
+/// receipt ids, ...), so they are followed only when present; register an
+/// extractor for the list's media type to resolve them.
+struct MerkleListExtractor;
+
+impl Extractor for MerkleListExtractor {
+    fn name(&self) -> &'static str {
+        "MerkleList"
+    }
+
+    fn matches(&self, value: &Value) -> bool {
+        mythos_merkle::parse_merkle_node(value)
+            .is_ok_and(|h| h.kind == KIND_MERKLE_LIST_LEAF || h.kind == KIND_MERKLE_LIST_INTERNAL)
+    }
+
+    fn references(&self, value: &Value) -> Result<Vec<Reference>> {
+        let bytes = mythos_can::encode_value(value)
+            .map_err(|e| Error::Corrupt(format!("MerkleList node: {}", e)))?;
+        let node = mythos_merkle::decode_merkle_list_node(&bytes)
+            .map_err(|e| Error::Corrupt(format!("MerkleList node: {}", e)))?;
+
+        let (hashes, reference): (_, fn(Cid, ObjectKind) -> Reference) = match node {
+            MerkleListNode::Leaf(leaf) => (leaf.values, Reference::optional),
+            MerkleListNode::Internal(internal) => (internal.children, Reference::new),
+        };
+        Ok(hashes
+            .iter()
+            .filter(|h| h.alg == 1)
+            .filter_map(|h| Cid::try_from(&h.bytes[..]).ok())
+            .map(|cid| reference(cid, ObjectKind::Unknown))
+            .collect())
+    }
+}
+
+/// ChunkLeaf / ChunkInternal (RFC-0004 §6)
+struct ChunkNodeExtractor;
+
+impl Extractor for ChunkNodeExtractor {
+    fn name(&self) -> &'static str {
+        "ChunkedBlobNode"
+    }
+
+    fn matches(&self, value: &Value) -> bool {
+        mythos_merkle::parse_merkle_node(value)
+            .is_ok_and(|h| h.kind == KIND_CHUNK_LEAF || h.kind == KIND_CHUNK_INTERNAL)
+    }
+
+    fn references(&self, value: &Value) -> Result<Vec<Reference>> {
+        let bytes = mythos_can::encode_value(value)
+            .map_err(|e| Error::Corrupt(format!("ChunkedBlob node: {}", e)))?;
+        chunk_node_refs(&bytes)
+    }
+}
+
+/// BlobRef (A.8)
+struct BlobRefExtractor;
+
+impl Extractor for BlobRefExtractor {
+    fn name(&self) -> &'static str {
+        "BlobRef"
+    }
+
+    fn matches(&self, value: &Value) -> bool {
+        is_blob_ref(Some(value))
+    }
+
+    fn references(&self, value: &Value) -> Result<Vec<Reference>> {
+        embedded_blob_ref(Some(value))
+    }
+}
+
+/// Receipt (A.9)
+struct ReceiptExtractor;
+
+impl Extractor for ReceiptExtractor {
+    fn name(&self) -> &'static str {
+        "Receipt"
+    }
+
+    fn matches(&self, value: &Value) -> bool {
+        is_hash(field(value, 2))
+            && is_hash(field(value, 3))
+            && is_hash(field(value, 4))
+            && matches!(field(value, 5), Some(Value::Bytes(_)))
+            && is_map(field(value, 6))
+            && matches!(field(value, 7), Some(Value::IVarint(_)))
+            && matches!(field(value, 8), Some(Value::UVarint(_)))
+    }
+
+    fn references(&self, value: &Value) -> Result<Vec<Reference>> {
+        let mut refs = Vec::new();
+        optional_hash(&mut refs, field(value, 3));
+        optional_hash(&mut refs, field(value, 4));
+        optional_hash_list(&mut refs, field(value, 9));
+        Ok(refs)
+    }
+}
+
+/// DatasetDef (A.15)
+struct DatasetDefExtractor;
+
+impl Extractor for DatasetDefExtractor {
+    fn name(&self) -> &'static str {
+        "DatasetDef"
+    }
+
+    fn matches(&self, value: &Value) -> bool {
+        is_hash(field(value, 1))
+            && matches!(field(value, 2), Some(Value::List(_)))
+            && is_map(field(value, 3))
+            && is_map(field(value, 4))
+    }
+
+    fn references(&self, value: &Value) -> Result<Vec<Reference>> {
+        let roots = match field(value, 2) {
+            Some(Value::List(roots)) => roots,
+            _ => return Err(Error::Corrupt("DatasetDef corpus_roots".into())),
+        };
+        roots
+            .iter()
+            .map(|root| {
+                hash_cid(Some(root))
+                    .map(|cid| Reference::new(cid, ObjectKind::Unknown))
+                    .ok_or_else(|| Error::Corrupt("DatasetDef corpus root must be a Hash".into()))
+            })
+            .collect()
+    }
+}
+
+/// DatasetRef (A.15)
+struct DatasetRefExtractor;
+
+impl Extractor for DatasetRefExtractor {
+    fn name(&self) -> &'static str {
+        "DatasetRef"
+    }
+
+    fn matches(&self, value: &Value) -> bool {
+        is_hash(field(value, 1))
+            && is_blob_ref(field(value, 2))
+            && matches!(field(value, 3), Some(Value::UVarint(_)))
+            && is_hash(field(value, 4))
+    }
+
+    fn references(&self, value: &Value) -> Result<Vec<Reference>> {
+        let mut refs = embedded_blob_ref(field(value, 2))?;
+        optional_hash(&mut refs, field(value, 1));
+        optional_hash(&mut refs, field(value, 4));
+        Ok(refs)
+    }
+}
+
+/// EpisodeRef (A.15)
+struct EpisodeRefExtractor;
+
+impl Extractor for EpisodeRefExtractor {
+    fn name(&self) -> &'static str {
+        "EpisodeRef"
+    }
+
+    fn matches(&self, value: &Value) -> bool {
+        is_hash(field(value, 1))
+            && field(value, 2).is_some_and(|t| TraceRefExtractor.matches(t))
+            && is_hash(field(value, 3))
+            && is_hash(field(value, 4))
+            && matches!(field(value, 5), Some(Value::IVarint(_)))
+    }
+
+    fn references(&self, value: &Value) -> Result<Vec<Reference>> {
+        let trace = field(value, 2).ok_or_else(|| Error::Corrupt("EpisodeRef trace".into()))?;
+        let mut refs = TraceRefExtractor.references(trace)?;
+        optional_hash(&mut refs, field(value, 3));
+        optional_hash(&mut refs, field(value, 4));
+        Ok(refs)
+    }
+}
+
+/// TraceRef (A.15)
+struct TraceRefExtractor;
+
+impl Extractor for TraceRefExtractor {
+    fn name(&self) -> &'static str {
+        "TraceRef"
+    }
+
+    fn matches(&self, value: &Value) -> bool {
+        fields(value).is_some_and(|f| f.len() == 2)
+            && is_blob_ref(field(value, 1))
+            && matches!(field(value, 2), Some(Value::List(_)))
+    }
+
+    fn references(&self, value: &Value) -> Result<Vec<Reference>> {
+        let mut refs = embedded_blob_ref(field(value, 1))?;
+        optional_hash_list(&mut refs, field(value, 2));
+        Ok(refs)
+    }
+}
+
+/// ModelRef (A.15)
+struct ModelRefExtractor;
+
+impl Extractor for ModelRefExtractor {
+    fn name(&self) -> &'static str {
+        "ModelRef"
+    }
+
+    fn matches(&self, value: &Value) -> bool {
+        is_hash(field(value, 1))
+            && is_blob_ref(field(value, 2))
+            && is_blob_ref(field(value, 3))
+            && is_hash(field(value, 4))
+    }
+
+    fn references(&self, value: &Value) -> Result<Vec<Reference>> {
+        let mut refs = embedded_blob_ref(field(value, 2))?;
+        refs.extend(embedded_blob_ref(field(value, 3))?);
+        optional_hash(&mut refs, field(value, 4));
+        optional_hash(&mut refs, field(value, 5));
+        Ok(refs)
+    }
+}
+
+/// IRBundle (A.14)
+struct IrBundleExtractor;
+
+impl Extractor for IrBundleExtractor {
+    fn name(&self) -> &'static str {
+        "IRBundle"
+    }
+
+    fn matches(&self, value: &Value) -> bool {
+        fields(value).is_some_and(|f| f.len() == 9) && (1..=9).all(|n| is_map(field(value, n)))
+    }
+
+    fn references(&self, value: &Value) -> Result<Vec<Reference>> {
+        // ConstPool { 1: [Const { 1: kind, .., 6: hash }] }
+        let mut refs = Vec::new();
+        if let Some(Value::List(consts)) = field(value, 2).and_then(|pool| field(pool, 1)) {
+            for c in consts {
+                optional_hash(&mut refs, field(c, 6));
+            }
+        }
+        Ok(refs)
+    }
+}
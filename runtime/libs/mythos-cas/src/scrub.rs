@@ -0,0 +1,221 @@
+/// Integrity scrub (fsck)
+///
+/// Re-hashes every stored object in CID order. Objects whose bytes no
+/// longer match their CID are quarantined. Every object
+/// `ScrubOptions::registry` recognises has its references checked: missing
+/// required targets are reported, and targets referenced as ChunkedBlob
+/// nodes must decode as one. A target referenced through a media type the
+/// registry routes is decoded as that type, and its own references are
+/// checked the same way.
+///
+/// Objects that hash correctly but fail structural validation are
+/// reported, not quarantined: their CID is honest, so they are bad input
+/// rather than disk corruption.
+///
+/// A run can stop after `max_objects` and be resumed from the returned
+/// cursor; `max_bytes_per_sec` throttles reads so a scrub can run on a
+/// live node.
+use crate::error::{Error, Result};
+use crate::refs::{ObjectKind, Reference};
+use crate::registry::Registry;
+use crate::{BlobStore, Cid};
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone)]
+pub struct ScrubOptions<'a> {
+    /// Start after this CID (the `cursor` of an earlier, incomplete run)
+    pub resume_after: Option<Cid>,
+    /// Stop after checking this many objects
+    pub max_objects: Option<u64>,
+    /// Read throughput cap
+    pub max_bytes_per_sec: Option<u64>,
+    /// Move corrupt objects into quarantine (otherwise only report them)
+    pub quarantine: bool,
+    /// Types whose references are checked
+    pub registry: &'a Registry,
+}
+
+impl Default for ScrubOptions<'static> {
+    fn default() -> Self {
+        ScrubOptions {
+            resume_after: None,
+            max_objects: None,
+            max_bytes_per_sec: None,
+            quarantine: true,
+            registry: Registry::builtin(),
+        }
+    }
+}
+
+/// A reference whose target is not in the store
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MissingRef {
+    pub parent: Cid,
+    pub child: Cid,
+}
+
+/// Outcome of a scrub run
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ScrubReport {
+    pub checked: u64,
+    pub bytes_read: u64,
+    /// Objects whose bytes do not hash to their CID
+    pub corrupt: Vec<Cid>,
+    /// Corrupt objects moved into quarantine
+    pub quarantined: Vec<Cid>,
+    /// Objects that hash correctly but fail validation, with the reason
+    pub invalid: Vec<(Cid, String)>,
+    pub missing: Vec<MissingRef>,
+    /// Last CID checked; pass as `resume_after` to continue
+    pub cursor: Option<Cid>,
+    /// True when the run reached the end of the store
+    pub complete: bool,
+}
+
+/// Check stored objects, in CID order, starting after `resume_after`
+pub fn scrub<S: BlobStore + ?Sized>(store: &S, options: &ScrubOptions) -> Result<ScrubReport> {
+    let mut cids = store.list()?;
+    cids.sort_unstable();
+    if let Some(after) = options.resume_after {
+        cids.retain(|cid| *cid > after);
+    }
+
+    let mut report = ScrubReport::default();
+    let throttle = options.max_bytes_per_sec.map(Throttle::new);
+
+    for cid in &cids {
+        if options.max_objects.is_some_and(|max| report.checked >= max) {
+            return Ok(report);
+        }
+
+        check_object(store, cid, options, &mut report)?;
+        report.checked += 1;
+        report.cursor = Some(*cid);
+
+        if let Some(throttle) = &throttle {
+            throttle.wait(report.bytes_read);
+        }
+    }
+
+    report.complete = true;
+    Ok(report)
+}
+
+fn check_object<S: BlobStore + ?Sized>(
+    store: &S,
+    cid: &Cid,
+    options: &ScrubOptions,
+    report: &mut ScrubReport,
+) -> Result<()> {
+    let bytes = match store.get(cid) {
+        Ok(bytes) => bytes,
+        // Deleted since listing, e.g. by a concurrent GC
+        Err(Error::NotFound(_)) => return Ok(()),
+        Err(Error::Corrupt(_)) => {
+            report.corrupt.push(*cid);
+            if options.quarantine && store.quarantine(cid)? {
+                report.quarantined.push(*cid);
+            }
+            return Ok(());
+        }
+        Err(e) => return Err(e),
+    };
+    report.bytes_read += bytes.len() as u64;
+
+    let target = Reference::new(*cid, ObjectKind::Unknown);
+    let refs = match options.registry.references(&bytes, &target) {
+        Ok(refs) => refs,
+        Err(Error::Corrupt(reason)) => {
+            report.invalid.push((*cid, reason));
+            return Ok(());
+        }
+        Err(e) => return Err(e),
+    };
+
+    // Nodes reached through a routed media type, e.g. the leaves of a
+    // dataset manifest, are only that type by reference
+    let mut pending = vec![(*cid, refs)];
+    let mut routed = HashSet::new();
+    while let Some((parent, refs)) = pending.pop() {
+        for reference in refs {
+            if !store.has(&reference.cid)? {
+                let missing = MissingRef {
+                    parent,
+                    child: reference.cid,
+                };
+                if !reference.optional && !report.missing.contains(&missing) {
+                    report.missing.push(missing);
+                }
+                continue;
+            }
+
+            // Unknown targets are checked on their own turn; a target that
+            // is only a node by reference has to be decoded as one here
+            if reference.kind == ObjectKind::ChunkNode {
+                let child = match store.get(&reference.cid) {
+                    Ok(child) => child,
+                    Err(Error::NotFound(_)) | Err(Error::Corrupt(_)) => continue,
+                    Err(e) => return Err(e),
+                };
+                report.bytes_read += child.len() as u64;
+                if let Err(e) = mythos_blob::decode_chunk_node(&child) {
+                    invalid(
+                        report,
+                        reference.cid,
+                        format!("referenced as ChunkedBlob node: {}", e),
+                    );
+                }
+            } else if options.registry.routes(&reference) && routed.insert(reference.cid) {
+                let child = match store.get(&reference.cid) {
+                    Ok(child) => child,
+                    Err(Error::NotFound(_)) | Err(Error::Corrupt(_)) => continue,
+                    Err(e) => return Err(e),
+                };
+                report.bytes_read += child.len() as u64;
+                match options.registry.references(&child, &reference) {
+                    Ok(refs) => pending.push((reference.cid, refs)),
+                    Err(Error::Corrupt(reason)) => {
+                        let media = reference.media.as_deref().unwrap_or_default();
+                        invalid(
+                            report,
+                            reference.cid,
+                            format!("referenced as {}: {}", media, reason),
+                        );
+                    }
+                    Err(e) => return Err(e),
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn invalid(report: &mut ScrubReport, cid: Cid, reason: String) {
+    if !report.invalid.iter().any(|(c, _)| *c == cid) {
+        report.invalid.push((cid, reason));
+    }
+}
+
+/// Sleeps to keep average read throughput under a cap
+struct Throttle {
+    started: Instant,
+    bytes_per_sec: u64,
+}
+
+impl Throttle {
+    fn new(bytes_per_sec: u64) -> Self {
+        Throttle {
+            started: Instant::now(),
+            bytes_per_sec: bytes_per_sec.max(1),
+        }
+    }
+
+    fn wait(&self, total_bytes: u64) {
+        let due = Duration::from_secs_f64(total_bytes as f64 / self.bytes_per_sec as f64);
+        let elapsed = self.started.elapsed();
+        if due > elapsed {
+            std::thread::sleep(due - elapsed);
+        }
+    }
+}
@@ -0,0 +1,46 @@
+/// Node and chunk sources backed by a BlobStore
+///
+/// `StoreSource` lets the Merkle diff, blob verification and range reads
+/// pull nodes and chunks straight from a store. Missing objects map to the
+/// callers' not-found errors; other store failures map to `Source`.
+use crate::error::{Error, Result};
+use crate::{BlobStore, Cid};
+
+/// Adapter exposing a store as Merkle/blob node and chunk sources
+#[derive(Debug, Clone, Copy)]
+pub struct StoreSource<'a, S: ?Sized>(pub &'a S);
+
+impl<S: BlobStore + ?Sized> mythos_merkle::NodeSource for StoreSource<'_, S> {
+    fn fetch_node(&self, cid: &Cid) -> mythos_merkle::Result<Vec<u8>> {
+        self.0.get(cid).map_err(|e| match e {
+            Error::NotFound(cid) => mythos_merkle::Error::NodeNotFound(cid),
+            other => mythos_merkle::Error::Source(other.to_string()),
+        })
+    }
+}
+
+impl<S: BlobStore + ?Sized> mythos_blob::NodeSource for StoreSource<'_, S> {
+    fn fetch_node(&self, cid: &Cid) -> mythos_blob::Result<Vec<u8>> {
+        self.0.get(cid).map_err(|e| match e {
+            Error::NotFound(cid) => mythos_blob::Error::NodeNotFound(cid),
+            other => mythos_blob::Error::Source(other.to_string()),
+        })
+    }
+}
+
+impl<S: BlobStore + ?Sized> mythos_blob::ChunkSource for StoreSource<'_, S> {
+    fn fetch_chunk(&self, hash: &Cid) -> mythos_blob::Result<Vec<u8>> {
+        self.0.get(hash).map_err(|e| match e {
+            Error::NotFound(hash) => mythos_blob::Error::ChunkNotFound(hash),
+            other => mythos_blob::Error::Source(other.to_string()),
+        })
+    }
+}
+
+/// Store built nodes, e.g. `MerkleListBuild::nodes` or `ChunkedBlobBuild::nodes`
+pub fn put_nodes<S: BlobStore + ?Sized>(store: &S, nodes: &[(Cid, Vec<u8>)]) -> Result<()> {
+    for (cid, bytes) in nodes {
+        store.put_with_cid(cid, bytes)?;
+    }
+    Ok(())
+}
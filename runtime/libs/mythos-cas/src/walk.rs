@@ -0,0 +1,111 @@
+/// Generic object graph traversal
+///
+/// Breadth-first from a set of roots, visiting each object once at its
+/// shortest distance from a root, so cycles and shared subgraphs terminate
+/// and `max_depth` cuts the graph the same way whatever order references
+/// are listed in. References are classified by a `Registry`. A missing
+/// required target is reported; a missing optional one is skipped silently.
+///
+/// An object reached both by shape and through a media type the registry
+/// routes (e.g. a MerkleList that is both a corpus and a dataset manifest)
+/// is visited once, but its references are followed both ways, in either
+/// order.
+use crate::error::Result;
+use crate::refs::{ObjectKind, Reference};
+use crate::registry::Registry;
+use crate::{BlobStore, Cid};
+use std::collections::{HashSet, VecDeque};
+
+#[derive(Debug, Clone, Default)]
+pub struct WalkOptions {
+    /// Do not follow references below this depth (roots are depth 0)
+    pub max_depth: Option<u32>,
+    /// Stop after visiting this many objects
+    pub max_objects: Option<u64>,
+}
+
+/// An object reached by `walk`
+#[derive(Debug)]
+pub struct Visit<'a> {
+    pub cid: Cid,
+    pub depth: u32,
+    /// What the referrer said the object is
+    pub kind: ObjectKind,
+    pub bytes: &'a [u8],
+    pub refs: &'a [Reference],
+}
+
+/// Outcome of a walk
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WalkReport {
+    pub visited: u64,
+    /// Required references whose target is not in the store
+    pub missing: Vec<Cid>,
+    /// A limit stopped the walk before the graph was exhausted
+    pub truncated: bool,
+}
+
+/// Visit every object reachable from `roots`
+///
+/// Fails with `Error::Corrupt` if an object does not decode as what its
+/// referrer says it is, or with the first error returned by `visit`.
+pub fn walk<S, F>(
+    store: &S,
+    registry: &Registry,
+    roots: &[Cid],
+    options: &WalkOptions,
+    mut visit: F,
+) -> Result<WalkReport>
+where
+    S: BlobStore + ?Sized,
+    F: FnMut(&Visit) -> Result<()>,
+{
+    let mut report = WalkReport::default();
+    // Objects are queued in order of depth, so the first time a CID is
+    // queued is at its shortest distance from a root. Routed and shape
+    // traversals of one CID are queued separately
+    let mut seen = HashSet::new();
+    let mut visited = HashSet::new();
+    let mut queue: VecDeque<(Reference, u32)> = roots
+        .iter()
+        .filter(|cid| seen.insert((**cid, false)))
+        .map(|cid| (Reference::new(*cid, ObjectKind::Unknown), 0))
+        .collect();
+
+    while let Some((reference, depth)) = queue.pop_front() {
+        if options.max_objects.is_some_and(|max| report.visited >= max) {
+            report.truncated = true;
+            break;
+        }
+        if !store.has(&reference.cid)? {
+            if !reference.optional && !report.missing.contains(&reference.cid) {
+                report.missing.push(reference.cid);
+            }
+            continue;
+        }
+
+        let bytes = store.get(&reference.cid)?;
+        let refs = registry.references(&bytes, &reference)?;
+        if visited.insert(reference.cid) {
+            visit(&Visit {
+                cid: reference.cid,
+                depth,
+                kind: reference.kind,
+                bytes: &bytes,
+                refs: &refs,
+            })?;
+            report.visited += 1;
+        }
+
+        if options.max_depth.is_some_and(|max| depth >= max) {
+            report.truncated |= !refs.is_empty();
+            continue;
+        }
+        for r in refs {
+            if seen.insert((r.cid, registry.routes(&r))) {
+                queue.push_back((r, depth + 1));
+            }
+        }
+    }
+    Ok(report)
+}
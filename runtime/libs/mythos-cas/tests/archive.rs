@@ -0,0 +1,199 @@
+/// Archive export/import tests
+use mythos_blob::{read_range, BlobRef, MIN_CHUNK_SIZE};
+use mythos_can::Value;
+use mythos_cas::{
+    cid_from_bytes, export_archive, import_archive, put_nodes, BlobStore, Cid, Error, FsStore,
+    PackStore, StoreSource, MAX_FRAME_LEN,
+};
+use mythos_merkle::{build_merkle_list, diff_merkle_lists, HashValue};
+
+mod common;
+use common::{hash, pattern, put_blob, TempDir};
+
+fn frame(value: &Value) -> Vec<u8> {
+    let bytes = mythos_can::encode_value(value).unwrap();
+    let mut out = Vec::new();
+    mythos_can::encode_uvarint(&mut out, bytes.len() as u64).unwrap();
+    out.extend(bytes);
+    out
+}
+
+/// Length of the UVARINT frame length at the start of `frame`
+fn frame_prefix(frame: &[u8]) -> usize {
+    frame.iter().position(|b| b & 0x80 == 0).unwrap() + 1
+}
+
+fn header(roots: &[Cid]) -> Vec<u8> {
+    frame(&Value::Map(vec![
+        (Value::UVarint(1), Value::UVarint(1)),
+        (
+            Value::UVarint(2),
+            Value::List(roots.iter().map(hash).collect()),
+        ),
+    ]))
+}
+
+fn record(cid: &Cid, bytes: &[u8]) -> Vec<u8> {
+    frame(&Value::Map(vec![
+        (Value::UVarint(1), hash(cid)),
+        (Value::UVarint(2), Value::Bytes(bytes.to_vec())),
+    ]))
+}
+
+#[test]
+fn test_roundtrip_between_backends() {
+    let src_dir = TempDir::new("src");
+    let dst_dir = TempDir::new("dst");
+    let src = FsStore::open(&src_dir.0).unwrap();
+
+    let data = pattern(MIN_CHUNK_SIZE as usize * 3 + 11);
+    let blob = put_blob(&src, &data).cid;
+    let values: Vec<HashValue> = (0..1200u32)
+        .map(|i| HashValue {
+            alg: 1,
+            bytes: src.put(format!("row {}", i).as_bytes()).unwrap().to_vec(),
+        })
+        .collect();
+    let list = build_merkle_list(&values).unwrap();
+    put_nodes(&src, &list.nodes).unwrap();
+    src.put(b"unrelated").unwrap();
+
+    let mut archive = Vec::new();
+    let exported = export_archive(&src, &[blob, list.root], &mut archive).unwrap();
+    assert_eq!(exported.objects, src.list().unwrap().len() as u64 - 1);
+
+    let dst = PackStore::open(&dst_dir.0).unwrap();
+    let imported = import_archive(&dst, &mut &archive[..]).unwrap();
+    assert_eq!(imported, exported);
+    assert!(!dst.has(&cid_from_bytes(b"unrelated")).unwrap());
+
+    // The imported DAGs are complete and usable
+    let source = StoreSource(&dst);
+    let blob_ref = BlobRef::from_bytes(&dst.get(&blob).unwrap()).unwrap();
+    let root: Cid = blob_ref.cid[..].try_into().unwrap();
+    assert_eq!(
+        read_range(&root, 0..blob_ref.size, &source, &source)
+            .unwrap()
+            .data,
+        data
+    );
+    assert!(diff_merkle_lists(&source, &list.root, &list.root)
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn test_export_fails_on_missing_object() {
+    let dir = TempDir::new("missing");
+    let store = FsStore::open(&dir.0).unwrap();
+
+    let absent = cid_from_bytes(b"absent");
+    let list = build_merkle_list(&[HashValue {
+        alg: 1,
+        bytes: absent.to_vec(),
+    }])
+    .unwrap();
+    put_nodes(&store, &list.nodes).unwrap();
+
+    // List values may name objects kept elsewhere
+    let summary = export_archive(&store, &[list.root], &mut Vec::new()).unwrap();
+    assert_eq!(summary.objects, list.nodes.len() as u64);
+
+    // A BlobRef's target is required
+    let blob_ref = BlobRef {
+        cid: absent.to_vec(),
+        size: 6,
+        media: "application/octet-stream".into(),
+        codec: 0,
+        chunks: 0,
+        encryption: None,
+        provenance: None,
+    };
+    let root = store.put(&blob_ref.to_bytes().unwrap()).unwrap();
+    let result = export_archive(&store, &[root], &mut Vec::new());
+    assert!(matches!(result, Err(Error::NotFound(_))));
+}
+
+#[test]
+fn test_export_frame_limit() {
+    let dir = TempDir::new("frame-limit");
+    let store = FsStore::open(&dir.0).unwrap();
+
+    // MYTHOS-CAN overhead of a record around an object this size
+    let probe = record(&[0; 32], &vec![0; MAX_FRAME_LEN as usize / 2]);
+    let overhead = probe.len() - MAX_FRAME_LEN as usize / 2 - frame_prefix(&probe);
+    let largest = MAX_FRAME_LEN as usize - overhead;
+
+    let mut bytes = pattern(largest);
+    let at_limit = store.put(&bytes).unwrap();
+    let mut archive = Vec::new();
+    export_archive(&store, &[at_limit], &mut archive).unwrap();
+    let import_dir = TempDir::new("frame-limit-import");
+    let imported = FsStore::open(&import_dir.0).unwrap();
+    let summary = import_archive(&imported, &mut archive.as_slice()).unwrap();
+    assert_eq!(summary.bytes, largest as u64);
+    assert_eq!(imported.get(&at_limit).unwrap(), bytes);
+
+    // One byte more would make an archive import refuses
+    bytes.push(0);
+    let over = store.put(&bytes).unwrap();
+    let result = export_archive(&store, &[over], &mut Vec::new());
+    assert!(matches!(result, Err(Error::InvalidArchive(_))));
+}
+
+#[test]
+fn test_import_rejects_tampered_record() {
+    let dir = TempDir::new("tampered");
+    let store = FsStore::open(&dir.0).unwrap();
+
+    let cid = cid_from_bytes(b"evidence");
+    let mut archive = header(&[cid]);
+    archive.extend(record(&cid, b"evidenze"));
+
+    let result = import_archive(&store, &mut &archive[..]);
+    assert!(matches!(result, Err(Error::CidMismatch { .. })));
+    assert!(store.list().unwrap().is_empty());
+}
+
+#[test]
+fn test_import_rejects_missing_root() {
+    let dir = TempDir::new("no-root");
+    let store = FsStore::open(&dir.0).unwrap();
+
+    let present = cid_from_bytes(b"present");
+    let claimed = cid_from_bytes(b"claimed");
+    let mut archive = header(&[present, claimed]);
+    archive.extend(record(&present, b"present"));
+
+    let result = import_archive(&store, &mut &archive[..]);
+    assert!(matches!(result, Err(Error::MissingRoot(root)) if root == hex::encode(claimed)));
+}
+
+#[test]
+fn test_import_rejects_malformed_streams() {
+    let dir = TempDir::new("malformed");
+    let store = FsStore::open(&dir.0).unwrap();
+    let cid = cid_from_bytes(b"x");
+
+    let empty: &[u8] = &[];
+    assert!(matches!(
+        import_archive(&store, &mut &empty[..]),
+        Err(Error::InvalidArchive(_))
+    ));
+
+    let mut truncated = header(&[cid]);
+    truncated.extend(&record(&cid, b"x")[..5]);
+    assert!(matches!(
+        import_archive(&store, &mut &truncated[..]),
+        Err(Error::InvalidArchive(_))
+    ));
+
+    let future = frame(&Value::Map(vec![
+        (Value::UVarint(1), Value::UVarint(2)),
+        (Value::UVarint(2), Value::List(vec![])),
+    ]));
+    assert!(matches!(
+        import_archive(&store, &mut &future[..]),
+        Err(Error::InvalidArchive(_))
+    ));
+}
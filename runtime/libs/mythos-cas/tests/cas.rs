@@ -0,0 +1,146 @@
+/// Filesystem store and StoreSource tests
+use mythos_blob::{read_range, ChunkedBlobBuilder, VerifyingReader, MIN_CHUNK_SIZE};
+use mythos_cas::{cid_from_bytes, put_nodes, BlobStore, Error, StoreSource};
+use mythos_merkle::{build_merkle_list, diff_merkle_lists, DiffRange, HashValue};
+use std::fs;
+use std::io::Read;
+
+mod common;
+use common::{pattern, TempStore};
+
+#[test]
+fn test_put_get_roundtrip() {
+    let temp = TempStore::new("roundtrip");
+    let store = &temp.store;
+
+    let cid = store.put(b"hello mythos").unwrap();
+    assert_eq!(cid, cid_from_bytes(b"hello mythos"));
+    assert!(store.has(&cid).unwrap());
+    assert_eq!(store.get(&cid).unwrap(), b"hello mythos");
+    assert_eq!(store.stat(&cid).unwrap().unwrap().size, 12);
+
+    // Sharded by the first byte of the CID
+    let path = store.object_path(&cid);
+    let hex = hex::encode(cid);
+    assert!(path.ends_with(format!("objects/{}/{}", &hex[..2], &hex[2..])));
+
+    // Storing again is a no-op
+    assert_eq!(store.put(b"hello mythos").unwrap(), cid);
+}
+
+#[test]
+fn test_missing_and_delete() {
+    let temp = TempStore::new("delete");
+    let store = &temp.store;
+
+    let cid = store.put(b"short-lived").unwrap();
+    assert!(store.delete(&cid).unwrap());
+    assert!(!store.delete(&cid).unwrap());
+    assert!(!store.has(&cid).unwrap());
+    assert!(store.stat(&cid).unwrap().is_none());
+    assert!(matches!(store.get(&cid), Err(Error::NotFound(_))));
+}
+
+#[test]
+fn test_put_stream() {
+    let temp = TempStore::new("stream");
+    let store = &temp.store;
+    let data = pattern(200_000);
+
+    let (cid, size) = store.put_stream(&mut &data[..]).unwrap();
+    assert_eq!(cid, cid_from_bytes(&data));
+    assert_eq!(size, data.len() as u64);
+    assert_eq!(store.get(&cid).unwrap(), data);
+
+    // A duplicate stream leaves no temp files behind
+    store.put_stream(&mut &data[..]).unwrap();
+    assert_eq!(fs::read_dir(temp.dir.0.join("tmp")).unwrap().count(), 0);
+}
+
+#[test]
+fn test_put_with_wrong_cid_rejected() {
+    let temp = TempStore::new("mismatch");
+    let store = &temp.store;
+
+    let cid = cid_from_bytes(b"expected");
+    let result = store.put_with_cid(&cid, b"something else");
+    assert!(matches!(result, Err(Error::CidMismatch { .. })));
+    assert!(!store.has(&cid).unwrap());
+}
+
+#[test]
+fn test_corrupt_object_detected() {
+    let temp = TempStore::new("corrupt");
+    let store = &temp.store;
+
+    let cid = store.put(b"original bytes").unwrap();
+    fs::write(store.object_path(&cid), b"tampered bytes").unwrap();
+    assert!(matches!(store.get(&cid), Err(Error::Corrupt(_))));
+}
+
+#[test]
+fn test_chunked_blob_through_store() {
+    let temp = TempStore::new("blob");
+    let store = &temp.store;
+    let data = pattern(MIN_CHUNK_SIZE as usize * 4 + 100);
+
+    let build = ChunkedBlobBuilder::new()
+        .chunk_size(MIN_CHUNK_SIZE)
+        .build_with(&data[..], |_, hash, bytes| {
+            store
+                .put_with_cid(hash, bytes)
+                .map_err(|e| mythos_blob::Error::Source(e.to_string()))
+        })
+        .unwrap();
+    put_nodes(store, &build.nodes).unwrap();
+    let blob_ref_cid = store.put(&build.blob_ref.to_bytes().unwrap()).unwrap();
+
+    // Resolve the BlobRef, then stream the blob back out of the store
+    let blob_ref = mythos_blob::BlobRef::from_bytes(&store.get(&blob_ref_cid).unwrap()).unwrap();
+    let source = StoreSource(store);
+    blob_ref.check_dag(&source).unwrap();
+
+    let root: [u8; 32] = blob_ref.cid[..].try_into().unwrap();
+    let range = read_range(&root, 0..blob_ref.size, &source, &source).unwrap();
+    assert_eq!(range.data, data);
+
+    let mut out = Vec::new();
+    VerifyingReader::open(&blob_ref, source, &range.data[..])
+        .unwrap()
+        .read_to_end(&mut out)
+        .unwrap();
+    assert_eq!(out, data);
+}
+
+#[test]
+fn test_missing_node_maps_to_source_error() {
+    let temp = TempStore::new("missing-node");
+    let source = StoreSource(&temp.store);
+    let cid = cid_from_bytes(b"never stored");
+
+    let result = VerifyingReader::new(&cid, source, &[][..]);
+    assert!(matches!(result, Err(mythos_blob::Error::NodeNotFound(_))));
+}
+
+#[test]
+fn test_merkle_diff_from_store() {
+    let temp = TempStore::new("merkle");
+    let store = &temp.store;
+
+    let values: Vec<HashValue> = (0..2000u64)
+        .map(|i| HashValue {
+            alg: 1,
+            bytes: cid_from_bytes(&i.to_be_bytes()).to_vec(),
+        })
+        .collect();
+    let mut changed = values.clone();
+    changed[1500].bytes = cid_from_bytes(b"changed").to_vec();
+
+    let a = build_merkle_list(&values).unwrap();
+    let b = build_merkle_list(&changed).unwrap();
+    put_nodes(store, &a.nodes).unwrap();
+    put_nodes(store, &b.nodes).unwrap();
+
+    let diff = diff_merkle_lists(&StoreSource(store), &a.root, &b.root).unwrap();
+    assert_eq!(diff.ranges, vec![DiffRange::Changed(1500..1501)]);
+}
@@ -0,0 +1,115 @@
+// Fixtures shared by the mythos-cas integration tests
+#![allow(dead_code)]
+
+use mythos_blob::{BlobRef, ChunkedBlobBuilder, MIN_CHUNK_SIZE};
+use mythos_can::Value;
+use mythos_cas::{put_nodes, BlobStore, Cid, FsStore, GcOptions};
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+/// Fresh directory under the system temp dir, removed when dropped
+pub struct TempDir(pub PathBuf);
+
+impl TempDir {
+    pub fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("mythos-cas-{}-{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&path);
+        TempDir(path)
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+/// FsStore in a fresh `TempDir`
+pub struct TempStore {
+    pub store: FsStore,
+    pub dir: TempDir,
+}
+
+impl TempStore {
+    pub fn new(name: &str) -> Self {
+        let dir = TempDir::new(name);
+        let store = FsStore::open(&dir.0).unwrap();
+        TempStore { store, dir }
+    }
+
+    /// Backdate every stored object past the default grace period
+    pub fn age_all(&self) {
+        let past = SystemTime::now() - 2 * GcOptions::default().grace;
+        for cid in self.store.list().unwrap() {
+            File::options()
+                .write(true)
+                .open(self.store.object_path(&cid))
+                .unwrap()
+                .set_modified(past)
+                .unwrap();
+        }
+    }
+}
+
+pub fn pattern(len: usize) -> Vec<u8> {
+    (0..len).map(|i| (i * 31 % 251) as u8).collect()
+}
+
+/// Struct with UVarint keys
+pub fn map(fields: Vec<(u64, Value)>) -> Value {
+    Value::Map(
+        fields
+            .into_iter()
+            .map(|(k, v)| (Value::UVarint(k), v))
+            .collect(),
+    )
+}
+
+/// SHA-256 Hash value for `cid`
+pub fn hash(cid: &Cid) -> Value {
+    map(vec![
+        (1, Value::UVarint(1)),
+        (2, Value::Bytes(cid.to_vec())),
+    ])
+}
+
+/// A chunked blob stored by `put_blob`
+pub struct StoredBlob {
+    /// CID of the stored BlobRef
+    pub cid: Cid,
+    pub chunks: Vec<Cid>,
+    pub nodes: Vec<Cid>,
+}
+
+impl StoredBlob {
+    /// Every object stored for the blob, BlobRef included
+    pub fn objects(&self) -> HashSet<Cid> {
+        let mut objects: HashSet<Cid> = self.chunks.iter().chain(&self.nodes).copied().collect();
+        objects.insert(self.cid);
+        objects
+    }
+}
+
+/// Store `data` as a chunked blob with its DAG nodes and BlobRef
+pub fn put_blob<S: BlobStore + ?Sized>(store: &S, data: &[u8]) -> StoredBlob {
+    let mut chunks = Vec::new();
+    let build = ChunkedBlobBuilder::new()
+        .chunk_size(MIN_CHUNK_SIZE)
+        .build_with(data, |_, hash, bytes| {
+            chunks.push(*hash);
+            store
+                .put_with_cid(hash, bytes)
+                .map_err(|e| mythos_blob::Error::Source(e.to_string()))
+        })
+        .unwrap();
+    put_nodes(store, &build.nodes).unwrap();
+
+    let blob_ref = BlobRef::from_build(&build, "application/octet-stream");
+    StoredBlob {
+        cid: store.put(&blob_ref.to_bytes().unwrap()).unwrap(),
+        chunks,
+        nodes: build.nodes.iter().map(|(cid, _)| *cid).collect(),
+    }
+}
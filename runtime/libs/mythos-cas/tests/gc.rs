@@ -0,0 +1,235 @@
+/// Pin and garbage collection tests
+use mythos_blob::{BlobRef, MIN_CHUNK_SIZE};
+use mythos_cas::{
+    collect_garbage, put_nodes, BlobStore, Cid, Error, FsStore, GcOptions, Pin, PinStore,
+};
+use mythos_merkle::{build_merkle_list, HashValue};
+use std::collections::HashSet;
+use std::fs;
+use std::time::{Duration, SystemTime};
+
+mod common;
+use common::{pattern, put_blob, TempStore};
+
+const HOUR: Duration = Duration::from_secs(60 * 60);
+
+fn stored(store: &FsStore) -> HashSet<Cid> {
+    store.list().unwrap().into_iter().collect()
+}
+
+#[test]
+fn test_pin_records() {
+    let temp = TempStore::new("pins");
+    let store = &temp.store;
+    let cid = store.put(b"pinned").unwrap();
+
+    let pin = store.pin(&cid, Some(HOUR)).unwrap();
+    assert!(pin.is_live(SystemTime::now()));
+    assert!(!pin.is_live(SystemTime::now() + 2 * HOUR));
+    assert_eq!(store.pins().unwrap(), vec![pin.clone()]);
+    assert_eq!(Pin::from_bytes(&pin.to_bytes().unwrap()).unwrap(), pin);
+
+    // Re-pinning replaces the TTL
+    let forever = store.pin(&cid, None).unwrap();
+    assert_eq!(store.pins().unwrap(), vec![forever]);
+
+    assert!(store.unpin(&cid).unwrap());
+    assert!(!store.unpin(&cid).unwrap());
+    assert!(store.pins().unwrap().is_empty());
+
+    let absent = [7u8; 32];
+    assert!(matches!(store.pin(&absent, None), Err(Error::NotFound(_))));
+}
+
+#[test]
+fn test_gc_keeps_pinned_blob() {
+    let temp = TempStore::new("blob");
+    let store = &temp.store;
+
+    let kept = put_blob(store, &pattern(MIN_CHUNK_SIZE as usize * 3 + 5));
+    let dropped = put_blob(store, &pattern(MIN_CHUNK_SIZE as usize * 2 + 9)[7..]);
+    store.pin(&kept.cid, None).unwrap();
+    let (kept, dropped) = (kept.objects(), dropped.objects());
+    temp.age_all();
+
+    let report = collect_garbage(store, &GcOptions::default()).unwrap();
+
+    let garbage: HashSet<Cid> = dropped.difference(&kept).copied().collect();
+    assert_eq!(report.marked, kept.len() as u64);
+    assert_eq!(
+        report.swept.iter().copied().collect::<HashSet<_>>(),
+        garbage
+    );
+    assert!(report.missing.is_empty());
+    assert_eq!(stored(store), kept);
+}
+
+#[test]
+fn test_gc_dry_run() {
+    let temp = TempStore::new("dry-run");
+    let store = &temp.store;
+
+    let objects = put_blob(store, &pattern(MIN_CHUNK_SIZE as usize * 2)).objects();
+    temp.age_all();
+
+    let options = GcOptions {
+        dry_run: true,
+        ..GcOptions::default()
+    };
+    let report = collect_garbage(store, &options).unwrap();
+
+    assert!(report.dry_run);
+    assert_eq!(report.swept.len(), objects.len());
+    assert!(report.swept_bytes > MIN_CHUNK_SIZE * 2);
+    assert_eq!(stored(store), objects);
+}
+
+#[test]
+fn test_gc_grace_period() {
+    let temp = TempStore::new("grace");
+    let store = &temp.store;
+
+    let old = store.put(b"old and unreferenced").unwrap();
+    temp.age_all();
+    let recent = store.put(b"just written").unwrap();
+
+    let report = collect_garbage(store, &GcOptions::default()).unwrap();
+    assert_eq!(report.swept, vec![old]);
+    assert_eq!(report.kept_recent, 1);
+    assert!(store.has(&recent).unwrap());
+}
+
+#[test]
+fn test_gc_unreadable_skips_sweep() {
+    let temp = TempStore::new("unreadable");
+    let store = &temp.store;
+
+    let blob = put_blob(store, &pattern(MIN_CHUNK_SIZE as usize * 3 + 5));
+    store.pin(&blob.cid, None).unwrap();
+    let garbage = store.put(b"old and unreferenced").unwrap();
+    let root: Cid = BlobRef::from_bytes(&store.get(&blob.cid).unwrap())
+        .unwrap()
+        .cid
+        .try_into()
+        .unwrap();
+
+    // Bit rot in the root node hides every chunk below it
+    let path = store.object_path(&root);
+    let node = fs::read(&path).unwrap();
+    fs::write(&path, b"bit rot").unwrap();
+    temp.age_all();
+
+    let report = collect_garbage(store, &GcOptions::default()).unwrap();
+    assert_eq!(report.unreadable, vec![root]);
+    assert!(report.swept.is_empty());
+    assert!(stored(store).is_superset(&blob.objects()));
+    assert!(store.has(&garbage).unwrap());
+
+    // Restored from a replica, the DAG is whole again
+    fs::write(&path, node).unwrap();
+    temp.age_all();
+    let report = collect_garbage(store, &GcOptions::default()).unwrap();
+    assert!(report.unreadable.is_empty());
+    assert_eq!(report.swept, vec![garbage]);
+    assert_eq!(stored(store), blob.objects());
+}
+
+#[test]
+fn test_reput_refreshes_object() {
+    let temp = TempStore::new("reput");
+    let store = &temp.store;
+
+    let cid = store.put(b"ingested twice").unwrap();
+    temp.age_all();
+
+    // A concurrent writer re-ingesting the object before pinning its root
+    store.put(b"ingested twice").unwrap();
+    let report = collect_garbage(store, &GcOptions::default()).unwrap();
+
+    assert!(report.swept.is_empty());
+    assert!(store.has(&cid).unwrap());
+}
+
+#[test]
+fn test_reput_during_sweep_survives() {
+    let temp = TempStore::new("reput-race");
+    let store = &temp.store;
+
+    let objects: Vec<Vec<u8>> = (0..200u32)
+        .map(|i| format!("object {}", i).into_bytes())
+        .collect();
+    for bytes in &objects {
+        store.put(bytes).unwrap();
+    }
+    temp.age_all();
+
+    // Every re-put is inside the grace period, so whatever the
+    // interleaving with the sweep, each object is stored once both finish
+    std::thread::scope(|s| {
+        let writer = s.spawn(|| {
+            objects
+                .iter()
+                .map(|bytes| store.put(bytes).unwrap())
+                .collect::<Vec<_>>()
+        });
+        collect_garbage(store, &GcOptions::default()).unwrap();
+        for cid in writer.join().unwrap() {
+            assert!(store.has(&cid).unwrap());
+        }
+    });
+}
+
+#[test]
+fn test_expired_pin_not_a_root() {
+    let temp = TempStore::new("expired");
+    let store = &temp.store;
+
+    let cid = store.put(b"pinned briefly").unwrap();
+    store.pin(&cid, Some(Duration::ZERO)).unwrap();
+    temp.age_all();
+
+    let report = collect_garbage(store, &GcOptions::default()).unwrap();
+    assert_eq!(report.expired_pins, vec![cid]);
+    assert_eq!(report.swept, vec![cid]);
+    assert!(!store.has(&cid).unwrap());
+}
+
+#[test]
+fn test_gc_follows_merkle_list_values() {
+    let temp = TempStore::new("merkle");
+    let store = &temp.store;
+
+    let items: Vec<Cid> = (0..1500u32)
+        .map(|i| store.put(format!("item {}", i).as_bytes()).unwrap())
+        .collect();
+    let values: Vec<HashValue> = items
+        .iter()
+        .map(|cid| HashValue {
+            alg: 1,
+            bytes: cid.to_vec(),
+        })
+        .collect();
+    let list = build_merkle_list(&values).unwrap();
+    put_nodes(store, &list.nodes).unwrap();
+
+    // One value that was never stored: list values are optional references
+    let mut with_missing = values[..10].to_vec();
+    with_missing.push(HashValue {
+        alg: 1,
+        bytes: vec![9u8; 32],
+    });
+    let partial = build_merkle_list(&with_missing).unwrap();
+    put_nodes(store, &partial.nodes).unwrap();
+
+    store.pin(&list.root, None).unwrap();
+    store.pin(&partial.root, None).unwrap();
+    temp.age_all();
+
+    let report = collect_garbage(store, &GcOptions::default()).unwrap();
+    assert!(report.swept.is_empty());
+    assert!(report.missing.is_empty());
+    assert_eq!(
+        report.marked,
+        (items.len() + list.nodes.len() + partial.nodes.len()) as u64
+    );
+}
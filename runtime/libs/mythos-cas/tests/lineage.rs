@@ -0,0 +1,131 @@
+/// Provenance lineage tests
+use mythos_blob::{BlobRef, ProvenanceRef};
+use mythos_can::Value;
+use mythos_cas::{cid_from_bytes, BlobStore, Cid, FsStore, Lineage, LineageIndex};
+use std::collections::{BTreeMap, HashSet};
+
+mod common;
+use common::TempDir;
+
+/// Store `data` with a BlobRef derived from `parents`
+fn derive(store: &FsStore, data: &[u8], parents: &[Cid], transform: &str) -> (Cid, BlobRef) {
+    let cid = store.put(data).unwrap();
+    let blob_ref = BlobRef {
+        cid: cid.to_vec(),
+        size: data.len() as u64,
+        media: "application/octet-stream".into(),
+        codec: 0,
+        chunks: 0,
+        encryption: None,
+        provenance: Some(ProvenanceRef {
+            parents: parents.iter().map(|p| p.to_vec()).collect(),
+            transform: transform.into(),
+            params: BTreeMap::from([("seed".to_string(), "7".to_string())]),
+            code_hash: cid_from_bytes(transform.as_bytes()).to_vec(),
+            time_observed: None,
+        }),
+    };
+    store.put(&blob_ref.to_bytes().unwrap()).unwrap();
+    (cid, blob_ref)
+}
+
+fn cids(lineage: &Lineage) -> HashSet<(Cid, u32)> {
+    lineage.nodes.iter().map(|n| (n.cid, n.depth)).collect()
+}
+
+/// raw1, raw2 -> cleaned -> manifest -> model; raw2 -> stats
+fn fixture(store: &FsStore) -> [Cid; 6] {
+    let raw1 = store.put(b"raw trace 1").unwrap();
+    let raw2 = store.put(b"raw trace 2").unwrap();
+    let (cleaned, _) = derive(store, b"cleaned", &[raw1, raw2], "clean");
+    let (manifest, manifest_ref) = derive(store, b"manifest", &[cleaned], "select");
+    let (model, _) = derive(store, b"weights", &[manifest], "train");
+    let (stats, _) = derive(store, b"stats", &[raw2], "count");
+
+    // The manifest BlobRef also appears embedded in a DatasetRef
+    let dataset_ref = Value::Map(vec![
+        (Value::UVarint(1), Value::Bytes(vec![1])),
+        (Value::UVarint(2), manifest_ref.to_value()),
+    ]);
+    store
+        .put(&mythos_can::encode_value(&dataset_ref).unwrap())
+        .unwrap();
+
+    [raw1, raw2, cleaned, manifest, model, stats]
+}
+
+#[test]
+fn test_ancestors_and_descendants() {
+    let dir = TempDir::new("queries");
+    let store = FsStore::open(&dir.0).unwrap();
+    let [raw1, raw2, cleaned, manifest, model, stats] = fixture(&store);
+
+    let index = LineageIndex::build(&store).unwrap();
+    assert_eq!(index.edge_count(), 5);
+
+    // Which raw traces was the manifest derived from?
+    let up = index.ancestors(&manifest, None);
+    assert_eq!(
+        cids(&up),
+        HashSet::from([(cleaned, 1), (raw1, 2), (raw2, 2)])
+    );
+    assert_eq!(up.edges.len(), 3);
+    assert!(!up.truncated);
+
+    // What depends on raw2?
+    let down = index.descendants(&raw2, None);
+    assert_eq!(
+        cids(&down),
+        HashSet::from([(cleaned, 1), (stats, 1), (manifest, 2), (model, 3)])
+    );
+    assert_eq!(index.children(&raw2).len(), 2);
+    assert_eq!(index.parents(&model)[0].transform, "train");
+    assert!(index.descendants(&model, None).nodes.is_empty());
+}
+
+#[test]
+fn test_depth_limit() {
+    let dir = TempDir::new("depth");
+    let store = FsStore::open(&dir.0).unwrap();
+    let [_, raw2, cleaned, _, _, stats] = fixture(&store);
+    let index = LineageIndex::build(&store).unwrap();
+
+    let near = index.descendants(&raw2, Some(1));
+    assert_eq!(cids(&near), HashSet::from([(cleaned, 1), (stats, 1)]));
+    assert!(near.truncated);
+    let bytes = near.to_bytes().unwrap();
+    assert_eq!(Lineage::from_bytes(&bytes).unwrap(), near);
+
+    let none = index.descendants(&raw2, Some(0));
+    assert!(none.nodes.is_empty() && none.edges.is_empty());
+    assert!(none.truncated);
+}
+
+#[test]
+fn test_exports() {
+    let dir = TempDir::new("exports");
+    let store = FsStore::open(&dir.0).unwrap();
+    let [raw1, _, cleaned, manifest, _, _] = fixture(&store);
+    let index = LineageIndex::build(&store).unwrap();
+    let lineage = index.ancestors(&manifest, None);
+
+    let dot = lineage.to_dot();
+    assert!(dot.starts_with("digraph lineage {"));
+    assert!(dot.contains(&format!(
+        "\"{}\" -> \"{}\" [label=\"clean\"]",
+        hex::encode(raw1),
+        hex::encode(cleaned)
+    )));
+
+    // Canonical export round-trips and is stable
+    let bytes = lineage.to_bytes().unwrap();
+    assert_eq!(Lineage::from_bytes(&bytes).unwrap(), lineage);
+    assert_eq!(index.ancestors(&manifest, None).to_bytes().unwrap(), bytes);
+    let stored = store.put(&bytes).unwrap();
+    assert_eq!(
+        Lineage::from_bytes(&store.get(&stored).unwrap()).unwrap(),
+        lineage
+    );
+
+    assert!(Lineage::from_bytes(b"not canonical").is_err());
+}
@@ -0,0 +1,272 @@
+/// Pack-file backend tests
+use mythos_cas::{
+    cid_from_bytes, collect_garbage, scrub, BlobStore, Error, FsStore, GcOptions, PackOptions,
+    PackStore, PinStore, ScrubOptions,
+};
+use std::collections::HashSet;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+use std::time::Duration;
+
+mod common;
+use common::{pattern, TempDir};
+
+fn small(segment_size: u64) -> PackOptions {
+    PackOptions { segment_size }
+}
+
+/// Reader that always errors
+struct Failing;
+
+impl Read for Failing {
+    fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+        Err(io::Error::other("reader failed"))
+    }
+}
+
+fn segments(dir: &TempDir) -> Vec<PathBuf> {
+    let mut paths: Vec<PathBuf> = fs::read_dir(dir.0.join("packs"))
+        .unwrap()
+        .map(|e| e.unwrap().path())
+        .collect();
+    paths.sort();
+    paths
+}
+
+/// BlobStore behaviour both backends must share
+fn exercise<S: BlobStore>(store: &S) {
+    let a = store.put(b"receipt").unwrap();
+    assert_eq!(a, cid_from_bytes(b"receipt"));
+    assert_eq!(store.get(&a).unwrap(), b"receipt");
+    assert_eq!(store.put(b"receipt").unwrap(), a);
+    assert_eq!(store.stat(&a).unwrap().unwrap().size, 7);
+
+    let (b, size) = store.put_stream(&mut &b"ledger entry"[..]).unwrap();
+    assert_eq!((b, size), (cid_from_bytes(b"ledger entry"), 12));
+
+    let wrong = store.put_with_cid(&a, b"not a receipt");
+    assert!(matches!(wrong, Err(Error::CidMismatch { .. })));
+
+    let listed: HashSet<_> = store.list().unwrap().into_iter().collect();
+    assert_eq!(listed, HashSet::from([a, b]));
+
+    assert!(store.delete(&a).unwrap());
+    assert!(!store.delete(&a).unwrap());
+    assert!(!store.has(&a).unwrap());
+    assert!(store.stat(&a).unwrap().is_none());
+    assert!(matches!(store.get(&a), Err(Error::NotFound(_))));
+    assert_eq!(store.list().unwrap(), vec![b]);
+
+    // Only objects last written before the cutoff go
+    let written = store.stat(&b).unwrap().unwrap().modified;
+    assert!(!store.delete_if_older(&b, written).unwrap());
+    assert!(store.has(&b).unwrap());
+    let later = written + Duration::from_secs(1);
+    assert!(store.delete_if_older(&b, later).unwrap());
+    assert!(!store.delete_if_older(&b, later).unwrap());
+    assert!(store.list().unwrap().is_empty());
+}
+
+#[test]
+fn test_backends_behave_alike() {
+    let fs_dir = TempDir::new("conformance-fs");
+    exercise(&FsStore::open(&fs_dir.0).unwrap());
+
+    let pack_dir = TempDir::new("conformance-pack");
+    exercise(&PackStore::open(&pack_dir.0).unwrap());
+}
+
+#[test]
+fn test_reopen_replays_index() {
+    let dir = TempDir::new("reopen");
+    let (kept, deleted) = {
+        let store = PackStore::open(&dir.0).unwrap();
+        let kept = store.put(b"kept").unwrap();
+        let deleted = store.put(b"deleted").unwrap();
+        store.delete(&deleted).unwrap();
+        (kept, deleted)
+    };
+
+    let store = PackStore::open(&dir.0).unwrap();
+    assert_eq!(store.list().unwrap(), vec![kept]);
+    assert_eq!(store.get(&kept).unwrap(), b"kept");
+    assert!(!store.has(&deleted).unwrap());
+
+    // The deleted object can be stored again
+    store.put(b"deleted").unwrap();
+    assert!(store.has(&deleted).unwrap());
+}
+
+#[test]
+fn test_single_writer() {
+    let dir = TempDir::new("locked");
+    let store = PackStore::open(&dir.0).unwrap();
+    assert!(matches!(PackStore::open(&dir.0), Err(Error::Locked(_))));
+
+    drop(store);
+    PackStore::open(&dir.0).unwrap();
+}
+
+#[test]
+fn test_put_stream_spools() {
+    let dir = TempDir::new("stream");
+    let store = PackStore::open(&dir.0).unwrap();
+    let tmp = dir.0.join("tmp");
+    let data = pattern(3 << 20);
+
+    let (cid, size) = store.put_stream(&mut data.as_slice()).unwrap();
+    assert_eq!((cid, size), (cid_from_bytes(&data), data.len() as u64));
+    assert_eq!(store.get(&cid).unwrap(), data);
+    assert_eq!(fs::read_dir(&tmp).unwrap().count(), 0);
+
+    // A failing reader appends nothing and leaves no temp file behind
+    let lens: Vec<u64> = segments(&dir)
+        .iter()
+        .map(|p| fs::metadata(p).unwrap().len())
+        .collect();
+    let mut failing = data.as_slice().chain(Failing);
+    assert!(store.put_stream(&mut failing).is_err());
+    assert_eq!(fs::read_dir(&tmp).unwrap().count(), 0);
+    let after: Vec<u64> = segments(&dir)
+        .iter()
+        .map(|p| fs::metadata(p).unwrap().len())
+        .collect();
+    assert_eq!(after, lens);
+    assert_eq!(store.list().unwrap(), vec![cid]);
+}
+
+#[test]
+fn test_segments_roll_and_compact() {
+    let dir = TempDir::new("compact");
+    let store = PackStore::open_with(&dir.0, small(1024)).unwrap();
+
+    let cids: Vec<_> = (0..100u32)
+        .map(|i| {
+            store
+                .put(format!("object {:04}", i).repeat(4).as_bytes())
+                .unwrap()
+        })
+        .collect();
+    assert!(store.segment_count().unwrap() > 3);
+
+    for cid in &cids[..80] {
+        store.delete(cid).unwrap();
+    }
+    let report = store.compact().unwrap();
+    assert_eq!(report.live_objects, 20);
+    assert!(report.bytes_after < report.bytes_before / 3);
+
+    for cid in &cids[80..] {
+        assert!(store.get(cid).is_ok());
+    }
+    drop(store);
+
+    let store = PackStore::open_with(&dir.0, small(1024)).unwrap();
+    let listed: HashSet<_> = store.list().unwrap().into_iter().collect();
+    assert_eq!(listed, cids[80..].iter().copied().collect());
+}
+
+#[test]
+fn test_torn_tail_truncated() {
+    let dir = TempDir::new("torn");
+    let cid = {
+        let store = PackStore::open(&dir.0).unwrap();
+        store.put(b"complete record").unwrap()
+    };
+
+    // A crash mid-append leaves half a record header
+    let newest = segments(&dir).pop().unwrap();
+    let len = fs::metadata(&newest).unwrap().len();
+    OpenOptions::new()
+        .append(true)
+        .open(&newest)
+        .unwrap()
+        .write_all(&[0xab; 20])
+        .unwrap();
+
+    let store = PackStore::open(&dir.0).unwrap();
+    assert_eq!(fs::metadata(&newest).unwrap().len(), len);
+    assert_eq!(store.get(&cid).unwrap(), b"complete record");
+    let next = store.put(b"after recovery").unwrap();
+    drop(store);
+
+    let store = PackStore::open(&dir.0).unwrap();
+    assert_eq!(store.get(&next).unwrap(), b"after recovery");
+}
+
+#[test]
+fn test_torn_segment_create_recovered() {
+    let dir = TempDir::new("torn-create");
+    let cid = {
+        let store = PackStore::open(&dir.0).unwrap();
+        store.put(b"complete record").unwrap()
+    };
+
+    // A crash while rolling leaves the next segment empty, or holding
+    // part of the magic
+    let first = segments(&dir).pop().unwrap();
+    let id = u64::from_str_radix(first.file_stem().unwrap().to_str().unwrap(), 16).unwrap();
+    for (next, head) in [(id + 1, &b""[..]), (id + 2, &b"MYTH"[..])] {
+        let torn = dir.0.join("packs").join(format!("{:016x}.pack", next));
+        fs::write(&torn, head).unwrap();
+
+        let store = PackStore::open(&dir.0).unwrap();
+        assert_eq!(fs::read(&torn).unwrap(), b"MYTHPK01");
+        assert_eq!(store.get(&cid).unwrap(), b"complete record");
+        let after = store.put(format!("after {}", next).as_bytes()).unwrap();
+        drop(store);
+
+        let store = PackStore::open(&dir.0).unwrap();
+        assert!(store.has(&after).unwrap());
+    }
+
+    // Only the newest segment can have been cut off while being created
+    let newest = segments(&dir).pop().unwrap();
+    let stale = dir.0.join("packs").join(format!("{:016x}.pack", id + 1));
+    fs::write(&stale, b"MYTH").unwrap();
+    assert_ne!(stale, newest);
+    assert!(matches!(PackStore::open(&dir.0), Err(Error::Corrupt(_))));
+}
+
+#[test]
+fn test_corruption_found_by_scrub() {
+    let dir = TempDir::new("corrupt");
+    let store = PackStore::open(&dir.0).unwrap();
+    let cid = store.put(b"ledger entry 42").unwrap();
+
+    // Flip the last byte of the object
+    let segment = segments(&dir).pop().unwrap();
+    let mut file = OpenOptions::new().write(true).open(&segment).unwrap();
+    file.seek(SeekFrom::End(-1)).unwrap();
+    file.write_all(b"!").unwrap();
+
+    assert!(matches!(store.get(&cid), Err(Error::Corrupt(_))));
+    let report = scrub(&store, &ScrubOptions::default()).unwrap();
+    assert_eq!(report.quarantined, vec![cid]);
+    assert!(!store.has(&cid).unwrap());
+    assert_eq!(
+        fs::read(store.quarantine_path(&cid)).unwrap(),
+        b"ledger entry 4!"
+    );
+}
+
+#[test]
+fn test_gc_over_packs() {
+    let dir = TempDir::new("gc");
+    let store = PackStore::open(&dir.0).unwrap();
+    let pinned = store.put(b"pinned").unwrap();
+    let loose = store.put(b"loose").unwrap();
+    store.pin(&pinned, None).unwrap();
+
+    let options = GcOptions {
+        grace: Duration::ZERO,
+        ..GcOptions::default()
+    };
+    let report = collect_garbage(&store, &options).unwrap();
+    assert_eq!(report.swept, vec![loose]);
+    assert!(store.has(&pinned).unwrap());
+
+    store.compact().unwrap();
+    assert_eq!(store.list().unwrap(), vec![pinned]);
+}
@@ -0,0 +1,459 @@
+/// Object registry and graph walker tests
+use mythos_blob::BlobRef;
+use mythos_can::Value;
+use mythos_cas::{
+    cid_from_bytes, collect_garbage, export_archive, walk, BlobStore, Cid, Error, Extractor,
+    FsStore, GcOptions, ObjectKind, PinStore, Reference, Registry, WalkOptions,
+};
+use mythos_merkle::{build_merkle_list, HashValue};
+use std::time::Duration;
+
+mod common;
+use common::{hash, map, TempDir, TempStore};
+
+fn blob_ref(cid: &Cid) -> Value {
+    BlobRef {
+        cid: cid.to_vec(),
+        size: 4,
+        media: "application/octet-stream".into(),
+        codec: 0,
+        chunks: 0,
+        encryption: None,
+        provenance: None,
+    }
+    .to_value()
+}
+
+fn trace_ref(trace: &Cid, receipts: &[Cid]) -> Value {
+    map(vec![
+        (1, blob_ref(trace)),
+        (2, Value::List(receipts.iter().map(hash).collect())),
+    ])
+}
+
+fn receipt(request: &Cid, response: &Cid) -> Value {
+    map(vec![
+        (1, hash(&cid_from_bytes(b"receipt id"))),
+        (2, hash(&cid_from_bytes(b"tool"))),
+        (3, hash(request)),
+        (4, hash(response)),
+        (5, Value::Bytes(b"key".to_vec())),
+        (
+            6,
+            map(vec![(1, Value::UVarint(1)), (2, Value::Bytes(vec![7; 32]))]),
+        ),
+        (7, Value::IVarint(1_700_000_000_000_000)),
+        (8, Value::UVarint(200)),
+        (
+            11,
+            map(vec![(1, Value::UVarint(1)), (3, Value::Bytes(vec![0; 64]))]),
+        ),
+    ])
+}
+
+fn encode(value: &Value) -> Vec<u8> {
+    mythos_can::encode_value(value).unwrap()
+}
+
+fn classify(value: &Value) -> (&'static str, Vec<(Cid, bool)>) {
+    let info = Registry::builtin().classify(value).unwrap().unwrap();
+    let refs = info.refs.iter().map(|r| (r.cid, r.optional)).collect();
+    (info.type_name, refs)
+}
+
+#[test]
+fn test_builtin_shapes() {
+    let a = cid_from_bytes(b"a");
+    let b = cid_from_bytes(b"b");
+    let c = cid_from_bytes(b"c");
+
+    assert_eq!(classify(&blob_ref(&a)), ("BlobRef", vec![(a, false)]));
+    assert_eq!(
+        classify(&receipt(&a, &b)),
+        ("Receipt", vec![(a, true), (b, true)])
+    );
+    assert_eq!(
+        classify(&trace_ref(&a, &[b])),
+        ("TraceRef", vec![(a, false), (b, true)])
+    );
+    assert_eq!(
+        classify(&map(vec![
+            (1, hash(&c)),
+            (2, trace_ref(&a, &[])),
+            (3, hash(&b)),
+            (4, hash(&c)),
+            (5, Value::IVarint(0)),
+        ])),
+        ("EpisodeRef", vec![(a, false), (b, true), (c, true)])
+    );
+    assert_eq!(
+        classify(&map(vec![
+            (1, hash(&c)),
+            (2, Value::List(vec![hash(&a), hash(&b)])),
+            (3, map(vec![])),
+            (4, map(vec![])),
+        ])),
+        ("DatasetDef", vec![(a, false), (b, false)])
+    );
+    assert_eq!(
+        classify(&map(vec![
+            (1, hash(&c)),
+            (2, blob_ref(&a)),
+            (3, Value::UVarint(10)),
+            (4, hash(&b)),
+        ])),
+        ("DatasetRef", vec![(a, false), (c, true), (b, true)])
+    );
+    assert_eq!(
+        classify(&map(vec![
+            (1, hash(&c)),
+            (2, blob_ref(&a)),
+            (3, blob_ref(&b)),
+            (4, hash(&c)),
+        ])),
+        ("ModelRef", vec![(a, false), (b, false), (c, true)])
+    );
+
+    let consts = map(vec![(
+        1,
+        Value::List(vec![
+            map(vec![(1, Value::UVarint(1)), (2, Value::IVarint(-1))]),
+            map(vec![(1, Value::UVarint(5)), (6, hash(&a))]),
+        ]),
+    )]);
+    let mut bundle = vec![(1, map(vec![])), (2, consts)];
+    bundle.extend((3..=9).map(|n| (n, map(vec![]))));
+    assert_eq!(classify(&map(bundle)), ("IRBundle", vec![(a, true)]));
+
+    let list = build_merkle_list(&[HashValue {
+        alg: 1,
+        bytes: a.to_vec(),
+    }])
+    .unwrap();
+    let node = mythos_can::decode_value_exact(&list.nodes[0].1).unwrap();
+    assert_eq!(classify(&node), ("MerkleList", vec![(a, true)]));
+
+    let unknown = map(vec![(1, Value::Text("hello".into()))]);
+    assert!(Registry::builtin().classify(&unknown).unwrap().is_none());
+}
+
+/// Toy type: `{ 1: "note", 2: [Hash] }`
+struct NoteExtractor;
+
+impl Extractor for NoteExtractor {
+    fn name(&self) -> &'static str {
+        "Note"
+    }
+
+    fn matches(&self, value: &Value) -> bool {
+        match value {
+            Value::Map(pairs) => pairs
+                .iter()
+                .any(|(k, v)| *k == Value::UVarint(1) && *v == Value::Text("note".into())),
+            _ => false,
+        }
+    }
+
+    fn references(&self, value: &Value) -> mythos_cas::Result<Vec<Reference>> {
+        let Value::Map(pairs) = value else {
+            return Err(Error::Corrupt("Note must be MAP".into()));
+        };
+        let mut refs = Vec::new();
+        for (k, v) in pairs {
+            if let (Value::UVarint(2), Value::List(links)) = (k, v) {
+                for link in links {
+                    let Value::Map(hash) = link else { continue };
+                    if let Some((_, Value::Bytes(bytes))) =
+                        hash.iter().find(|(k, _)| *k == Value::UVarint(2))
+                    {
+                        let cid = Cid::try_from(&bytes[..]).unwrap();
+                        refs.push(Reference::new(cid, ObjectKind::Unknown));
+                    }
+                }
+            }
+        }
+        Ok(refs)
+    }
+}
+
+fn note(links: &[Cid]) -> Value {
+    map(vec![
+        (1, Value::Text("note".into())),
+        (2, Value::List(links.iter().map(hash).collect())),
+    ])
+}
+
+#[test]
+fn test_custom_extractor() {
+    let target = cid_from_bytes(b"target");
+    let value = note(&[target]);
+    assert!(Registry::builtin().classify(&value).unwrap().is_none());
+
+    let mut registry = Registry::default();
+    registry.register(NoteExtractor);
+    let info = registry.classify(&value).unwrap().unwrap();
+    assert_eq!(info.type_name, "Note");
+    assert_eq!(info.refs, vec![Reference::new(target, ObjectKind::Unknown)]);
+    assert_eq!(registry.type_names().last(), Some(&"Note"));
+
+    // A media type routes straight to its extractor, skipping shape checks
+    let mut registry = Registry::empty();
+    registry.register_media("application/x-note", NoteExtractor);
+    let bare = map(vec![(2, Value::List(vec![hash(&target)]))]);
+    assert!(registry.classify(&bare).unwrap().is_none());
+    let info = registry
+        .classify_media("application/x-note", &bare)
+        .unwrap()
+        .unwrap();
+    assert_eq!(info.refs.len(), 1);
+}
+
+#[test]
+fn test_gc_follows_registered_extractors() {
+    let temp = TempStore::new("gc-registry");
+    let store = &temp.store;
+    let target = store.put(b"target").unwrap();
+    let root = store.put(&encode(&note(&[target]))).unwrap();
+    // Untagged note reached through a BlobRef naming its media type
+    let routed_target = store.put(b"routed target").unwrap();
+    let bare = map(vec![(2, Value::List(vec![hash(&routed_target)]))]);
+    let bare_cid = store.put(&encode(&bare)).unwrap();
+    let mut bare_ref = BlobRef::from_value(&blob_ref(&bare_cid)).unwrap();
+    bare_ref.media = "application/x-note".into();
+    let routed = store.put(&bare_ref.to_bytes().unwrap()).unwrap();
+    store.pin(&root, None).unwrap();
+    store.pin(&routed, None).unwrap();
+    temp.age_all();
+
+    // The built-in registry does not know notes, so their targets look
+    // unreferenced
+    let builtin = collect_garbage(
+        store,
+        &GcOptions {
+            dry_run: true,
+            ..GcOptions::default()
+        },
+    )
+    .unwrap();
+    let mut swept = builtin.swept.clone();
+    swept.sort_unstable();
+    let mut expected = vec![target, routed_target];
+    expected.sort_unstable();
+    assert_eq!(swept, expected);
+
+    let mut registry = Registry::default();
+    registry.register(NoteExtractor);
+    registry.register_media("application/x-note", NoteExtractor);
+    let report = collect_garbage(
+        store,
+        &GcOptions {
+            registry: &registry,
+            ..GcOptions::default()
+        },
+    )
+    .unwrap();
+    assert!(report.swept.is_empty());
+    assert_eq!(report.marked, 5);
+    assert!(store.has(&target).unwrap());
+    assert!(store.has(&routed_target).unwrap());
+}
+
+/// Routed-only type that references nothing
+struct OpaqueExtractor;
+
+impl Extractor for OpaqueExtractor {
+    fn name(&self) -> &'static str {
+        "Opaque"
+    }
+
+    fn matches(&self, _: &Value) -> bool {
+        false
+    }
+
+    fn references(&self, _: &Value) -> mythos_cas::Result<Vec<Reference>> {
+        Ok(Vec::new())
+    }
+}
+
+#[test]
+fn test_routed_and_shape_both_followed() {
+    let temp = TempStore::new("routed-and-shape");
+    let store = &temp.store;
+    let mut registry = Registry::default();
+    registry.register(NoteExtractor);
+    registry.register_media("application/x-opaque", OpaqueExtractor);
+
+    // A note reached both by shape (via mid) and routed as opaque (via
+    // the BlobRef); only shape leads on to the leaf
+    let leaf = store.put(b"leaf").unwrap();
+    let shared = store.put(&encode(&note(&[leaf]))).unwrap();
+    let mid = store.put(&encode(&note(&[shared]))).unwrap();
+    let mut opaque = BlobRef::from_value(&blob_ref(&shared)).unwrap();
+    opaque.media = "application/x-opaque".into();
+    let opaque = store.put(&opaque.to_bytes().unwrap()).unwrap();
+    temp.age_all();
+
+    // Each order reaches the shared note routed first in one traversal
+    for refs in [[opaque, mid], [mid, opaque]] {
+        let root = store.put(&encode(&note(&refs))).unwrap();
+        let mut visited = Vec::new();
+        walk(
+            store,
+            &registry,
+            &[root],
+            &WalkOptions::default(),
+            |visit| {
+                visited.push(visit.cid);
+                Ok(())
+            },
+        )
+        .unwrap();
+        visited.sort();
+        let mut expected = vec![root, opaque, mid, shared, leaf];
+        expected.sort();
+        assert_eq!(visited, expected);
+
+        store.pin(&root, None).unwrap();
+        let options = GcOptions {
+            dry_run: true,
+            registry: &registry,
+            ..GcOptions::default()
+        };
+        let report = collect_garbage(store, &options).unwrap();
+        assert!(report.swept.is_empty(), "{:?}", report.swept);
+        store.unpin(&root).unwrap();
+    }
+}
+
+#[test]
+fn test_walk_limits() {
+    let dir = TempDir::new("walk");
+    let store = FsStore::open(&dir.0).unwrap();
+    let mut registry = Registry::default();
+    registry.register(NoteExtractor);
+
+    // root -> mid -> leaf, plus root -> absent
+    let leaf = store.put(b"leaf").unwrap();
+    let mid = store.put(&encode(&note(&[leaf]))).unwrap();
+    let absent = cid_from_bytes(b"absent");
+    let root = store.put(&encode(&note(&[mid, absent, leaf]))).unwrap();
+
+    let mut visited = Vec::new();
+    let report = walk(
+        &store,
+        &registry,
+        &[root],
+        &WalkOptions::default(),
+        |visit| {
+            visited.push((visit.cid, visit.depth));
+            Ok(())
+        },
+    )
+    .unwrap();
+    // leaf is visited once, at its shortest distance from the root
+    assert_eq!(visited, vec![(root, 0), (mid, 1), (leaf, 1)]);
+    assert_eq!(report.visited, 3);
+    assert_eq!(report.missing, vec![absent]);
+    assert!(!report.truncated);
+
+    let shallow = WalkOptions {
+        max_depth: Some(0),
+        max_objects: None,
+    };
+    let report = walk(&store, &registry, &[root], &shallow, |_| Ok(())).unwrap();
+    assert_eq!(report.visited, 1);
+    assert!(report.truncated);
+
+    let few = WalkOptions {
+        max_depth: None,
+        max_objects: Some(2),
+    };
+    let report = walk(&store, &registry, &[root], &few, |_| Ok(())).unwrap();
+    assert_eq!(report.visited, 2);
+    assert!(report.truncated);
+
+    // The visitor can abort the walk
+    let result = walk(
+        &store,
+        &registry,
+        &[root],
+        &WalkOptions::default(),
+        |visit| match visit.cid == mid {
+            true => Err(Error::Corrupt("stop".into())),
+            false => Ok(()),
+        },
+    );
+    assert!(matches!(result, Err(Error::Corrupt(_))));
+}
+
+#[test]
+fn test_walk_depth_ignores_reference_order() {
+    let dir = TempDir::new("walk-order");
+    let store = FsStore::open(&dir.0).unwrap();
+    let mut registry = Registry::default();
+    registry.register(NoteExtractor);
+
+    // root -> a -> b -> c, and root -> b directly
+    let c = store.put(b"c").unwrap();
+    let b = store.put(&encode(&note(&[c]))).unwrap();
+    let a = store.put(&encode(&note(&[b]))).unwrap();
+    let options = WalkOptions {
+        max_depth: Some(2),
+        max_objects: None,
+    };
+
+    for refs in [[a, b], [b, a]] {
+        let root = store.put(&encode(&note(&refs))).unwrap();
+        let mut visited = Vec::new();
+        walk(&store, &registry, &[root], &options, |visit| {
+            visited.push((visit.cid, visit.depth));
+            Ok(())
+        })
+        .unwrap();
+
+        visited.sort();
+        let mut expected = vec![(root, 0), (a, 1), (b, 1), (c, 2)];
+        expected.sort();
+        assert_eq!(visited, expected);
+    }
+}
+
+#[test]
+fn test_optional_refs() {
+    let dir = TempDir::new("optional");
+    let store = FsStore::open(&dir.0).unwrap();
+
+    // The request is kept elsewhere; the response is stored here
+    let request = cid_from_bytes(b"request");
+    let response = store.put(b"response").unwrap();
+    let trace = store.put(b"trace").unwrap();
+    let receipt_cid = store.put(&encode(&receipt(&request, &response))).unwrap();
+    let root = store
+        .put(&encode(&trace_ref(&trace, &[receipt_cid])))
+        .unwrap();
+    store.pin(&root, None).unwrap();
+
+    let report = walk(
+        &store,
+        Registry::builtin(),
+        &[root],
+        &WalkOptions::default(),
+        |_| Ok(()),
+    )
+    .unwrap();
+    assert_eq!(report.visited, 4);
+    assert!(report.missing.is_empty());
+
+    let mut archive = Vec::new();
+    let summary = export_archive(&store, &[root], &mut archive).unwrap();
+    assert_eq!(summary.objects, 4);
+
+    let options = GcOptions {
+        grace: Duration::ZERO,
+        ..GcOptions::default()
+    };
+    let gc = collect_garbage(&store, &options).unwrap();
+    assert!(gc.swept.is_empty());
+    assert!(gc.missing.is_empty());
+    assert!(store.has(&response).unwrap());
+}
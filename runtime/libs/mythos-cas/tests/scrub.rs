@@ -0,0 +1,158 @@
+/// Integrity scrub tests
+use mythos_blob::{BlobRef, ChunkedBlobBuilder, MIN_CHUNK_SIZE};
+use mythos_cas::{cid_from_bytes, put_nodes, scrub, BlobStore, ScrubOptions};
+use mythos_merkle::{build_merkle_list, HashValue};
+use std::fs;
+use std::time::Instant;
+
+mod common;
+use common::{pattern, put_blob, TempStore};
+
+#[test]
+fn test_clean_store() {
+    let temp = TempStore::new("clean");
+    let store = &temp.store;
+    put_blob(store, &pattern(MIN_CHUNK_SIZE as usize * 3));
+
+    let report = scrub(store, &ScrubOptions::default()).unwrap();
+    assert!(report.complete);
+    assert_eq!(report.checked, store.list().unwrap().len() as u64);
+    assert!(report.corrupt.is_empty());
+    assert!(report.invalid.is_empty());
+    assert!(report.missing.is_empty());
+}
+
+#[test]
+fn test_corrupt_object_quarantined() {
+    let temp = TempStore::new("corrupt");
+    let store = &temp.store;
+    let chunks = put_blob(store, &pattern(MIN_CHUNK_SIZE as usize * 2)).chunks;
+
+    let bad = chunks[1];
+    fs::write(store.object_path(&bad), b"bit rot").unwrap();
+
+    let report = scrub(store, &ScrubOptions::default()).unwrap();
+    assert_eq!(report.corrupt, vec![bad]);
+    assert_eq!(report.quarantined, vec![bad]);
+    assert!(!store.has(&bad).unwrap());
+    assert_eq!(fs::read(store.quarantine_path(&bad)).unwrap(), b"bit rot");
+
+    // With the object gone, the next pass reports the dangling reference
+    let report = scrub(store, &ScrubOptions::default()).unwrap();
+    assert!(report.corrupt.is_empty());
+    assert_eq!(report.missing.len(), 1);
+    assert_eq!(report.missing[0].child, bad);
+}
+
+#[test]
+fn test_report_only() {
+    let temp = TempStore::new("report-only");
+    let store = &temp.store;
+    let cid = store.put(b"original").unwrap();
+    fs::write(store.object_path(&cid), b"tampered").unwrap();
+
+    let options = ScrubOptions {
+        quarantine: false,
+        ..ScrubOptions::default()
+    };
+    let report = scrub(store, &options).unwrap();
+    assert_eq!(report.corrupt, vec![cid]);
+    assert!(report.quarantined.is_empty());
+    assert!(store.has(&cid).unwrap());
+}
+
+#[test]
+fn test_missing_merkle_nodes() {
+    let temp = TempStore::new("missing");
+    let store = &temp.store;
+
+    // Values name objects kept elsewhere; only the nodes are required
+    let values: Vec<HashValue> = (0..1500u32)
+        .map(|i| HashValue {
+            alg: 1,
+            bytes: cid_from_bytes(format!("item {}", i).as_bytes()).to_vec(),
+        })
+        .collect();
+    let list = build_merkle_list(&values).unwrap();
+    put_nodes(store, &list.nodes).unwrap();
+    let (lost, _) = list
+        .nodes
+        .iter()
+        .find(|(cid, _)| *cid != list.root)
+        .unwrap();
+    assert!(store.delete(lost).unwrap());
+
+    let report = scrub(store, &ScrubOptions::default()).unwrap();
+    assert_eq!(report.missing.len(), 1);
+    assert_eq!(report.missing[0].child, *lost);
+    assert!(report.invalid.is_empty());
+}
+
+#[test]
+fn test_chunk_node_reference_must_validate() {
+    let temp = TempStore::new("invalid");
+    let store = &temp.store;
+
+    // A BlobRef claiming a chunked DAG whose root is not a node
+    let not_a_node = store.put(b"plain bytes").unwrap();
+    let mut blob_ref = BlobRef::from_build(
+        &ChunkedBlobBuilder::new()
+            .chunk_size(MIN_CHUNK_SIZE)
+            .build(&b"x"[..])
+            .unwrap(),
+        "application/octet-stream",
+    );
+    blob_ref.cid = not_a_node.to_vec();
+    store.put(&blob_ref.to_bytes().unwrap()).unwrap();
+
+    let report = scrub(store, &ScrubOptions::default()).unwrap();
+    assert_eq!(report.invalid.len(), 1);
+    assert_eq!(report.invalid[0].0, not_a_node);
+}
+
+#[test]
+fn test_resume_in_batches() {
+    let temp = TempStore::new("resume");
+    let store = &temp.store;
+    for i in 0..10u8 {
+        store.put(&[i; 16]).unwrap();
+    }
+
+    let mut options = ScrubOptions {
+        max_objects: Some(4),
+        ..ScrubOptions::default()
+    };
+    let mut checked = 0;
+    let mut runs = 0;
+    loop {
+        let report = scrub(store, &options).unwrap();
+        checked += report.checked;
+        runs += 1;
+        if report.complete {
+            break;
+        }
+        options.resume_after = report.cursor;
+    }
+
+    assert_eq!(checked, 10);
+    assert_eq!(runs, 3);
+}
+
+#[test]
+fn test_rate_limit() {
+    let temp = TempStore::new("rate");
+    let store = &temp.store;
+    for i in 0..4u8 {
+        store.put(&[i; 1000]).unwrap();
+    }
+
+    // 4000 bytes at 20 kB/s takes at least 0.2s
+    let options = ScrubOptions {
+        max_bytes_per_sec: Some(20_000),
+        ..ScrubOptions::default()
+    };
+    let started = Instant::now();
+    let report = scrub(store, &options).unwrap();
+    assert_eq!(report.bytes_read, 4000);
+    assert!(started.elapsed().as_millis() >= 190);
+}
@@ -0,0 +1,70 @@
+//! Baseline codebook (RFC-0001 Appendix B)
+//!
+//! The baseline is generated from the spec rather than shipped: one
+//! entry per §11 opcode, per small integer and field number 0..=255, and
+//! per A.12 CapScope kind, each with `value_u64` set, listed by ascending
+//! codeword.
+
+use crate::entry::{CodebookEntry, EntryValue};
+use crate::error::{Error, Result};
+use mythos_can::Value;
+
+/// Entry kinds (B.1)
+pub const KIND_OPCODE: u8 = 1;
+pub const KIND_SMALL_INT: u8 = 2;
+pub const KIND_FIELD_NUM: u8 = 3;
+pub const KIND_ENUM_TAG: u8 = 4;
+
+/// First codeword of each baseline range (B.2); each range is 256 wide
+pub const OPCODE_BASE: u16 = 0x0100;
+pub const SMALL_INT_BASE: u16 = 0x0200;
+pub const FIELD_NUM_BASE: u16 = 0x0300;
+pub const ENUM_TAG_BASE: u16 = 0x0400;
+
+/// The minimum opcode set (§11)
+pub const OPCODES: [u8; 18] = [
+    0x01, 0x02, // NOP, HALT
+    0x10, 0x11, 0x12, 0x13, // JMP, JZ, CALL, RET
+    0x20, 0x21, 0x22, 0x23, 0x24, 0x25, // CONST, MAKE, GET, SET, LIST, MATCH
+    0x30, 0x31, 0x32, // REQUIRE_CAP, EFFECT, ASSERT
+    0x40, 0x41, 0x42, // BLOB_PUT, BLOB_GET, BLOB_CHUNK_GET
+];
+
+/// CapScope kinds, BlobRead (1) to CellWrite (11) (A.12)
+pub const CAP_SCOPE_KINDS: [u8; 11] = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11];
+
+/// Every baseline entry, by ascending codeword
+pub fn baseline_entries() -> Vec<CodebookEntry> {
+    let opcodes = OPCODES
+        .iter()
+        .map(|&op| (OPCODE_BASE + op as u16, KIND_OPCODE, op as u64));
+    let small_ints = (0..=255u8).map(|n| (SMALL_INT_BASE + n as u16, KIND_SMALL_INT, n as u64));
+    let field_nums = (0..=255u8).map(|f| (FIELD_NUM_BASE + f as u16, KIND_FIELD_NUM, f as u64));
+    let cap_kinds = CAP_SCOPE_KINDS
+        .iter()
+        .map(|&k| (ENUM_TAG_BASE + k as u16, KIND_ENUM_TAG, k as u64));
+
+    opcodes
+        .chain(small_ints)
+        .chain(field_nums)
+        .chain(cap_kinds)
+        .map(|(codeword, kind, value)| CodebookEntry::new(codeword, kind, EntryValue::U64(value)))
+        .collect()
+}
+
+/// Canonical bytes of the baseline entry list
+pub fn baseline_bytes() -> Result<Vec<u8>> {
+    let entries = Value::List(
+        baseline_entries()
+            .iter()
+            .map(CodebookEntry::to_value)
+            .collect(),
+    );
+    mythos_can::encode_value(&entries)
+        .map_err(|e| Error::InvalidStructure(format!("Baseline encode: {}", e)))
+}
+
+/// Baseline CodebookID (B.7)
+pub fn baseline_codebook_id() -> Result<[u8; 32]> {
+    Ok(crate::codebook_id_from_bytes(&baseline_bytes()?))
+}
@@ -0,0 +1,151 @@
+//! Validated codebooks with lookup in both directions
+//!
+//! `Codebook::from_bytes` loads a `list(CodebookEntry)` from a peer and
+//! checks it: every entry valid on its own (`CodebookEntry::validate`),
+//! codewords strictly ascending, and no kind mapping one value to two
+//! codewords, so each direction of lookup has one answer.
+
+use crate::baseline::baseline_entries;
+use crate::codebook_id_from_bytes;
+use crate::entry::{CodebookEntry, EntryValue};
+use crate::error::{Error, Result};
+use mythos_can::Value;
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Codebook {
+    entries: Vec<CodebookEntry>,
+    codebook_id: [u8; 32],
+    by_value: BTreeMap<(u8, EntryValue), u16>,
+}
+
+impl Codebook {
+    /// Validate `entries`, which must be ordered by codeword
+    pub fn new(entries: Vec<CodebookEntry>) -> Result<Self> {
+        let mut by_value = BTreeMap::new();
+        let mut previous: Option<u16> = None;
+        for entry in &entries {
+            match previous {
+                Some(p) if p == entry.codeword => {
+                    return Err(Error::DuplicateCodeword(entry.codeword))
+                }
+                Some(p) if p > entry.codeword => {
+                    return Err(Error::CodewordOrder {
+                        previous: p,
+                        codeword: entry.codeword,
+                    })
+                }
+                _ => {}
+            }
+            previous = Some(entry.codeword);
+
+            entry.validate()?;
+            if let Some(other) = by_value.insert((entry.kind, entry.value()?), entry.codeword) {
+                return Err(Error::DuplicateValue(format!(
+                    "codewords {:#06x} and {:#06x} share kind {} and value",
+                    other, entry.codeword, entry.kind
+                )));
+            }
+        }
+
+        let bytes = encode(&entries_value(&entries))?;
+        Ok(Codebook {
+            entries,
+            codebook_id: codebook_id_from_bytes(&bytes),
+            by_value,
+        })
+    }
+
+    /// The baseline codebook (Appendix B)
+    pub fn baseline() -> Result<Self> {
+        Codebook::new(baseline_entries())
+    }
+
+    /// Decode and validate canonical `list(CodebookEntry)` bytes
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let value = mythos_can::decode_value_exact(bytes)
+            .map_err(|e| Error::InvalidStructure(format!("Decode: {}", e)))?;
+        Codebook::from_value(&value)
+    }
+
+    pub fn from_value(value: &Value) -> Result<Self> {
+        match value {
+            Value::List(items) => Codebook::new(
+                items
+                    .iter()
+                    .map(CodebookEntry::from_value)
+                    .collect::<Result<_>>()?,
+            ),
+            _ => Err(Error::InvalidStructure(
+                "Codebook must be LIST of CodebookEntry".into(),
+            )),
+        }
+    }
+
+    pub fn to_value(&self) -> Value {
+        entries_value(&self.entries)
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        encode(&self.to_value())
+    }
+
+    /// CodebookID (B.7)
+    pub fn codebook_id(&self) -> [u8; 32] {
+        self.codebook_id
+    }
+
+    /// Fail with `Error::IdMismatch` unless the CodebookID is `expected`,
+    /// e.g. the one a packet header declares
+    pub fn verify_id(&self, expected: &[u8; 32]) -> Result<()> {
+        if &self.codebook_id == expected {
+            Ok(())
+        } else {
+            Err(Error::IdMismatch {
+                expected: hex::encode(expected),
+                computed: hex::encode(self.codebook_id),
+            })
+        }
+    }
+
+    /// Entries by ascending codeword
+    pub fn entries(&self) -> &[CodebookEntry] {
+        &self.entries
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The entry for `codeword`
+    pub fn entry(&self, codeword: u16) -> Option<&CodebookEntry> {
+        self.entries
+            .binary_search_by_key(&codeword, |entry| entry.codeword)
+            .ok()
+            .map(|i| &self.entries[i])
+    }
+
+    /// Codeword → (kind, value)
+    pub fn lookup(&self, codeword: u16) -> Option<(u8, EntryValue)> {
+        let entry = self.entry(codeword)?;
+        // Validated in `new`
+        entry.value().ok().map(|value| (entry.kind, value))
+    }
+
+    /// (kind, value) → codeword
+    pub fn codeword(&self, kind: u8, value: &EntryValue) -> Option<u16> {
+        self.by_value.get(&(kind, value.clone())).copied()
+    }
+}
+
+fn entries_value(entries: &[CodebookEntry]) -> Value {
+    Value::List(entries.iter().map(CodebookEntry::to_value).collect())
+}
+
+fn encode(value: &Value) -> Result<Vec<u8>> {
+    mythos_can::encode_value(value).map_err(|e| Error::InvalidStructure(format!("Encode: {}", e)))
+}
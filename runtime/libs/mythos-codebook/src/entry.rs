@@ -0,0 +1,229 @@
+//! CodebookEntry (RFC-0001 B.1)
+
+use crate::baseline::{
+    ENUM_TAG_BASE, FIELD_NUM_BASE, KIND_ENUM_TAG, KIND_FIELD_NUM, KIND_OPCODE, KIND_SMALL_INT,
+    OPCODE_BASE, SMALL_INT_BASE,
+};
+use crate::error::{Error, Result};
+use mythos_can::Value;
+
+/// The value an entry's codeword stands for
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum EntryValue {
+    U64(u64),
+    Bytes(Vec<u8>),
+    /// SHA-256 digest of a Hash (alg 1)
+    Hash([u8; 32]),
+    Text(String),
+}
+
+/// One codeword and what it stands for
+///
+/// Exactly one `value_*` field is set. The baseline kinds 1..=4 use
+/// `value_u64` and must sit in their own B.2 range at `base + value`;
+/// other kinds are peer-defined and live outside the baseline ranges.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodebookEntry {
+    pub codeword: u16,
+    pub kind: u8,
+    pub value_u64: Option<u64>,
+    pub value_bytes: Option<Vec<u8>>,
+    pub value_hash: Option<[u8; 32]>,
+    pub value_text: Option<String>,
+}
+
+impl CodebookEntry {
+    /// An entry holding `value`
+    pub fn new(codeword: u16, kind: u8, value: EntryValue) -> Self {
+        let (mut value_u64, mut value_bytes, mut value_hash, mut value_text) =
+            (None, None, None, None);
+        match value {
+            EntryValue::U64(n) => value_u64 = Some(n),
+            EntryValue::Bytes(bytes) => value_bytes = Some(bytes),
+            EntryValue::Hash(hash) => value_hash = Some(hash),
+            EntryValue::Text(text) => value_text = Some(text),
+        }
+        CodebookEntry {
+            codeword,
+            kind,
+            value_u64,
+            value_bytes,
+            value_hash,
+            value_text,
+        }
+    }
+
+    /// The populated value field; fails unless exactly one is set
+    pub fn value(&self) -> Result<EntryValue> {
+        let mut values = [
+            self.value_u64.map(EntryValue::U64),
+            self.value_bytes.clone().map(EntryValue::Bytes),
+            self.value_hash.map(EntryValue::Hash),
+            self.value_text.clone().map(EntryValue::Text),
+        ]
+        .into_iter()
+        .flatten();
+
+        match (values.next(), values.next()) {
+            (Some(value), None) => Ok(value),
+            (None, _) => Err(Error::InvalidValue(format!(
+                "codeword {:#06x} has no value",
+                self.codeword
+            ))),
+            (Some(_), Some(_)) => Err(Error::InvalidValue(format!(
+                "codeword {:#06x} has more than one value",
+                self.codeword
+            ))),
+        }
+    }
+
+    /// Check the entry on its own: one value, of the type its kind uses,
+    /// and baseline ranges used only by their kind
+    pub fn validate(&self) -> Result<()> {
+        let value = self.value()?;
+
+        let reserved = reserved_kind(self.codeword);
+        let base = match baseline_base(self.kind) {
+            Some(base) => base,
+            None if reserved.is_none() => return Ok(()),
+            None => {
+                return Err(Error::KindMismatch {
+                    codeword: self.codeword,
+                    kind: self.kind,
+                })
+            }
+        };
+        if reserved != Some(self.kind) {
+            return Err(Error::KindMismatch {
+                codeword: self.codeword,
+                kind: self.kind,
+            });
+        }
+
+        let expected = (self.codeword - base) as u64;
+        match value {
+            EntryValue::U64(n) if n == expected => Ok(()),
+            EntryValue::U64(n) => Err(Error::InvalidValue(format!(
+                "codeword {:#06x} must have value {}, has {}",
+                self.codeword, expected, n
+            ))),
+            _ => Err(Error::InvalidValue(format!(
+                "codeword {:#06x} of kind {} must use value_u64",
+                self.codeword, self.kind
+            ))),
+        }
+    }
+
+    pub fn to_value(&self) -> Value {
+        let mut fields = vec![
+            (Value::UVarint(1), Value::UVarint(self.codeword as u64)),
+            (Value::UVarint(2), Value::UVarint(self.kind as u64)),
+        ];
+        if let Some(n) = self.value_u64 {
+            fields.push((Value::UVarint(3), Value::UVarint(n)));
+        }
+        if let Some(bytes) = &self.value_bytes {
+            fields.push((Value::UVarint(4), Value::Bytes(bytes.clone())));
+        }
+        if let Some(hash) = &self.value_hash {
+            fields.push((
+                Value::UVarint(5),
+                Value::Map(vec![
+                    (Value::UVarint(1), Value::UVarint(1)),
+                    (Value::UVarint(2), Value::Bytes(hash.to_vec())),
+                ]),
+            ));
+        }
+        if let Some(text) = &self.value_text {
+            fields.push((Value::UVarint(6), Value::Text(text.clone())));
+        }
+        Value::Map(fields)
+    }
+
+    /// Decode the struct; does not `validate`
+    pub fn from_value(value: &Value) -> Result<Self> {
+        let fields = match value {
+            Value::Map(pairs) => pairs,
+            _ => return Err(Error::InvalidStructure("CodebookEntry must be MAP".into())),
+        };
+
+        let (mut codeword, mut kind) = (None, None);
+        let mut entry = CodebookEntry {
+            codeword: 0,
+            kind: 0,
+            value_u64: None,
+            value_bytes: None,
+            value_hash: None,
+            value_text: None,
+        };
+        for (k, v) in fields {
+            match (k, v) {
+                (Value::UVarint(1), Value::UVarint(n)) => {
+                    codeword = Some(u16::try_from(*n).map_err(|_| {
+                        Error::InvalidStructure(format!("codeword out of range: {}", n))
+                    })?)
+                }
+                (Value::UVarint(2), Value::UVarint(n)) => {
+                    kind = Some(u8::try_from(*n).map_err(|_| {
+                        Error::InvalidStructure(format!("kind out of range: {}", n))
+                    })?)
+                }
+                (Value::UVarint(3), Value::UVarint(n)) => entry.value_u64 = Some(*n),
+                (Value::UVarint(4), Value::Bytes(bytes)) => entry.value_bytes = Some(bytes.clone()),
+                (Value::UVarint(5), hash) => entry.value_hash = Some(hash_from_value(hash)?),
+                (Value::UVarint(6), Value::Text(text)) => entry.value_text = Some(text.clone()),
+                (Value::UVarint(n @ 1..=6), _) => {
+                    return Err(Error::InvalidStructure(format!(
+                        "CodebookEntry field {} has the wrong type",
+                        n
+                    )))
+                }
+                (other, _) => {
+                    return Err(Error::InvalidStructure(format!(
+                        "CodebookEntry has unknown field {:?}",
+                        other
+                    )))
+                }
+            }
+        }
+
+        entry.codeword =
+            codeword.ok_or_else(|| Error::InvalidStructure("Missing codeword".into()))?;
+        entry.kind = kind.ok_or_else(|| Error::InvalidStructure("Missing kind".into()))?;
+        Ok(entry)
+    }
+}
+
+/// The kind a baseline range is reserved for (B.2)
+pub fn reserved_kind(codeword: u16) -> Option<u8> {
+    match codeword >> 8 {
+        0x01 => Some(KIND_OPCODE),
+        0x02 => Some(KIND_SMALL_INT),
+        0x03 => Some(KIND_FIELD_NUM),
+        0x04 => Some(KIND_ENUM_TAG),
+        _ => None,
+    }
+}
+
+fn baseline_base(kind: u8) -> Option<u16> {
+    match kind {
+        KIND_OPCODE => Some(OPCODE_BASE),
+        KIND_SMALL_INT => Some(SMALL_INT_BASE),
+        KIND_FIELD_NUM => Some(FIELD_NUM_BASE),
+        KIND_ENUM_TAG => Some(ENUM_TAG_BASE),
+        _ => None,
+    }
+}
+
+fn hash_from_value(value: &Value) -> Result<[u8; 32]> {
+    let invalid = || Error::InvalidStructure("value_hash must be a SHA-256 Hash".into());
+    match value {
+        Value::Map(pairs) if pairs.len() == 2 => match (&pairs[0], &pairs[1]) {
+            ((Value::UVarint(1), Value::UVarint(1)), (Value::UVarint(2), Value::Bytes(bytes))) => {
+                bytes.as_slice().try_into().map_err(|_| invalid())
+            }
+            _ => Err(invalid()),
+        },
+        _ => Err(invalid()),
+    }
+}
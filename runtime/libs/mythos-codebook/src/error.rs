@@ -0,0 +1,27 @@
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum Error {
+    #[error("Invalid structure: {0}")]
+    InvalidStructure(String),
+
+    #[error("Invalid value: {0}")]
+    InvalidValue(String),
+
+    #[error("Codeword {codeword:#06x} follows {previous:#06x}; codewords must ascend")]
+    CodewordOrder { previous: u16, codeword: u16 },
+
+    #[error("Duplicate codeword {0:#06x}")]
+    DuplicateCodeword(u16),
+
+    #[error("Codeword {codeword:#06x} cannot have kind {kind}")]
+    KindMismatch { codeword: u16, kind: u8 },
+
+    #[error("Duplicate value: {0}")]
+    DuplicateValue(String),
+
+    #[error("CodebookID mismatch: expected {expected}, computed {computed}")]
+    IdMismatch { expected: String, computed: String },
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
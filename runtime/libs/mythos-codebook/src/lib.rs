@@ -1,7 +1,26 @@
 //! MYTHOS Codebook
+//!
+//! CodebookID computation, the baseline codebook of RFC-0001
+//! Appendix B generated from the spec, and typed CodebookEntry with
+//! validated `Codebook` loading and lookup.
+
+mod baseline;
+mod codebook;
+mod entry;
+mod error;
+
+pub use baseline::{
+    baseline_bytes, baseline_codebook_id, baseline_entries, CAP_SCOPE_KINDS, ENUM_TAG_BASE,
+    FIELD_NUM_BASE, KIND_ENUM_TAG, KIND_FIELD_NUM, KIND_OPCODE, KIND_SMALL_INT, OPCODES,
+    OPCODE_BASE, SMALL_INT_BASE,
+};
+pub use codebook::Codebook;
+pub use entry::{reserved_kind, CodebookEntry, EntryValue};
+pub use error::{Error, Result};
 
 use sha2::{Digest, Sha256};
 
+/// CodebookID: SHA-256 of the canonical entry list bytes
 pub fn codebook_id_from_bytes(bytes: &[u8]) -> [u8; 32] {
     let mut hasher = Sha256::new();
     hasher.update(bytes);
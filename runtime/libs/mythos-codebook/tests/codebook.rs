@@ -0,0 +1,238 @@
+/// CodebookEntry and Codebook validation tests
+use mythos_can::Value;
+use mythos_codebook::{
+    baseline_bytes, baseline_codebook_id, Codebook, CodebookEntry, EntryValue, Error,
+    KIND_ENUM_TAG, KIND_FIELD_NUM, KIND_OPCODE, KIND_SMALL_INT,
+};
+
+const KIND_TOOL: u8 = 9;
+
+fn peer_entries() -> Vec<CodebookEntry> {
+    vec![
+        CodebookEntry::new(0x0010, KIND_TOOL, EntryValue::Hash([7; 32])),
+        CodebookEntry::new(0x0011, KIND_TOOL, EntryValue::Text("fetch".into())),
+        CodebookEntry::new(0x0131, KIND_OPCODE, EntryValue::U64(0x31)),
+        CodebookEntry::new(0x0205, KIND_SMALL_INT, EntryValue::U64(5)),
+        CodebookEntry::new(0x0305, KIND_FIELD_NUM, EntryValue::U64(5)),
+        CodebookEntry::new(0x0500, 20, EntryValue::Bytes(vec![1, 2, 3])),
+        CodebookEntry::new(0xffff, 21, EntryValue::U64(u64::MAX)),
+    ]
+}
+
+fn rejected(entries: Vec<CodebookEntry>) -> Error {
+    Codebook::new(entries).unwrap_err()
+}
+
+#[test]
+fn test_baseline_lookup() {
+    let baseline = Codebook::baseline().unwrap();
+    assert_eq!(baseline.len(), 541);
+    assert_eq!(baseline.to_bytes().unwrap(), baseline_bytes().unwrap());
+    assert_eq!(baseline.codebook_id(), baseline_codebook_id().unwrap());
+    baseline
+        .verify_id(&baseline_codebook_id().unwrap())
+        .unwrap();
+    assert!(matches!(
+        baseline.verify_id(&[0; 32]),
+        Err(Error::IdMismatch { .. })
+    ));
+
+    assert_eq!(
+        baseline.lookup(0x0142),
+        Some((KIND_OPCODE, EntryValue::U64(0x42)))
+    );
+    assert_eq!(baseline.lookup(0x0143), None);
+    assert_eq!(
+        baseline.lookup(0x040b),
+        Some((KIND_ENUM_TAG, EntryValue::U64(11)))
+    );
+
+    // The same value under different kinds has different codewords
+    let seven = EntryValue::U64(7);
+    assert_eq!(baseline.codeword(KIND_SMALL_INT, &seven), Some(0x0207));
+    assert_eq!(baseline.codeword(KIND_FIELD_NUM, &seven), Some(0x0307));
+    assert_eq!(baseline.codeword(KIND_ENUM_TAG, &seven), Some(0x0407));
+    assert_eq!(baseline.codeword(KIND_OPCODE, &seven), None);
+    assert_eq!(
+        baseline.codeword(KIND_SMALL_INT, &EntryValue::U64(256)),
+        None
+    );
+}
+
+#[test]
+fn test_peer_codebook_round_trip() {
+    let codebook = Codebook::new(peer_entries()).unwrap();
+    let bytes = codebook.to_bytes().unwrap();
+    let loaded = Codebook::from_bytes(&bytes).unwrap();
+
+    assert_eq!(loaded, codebook);
+    assert_eq!(loaded.entries(), &peer_entries()[..]);
+    assert_eq!(
+        loaded.codebook_id(),
+        mythos_codebook::codebook_id_from_bytes(&bytes)
+    );
+
+    for entry in peer_entries() {
+        let value = entry.value().unwrap();
+        assert_eq!(
+            loaded.lookup(entry.codeword),
+            Some((entry.kind, value.clone()))
+        );
+        assert_eq!(loaded.codeword(entry.kind, &value), Some(entry.codeword));
+    }
+    assert_eq!(
+        loaded.codeword(KIND_TOOL, &EntryValue::Text("other".into())),
+        None
+    );
+    assert_eq!(loaded.entry(0x0012), None);
+
+    let empty = Codebook::from_bytes(&[0x07, 0x00]).unwrap();
+    assert!(empty.is_empty());
+}
+
+#[test]
+fn test_codeword_order() {
+    let mut entries = peer_entries();
+    entries.swap(0, 1);
+    assert_eq!(
+        rejected(entries),
+        Error::CodewordOrder {
+            previous: 0x0011,
+            codeword: 0x0010
+        }
+    );
+
+    let mut entries = peer_entries();
+    entries[1].codeword = 0x0010;
+    assert_eq!(rejected(entries), Error::DuplicateCodeword(0x0010));
+
+    // One value per kind, so value → codeword is unambiguous
+    let mut entries = peer_entries();
+    entries[1] = CodebookEntry::new(0x0011, KIND_TOOL, EntryValue::Hash([7; 32]));
+    assert!(matches!(rejected(entries), Error::DuplicateValue(_)));
+}
+
+#[test]
+fn test_baseline_ranges() {
+    let mismatch = |codeword, kind| Error::KindMismatch { codeword, kind };
+
+    // Baseline range with another kind
+    let mut entries = peer_entries();
+    entries[3].kind = KIND_FIELD_NUM;
+    assert_eq!(rejected(entries), mismatch(0x0205, KIND_FIELD_NUM));
+
+    // Peer-defined kind in a baseline range
+    let entries = vec![CodebookEntry::new(0x04ff, KIND_TOOL, EntryValue::U64(1))];
+    assert_eq!(rejected(entries), mismatch(0x04ff, KIND_TOOL));
+
+    // Baseline kind outside its range
+    let entries = vec![CodebookEntry::new(
+        0x0050,
+        KIND_OPCODE,
+        EntryValue::U64(0x50),
+    )];
+    assert_eq!(rejected(entries), mismatch(0x0050, KIND_OPCODE));
+
+    // Codeword must be base + value
+    let entries = vec![CodebookEntry::new(
+        0x0205,
+        KIND_SMALL_INT,
+        EntryValue::U64(6),
+    )];
+    assert!(matches!(rejected(entries), Error::InvalidValue(_)));
+}
+
+#[test]
+fn test_value_fields() {
+    // Baseline kinds use value_u64 only
+    let entries = vec![CodebookEntry::new(
+        0x0131,
+        KIND_OPCODE,
+        EntryValue::Text("EFFECT".into()),
+    )];
+    assert!(matches!(rejected(entries), Error::InvalidValue(_)));
+
+    let mut none = CodebookEntry::new(0x0010, KIND_TOOL, EntryValue::U64(1));
+    none.value_u64 = None;
+    assert!(matches!(none.validate(), Err(Error::InvalidValue(_))));
+
+    let mut two = CodebookEntry::new(0x0010, KIND_TOOL, EntryValue::U64(1));
+    two.value_text = Some("one".into());
+    assert!(matches!(two.validate(), Err(Error::InvalidValue(_))));
+    assert!(matches!(rejected(vec![two]), Error::InvalidValue(_)));
+}
+
+#[test]
+fn test_decode_errors() {
+    let entry = |fields: Vec<(u64, Value)>| {
+        Value::Map(
+            fields
+                .into_iter()
+                .map(|(k, v)| (Value::UVarint(k), v))
+                .collect(),
+        )
+    };
+    let decode = |value: Value| {
+        let bytes = mythos_can::encode_value(&Value::List(vec![value])).unwrap();
+        Codebook::from_bytes(&bytes)
+    };
+
+    let bad = [
+        entry(vec![
+            (1, Value::UVarint(0x10000)),
+            (2, Value::UVarint(9)),
+            (3, Value::UVarint(1)),
+        ]),
+        entry(vec![
+            (1, Value::UVarint(0x10)),
+            (2, Value::UVarint(256)),
+            (3, Value::UVarint(1)),
+        ]),
+        entry(vec![(2, Value::UVarint(9)), (3, Value::UVarint(1))]),
+        entry(vec![(1, Value::UVarint(0x10)), (3, Value::UVarint(1))]),
+        entry(vec![
+            (1, Value::UVarint(0x10)),
+            (2, Value::UVarint(9)),
+            (3, Value::Text("1".into())),
+        ]),
+        entry(vec![
+            (1, Value::UVarint(0x10)),
+            (2, Value::UVarint(9)),
+            (7, Value::UVarint(1)),
+        ]),
+        entry(vec![
+            (1, Value::UVarint(0x10)),
+            (2, Value::UVarint(9)),
+            (5, Value::Bytes(vec![0; 32])),
+        ]),
+        entry(vec![
+            (1, Value::UVarint(0x10)),
+            (2, Value::UVarint(9)),
+            (
+                5,
+                Value::Map(vec![
+                    (Value::UVarint(1), Value::UVarint(2)),
+                    (Value::UVarint(2), Value::Bytes(vec![0; 32])),
+                ]),
+            ),
+        ]),
+        Value::List(vec![]),
+    ];
+    for value in bad {
+        assert!(
+            matches!(decode(value.clone()), Err(Error::InvalidStructure(_))),
+            "{:?}",
+            value
+        );
+    }
+
+    assert!(matches!(
+        Codebook::from_bytes(&[0x08, 0x00]),
+        Err(Error::InvalidStructure(_))
+    ));
+    // Trailing bytes are not canonical
+    assert!(matches!(
+        Codebook::from_bytes(&[0x07, 0x00, 0x00]),
+        Err(Error::InvalidStructure(_))
+    ));
+}
@@ -1,4 +1,8 @@
-use mythos_codebook::codebook_id_from_bytes;
+use mythos_codebook::{
+    baseline_bytes, baseline_codebook_id, baseline_entries, codebook_id_from_bytes,
+    CAP_SCOPE_KINDS, ENUM_TAG_BASE, FIELD_NUM_BASE, KIND_ENUM_TAG, KIND_FIELD_NUM, KIND_OPCODE,
+    KIND_SMALL_INT, OPCODES, OPCODE_BASE, SMALL_INT_BASE,
+};
 use std::fs;
 
 #[test]
@@ -16,3 +20,48 @@ fn test_codebook_001_id_matches() {
     let computed = codebook_id_from_bytes(&entries);
     assert_eq!(hex::encode(computed), expected, "Codebook ID mismatch");
 }
+
+#[test]
+fn test_codebook_001_regenerated_from_spec() {
+    let shipped =
+        fs::read("../../../mythos-v0.2-conformance/vectors/codebook/codebook_baseline_entries.bin")
+            .unwrap();
+    let expected = fs::read_to_string(
+        "../../../mythos-v0.2-conformance/vectors/codebook/codebook_baseline_id.hex",
+    )
+    .unwrap();
+
+    assert_eq!(baseline_bytes().unwrap(), shipped);
+    assert_eq!(
+        hex::encode(baseline_codebook_id().unwrap()),
+        expected.trim()
+    );
+}
+
+#[test]
+fn test_baseline_counts_and_order() {
+    let meta = fs::read_to_string(
+        "../../../mythos-v0.2-conformance/vectors/codebook/codebook_baseline_meta.json",
+    )
+    .unwrap();
+    let entries = baseline_entries();
+    let triples: Vec<(u16, u8, u64)> = entries
+        .iter()
+        .map(|e| (e.codeword, e.kind, e.value_u64.unwrap()))
+        .collect();
+    assert!(meta.contains(&format!("\"entry_count\": {}", triples.len())));
+    assert!(meta.contains(&format!("\"opcode_count\": {}", OPCODES.len())));
+    assert!(meta.contains(&format!("\"cap_kind_count\": {}", CAP_SCOPE_KINDS.len())));
+
+    assert!(triples.windows(2).all(|w| w[0].0 < w[1].0));
+    assert_eq!(triples[0], (OPCODE_BASE + 0x01, KIND_OPCODE, 0x01));
+    assert_eq!(triples[18], (SMALL_INT_BASE, KIND_SMALL_INT, 0));
+    assert_eq!(
+        triples[18 + 256 + 255],
+        (FIELD_NUM_BASE + 255, KIND_FIELD_NUM, 255)
+    );
+    assert_eq!(
+        triples.last(),
+        Some(&(ENUM_TAG_BASE + 11, KIND_ENUM_TAG, 11))
+    );
+}
@@ -0,0 +1,29 @@
+//! MYTHOS-CAN helpers for the typed structs that return this crate's
+//! errors; the field helpers live in `mythos_can::structs`
+
+use crate::cid_from_bytes;
+use crate::error::{Error, Result};
+use mythos_can::structs::without_fields;
+use mythos_can::Value;
+
+pub(crate) fn encode(value: &Value) -> Result<Vec<u8>> {
+    mythos_can::encode_value(value).map_err(|e| Error::InvalidStructure(format!("Encode: {}", e)))
+}
+
+pub(crate) fn decode(bytes: &[u8]) -> Result<Value> {
+    mythos_can::decode_value_exact(bytes)
+        .map_err(|e| Error::InvalidStructure(format!("Decode: {}", e)))
+}
+
+/// SHA-256 of a struct's canonical bytes with field 1 (its id) left out
+pub(crate) fn id_excluding_field_1(fields: Vec<(Value, Value)>) -> Result<[u8; 32]> {
+    Ok(cid_from_bytes(&encode(&without_fields(fields, &[1]))?))
+}
+
+pub(crate) fn id_mismatch(name: &'static str, stored: &[u8; 32], computed: &[u8; 32]) -> Error {
+    Error::IdMismatch {
+        name,
+        stored: hex::encode(stored),
+        computed: hex::encode(computed),
+    }
+}
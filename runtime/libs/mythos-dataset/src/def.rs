@@ -0,0 +1,371 @@
+//! DatasetDef, QueryDef, SamplingDef, StratifyDef and DatasetRef
+//! (RFC-0003 §3–6, RFC-0001 A.15)
+//!
+//! Typed structs with canonical MYTHOS-CAN encode/decode. Constructors
+//! compute the content ids (`dataset_def_id`, `query_id`); decoding
+//! recomputes them and rejects a struct whose stored id does not match.
+
+use crate::codec::{decode, encode, id_excluding_field_1, id_mismatch};
+use crate::error::{Error, Result};
+use crate::expr::{Expr, FieldPath};
+use mythos_blob::BlobRef;
+use mythos_can::structs::{
+    field, hash_value, known_fields, required, required_hash, required_list, required_uint,
+    struct_fields,
+};
+use mythos_can::Value;
+
+/// Media type of a dataset manifest blob (RFC-0003 §3.2)
+pub const MANIFEST_MEDIA: &str = "application/mythos.episode.manifest";
+
+/// Dataset definition (RFC-0001 A.15)
+#[derive(Debug, Clone, PartialEq)]
+pub struct DatasetDef {
+    pub dataset_def_id: [u8; 32],      // Field 1 - SHA-256 of fields 2..5
+    pub corpus_roots: Vec<[u8; 32]>,   // Field 2 - episode list roots
+    pub query: QueryDef,               // Field 3
+    pub sampling: SamplingDef,         // Field 4
+    pub stratify: Option<StratifyDef>, // Field 5 - optional
+}
+
+/// Episode filter (RFC-0003 §4.1)
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryDef {
+    pub query_id: [u8; 32], // Field 1 - SHA-256 of field 2
+    pub predicate: Expr,    // Field 2
+}
+
+/// Sampling modes (RFC-0003 §5.1)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum SamplingMode {
+    All = 1,
+    FirstN = 2,
+    HashN = 3,
+}
+
+/// How many filtered episodes to keep (RFC-0003 §5)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SamplingDef {
+    pub mode: SamplingMode, // Field 1
+    pub n: Option<u64>,     // Field 2 - required by FIRST_N and HASH_N
+    pub seed: [u8; 32],     // Field 3 - Hash bytes
+}
+
+/// Per-bucket sampling (RFC-0003 §6)
+#[derive(Debug, Clone, PartialEq)]
+pub struct StratifyDef {
+    pub key_path: FieldPath, // Field 1
+    pub per_bucket_n: u64,   // Field 2
+    pub seed: [u8; 32],      // Field 3 - Hash bytes
+}
+
+/// A built dataset (RFC-0001 A.15)
+#[derive(Debug, Clone, PartialEq)]
+pub struct DatasetRef {
+    pub dataset_def_id: [u8; 32], // Field 1
+    pub manifest: BlobRef,        // Field 2 - MerkleList of EpisodeIDs
+    pub count: u64,               // Field 3 - episodes in the manifest
+    pub receipt_id: [u8; 32],     // Field 4 - build receipt
+}
+
+impl DatasetDef {
+    /// Build a definition and compute its id
+    pub fn new(
+        corpus_roots: Vec<[u8; 32]>,
+        query: QueryDef,
+        sampling: SamplingDef,
+        stratify: Option<StratifyDef>,
+    ) -> Result<Self> {
+        let mut def = DatasetDef {
+            dataset_def_id: [0; 32],
+            corpus_roots,
+            query,
+            sampling,
+            stratify,
+        };
+        def.dataset_def_id = def.compute_id()?;
+        def.validate()?;
+        Ok(def)
+    }
+
+    /// SHA-256 of the canonical bytes without field 1 (RFC-0003 §3.1)
+    pub fn compute_id(&self) -> Result<[u8; 32]> {
+        id_excluding_field_1(self.fields())
+    }
+
+    /// Check nested structs and that the stored id is current
+    pub fn validate(&self) -> Result<()> {
+        if self.corpus_roots.is_empty() {
+            return Err(Error::InvalidStructure(
+                "DatasetDef needs at least one corpus root".into(),
+            ));
+        }
+        self.query.validate()?;
+        self.sampling.validate()?;
+        if let Some(stratify) = &self.stratify {
+            stratify.validate()?;
+        }
+
+        let computed = self.compute_id()?;
+        if computed != self.dataset_def_id {
+            return Err(id_mismatch(
+                "dataset_def_id",
+                &self.dataset_def_id,
+                &computed,
+            ));
+        }
+        Ok(())
+    }
+
+    fn fields(&self) -> Vec<(Value, Value)> {
+        let mut fields = vec![
+            (Value::UVarint(1), hash_value(&self.dataset_def_id)),
+            (
+                Value::UVarint(2),
+                Value::List(self.corpus_roots.iter().map(hash_value).collect()),
+            ),
+            (Value::UVarint(3), self.query.to_value()),
+            (Value::UVarint(4), self.sampling.to_value()),
+        ];
+        if let Some(stratify) = &self.stratify {
+            fields.push((Value::UVarint(5), stratify.to_value()));
+        }
+        fields
+    }
+
+    pub fn to_value(&self) -> Value {
+        Value::Map(self.fields())
+    }
+
+    pub fn from_value(value: &Value) -> Result<Self> {
+        let fields = struct_fields(value, "DatasetDef")?;
+        known_fields(fields, 5, "DatasetDef")?;
+
+        let def = DatasetDef {
+            dataset_def_id: required_hash(field(fields, 1), "DatasetDef.dataset_def_id")?,
+            corpus_roots: required_list(field(fields, 2), "DatasetDef.corpus_roots")?
+                .iter()
+                .map(|root| required_hash(Some(root), "DatasetDef corpus root"))
+                .collect::<mythos_can::Result<Vec<_>>>()?,
+            query: QueryDef::from_value(required(field(fields, 3), "DatasetDef.query")?)?,
+            sampling: SamplingDef::from_value(required(field(fields, 4), "DatasetDef.sampling")?)?,
+            stratify: field(fields, 5).map(StratifyDef::from_value).transpose()?,
+        };
+
+        def.validate()?;
+        Ok(def)
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        encode(&self.to_value())
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        DatasetDef::from_value(&decode(bytes)?)
+    }
+}
+
+impl QueryDef {
+    /// Build a query and compute its id
+    pub fn new(predicate: Expr) -> Result<Self> {
+        let mut query = QueryDef {
+            query_id: [0; 32],
+            predicate,
+        };
+        query.query_id = query.compute_id()?;
+        query.validate()?;
+        Ok(query)
+    }
+
+    /// SHA-256 of the canonical bytes without field 1 (RFC-0003 §4.1)
+    pub fn compute_id(&self) -> Result<[u8; 32]> {
+        id_excluding_field_1(self.fields())
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        self.predicate.validate()?;
+        let computed = self.compute_id()?;
+        if computed != self.query_id {
+            return Err(id_mismatch("query_id", &self.query_id, &computed));
+        }
+        Ok(())
+    }
+
+    fn fields(&self) -> Vec<(Value, Value)> {
+        vec![
+            (Value::UVarint(1), hash_value(&self.query_id)),
+            (Value::UVarint(2), self.predicate.to_value()),
+        ]
+    }
+
+    pub fn to_value(&self) -> Value {
+        Value::Map(self.fields())
+    }
+
+    pub fn from_value(value: &Value) -> Result<Self> {
+        let fields = struct_fields(value, "QueryDef")?;
+        known_fields(fields, 2, "QueryDef")?;
+
+        let query = QueryDef {
+            query_id: required_hash(field(fields, 1), "QueryDef.query_id")?,
+            predicate: Expr::from_value(required(field(fields, 2), "QueryDef.predicate")?)?,
+        };
+        query.validate()?;
+        Ok(query)
+    }
+}
+
+impl SamplingDef {
+    pub fn all(seed: [u8; 32]) -> Self {
+        SamplingDef {
+            mode: SamplingMode::All,
+            n: None,
+            seed,
+        }
+    }
+
+    pub fn first_n(n: u64, seed: [u8; 32]) -> Self {
+        SamplingDef {
+            mode: SamplingMode::FirstN,
+            n: Some(n),
+            seed,
+        }
+    }
+
+    pub fn hash_n(n: u64, seed: [u8; 32]) -> Self {
+        SamplingDef {
+            mode: SamplingMode::HashN,
+            n: Some(n),
+            seed,
+        }
+    }
+
+    /// FIRST_N and HASH_N need `n`; ALL must not have one
+    pub fn validate(&self) -> Result<()> {
+        match (self.mode, self.n) {
+            (SamplingMode::All, Some(_)) => {
+                Err(Error::InvalidSampling("ALL does not take n".into()))
+            }
+            (SamplingMode::FirstN | SamplingMode::HashN, None) => Err(Error::InvalidSampling(
+                format!("{:?} requires n", self.mode),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn to_value(&self) -> Value {
+        let mut fields = vec![(Value::UVarint(1), Value::UVarint(self.mode as u64))];
+        if let Some(n) = self.n {
+            fields.push((Value::UVarint(2), Value::UVarint(n)));
+        }
+        fields.push((Value::UVarint(3), hash_value(&self.seed)));
+        Value::Map(fields)
+    }
+
+    pub fn from_value(value: &Value) -> Result<Self> {
+        let fields = struct_fields(value, "SamplingDef")?;
+        known_fields(fields, 3, "SamplingDef")?;
+
+        let mode = match required_uint(field(fields, 1), "SamplingDef.mode", u8::MAX as u64)? {
+            1 => SamplingMode::All,
+            2 => SamplingMode::FirstN,
+            3 => SamplingMode::HashN,
+            other => return Err(Error::UnknownSamplingMode(other)),
+        };
+        let sampling = SamplingDef {
+            mode,
+            n: field(fields, 2)
+                .map(|n| required_uint(Some(n), "SamplingDef.n", u64::MAX))
+                .transpose()?,
+            seed: required_hash(field(fields, 3), "SamplingDef.seed")?,
+        };
+        sampling.validate()?;
+        Ok(sampling)
+    }
+}
+
+impl StratifyDef {
+    pub fn validate(&self) -> Result<()> {
+        self.key_path.validate()
+    }
+
+    pub fn to_value(&self) -> Value {
+        Value::Map(vec![
+            (Value::UVarint(1), self.key_path.to_value()),
+            (Value::UVarint(2), Value::UVarint(self.per_bucket_n)),
+            (Value::UVarint(3), hash_value(&self.seed)),
+        ])
+    }
+
+    pub fn from_value(value: &Value) -> Result<Self> {
+        let fields = struct_fields(value, "StratifyDef")?;
+        known_fields(fields, 3, "StratifyDef")?;
+
+        let stratify = StratifyDef {
+            key_path: FieldPath::from_value(required(field(fields, 1), "StratifyDef.key_path")?)?,
+            per_bucket_n: required_uint(field(fields, 2), "StratifyDef.per_bucket_n", u64::MAX)?,
+            seed: required_hash(field(fields, 3), "StratifyDef.seed")?,
+        };
+        stratify.validate()?;
+        Ok(stratify)
+    }
+}
+
+impl DatasetRef {
+    /// Check the manifest BlobRef and its media type
+    pub fn validate(&self) -> Result<()> {
+        self.manifest
+            .validate()
+            .map_err(|e| Error::InvalidStructure(format!("DatasetRef.manifest: {}", e)))?;
+        if self.manifest.media != MANIFEST_MEDIA {
+            return Err(Error::InvalidStructure(format!(
+                "DatasetRef.manifest media is {:?}, expected {}",
+                self.manifest.media, MANIFEST_MEDIA
+            )));
+        }
+        Ok(())
+    }
+
+    /// Manifest root CID
+    pub fn manifest_root(&self) -> Result<[u8; 32]> {
+        self.manifest.cid.as_slice().try_into().map_err(|_| {
+            Error::InvalidHash(format!(
+                "DatasetRef manifest cid has {} bytes",
+                self.manifest.cid.len()
+            ))
+        })
+    }
+
+    pub fn to_value(&self) -> Value {
+        Value::Map(vec![
+            (Value::UVarint(1), hash_value(&self.dataset_def_id)),
+            (Value::UVarint(2), self.manifest.to_value()),
+            (Value::UVarint(3), Value::UVarint(self.count)),
+            (Value::UVarint(4), hash_value(&self.receipt_id)),
+        ])
+    }
+
+    pub fn from_value(value: &Value) -> Result<Self> {
+        let fields = struct_fields(value, "DatasetRef")?;
+        known_fields(fields, 4, "DatasetRef")?;
+
+        let dataset = DatasetRef {
+            dataset_def_id: required_hash(field(fields, 1), "DatasetRef.dataset_def_id")?,
+            manifest: BlobRef::from_value(required(field(fields, 2), "DatasetRef.manifest")?)
+                .map_err(|e| Error::InvalidStructure(format!("DatasetRef.manifest: {}", e)))?,
+            count: required_uint(field(fields, 3), "DatasetRef.count", u64::MAX)?,
+            receipt_id: required_hash(field(fields, 4), "DatasetRef.receipt_id")?,
+        };
+        dataset.validate()?;
+        Ok(dataset)
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        encode(&self.to_value())
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        DatasetRef::from_value(&decode(bytes)?)
+    }
+}
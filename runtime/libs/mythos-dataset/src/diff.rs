@@ -0,0 +1,218 @@
+//! Manifest diff and overlap report
+//!
+//! `diff_manifests` walks two manifest MerkleLists in lockstep, one node
+//! of each at a time, and counts the EpisodeIDs in both, only in A and
+//! only in B. Manifests list their IDs in strictly ascending order (§7
+//! step 7); a list out of order fails with `Error::InvalidCorpus`, since
+//! the merge would silently miss shared IDs. Unlike
+//! `mythos_merkle::diff_merkle_lists`, which compares by position, this
+//! compares the manifests as sets.
+//!
+//! With `DiffDetail::Full` the three ID sets are collected as well; a
+//! summary holds only the counts, so memory stays flat for any size.
+
+use crate::build::ListIds;
+use crate::def::DatasetRef;
+use crate::error::{Error, Result};
+use crate::sample::EpisodeId;
+use mythos_cas::BlobStore;
+use std::cmp::Ordering;
+use std::fmt::Write;
+
+/// What `diff_manifests` collects
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffDetail {
+    /// Counts only
+    Summary,
+    /// Counts and the IDs in each set
+    Full,
+}
+
+/// How manifest A relates to manifest B
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ManifestDiff {
+    pub a_count: u64,
+    pub b_count: u64,
+    /// IDs in both manifests
+    pub overlap: u64,
+    /// Empty unless `DiffDetail::Full`; likewise `only_a` and `only_b`
+    pub shared: Vec<EpisodeId>,
+    pub only_a: Vec<EpisodeId>,
+    pub only_b: Vec<EpisodeId>,
+}
+
+impl ManifestDiff {
+    pub fn only_a_count(&self) -> u64 {
+        self.a_count - self.overlap
+    }
+
+    pub fn only_b_count(&self) -> u64 {
+        self.b_count - self.overlap
+    }
+
+    /// |A ∩ B| / |A ∪ B|; 1.0 when both are empty
+    pub fn jaccard(&self) -> f64 {
+        let union = self.a_count + self.b_count - self.overlap;
+        if union == 0 {
+            return 1.0;
+        }
+        self.overlap as f64 / union as f64
+    }
+
+    pub fn is_disjoint(&self) -> bool {
+        self.overlap == 0
+    }
+
+    /// Fail with `Error::Overlap` unless no ID is in both manifests
+    ///
+    /// The train/eval contamination check.
+    pub fn ensure_disjoint(&self) -> Result<()> {
+        if self.is_disjoint() {
+            Ok(())
+        } else {
+            Err(Error::Overlap(self.overlap))
+        }
+    }
+
+    /// Text report: the counts and Jaccard index, then any collected IDs
+    /// as hex, one per line under `shared:`, `only_a:` and `only_b:`
+    pub fn report(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "a_count: {}", self.a_count);
+        let _ = writeln!(out, "b_count: {}", self.b_count);
+        let _ = writeln!(out, "overlap: {}", self.overlap);
+        let _ = writeln!(out, "only_a: {}", self.only_a_count());
+        let _ = writeln!(out, "only_b: {}", self.only_b_count());
+        let _ = writeln!(out, "jaccard: {:.6}", self.jaccard());
+
+        let lists = [
+            ("shared", &self.shared),
+            ("only_a", &self.only_a),
+            ("only_b", &self.only_b),
+        ];
+        for (name, ids) in lists {
+            if ids.is_empty() {
+                continue;
+            }
+            let _ = writeln!(out, "\n{}:", name);
+            for id in ids {
+                let _ = writeln!(out, "{}", hex::encode(id));
+            }
+        }
+        out
+    }
+}
+
+/// Diff two manifests by root CID
+pub fn diff_manifests<S>(
+    store: &S,
+    a_root: &[u8; 32],
+    b_root: &[u8; 32],
+    detail: DiffDetail,
+) -> Result<ManifestDiff>
+where
+    S: BlobStore + ?Sized,
+{
+    let full = detail == DiffDetail::Full;
+    let mut a = Ascending::new(ListIds::new(store, a_root), "A");
+    let mut b = Ascending::new(ListIds::new(store, b_root), "B");
+    let mut diff = ManifestDiff::default();
+
+    let (mut next_a, mut next_b) = (a.next()?, b.next()?);
+    loop {
+        let order = match (next_a, next_b) {
+            (None, None) => break,
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (Some(x), Some(y)) => x.cmp(&y),
+        };
+        match order {
+            Ordering::Less => {
+                if full {
+                    diff.only_a.extend(next_a);
+                }
+                next_a = a.next()?;
+            }
+            Ordering::Greater => {
+                if full {
+                    diff.only_b.extend(next_b);
+                }
+                next_b = b.next()?;
+            }
+            Ordering::Equal => {
+                diff.overlap += 1;
+                if full {
+                    diff.shared.extend(next_a);
+                }
+                next_a = a.next()?;
+                next_b = b.next()?;
+            }
+        }
+    }
+
+    diff.a_count = a.count;
+    diff.b_count = b.count;
+    Ok(diff)
+}
+
+/// Diff the manifests of two DatasetRefs
+///
+/// Each manifest must hold the `count` its DatasetRef records.
+pub fn diff_datasets<S>(
+    store: &S,
+    a: &DatasetRef,
+    b: &DatasetRef,
+    detail: DiffDetail,
+) -> Result<ManifestDiff>
+where
+    S: BlobStore + ?Sized,
+{
+    a.validate()?;
+    b.validate()?;
+    let diff = diff_manifests(store, &a.manifest_root()?, &b.manifest_root()?, detail)?;
+
+    for (name, dataset, count) in [("A", a, diff.a_count), ("B", b, diff.b_count)] {
+        if dataset.count != count {
+            return Err(Error::InvalidCorpus(format!(
+                "manifest {} lists {} IDs, DatasetRef count is {}",
+                name, count, dataset.count
+            )));
+        }
+    }
+    Ok(diff)
+}
+
+/// A manifest's IDs, checked to be strictly ascending, and their count
+struct Ascending<I> {
+    ids: I,
+    name: &'static str,
+    last: Option<EpisodeId>,
+    count: u64,
+}
+
+impl<I: Iterator<Item = Result<EpisodeId>>> Ascending<I> {
+    fn new(ids: I, name: &'static str) -> Self {
+        Ascending {
+            ids,
+            name,
+            last: None,
+            count: 0,
+        }
+    }
+
+    fn next(&mut self) -> Result<Option<EpisodeId>> {
+        let Some(id) = self.ids.next().transpose()? else {
+            return Ok(None);
+        };
+        if self.last.is_some_and(|last| last >= id) {
+            return Err(Error::InvalidCorpus(format!(
+                "manifest {} is not strictly ascending at {}",
+                self.name,
+                hex::encode(id)
+            )));
+        }
+        self.last = Some(id);
+        self.count += 1;
+        Ok(Some(id))
+    }
+}
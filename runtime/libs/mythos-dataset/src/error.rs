@@ -0,0 +1,94 @@
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum Error {
+    #[error("Invalid structure: {0}")]
+    InvalidStructure(String),
+
+    #[error("Hash must be SHA-256 with 32 bytes: {0}")]
+    InvalidHash(String),
+
+    #[error("Unknown Expr op {0}")]
+    UnknownOp(u64),
+
+    #[error("Invalid Expr: {0}")]
+    InvalidExpr(String),
+
+    #[error("Invalid Operand: {0}")]
+    InvalidOperand(String),
+
+    #[error("Query syntax error at byte {offset}: {message}")]
+    QuerySyntax { offset: usize, message: String },
+
+    #[error("Unknown FieldPath root {0}")]
+    UnknownPathRoot(u64),
+
+    #[error("Unknown sampling mode {0}")]
+    UnknownSamplingMode(u64),
+
+    #[error("Invalid sampling: {0}")]
+    InvalidSampling(String),
+
+    #[error("Invalid corpus: {0}")]
+    InvalidCorpus(String),
+
+    #[error("EpisodeRef not found: {0}")]
+    EpisodeNotFound(String),
+
+    #[error("Receipt not found: {0}")]
+    ReceiptNotFound(String),
+
+    #[error("No episodes selected; a manifest cannot be empty")]
+    EmptyDataset,
+
+    #[error("{0} EpisodeIDs are in both manifests")]
+    Overlap(u64),
+
+    #[error("Export failed: {0}")]
+    Export(String),
+
+    #[error("Store error: {0}")]
+    Store(String),
+
+    #[error("{name} mismatch: stored {stored}, computed {computed}")]
+    IdMismatch {
+        name: &'static str,
+        stored: String,
+        computed: String,
+    },
+}
+
+/// Struct decoding errors keep their variant; anything else the codec
+/// reports is a malformed structure
+impl From<mythos_can::Error> for Error {
+    fn from(e: mythos_can::Error) -> Self {
+        match e {
+            mythos_can::Error::InvalidStructure(reason) => Error::InvalidStructure(reason),
+            mythos_can::Error::InvalidHash(reason) => Error::InvalidHash(reason),
+            other => Error::InvalidStructure(other.to_string()),
+        }
+    }
+}
+
+/// Struct decoding errors from episode types keep their variant; other
+/// episode errors come from the store
+impl From<mythos_episode::Error> for Error {
+    fn from(e: mythos_episode::Error) -> Self {
+        match e {
+            mythos_episode::Error::InvalidStructure(reason) => Error::InvalidStructure(reason),
+            mythos_episode::Error::InvalidHash(reason) => Error::InvalidHash(reason),
+            mythos_episode::Error::IdMismatch {
+                name,
+                stored,
+                computed,
+            } => Error::IdMismatch {
+                name,
+                stored,
+                computed,
+            },
+            other => Error::Store(other.to_string()),
+        }
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
@@ -0,0 +1,250 @@
+//! Predicate evaluation (RFC-0003 §4.4)
+//!
+//! An Expr evaluates over one episode: its EpisodeRef, the Signals
+//! attached to it and the Receipts in its trace, all as decoded
+//! MYTHOS-CAN values. FieldPath segments index field-numbered MAPs; any
+//! absent segment, or a segment that lands on a non-MAP, reads as missing.
+//!
+//! Comparison rules:
+//! - against missing, every comparison and IN_SET is FALSE except NE,
+//!   which is TRUE;
+//! - integers compare numerically across UVARINT and IVARINT;
+//! - a Hash literal matches a Hash struct by its bytes (EQ/NE only);
+//! - a value of another type never equals the literal and is not ordered
+//!   against it.
+//!
+//! A Signal path reads the field from every attached Signal and holds if
+//! any Signal satisfies the comparison; with no Signal carrying the field
+//! it is missing.
+
+use crate::error::{Error, Result};
+use crate::expr::{Expr, ExprOp, FieldPath, Operand, PathRoot};
+use mythos_can::structs::field;
+use mythos_can::Value;
+use std::cmp::Ordering;
+
+// Receipt fields read by HAS_TOOL / HAS_STATUS (RFC-0001 A.9)
+const RECEIPT_TOOL_ID: u64 = 2;
+const RECEIPT_STATUS: u64 = 8;
+
+/// The data one predicate evaluation sees
+#[derive(Debug, Clone, Copy)]
+pub struct EvalContext<'a> {
+    /// EpisodeRef
+    pub episode: &'a Value,
+    /// Signals whose episode_id is this episode
+    pub signals: &'a [Value],
+    /// Receipts in the episode trace
+    pub receipts: &'a [Value],
+}
+
+/// Which parts of an episode a predicate or key path reads
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Reads {
+    pub episode: bool,
+    pub signals: bool,
+    pub receipts: bool,
+}
+
+impl Reads {
+    pub(crate) fn union(self, other: Reads) -> Reads {
+        Reads {
+            episode: self.episode || other.episode,
+            signals: self.signals || other.signals,
+            receipts: self.receipts || other.receipts,
+        }
+    }
+}
+
+impl<'a> EvalContext<'a> {
+    /// Context for an episode with no Signals or Receipts loaded
+    pub fn episode(episode: &'a Value) -> Self {
+        EvalContext {
+            episode,
+            signals: &[],
+            receipts: &[],
+        }
+    }
+}
+
+impl FieldPath {
+    /// Follow the segments from `value`; `None` is the canonical missing
+    pub fn resolve<'v>(&self, value: &'v Value) -> Option<&'v Value> {
+        self.segments.iter().try_fold(value, |current, segment| {
+            field(map_fields(current)?, *segment as u64)
+        })
+    }
+
+    pub fn reads(&self) -> Reads {
+        match self.root {
+            PathRoot::Episode => Reads {
+                episode: true,
+                ..Reads::default()
+            },
+            PathRoot::Signal => Reads {
+                signals: true,
+                ..Reads::default()
+            },
+        }
+    }
+
+    /// Every value the path reads in `context`
+    fn read<'v>(&self, context: &EvalContext<'v>) -> Vec<&'v Value> {
+        match self.root {
+            PathRoot::Episode => self.resolve(context.episode).into_iter().collect(),
+            PathRoot::Signal => context
+                .signals
+                .iter()
+                .filter_map(|signal| self.resolve(signal))
+                .collect(),
+        }
+    }
+}
+
+impl Expr {
+    /// Evaluate against one episode
+    ///
+    /// The Expr should have passed `validate`; a malformed node fails with
+    /// `Error::InvalidExpr`.
+    pub fn evaluate(&self, context: &EvalContext) -> Result<bool> {
+        match self.op {
+            ExprOp::True => Ok(true),
+            ExprOp::False => Ok(false),
+            ExprOp::And => {
+                for arg in self.args()? {
+                    if !arg.evaluate(context)? {
+                        return Ok(false);
+                    }
+                }
+                Ok(true)
+            }
+            ExprOp::Or => {
+                for arg in self.args()? {
+                    if arg.evaluate(context)? {
+                        return Ok(true);
+                    }
+                }
+                Ok(false)
+            }
+            ExprOp::Not => match self.args()? {
+                [arg] => Ok(!arg.evaluate(context)?),
+                _ => Err(self.malformed()),
+            },
+            op if op.is_comparison() => {
+                let (path, lit) = match (&self.path, &self.lit) {
+                    // Hashes only compare for equality
+                    (_, Some(Operand::Hash(_))) if !matches!(op, ExprOp::Eq | ExprOp::Ne) => {
+                        return Err(self.malformed())
+                    }
+                    (Some(path), Some(lit)) => (path, lit),
+                    _ => return Err(self.malformed()),
+                };
+                let values = path.read(context);
+                if values.is_empty() {
+                    return Ok(op == ExprOp::Ne);
+                }
+                Ok(values.into_iter().any(|v| compare(op, v, lit)))
+            }
+            ExprOp::InSet => {
+                let (path, set) = match (&self.path, &self.set) {
+                    (Some(path), Some(set)) => (path, set),
+                    _ => return Err(self.malformed()),
+                };
+                Ok(path
+                    .read(context)
+                    .into_iter()
+                    .any(|v| set.iter().any(|lit| compare(ExprOp::Eq, v, lit))))
+            }
+            ExprOp::HasTool => match &self.lit {
+                Some(lit @ Operand::Hash(_)) => Ok(context.receipts.iter().any(|receipt| {
+                    receipt_field(receipt, RECEIPT_TOOL_ID)
+                        .is_some_and(|tool| compare(ExprOp::Eq, tool, lit))
+                })),
+                _ => Err(self.malformed()),
+            },
+            ExprOp::HasStatus => match &self.lit {
+                Some(lit @ Operand::U64(_)) => Ok(context.receipts.iter().any(|receipt| {
+                    receipt_field(receipt, RECEIPT_STATUS)
+                        .is_some_and(|status| compare(ExprOp::Eq, status, lit))
+                })),
+                _ => Err(self.malformed()),
+            },
+            _ => Err(self.malformed()),
+        }
+    }
+
+    /// What evaluation needs loaded; constant predicates read nothing
+    pub fn reads(&self) -> Reads {
+        let own = match (self.op, &self.path) {
+            (ExprOp::HasTool | ExprOp::HasStatus, _) => Reads {
+                receipts: true,
+                ..Reads::default()
+            },
+            (_, Some(path)) => path.reads(),
+            _ => Reads::default(),
+        };
+        self.args
+            .iter()
+            .flatten()
+            .fold(own, |reads, arg| reads.union(arg.reads()))
+    }
+
+    fn args(&self) -> Result<&[Expr]> {
+        self.args.as_deref().ok_or_else(|| self.malformed())
+    }
+
+    fn malformed(&self) -> Error {
+        Error::InvalidExpr(format!("malformed {:?} node", self.op))
+    }
+}
+
+fn map_fields(value: &Value) -> Option<&[(Value, Value)]> {
+    match value {
+        Value::Map(pairs) => Some(pairs),
+        _ => None,
+    }
+}
+
+fn receipt_field(receipt: &Value, n: u64) -> Option<&Value> {
+    field(map_fields(receipt)?, n)
+}
+
+/// `value <op> lit` for a present value
+fn compare(op: ExprOp, value: &Value, lit: &Operand) -> bool {
+    match order(value, lit) {
+        Some(ordering) => match op {
+            ExprOp::Eq => ordering == Ordering::Equal,
+            ExprOp::Ne => ordering != Ordering::Equal,
+            ExprOp::Lt => ordering == Ordering::Less,
+            ExprOp::Le => ordering != Ordering::Greater,
+            ExprOp::Gt => ordering == Ordering::Greater,
+            ExprOp::Ge => ordering != Ordering::Less,
+            _ => false,
+        },
+        // Different types: unequal and unordered
+        None => op == ExprOp::Ne,
+    }
+}
+
+fn order(value: &Value, lit: &Operand) -> Option<Ordering> {
+    let int = |v: &Value| match v {
+        Value::UVarint(n) => Some(*n as i128),
+        Value::IVarint(n) => Some(*n as i128),
+        _ => None,
+    };
+
+    match lit {
+        Operand::U64(n) => Some(int(value)?.cmp(&(*n as i128))),
+        Operand::I64(n) => Some(int(value)?.cmp(&(*n as i128))),
+        // Only reached for Eq and Ne
+        Operand::Hash(bytes) => {
+            let fields = map_fields(value)?;
+            match (field(fields, 1), field(fields, 2)) {
+                (Some(Value::UVarint(1)), Some(Value::Bytes(b))) if fields.len() == 2 => {
+                    Some(b.as_slice().cmp(bytes.as_slice()))
+                }
+                _ => None,
+            }
+        }
+    }
+}
@@ -0,0 +1,278 @@
+//! Dataset export to JSON Lines
+//!
+//! `export_jsonl` walks a DatasetRef manifest in order and writes one JSON
+//! object per line, using the MYTHOS-CAN JSON mapping in `json`. The
+//! first line is a header naming the dataset:
+//!
+//! ```text
+//! {"format":"mythos.dataset.jsonl","dataset_def_id":"…","manifest":"…",
+//!  "receipt_id":"…","count":N,"fields":null}
+//! ```
+//!
+//! and each following line is one episode:
+//!
+//! ```text
+//! {"episode_id":"…","episode":{…},"trace":{…},"signals":[{…},…]}
+//! ```
+//!
+//! `trace` is the trace blob read from the store, verified and decoded:
+//! `{"media":…,"value":…}` if its bytes are canonical MYTHOS-CAN, else
+//! `{"media":…,"bytes":"<hex>"}`. Encrypted traces are not exported.
+//!
+//! With `ExportOptions::fields` set, `episode` and each Signal keep only
+//! the listed paths (nesting kept), `episode` or `signals` is left out
+//! when no path has its root, and the header lists the paths.
+
+use crate::build::{EpisodeSource, ListIds};
+use crate::def::DatasetRef;
+use crate::error::{Error, Result};
+use crate::expr::{FieldPath, PathRoot};
+use crate::json::{write_json, write_string};
+use crate::query::{format_field_path, Schema};
+use mythos_blob::{decode_chunk_node, read_range, BlobRef, DecodingReader};
+use mythos_can::Value;
+use mythos_cas::{BlobStore, StoreSource};
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::io::{Read, Write};
+
+/// `format` of the header line
+pub const EXPORT_FORMAT: &str = "mythos.dataset.jsonl";
+
+// EpisodeRef.trace_ref.trace_blob (RFC-0001 A.15)
+const TRACE_BLOB_PATH: [u32; 2] = [2, 1];
+
+/// What `export_jsonl` writes for each episode
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ExportOptions {
+    /// Only these paths; `None` exports whole EpisodeRefs and Signals
+    pub fields: Option<Vec<FieldPath>>,
+    /// Leave out `trace` rather than reading trace blobs
+    pub skip_traces: bool,
+}
+
+/// Write the header and one line per manifest entry; returns the number
+/// of episodes written
+///
+/// Fails with `Error::EpisodeNotFound` if `episodes` lacks a listed
+/// episode and `Error::InvalidCorpus` if the manifest does not hold
+/// `dataset.count` IDs.
+pub fn export_jsonl<E, S, W>(
+    dataset: &DatasetRef,
+    episodes: &E,
+    store: &S,
+    options: &ExportOptions,
+    out: &mut W,
+) -> Result<u64>
+where
+    E: EpisodeSource + ?Sized,
+    S: BlobStore + ?Sized,
+    W: Write + ?Sized,
+{
+    dataset.validate()?;
+    for path in options.fields.iter().flatten() {
+        path.validate()?;
+    }
+
+    write_line(out, &header(dataset, options))?;
+
+    let mut written = 0;
+    for id in ListIds::new(store, &dataset.manifest_root()?) {
+        let id = id?;
+        let episode = episodes
+            .episode(&id)?
+            .ok_or_else(|| Error::EpisodeNotFound(hex::encode(id)))?;
+
+        let mut line = String::from("{\"episode_id\":");
+        write_string(&mut line, &hex::encode(id));
+
+        if let Some(paths) = selected(options, PathRoot::Episode) {
+            line.push_str(",\"episode\":");
+            write_json(&mut line, &project(&episode, &paths))?;
+        }
+        if !options.skip_traces {
+            line.push_str(",\"trace\":");
+            write_trace(&mut line, store, &episode)?;
+        }
+        if let Some(paths) = selected(options, PathRoot::Signal) {
+            let signals = episodes.signals(&id)?;
+            line.push_str(",\"signals\":");
+            write_json(
+                &mut line,
+                &Value::List(signals.iter().map(|s| project(s, &paths)).collect()),
+            )?;
+        }
+        line.push('}');
+
+        write_line(out, &line)?;
+        written += 1;
+    }
+
+    if written != dataset.count {
+        return Err(Error::InvalidCorpus(format!(
+            "manifest lists {} IDs, DatasetRef count is {}",
+            written, dataset.count
+        )));
+    }
+    Ok(written)
+}
+
+fn header(dataset: &DatasetRef, options: &ExportOptions) -> String {
+    let mut line = String::from("{\"format\":");
+    write_string(&mut line, EXPORT_FORMAT);
+    line.push_str(",\"dataset_def_id\":");
+    write_string(&mut line, &hex::encode(dataset.dataset_def_id));
+    line.push_str(",\"manifest\":");
+    write_string(&mut line, &hex::encode(&dataset.manifest.cid));
+    line.push_str(",\"receipt_id\":");
+    write_string(&mut line, &hex::encode(dataset.receipt_id));
+    let _ = write!(line, ",\"count\":{},\"fields\":", dataset.count);
+    match &options.fields {
+        None => line.push_str("null"),
+        Some(paths) => {
+            line.push('[');
+            for (i, path) in paths.iter().enumerate() {
+                if i > 0 {
+                    line.push(',');
+                }
+                write_string(&mut line, &format_field_path(path, &Schema::empty()));
+            }
+            line.push(']');
+        }
+    }
+    line.push('}');
+    line
+}
+
+fn write_line<W: Write + ?Sized>(out: &mut W, line: &str) -> Result<()> {
+    out.write_all(line.as_bytes())
+        .and_then(|_| out.write_all(b"\n"))
+        .map_err(|e| Error::Export(e.to_string()))
+}
+
+/// The selected paths under `root`: `None` if there are none, and empty,
+/// meaning "everything", when there is no projection
+fn selected(options: &ExportOptions, root: PathRoot) -> Option<Vec<&FieldPath>> {
+    match &options.fields {
+        None => Some(Vec::new()),
+        Some(paths) => {
+            let paths: Vec<&FieldPath> = paths.iter().filter(|path| path.root == root).collect();
+            (!paths.is_empty()).then_some(paths)
+        }
+    }
+}
+
+/// Selected subtrees of a struct
+enum Projected {
+    Whole(Value),
+    Fields(BTreeMap<u32, Projected>),
+}
+
+/// Keep only `paths` of `value`, or all of it when `paths` is empty
+///
+/// A path that is absent, or that passes through a non-MAP, selects
+/// nothing; a path under another selected path adds nothing.
+fn project(value: &Value, paths: &[&FieldPath]) -> Value {
+    if paths.is_empty() {
+        return value.clone();
+    }
+
+    let mut fields = BTreeMap::new();
+    for path in paths {
+        if let Some(selected) = path.resolve(value) {
+            insert(&mut fields, &path.segments, selected);
+        }
+    }
+    to_value(Projected::Fields(fields))
+}
+
+fn insert(fields: &mut BTreeMap<u32, Projected>, segments: &[u32], value: &Value) {
+    let Some((first, rest)) = segments.split_first() else {
+        return;
+    };
+    if rest.is_empty() {
+        fields.insert(*first, Projected::Whole(value.clone()));
+        return;
+    }
+    match fields
+        .entry(*first)
+        .or_insert_with(|| Projected::Fields(BTreeMap::new()))
+    {
+        Projected::Fields(inner) => insert(inner, rest, value),
+        Projected::Whole(_) => {}
+    }
+}
+
+fn to_value(projected: Projected) -> Value {
+    match projected {
+        Projected::Whole(value) => value,
+        Projected::Fields(fields) => {
+            let mut pairs: Vec<(Value, Value)> = fields
+                .into_iter()
+                .map(|(k, v)| (Value::UVarint(k as u64), to_value(v)))
+                .collect();
+            // Canonical order is by encoded key bytes, not numeric order
+            pairs.sort_by_key(|(k, _)| mythos_can::encode_value(k).unwrap_or_default());
+            Value::Map(pairs)
+        }
+    }
+}
+
+fn write_trace<S: BlobStore + ?Sized>(out: &mut String, store: &S, episode: &Value) -> Result<()> {
+    let blob = FieldPath::episode(&TRACE_BLOB_PATH)
+        .resolve(episode)
+        .ok_or_else(|| Error::InvalidStructure("EpisodeRef has no trace_blob".into()))?;
+    let blob = BlobRef::from_value(blob)
+        .map_err(|e| Error::InvalidStructure(format!("trace_blob: {}", e)))?;
+    let bytes = read_blob(store, &blob)?;
+
+    out.push_str("{\"media\":");
+    write_string(out, &blob.media);
+    match mythos_can::decode_value_exact(&bytes) {
+        Ok(value) => {
+            out.push_str(",\"value\":");
+            write_json(out, &value)?;
+        }
+        Err(_) => {
+            out.push_str(",\"bytes\":");
+            write_string(out, &hex::encode(&bytes));
+        }
+    }
+    out.push('}');
+    Ok(())
+}
+
+/// Verified, decoded content of an unencrypted blob
+fn read_blob<S: BlobStore + ?Sized>(store: &S, blob: &BlobRef) -> Result<Vec<u8>> {
+    let cid_hex = hex::encode(&blob.cid);
+    let trace_error = |e: String| Error::Export(format!("trace blob {}: {}", cid_hex, e));
+    if blob.encryption.is_some() {
+        return Err(trace_error("encrypted".into()));
+    }
+    let root: [u8; 32] =
+        blob.cid.as_slice().try_into().map_err(|_| {
+            Error::InvalidHash(format!("trace blob cid has {} bytes", blob.cid.len()))
+        })?;
+
+    // A zero chunk count also stands for counts above u32::MAX, so the root
+    // object decides: a ChunkedBlob node, or the whole encoded content
+    let source = StoreSource(store);
+    let bytes =
+        mythos_blob::fetch_verified(&source, &root).map_err(|e| trace_error(e.to_string()))?;
+    let encoded = match decode_chunk_node(&bytes) {
+        Ok(node) => {
+            read_range(&root, 0..node.total_size(), &source, &source)
+                .map_err(|e| trace_error(e.to_string()))?
+                .data
+        }
+        Err(e) if blob.chunks > 0 => return Err(trace_error(e.to_string())),
+        Err(_) => bytes,
+    };
+
+    let mut decoded = Vec::new();
+    DecodingReader::new(blob, &encoded[..])
+        .map_err(|e| trace_error(e.to_string()))?
+        .read_to_end(&mut decoded)
+        .map_err(|e| trace_error(e.to_string()))?;
+    Ok(decoded)
+}
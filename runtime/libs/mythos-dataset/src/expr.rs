@@ -0,0 +1,427 @@
+//! Expr, FieldPath and Operand (RFC-0003 §4.2–4.3)
+//!
+//! Typed predicate AST with canonical MYTHOS-CAN encode/decode. Optional
+//! list fields keep the absent/empty distinction so a decoded Expr
+//! re-encodes to the same bytes; `validate` enforces which fields each op
+//! takes.
+
+use crate::codec::{decode, encode};
+use crate::error::{Error, Result};
+use mythos_can::structs::{
+    field, hash_value, known_fields, optional_list, required_hash, required_uint, struct_fields,
+};
+use mythos_can::Value;
+
+/// Deepest Expr nesting accepted by `validate`
+pub const MAX_EXPR_DEPTH: usize = 64;
+
+/// Expr ops (RFC-0003 §4.2)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum ExprOp {
+    True = 1,
+    False = 2,
+    Eq = 10,
+    Ne = 11,
+    Lt = 12,
+    Le = 13,
+    Gt = 14,
+    Ge = 15,
+    And = 20,
+    Or = 21,
+    Not = 22,
+    InSet = 30,
+    HasTool = 40,
+    HasStatus = 41,
+}
+
+impl ExprOp {
+    pub fn from_u64(op: u64) -> Result<Self> {
+        Ok(match op {
+            1 => ExprOp::True,
+            2 => ExprOp::False,
+            10 => ExprOp::Eq,
+            11 => ExprOp::Ne,
+            12 => ExprOp::Lt,
+            13 => ExprOp::Le,
+            14 => ExprOp::Gt,
+            15 => ExprOp::Ge,
+            20 => ExprOp::And,
+            21 => ExprOp::Or,
+            22 => ExprOp::Not,
+            30 => ExprOp::InSet,
+            40 => ExprOp::HasTool,
+            41 => ExprOp::HasStatus,
+            other => return Err(Error::UnknownOp(other)),
+        })
+    }
+
+    /// EQ, NE, LT, LE, GT or GE
+    pub fn is_comparison(self) -> bool {
+        matches!(
+            self,
+            ExprOp::Eq | ExprOp::Ne | ExprOp::Lt | ExprOp::Le | ExprOp::Gt | ExprOp::Ge
+        )
+    }
+}
+
+/// FieldPath roots (RFC-0003 §4.3)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum PathRoot {
+    Episode = 1,
+    Signal = 2,
+}
+
+/// Field numbers to follow from an EpisodeRef or Signal
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FieldPath {
+    pub root: PathRoot,     // Field 1
+    pub segments: Vec<u32>, // Field 2 - field numbers, outermost first
+}
+
+/// Literal operand (RFC-0001 A.14 kinds usable outside IR)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Operand {
+    U64(u64),
+    I64(i64),
+    Hash([u8; 32]),
+}
+
+// Operand kinds (RFC-0001 A.14)
+const OPERAND_U64: u64 = 4;
+const OPERAND_I64: u64 = 5;
+const OPERAND_HASH: u64 = 6;
+
+/// Predicate expression node
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expr {
+    pub op: ExprOp,                // Field 1
+    pub args: Option<Vec<Expr>>,   // Field 2 - optional
+    pub path: Option<FieldPath>,   // Field 3 - optional
+    pub lit: Option<Operand>,      // Field 4 - optional
+    pub set: Option<Vec<Operand>>, // Field 5 - optional
+}
+
+impl FieldPath {
+    pub fn episode(segments: &[u32]) -> Self {
+        FieldPath {
+            root: PathRoot::Episode,
+            segments: segments.to_vec(),
+        }
+    }
+
+    pub fn signal(segments: &[u32]) -> Self {
+        FieldPath {
+            root: PathRoot::Signal,
+            segments: segments.to_vec(),
+        }
+    }
+
+    /// Segments are non-zero field numbers
+    pub fn validate(&self) -> Result<()> {
+        if self.segments.is_empty() {
+            return Err(Error::InvalidExpr("FieldPath has no segments".into()));
+        }
+        if self.segments.contains(&0) {
+            return Err(Error::InvalidExpr("FieldPath segment 0".into()));
+        }
+        Ok(())
+    }
+
+    pub fn to_value(&self) -> Value {
+        Value::Map(vec![
+            (Value::UVarint(1), Value::UVarint(self.root as u64)),
+            (
+                Value::UVarint(2),
+                Value::List(
+                    self.segments
+                        .iter()
+                        .map(|s| Value::UVarint(*s as u64))
+                        .collect(),
+                ),
+            ),
+        ])
+    }
+
+    pub fn from_value(value: &Value) -> Result<Self> {
+        let fields = struct_fields(value, "FieldPath")?;
+        known_fields(fields, 2, "FieldPath")?;
+
+        let root = match required_uint(field(fields, 1), "FieldPath.root", u8::MAX as u64)? {
+            1 => PathRoot::Episode,
+            2 => PathRoot::Signal,
+            other => return Err(Error::UnknownPathRoot(other)),
+        };
+        let segments = match field(fields, 2) {
+            Some(Value::List(items)) => items
+                .iter()
+                .map(|s| required_uint(Some(s), "FieldPath segment", u32::MAX as u64))
+                .map(|s| s.map(|s| s as u32))
+                .collect::<mythos_can::Result<Vec<_>>>()?,
+            _ => return Err(Error::InvalidStructure("Missing FieldPath.segments".into())),
+        };
+
+        let path = FieldPath { root, segments };
+        path.validate()?;
+        Ok(path)
+    }
+}
+
+impl Operand {
+    pub fn to_value(&self) -> Value {
+        let (kind, payload) = match self {
+            Operand::U64(n) => (OPERAND_U64, (2, Value::UVarint(*n))),
+            Operand::I64(n) => (OPERAND_I64, (3, Value::IVarint(*n))),
+            Operand::Hash(h) => (OPERAND_HASH, (4, hash_value(h))),
+        };
+        Value::Map(vec![
+            (Value::UVarint(1), Value::UVarint(kind)),
+            (Value::UVarint(payload.0), payload.1),
+        ])
+    }
+
+    pub fn from_value(value: &Value) -> Result<Self> {
+        let fields = struct_fields(value, "Operand")?;
+        known_fields(fields, 6, "Operand")?;
+        if fields.len() != 2 {
+            return Err(Error::InvalidOperand(
+                "Operand must hold exactly one value".into(),
+            ));
+        }
+
+        match required_uint(field(fields, 1), "Operand.kind", u8::MAX as u64)? {
+            OPERAND_U64 => match field(fields, 2) {
+                Some(Value::UVarint(n)) => Ok(Operand::U64(*n)),
+                _ => Err(Error::InvalidOperand("U64 operand needs field 2".into())),
+            },
+            OPERAND_I64 => match field(fields, 3) {
+                Some(Value::IVarint(n)) => Ok(Operand::I64(*n)),
+                _ => Err(Error::InvalidOperand("I64 operand needs field 3".into())),
+            },
+            OPERAND_HASH => Ok(Operand::Hash(required_hash(
+                field(fields, 4),
+                "Operand.hash",
+            )?)),
+            kind @ 1..=3 => Err(Error::InvalidOperand(format!(
+                "IR operand kind {} is not a literal",
+                kind
+            ))),
+            kind => Err(Error::InvalidOperand(format!("unknown kind {}", kind))),
+        }
+    }
+}
+
+impl Expr {
+    fn bare(op: ExprOp) -> Self {
+        Expr {
+            op,
+            args: None,
+            path: None,
+            lit: None,
+            set: None,
+        }
+    }
+
+    /// TRUE or FALSE
+    pub fn constant(value: bool) -> Self {
+        Expr::bare(if value { ExprOp::True } else { ExprOp::False })
+    }
+
+    /// `path <op> lit` for a comparison op
+    pub fn compare(op: ExprOp, path: FieldPath, lit: Operand) -> Self {
+        Expr {
+            path: Some(path),
+            lit: Some(lit),
+            ..Expr::bare(op)
+        }
+    }
+
+    pub fn eq(path: FieldPath, lit: Operand) -> Self {
+        Expr::compare(ExprOp::Eq, path, lit)
+    }
+
+    pub fn and(args: Vec<Expr>) -> Self {
+        Expr {
+            args: Some(args),
+            ..Expr::bare(ExprOp::And)
+        }
+    }
+
+    pub fn or(args: Vec<Expr>) -> Self {
+        Expr {
+            args: Some(args),
+            ..Expr::bare(ExprOp::Or)
+        }
+    }
+
+    pub fn negate(arg: Expr) -> Self {
+        Expr {
+            args: Some(vec![arg]),
+            ..Expr::bare(ExprOp::Not)
+        }
+    }
+
+    pub fn in_set(path: FieldPath, set: Vec<Operand>) -> Self {
+        Expr {
+            path: Some(path),
+            set: Some(set),
+            ..Expr::bare(ExprOp::InSet)
+        }
+    }
+
+    pub fn has_tool(tool_id: [u8; 32]) -> Self {
+        Expr {
+            lit: Some(Operand::Hash(tool_id)),
+            ..Expr::bare(ExprOp::HasTool)
+        }
+    }
+
+    pub fn has_status(status: u16) -> Self {
+        Expr {
+            lit: Some(Operand::U64(status as u64)),
+            ..Expr::bare(ExprOp::HasStatus)
+        }
+    }
+
+    /// Check each node carries exactly the fields its op takes
+    pub fn validate(&self) -> Result<()> {
+        self.validate_at(1)
+    }
+
+    fn validate_at(&self, depth: usize) -> Result<()> {
+        if depth > MAX_EXPR_DEPTH {
+            return Err(Error::InvalidExpr(format!(
+                "nesting deeper than {}",
+                MAX_EXPR_DEPTH
+            )));
+        }
+
+        let (args, path, lit, set) = (
+            self.args.is_some(),
+            self.path.is_some(),
+            self.lit.is_some(),
+            self.set.is_some(),
+        );
+        let shape_ok = match self.op {
+            ExprOp::True | ExprOp::False => !args && !path && !lit && !set,
+            op if op.is_comparison() => !args && path && lit && !set,
+            ExprOp::And | ExprOp::Or | ExprOp::Not => args && !path && !lit && !set,
+            ExprOp::InSet => !args && path && !lit && set,
+            _ => !args && !path && lit && !set,
+        };
+        if !shape_ok {
+            return Err(Error::InvalidExpr(format!(
+                "{:?} with args={} path={} lit={} set={}",
+                self.op, args, path, lit, set
+            )));
+        }
+
+        match (self.op, &self.args, &self.lit) {
+            (ExprOp::And | ExprOp::Or, Some(args), _) if args.is_empty() => {
+                return Err(Error::InvalidExpr(format!("{:?} needs arguments", self.op)))
+            }
+            (ExprOp::Not, Some(args), _) if args.len() != 1 => {
+                return Err(Error::InvalidExpr("Not takes one argument".into()))
+            }
+            (ExprOp::HasTool, _, Some(lit)) if !matches!(lit, Operand::Hash(_)) => {
+                return Err(Error::InvalidExpr("HasTool takes a tool_id Hash".into()))
+            }
+            (ExprOp::HasStatus, _, Some(lit)) if !matches!(lit, Operand::U64(0..=0xffff)) => {
+                return Err(Error::InvalidExpr("HasStatus takes a u16 status".into()))
+            }
+            // Hashes only compare for equality
+            (ExprOp::Lt | ExprOp::Le | ExprOp::Gt | ExprOp::Ge, _, Some(Operand::Hash(_))) => {
+                return Err(Error::InvalidExpr(format!(
+                    "{:?} is not defined on Hash",
+                    self.op
+                )))
+            }
+            _ => {}
+        }
+
+        if let Some(path) = &self.path {
+            path.validate()?;
+        }
+        for arg in self.args.iter().flatten() {
+            arg.validate_at(depth + 1)?;
+        }
+        Ok(())
+    }
+
+    pub fn to_value(&self) -> Value {
+        let mut fields = vec![(Value::UVarint(1), Value::UVarint(self.op as u64))];
+        if let Some(args) = &self.args {
+            fields.push((
+                Value::UVarint(2),
+                Value::List(args.iter().map(Expr::to_value).collect()),
+            ));
+        }
+        if let Some(path) = &self.path {
+            fields.push((Value::UVarint(3), path.to_value()));
+        }
+        if let Some(lit) = &self.lit {
+            fields.push((Value::UVarint(4), lit.to_value()));
+        }
+        if let Some(set) = &self.set {
+            fields.push((
+                Value::UVarint(5),
+                Value::List(set.iter().map(Operand::to_value).collect()),
+            ));
+        }
+        Value::Map(fields)
+    }
+
+    pub fn from_value(value: &Value) -> Result<Self> {
+        let expr = Expr::parse(value, 1)?;
+        expr.validate()?;
+        Ok(expr)
+    }
+
+    fn parse(value: &Value, depth: usize) -> Result<Self> {
+        if depth > MAX_EXPR_DEPTH {
+            return Err(Error::InvalidExpr(format!(
+                "nesting deeper than {}",
+                MAX_EXPR_DEPTH
+            )));
+        }
+        let fields = struct_fields(value, "Expr")?;
+        known_fields(fields, 5, "Expr")?;
+
+        let op = ExprOp::from_u64(required_uint(field(fields, 1), "Expr.op", u64::MAX)?)?;
+        let args = optional_list(field(fields, 2), "Expr.args")?
+            .map(|items| {
+                items
+                    .iter()
+                    .map(|item| Expr::parse(item, depth + 1))
+                    .collect::<Result<Vec<_>>>()
+            })
+            .transpose()?;
+        let path = field(fields, 3).map(FieldPath::from_value).transpose()?;
+        let lit = field(fields, 4).map(Operand::from_value).transpose()?;
+        let set = optional_list(field(fields, 5), "Expr.set")?
+            .map(|items| {
+                items
+                    .iter()
+                    .map(Operand::from_value)
+                    .collect::<Result<Vec<_>>>()
+            })
+            .transpose()?;
+
+        Ok(Expr {
+            op,
+            args,
+            path,
+            lit,
+            set,
+        })
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        encode(&self.to_value())
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        Expr::from_value(&decode(bytes)?)
+    }
+}
@@ -0,0 +1,103 @@
+//! JSON mapping of MYTHOS-CAN values
+//!
+//! - NULL, BOOL → `null`, `true`, `false`
+//! - UVARINT, IVARINT → number, exact to 64 bits
+//! - BYTES → string of lowercase hex
+//! - TEXT → string
+//! - LIST → array
+//! - MAP → object in canonical key order; UVARINT and IVARINT keys become
+//!   their decimal string, TEXT keys themselves and BYTES keys hex
+//!
+//! A MAP key of another type, or two keys with the same JSON string, has
+//! no JSON form and fails with `Error::InvalidStructure`.
+
+use crate::error::{Error, Result};
+use mythos_can::Value;
+use std::collections::BTreeSet;
+use std::fmt::Write;
+
+/// JSON text for a value, without whitespace
+pub fn to_json(value: &Value) -> Result<String> {
+    let mut out = String::new();
+    write_json(&mut out, value)?;
+    Ok(out)
+}
+
+/// Append the JSON text for `value` to `out`
+pub fn write_json(out: &mut String, value: &Value) -> Result<()> {
+    match value {
+        Value::Null => out.push_str("null"),
+        Value::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+        Value::UVarint(n) => {
+            let _ = write!(out, "{}", n);
+        }
+        Value::IVarint(n) => {
+            let _ = write!(out, "{}", n);
+        }
+        Value::Bytes(bytes) => write_string(out, &hex::encode(bytes)),
+        Value::Text(text) => write_string(out, text),
+        Value::List(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_json(out, item)?;
+            }
+            out.push(']');
+        }
+        Value::Map(pairs) => {
+            let mut seen = BTreeSet::new();
+            out.push('{');
+            for (i, (key, item)) in pairs.iter().enumerate() {
+                let key = json_key(key)?;
+                if !seen.insert(key.clone()) {
+                    return Err(Error::InvalidStructure(format!(
+                        "MAP keys collide as JSON key {:?}",
+                        key
+                    )));
+                }
+                if i > 0 {
+                    out.push(',');
+                }
+                write_string(out, &key);
+                out.push(':');
+                write_json(out, item)?;
+            }
+            out.push('}');
+        }
+    }
+    Ok(())
+}
+
+/// Append a JSON string literal
+pub(crate) fn write_string(out: &mut String, text: &str) {
+    out.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+fn json_key(key: &Value) -> Result<String> {
+    match key {
+        Value::UVarint(n) => Ok(n.to_string()),
+        Value::IVarint(n) => Ok(n.to_string()),
+        Value::Text(text) => Ok(text.clone()),
+        Value::Bytes(bytes) => Ok(hex::encode(bytes)),
+        other => Err(Error::InvalidStructure(format!(
+            "MAP key {:?} has no JSON form",
+            other
+        ))),
+    }
+}
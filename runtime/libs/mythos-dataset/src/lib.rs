@@ -1,6 +1,45 @@
 //! MYTHOS Dataset (RFC-0003)
 //!
-//! DatasetDef ID computation with field exclusion
+//! Typed DatasetDef, QueryDef, SamplingDef, StratifyDef and Expr with
+//! canonical encode/decode, plus DatasetDef ID computation with field
+//! exclusion over a raw `Value`. `Expr::evaluate` runs a predicate over
+//! one episode; `Sampler` and `StratifiedSampler` select from the
+//! matching EpisodeIDs. `build_dataset` runs the RFC-0003 §7 build
+//! against a CAS store and returns the DatasetRef. `compile_query` turns
+//! query text into an Expr; `diff_manifests` reports how two manifests
+//! overlap, and `export_jsonl` writes a dataset out as JSON Lines.
+//! `manifest_registry` lets mythos-cas GC, scrub and archive export follow
+//! a manifest to its episodes.
+
+mod build;
+mod codec;
+mod def;
+mod diff;
+mod error;
+mod eval;
+mod export;
+mod expr;
+mod json;
+mod manifest;
+mod query;
+mod sample;
+
+pub use build::{
+    build_code_hash, build_dataset, build_tool_id, select_episodes, BuildOptions, EpisodeSource,
+    ListIds, NoEpisodeData, StoreEpisodes, BUILD_TRANSFORM,
+};
+pub use def::{
+    DatasetDef, DatasetRef, QueryDef, SamplingDef, SamplingMode, StratifyDef, MANIFEST_MEDIA,
+};
+pub use diff::{diff_datasets, diff_manifests, DiffDetail, ManifestDiff};
+pub use error::{Error, Result};
+pub use eval::{EvalContext, Reads};
+pub use export::{export_jsonl, ExportOptions, EXPORT_FORMAT};
+pub use expr::{Expr, ExprOp, FieldPath, Operand, PathRoot, MAX_EXPR_DEPTH};
+pub use json::{to_json, write_json};
+pub use manifest::{manifest_registry, ManifestExtractor};
+pub use query::{compile_query, format_field_path, format_query, parse_field_path, Schema};
+pub use sample::{bucket_seed, score, EpisodeId, HashNSampler, Sampler, StratifiedSampler};
 
 use mythos_can::Value;
 use sha2::{Digest, Sha256};
@@ -15,7 +54,7 @@ pub fn cid_from_bytes(bytes: &[u8]) -> [u8; 32] {
 /// Compute DatasetDef ID from canonical bytes excluding field 1
 ///
 /// Similar to receipt_id: dataset_def_id = SHA-256(canonical_bytes(def_without_field_1))
-pub fn compute_dataset_def_id(def_map: &Value) -> Result<[u8; 32], String> {
+pub fn compute_dataset_def_id(def_map: &Value) -> std::result::Result<[u8; 32], String> {
     let fields = match def_map {
         Value::Map(pairs) => pairs,
         _ => return Err("DatasetDef must be MAP".into()),
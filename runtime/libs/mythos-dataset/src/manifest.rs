@@ -0,0 +1,98 @@
+//! Manifest references for CAS traversal
+//!
+//! A manifest MerkleList holds EpisodeIDs, which are not the CIDs of the
+//! stored EpisodeRefs, so the built-in MerkleList extractor treats them as
+//! optional and GC, scrub and archive export stop at the list. A
+//! `ManifestExtractor` resolves each EpisodeID through an `EpisodeIndex`
+//! to its EpisodeRef, attached Signals and indexed Receipts.
+//! `manifest_registry` routes `MANIFEST_MEDIA` to it, so a walk from a
+//! DatasetRef reaches every episode in the dataset.
+//!
+//! An EpisodeID the index does not know is kept as a required reference
+//! to itself, so a dataset with unindexed episodes is reported missing
+//! rather than silently cut short.
+
+use crate::def::MANIFEST_MEDIA;
+use mythos_can::Value;
+use mythos_cas::{Extractor, ObjectKind, Reference, Registry};
+use mythos_episode::EpisodeIndex;
+use mythos_merkle::{MerkleListNode, KIND_MERKLE_LIST_INTERNAL, KIND_MERKLE_LIST_LEAF};
+
+/// Follows manifest MerkleList nodes to the episodes they list
+#[derive(Debug, Clone)]
+pub struct ManifestExtractor {
+    index: EpisodeIndex,
+}
+
+impl ManifestExtractor {
+    pub fn new(index: EpisodeIndex) -> Self {
+        ManifestExtractor { index }
+    }
+
+    fn episode_refs(&self, episode_id: [u8; 32], refs: &mut Vec<Reference>) {
+        let Some(cid) = self.index.episode_cid(&episode_id) else {
+            refs.push(Reference::new(episode_id, ObjectKind::Unknown));
+            return;
+        };
+        refs.push(Reference::new(cid, ObjectKind::Unknown));
+        for cid in self.index.signal_cids(&episode_id) {
+            refs.push(Reference::new(cid, ObjectKind::Unknown));
+        }
+        // Receipts the index cannot resolve may be kept elsewhere
+        for receipt_id in self.index.receipt_ids(&episode_id) {
+            refs.push(match self.index.receipt_cid(receipt_id) {
+                Some(cid) => Reference::new(cid, ObjectKind::Unknown),
+                None => Reference::optional(*receipt_id, ObjectKind::Unknown),
+            });
+        }
+    }
+}
+
+impl Extractor for ManifestExtractor {
+    fn name(&self) -> &'static str {
+        "DatasetManifest"
+    }
+
+    fn matches(&self, value: &Value) -> bool {
+        mythos_merkle::parse_merkle_node(value)
+            .is_ok_and(|h| h.kind == KIND_MERKLE_LIST_LEAF || h.kind == KIND_MERKLE_LIST_INTERNAL)
+    }
+
+    fn references(&self, value: &Value) -> mythos_cas::Result<Vec<Reference>> {
+        let corrupt = |e: String| mythos_cas::Error::Corrupt(format!("Manifest node: {}", e));
+        let bytes = mythos_can::encode_value(value).map_err(|e| corrupt(e.to_string()))?;
+        let node =
+            mythos_merkle::decode_merkle_list_node(&bytes).map_err(|e| corrupt(e.to_string()))?;
+
+        let mut refs = Vec::new();
+        match node {
+            MerkleListNode::Internal(internal) => {
+                for child in &internal.children {
+                    let cid = sha256_cid(child).ok_or_else(|| corrupt("child hash".into()))?;
+                    refs.push(Reference::new(cid, ObjectKind::Unknown).with_media(MANIFEST_MEDIA));
+                }
+            }
+            MerkleListNode::Leaf(leaf) => {
+                for value in &leaf.values {
+                    let id = sha256_cid(value).ok_or_else(|| corrupt("EpisodeID hash".into()))?;
+                    self.episode_refs(id, &mut refs);
+                }
+            }
+        }
+        Ok(refs)
+    }
+}
+
+/// Built-in registry with manifests resolved through `index`
+pub fn manifest_registry(index: EpisodeIndex) -> Registry {
+    let mut registry = Registry::default();
+    registry.register_media(MANIFEST_MEDIA, ManifestExtractor::new(index));
+    registry
+}
+
+fn sha256_cid(hash: &mythos_merkle::HashValue) -> Option<[u8; 32]> {
+    match hash.alg {
+        1 => hash.bytes.as_slice().try_into().ok(),
+        _ => None,
+    }
+}
@@ -0,0 +1,618 @@
+//! Text query language for dataset predicates
+//!
+//! `compile_query` parses text such as
+//!
+//! ```text
+//! episode.time_observed >= 100 and has_tool(0x…) and signal.3 in {1, 2}
+//! ```
+//!
+//! into a validated Expr, and `format_query` prints an Expr back as text.
+//! Grammar, loosest binding first:
+//!
+//! ```text
+//! or      := and ("or" and)*
+//! and     := unary ("and" unary)*
+//! unary   := "not" unary | primary
+//! primary := "true" | "false" | "(" or ")"
+//!          | "has_tool" "(" hash ")" | "has_status" "(" int ")"
+//!          | path ("==" | "!=" | "<" | "<=" | ">" | ">=") literal
+//!          | path "in" "{" [literal ("," literal)*] "}"
+//! path    := ("episode" | "signal") ("." (name | int))+
+//! literal := int | "-" int | hash      hash := "0x" 64 hex digits
+//! ```
+//!
+//! Field names resolve to field numbers through a `Schema`. Keywords are
+//! case-insensitive; field names are not. `parse_field_path` reads a lone
+//! `path`, as used by export projections.
+//!
+//! Compiled Exprs are in one normal form, so equivalent text compiles to
+//! byte-identical Exprs and the QueryDef id does not depend on spelling:
+//! - a name and its field number give the same segment;
+//! - a chain of `and` (or `or`) is one node whatever the parentheses, so
+//!   `a and (b and c)` is `AND[a, b, c]`; operands keep their order;
+//! - integers of 0 and up are U64, negative ones I64, so `-0` is `0`;
+//! - hex digits may be either case;
+//! - IN_SET members are sorted and deduplicated.
+//!
+//! `format_query` prints names where the schema has them and parenthesises
+//! only where precedence needs it, so a compiled Expr prints and compiles
+//! back to itself.
+
+use crate::error::{Error, Result};
+use crate::expr::{Expr, ExprOp, FieldPath, Operand, PathRoot, MAX_EXPR_DEPTH};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+/// Field names per FieldPath root, for compiling and printing queries
+///
+/// A name is defined under a parent path of field numbers, so
+/// `trace_ref.trace_blob` is `trace_blob` under `[2]` for the episode root.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Schema {
+    numbers: BTreeMap<(u8, Vec<u32>, String), u32>,
+    names: BTreeMap<(u8, Vec<u32>, u32), String>,
+}
+
+impl Schema {
+    /// No names; paths are field numbers only
+    pub fn empty() -> Self {
+        Schema::default()
+    }
+
+    /// Names for the EpisodeRef and Signal fields of RFC-0001 A.15
+    pub fn a15() -> Self {
+        let mut schema = Schema::empty();
+        let mut add = |root, parent: &[u32], name: &str, number| {
+            schema
+                .add_field(root, parent, name, number)
+                .expect("A.15 schema names are valid");
+        };
+
+        use PathRoot::{Episode, Signal};
+        add(Episode, &[], "episode_id", 1);
+        add(Episode, &[], "trace_ref", 2);
+        add(Episode, &[], "context_hash", 3);
+        add(Episode, &[], "outcome_hash", 4);
+        add(Episode, &[], "time_observed", 5);
+        add(Episode, &[2], "trace_blob", 1);
+        add(Episode, &[2], "receipt_ids", 2);
+        for (number, name) in ["cid", "size", "media", "codec", "chunks"]
+            .iter()
+            .enumerate()
+        {
+            add(Episode, &[2, 1], name, number as u32 + 1);
+        }
+
+        add(Signal, &[], "signal_id", 1);
+        add(Signal, &[], "episode_id", 2);
+        add(Signal, &[], "signal_type", 3);
+        add(Signal, &[], "value_kind", 4);
+        add(Signal, &[], "value_bytes", 5);
+        add(Signal, &[], "signer", 6);
+        add(Signal, &[], "time_observed", 7);
+        add(Signal, &[6], "scheme", 1);
+        add(Signal, &[6], "key", 2);
+        schema
+    }
+
+    /// Name field `number` of the struct at `parent`
+    ///
+    /// Names are `[A-Za-z_][A-Za-z0-9_]*`; each name and each field
+    /// number is defined at most once per parent.
+    pub fn add_field(
+        &mut self,
+        root: PathRoot,
+        parent: &[u32],
+        name: &str,
+        number: u32,
+    ) -> Result<()> {
+        let valid = name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if !valid || number == 0 || parent.contains(&0) {
+            return Err(Error::InvalidExpr(format!(
+                "schema field {}={} under {:?}",
+                name, number, parent
+            )));
+        }
+
+        let name_key = (root as u8, parent.to_vec(), name.to_string());
+        let number_key = (root as u8, parent.to_vec(), number);
+        if self.numbers.contains_key(&name_key) || self.names.contains_key(&number_key) {
+            return Err(Error::InvalidExpr(format!(
+                "schema field {}={} under {:?} already defined",
+                name, number, parent
+            )));
+        }
+        self.numbers.insert(name_key, number);
+        self.names.insert(number_key, name.to_string());
+        Ok(())
+    }
+
+    /// Field number of `name` under `parent`
+    pub fn number(&self, root: PathRoot, parent: &[u32], name: &str) -> Option<u32> {
+        self.numbers
+            .get(&(root as u8, parent.to_vec(), name.to_string()))
+            .copied()
+    }
+
+    /// Name of field `number` under `parent`
+    pub fn name(&self, root: PathRoot, parent: &[u32], number: u32) -> Option<&str> {
+        self.names
+            .get(&(root as u8, parent.to_vec(), number))
+            .map(String::as_str)
+    }
+}
+
+/// Compile query text to a validated Expr in normal form
+pub fn compile_query(text: &str, schema: &Schema) -> Result<Expr> {
+    let mut parser = Parser {
+        tokens: tokenize(text)?,
+        pos: 0,
+        end: text.len(),
+        schema,
+    };
+    let expr = parser.or(1)?;
+    if let Some(token) = parser.tokens.get(parser.pos) {
+        return Err(syntax(token.offset, "expected end of query"));
+    }
+    expr.validate()?;
+    Ok(expr)
+}
+
+/// Print an Expr as query text
+///
+/// Errors only on a node whose fields do not fit its op.
+pub fn format_query(expr: &Expr, schema: &Schema) -> Result<String> {
+    let mut out = String::new();
+    write_expr(&mut out, expr, schema)?;
+    Ok(out)
+}
+
+/// Parse one FieldPath such as `episode.trace_ref.trace_blob.size`
+pub fn parse_field_path(text: &str, schema: &Schema) -> Result<FieldPath> {
+    let mut parser = Parser {
+        tokens: tokenize(text)?,
+        pos: 0,
+        end: text.len(),
+        schema,
+    };
+    let root = match parser.next() {
+        Some(Tok::Word(w)) if w.eq_ignore_ascii_case("episode") => PathRoot::Episode,
+        Some(Tok::Word(w)) if w.eq_ignore_ascii_case("signal") => PathRoot::Signal,
+        _ => return Err(syntax(0, "expected episode or signal")),
+    };
+    let path = parser.path(root)?;
+    if let Some(token) = parser.tokens.get(parser.pos) {
+        return Err(syntax(token.offset, "expected end of path"));
+    }
+    Ok(path)
+}
+
+/// Print a FieldPath, with names where the schema has them
+pub fn format_field_path(path: &FieldPath, schema: &Schema) -> String {
+    let mut out = String::new();
+    write_path(&mut out, path, schema);
+    out
+}
+
+fn syntax(offset: usize, message: impl Into<String>) -> Error {
+    Error::QuerySyntax {
+        offset,
+        message: message.into(),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Tok {
+    Word(String),
+    Int(u64),
+    Hash([u8; 32]),
+    Minus,
+    Dot,
+    Comma,
+    LParen,
+    RParen,
+    LBrace,
+    RBrace,
+    Cmp(ExprOp),
+}
+
+#[derive(Debug)]
+struct Token {
+    tok: Tok,
+    offset: usize,
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>> {
+    let bytes = text.as_bytes();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let start = i;
+        let c = bytes[i];
+        let tok = match c {
+            b' ' | b'\t' | b'\r' | b'\n' => {
+                i += 1;
+                continue;
+            }
+            b'-' => Tok::Minus,
+            b'.' => Tok::Dot,
+            b',' => Tok::Comma,
+            b'(' => Tok::LParen,
+            b')' => Tok::RParen,
+            b'{' => Tok::LBrace,
+            b'}' => Tok::RBrace,
+            b'=' | b'!' | b'<' | b'>' => {
+                let two = bytes.get(i + 1) == Some(&b'=');
+                let op = match (c, two) {
+                    (b'=', true) => ExprOp::Eq,
+                    (b'!', true) => ExprOp::Ne,
+                    (b'<', false) => ExprOp::Lt,
+                    (b'<', true) => ExprOp::Le,
+                    (b'>', false) => ExprOp::Gt,
+                    (b'>', true) => ExprOp::Ge,
+                    _ => return Err(syntax(start, "expected ==, !=, <, <=, > or >=")),
+                };
+                i += if two { 2 } else { 1 };
+                tokens.push(Token {
+                    tok: Tok::Cmp(op),
+                    offset: start,
+                });
+                continue;
+            }
+            _ if c.is_ascii_alphanumeric() || c == b'_' => {
+                while i < bytes.len() && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'_') {
+                    i += 1;
+                }
+                tokens.push(Token {
+                    tok: word(&text[start..i], start)?,
+                    offset: start,
+                });
+                continue;
+            }
+            _ => {
+                let c = text[start..].chars().next().unwrap_or('?');
+                return Err(syntax(start, format!("unexpected {:?}", c)));
+            }
+        };
+        i += 1;
+        tokens.push(Token { tok, offset: start });
+    }
+    Ok(tokens)
+}
+
+/// A run of `[A-Za-z0-9_]`: a hash, an integer or a word
+fn word(text: &str, offset: usize) -> Result<Tok> {
+    if let Some(digits) = text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        let bytes = hex::decode(digits)
+            .ok()
+            .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+            .ok_or_else(|| syntax(offset, "a hash is 0x and 64 hex digits"))?;
+        return Ok(Tok::Hash(bytes));
+    }
+    if text.starts_with(|c: char| c.is_ascii_digit()) {
+        return text
+            .parse()
+            .map(Tok::Int)
+            .map_err(|_| syntax(offset, format!("bad integer {}", text)));
+    }
+    Ok(Tok::Word(text.to_string()))
+}
+
+struct Parser<'a> {
+    tokens: Vec<Token>,
+    pos: usize,
+    end: usize,
+    schema: &'a Schema,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Tok> {
+        self.tokens.get(self.pos).map(|token| &token.tok)
+    }
+
+    fn offset(&self) -> usize {
+        self.tokens
+            .get(self.pos)
+            .map_or(self.end, |token| token.offset)
+    }
+
+    fn next(&mut self) -> Option<Tok> {
+        let tok = self.peek().cloned();
+        self.pos += 1;
+        tok
+    }
+
+    fn expect(&mut self, expected: Tok, what: &str) -> Result<()> {
+        let offset = self.offset();
+        match self.next() {
+            Some(tok) if tok == expected => Ok(()),
+            _ => Err(syntax(offset, format!("expected {}", what))),
+        }
+    }
+
+    fn at_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Tok::Word(w)) if w.eq_ignore_ascii_case(keyword))
+    }
+
+    fn or(&mut self, depth: usize) -> Result<Expr> {
+        self.chain(depth, ExprOp::Or, "or", Parser::and)
+    }
+
+    fn and(&mut self, depth: usize) -> Result<Expr> {
+        self.chain(depth, ExprOp::And, "and", Parser::unary)
+    }
+
+    /// `operand (keyword operand)*`, flattening nested nodes of `op`
+    fn chain(
+        &mut self,
+        depth: usize,
+        op: ExprOp,
+        keyword: &str,
+        operand: fn(&mut Self, usize) -> Result<Expr>,
+    ) -> Result<Expr> {
+        let first = operand(self, depth)?;
+        if !self.at_keyword(keyword) {
+            return Ok(first);
+        }
+
+        let mut args = Vec::new();
+        let mut push = |expr: Expr| match expr {
+            Expr {
+                op: inner,
+                args: Some(inner_args),
+                ..
+            } if inner == op => args.extend(inner_args),
+            expr => args.push(expr),
+        };
+        push(first);
+        while self.at_keyword(keyword) {
+            self.pos += 1;
+            push(operand(self, depth)?);
+        }
+        Ok(match op {
+            ExprOp::And => Expr::and(args),
+            _ => Expr::or(args),
+        })
+    }
+
+    fn unary(&mut self, depth: usize) -> Result<Expr> {
+        if depth > MAX_EXPR_DEPTH {
+            return Err(syntax(
+                self.offset(),
+                format!("nesting deeper than {}", MAX_EXPR_DEPTH),
+            ));
+        }
+        if self.at_keyword("not") {
+            self.pos += 1;
+            return Ok(Expr::negate(self.unary(depth + 1)?));
+        }
+        self.primary(depth)
+    }
+
+    fn primary(&mut self, depth: usize) -> Result<Expr> {
+        let offset = self.offset();
+        let word = match self.next() {
+            Some(Tok::LParen) => {
+                let expr = self.or(depth + 1)?;
+                self.expect(Tok::RParen, ")")?;
+                return Ok(expr);
+            }
+            Some(Tok::Word(word)) => word.to_ascii_lowercase(),
+            _ => return Err(syntax(offset, "expected a predicate")),
+        };
+
+        match word.as_str() {
+            "true" => Ok(Expr::constant(true)),
+            "false" => Ok(Expr::constant(false)),
+            "has_tool" => {
+                self.expect(Tok::LParen, "(")?;
+                let lit_offset = self.offset();
+                let Some(Tok::Hash(tool_id)) = self.next() else {
+                    return Err(syntax(lit_offset, "has_tool takes a tool_id hash"));
+                };
+                self.expect(Tok::RParen, ")")?;
+                Ok(Expr::has_tool(tool_id))
+            }
+            "has_status" => {
+                self.expect(Tok::LParen, "(")?;
+                let lit_offset = self.offset();
+                let status = match self.next() {
+                    Some(Tok::Int(n)) => u16::try_from(n).ok(),
+                    _ => None,
+                }
+                .ok_or_else(|| syntax(lit_offset, "has_status takes a u16 status"))?;
+                self.expect(Tok::RParen, ")")?;
+                Ok(Expr::has_status(status))
+            }
+            "episode" => self.predicate(PathRoot::Episode),
+            "signal" => self.predicate(PathRoot::Signal),
+            _ => Err(syntax(offset, format!("unknown predicate {}", word))),
+        }
+    }
+
+    /// The rest of `path <cmp> literal` or `path in {…}` after the root
+    fn predicate(&mut self, root: PathRoot) -> Result<Expr> {
+        let path = self.path(root)?;
+        let offset = self.offset();
+        match self.next() {
+            Some(Tok::Cmp(op)) => Ok(Expr::compare(op, path, self.literal()?)),
+            Some(Tok::Word(w)) if w.eq_ignore_ascii_case("in") => {
+                self.expect(Tok::LBrace, "{")?;
+                let mut set = Vec::new();
+                if self.peek() != Some(&Tok::RBrace) {
+                    set.push(self.literal()?);
+                    while self.peek() == Some(&Tok::Comma) {
+                        self.pos += 1;
+                        set.push(self.literal()?);
+                    }
+                }
+                self.expect(Tok::RBrace, "}")?;
+                set.sort_by_key(operand_order);
+                set.dedup();
+                Ok(Expr::in_set(path, set))
+            }
+            _ => Err(syntax(offset, "expected a comparison or in")),
+        }
+    }
+
+    fn path(&mut self, root: PathRoot) -> Result<FieldPath> {
+        let mut segments = Vec::new();
+        while self.peek() == Some(&Tok::Dot) {
+            self.pos += 1;
+            let offset = self.offset();
+            let segment = match self.next() {
+                Some(Tok::Int(n)) => u32::try_from(n)
+                    .ok()
+                    .filter(|n| *n > 0)
+                    .ok_or_else(|| syntax(offset, "field numbers run from 1 to 2^32-1"))?,
+                Some(Tok::Word(name)) => self
+                    .schema
+                    .number(root, &segments, &name)
+                    .ok_or_else(|| syntax(offset, format!("unknown field {}", name)))?,
+                _ => return Err(syntax(offset, "expected a field name or number")),
+            };
+            segments.push(segment);
+        }
+        if segments.is_empty() {
+            return Err(syntax(self.offset(), "expected .field after the root"));
+        }
+        Ok(FieldPath { root, segments })
+    }
+
+    fn literal(&mut self) -> Result<Operand> {
+        let offset = self.offset();
+        match self.next() {
+            Some(Tok::Int(n)) => Ok(Operand::U64(n)),
+            Some(Tok::Hash(h)) => Ok(Operand::Hash(h)),
+            Some(Tok::Minus) => match self.next() {
+                Some(Tok::Int(0)) => Ok(Operand::U64(0)),
+                Some(Tok::Int(n)) if n <= i64::MAX as u64 + 1 => {
+                    Ok(Operand::I64((n as i64).wrapping_neg()))
+                }
+                _ => Err(syntax(offset, "expected an integer from -2^63")),
+            },
+            _ => Err(syntax(offset, "expected an integer or hash")),
+        }
+    }
+}
+
+/// IN_SET order: U64, then I64, then Hash, each ascending
+fn operand_order(operand: &Operand) -> (u8, i128, [u8; 32]) {
+    match operand {
+        Operand::U64(n) => (0, *n as i128, [0; 32]),
+        Operand::I64(n) => (1, *n as i128, [0; 32]),
+        Operand::Hash(h) => (2, 0, *h),
+    }
+}
+
+/// Binding strength: OR < AND < NOT < everything else
+fn precedence(expr: &Expr) -> u8 {
+    match expr.op {
+        ExprOp::Or => 1,
+        ExprOp::And => 2,
+        ExprOp::Not => 3,
+        _ => 4,
+    }
+}
+
+fn malformed(expr: &Expr) -> Error {
+    Error::InvalidExpr(format!("{:?} node is malformed", expr.op))
+}
+
+fn write_expr(out: &mut String, expr: &Expr, schema: &Schema) -> Result<()> {
+    match (expr.op, &expr.args, &expr.path, &expr.lit, &expr.set) {
+        (ExprOp::True, ..) => out.push_str("true"),
+        (ExprOp::False, ..) => out.push_str("false"),
+        (ExprOp::And | ExprOp::Or, Some(args), ..) if !args.is_empty() => {
+            let keyword = if expr.op == ExprOp::And {
+                " and "
+            } else {
+                " or "
+            };
+            for (i, arg) in args.iter().enumerate() {
+                if i > 0 {
+                    out.push_str(keyword);
+                }
+                // A nested node of the same op keeps its parentheses
+                write_operand(out, arg, precedence(arg) <= precedence(expr), schema)?;
+            }
+        }
+        (ExprOp::Not, Some(args), ..) if args.len() == 1 => {
+            out.push_str("not ");
+            write_operand(out, &args[0], precedence(&args[0]) < 3, schema)?;
+        }
+        (op, _, Some(path), Some(lit), _) if op.is_comparison() => {
+            write_path(out, path, schema);
+            let symbol = match op {
+                ExprOp::Eq => "==",
+                ExprOp::Ne => "!=",
+                ExprOp::Lt => "<",
+                ExprOp::Le => "<=",
+                ExprOp::Gt => ">",
+                _ => ">=",
+            };
+            let _ = write!(out, " {} ", symbol);
+            write_literal(out, lit);
+        }
+        (ExprOp::InSet, _, Some(path), _, Some(set)) => {
+            write_path(out, path, schema);
+            out.push_str(" in {");
+            for (i, lit) in set.iter().enumerate() {
+                if i > 0 {
+                    out.push_str(", ");
+                }
+                write_literal(out, lit);
+            }
+            out.push('}');
+        }
+        (ExprOp::HasTool, _, _, Some(lit @ Operand::Hash(_)), _) => {
+            out.push_str("has_tool(");
+            write_literal(out, lit);
+            out.push(')');
+        }
+        (ExprOp::HasStatus, _, _, Some(Operand::U64(status)), _) => {
+            let _ = write!(out, "has_status({})", status);
+        }
+        _ => return Err(malformed(expr)),
+    }
+    Ok(())
+}
+
+fn write_operand(out: &mut String, expr: &Expr, parens: bool, schema: &Schema) -> Result<()> {
+    if parens {
+        out.push('(');
+    }
+    write_expr(out, expr, schema)?;
+    if parens {
+        out.push(')');
+    }
+    Ok(())
+}
+
+fn write_path(out: &mut String, path: &FieldPath, schema: &Schema) {
+    out.push_str(match path.root {
+        PathRoot::Episode => "episode",
+        PathRoot::Signal => "signal",
+    });
+    for (i, segment) in path.segments.iter().enumerate() {
+        match schema.name(path.root, &path.segments[..i], *segment) {
+            Some(name) => {
+                let _ = write!(out, ".{}", name);
+            }
+            None => {
+                let _ = write!(out, ".{}", segment);
+            }
+        }
+    }
+}
+
+/// An I64 of 0 or more prints as a plain integer and compiles to U64;
+/// evaluation compares the two numerically
+fn write_literal(out: &mut String, lit: &Operand) {
+    let _ = match lit {
+        Operand::U64(n) => write!(out, "{}", n),
+        Operand::I64(n) => write!(out, "{}", n),
+        Operand::Hash(h) => write!(out, "0x{}", hex::encode(h)),
+    };
+}
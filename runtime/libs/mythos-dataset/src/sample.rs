@@ -0,0 +1,183 @@
+//! Sampling (RFC-0003 §5–6)
+//!
+//! Samplers consume EpisodeIDs in streaming corpus order and return the
+//! selection sorted ascending, as the manifest stores it. Duplicate IDs
+//! (an episode listed under two corpus roots) count once.
+//!
+//! HASH_N keeps the N lowest `(score, id)` pairs in a bounded max-heap, so
+//! memory is O(N) and time O(M log N) over M candidates; the result does
+//! not depend on the order candidates arrive in.
+
+use crate::codec::encode;
+use crate::def::{SamplingDef, SamplingMode, StratifyDef};
+use crate::error::Result;
+use mythos_can::Value;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet, BinaryHeap, HashSet};
+
+pub type EpisodeId = [u8; 32];
+
+/// `u64(first 8 bytes of SHA-256(seed || id))`, big-endian (§5.3)
+pub fn score(seed: &[u8; 32], id: &EpisodeId) -> u64 {
+    let digest = Sha256::new().chain_update(seed).chain_update(id).finalize();
+    u64::from_be_bytes(digest[..8].try_into().expect("SHA-256 is 32 bytes"))
+}
+
+/// `SHA-256(seed || canonical_bytes(bucket_key))` (§6.2)
+///
+/// A missing key is the empty BYTES value.
+pub fn bucket_seed(seed: &[u8; 32], key: Option<&Value>) -> Result<[u8; 32]> {
+    let key = match key {
+        Some(key) => encode(key)?,
+        None => encode(&Value::Bytes(Vec::new()))?,
+    };
+    Ok(Sha256::new()
+        .chain_update(seed)
+        .chain_update(&key)
+        .finalize()
+        .into())
+}
+
+/// The N lowest-scoring IDs, ties broken by ID ascending
+#[derive(Debug, Clone)]
+pub struct HashNSampler {
+    seed: [u8; 32],
+    n: u64,
+    /// Max-heap: the worst kept candidate is on top
+    heap: BinaryHeap<(u64, EpisodeId)>,
+    kept: HashSet<EpisodeId>,
+}
+
+impl HashNSampler {
+    pub fn new(seed: [u8; 32], n: u64) -> Self {
+        HashNSampler {
+            seed,
+            n,
+            heap: BinaryHeap::new(),
+            kept: HashSet::new(),
+        }
+    }
+
+    pub fn push(&mut self, id: EpisodeId) {
+        if self.n == 0 || self.kept.contains(&id) {
+            return;
+        }
+        let candidate = (score(&self.seed, &id), id);
+
+        if (self.heap.len() as u64) < self.n {
+            self.heap.push(candidate);
+            self.kept.insert(id);
+        } else if let Some(mut worst) = self.heap.peek_mut() {
+            if candidate < *worst {
+                self.kept.remove(&worst.1);
+                self.kept.insert(id);
+                *worst = candidate;
+            }
+        }
+    }
+
+    /// Selected IDs, ascending
+    pub fn finish(self) -> Vec<EpisodeId> {
+        let mut ids: Vec<_> = self.heap.into_iter().map(|(_, id)| id).collect();
+        ids.sort_unstable();
+        ids
+    }
+}
+
+/// Sampler for a SamplingDef
+#[derive(Debug, Clone)]
+pub enum Sampler {
+    All(BTreeSet<EpisodeId>),
+    FirstN { n: u64, ids: BTreeSet<EpisodeId> },
+    HashN(HashNSampler),
+}
+
+impl Sampler {
+    pub fn new(sampling: &SamplingDef) -> Result<Self> {
+        sampling.validate()?;
+        let n = sampling.n.unwrap_or(0);
+        Ok(match sampling.mode {
+            SamplingMode::All => Sampler::All(BTreeSet::new()),
+            SamplingMode::FirstN => Sampler::FirstN {
+                n,
+                ids: BTreeSet::new(),
+            },
+            SamplingMode::HashN => Sampler::HashN(HashNSampler::new(sampling.seed, n)),
+        })
+    }
+
+    pub fn push(&mut self, id: EpisodeId) {
+        match self {
+            Sampler::All(ids) => {
+                ids.insert(id);
+            }
+            Sampler::FirstN { n, ids } => {
+                if (ids.len() as u64) < *n {
+                    ids.insert(id);
+                }
+            }
+            Sampler::HashN(sampler) => sampler.push(id),
+        }
+    }
+
+    /// FIRST_N has all it needs; later candidates are ignored
+    pub fn is_full(&self) -> bool {
+        matches!(self, Sampler::FirstN { n, ids } if ids.len() as u64 >= *n)
+    }
+
+    /// Selected IDs, ascending
+    pub fn finish(self) -> Vec<EpisodeId> {
+        match self {
+            Sampler::All(ids) | Sampler::FirstN { ids, .. } => ids.into_iter().collect(),
+            Sampler::HashN(sampler) => sampler.finish(),
+        }
+    }
+}
+
+/// HASH_N within each bucket of a StratifyDef key (§6.2)
+#[derive(Debug, Clone)]
+pub struct StratifiedSampler {
+    seed: [u8; 32],
+    per_bucket_n: u64,
+    /// Canonical key bytes -> bucket sampler
+    buckets: BTreeMap<Vec<u8>, HashNSampler>,
+}
+
+impl StratifiedSampler {
+    pub fn new(stratify: &StratifyDef) -> Self {
+        StratifiedSampler {
+            seed: stratify.seed,
+            per_bucket_n: stratify.per_bucket_n,
+            buckets: BTreeMap::new(),
+        }
+    }
+
+    /// Add a candidate whose `key_path` read `key` (`None` if missing)
+    pub fn push(&mut self, id: EpisodeId, key: Option<&Value>) -> Result<()> {
+        let empty = Value::Bytes(Vec::new());
+        let key_bytes = encode(key.unwrap_or(&empty))?;
+
+        if let Some(bucket) = self.buckets.get_mut(&key_bytes) {
+            bucket.push(id);
+            return Ok(());
+        }
+        let mut bucket = HashNSampler::new(bucket_seed(&self.seed, key)?, self.per_bucket_n);
+        bucket.push(id);
+        self.buckets.insert(key_bytes, bucket);
+        Ok(())
+    }
+
+    pub fn bucket_count(&self) -> usize {
+        self.buckets.len()
+    }
+
+    /// Union of the bucket selections, ascending
+    pub fn finish(self) -> Vec<EpisodeId> {
+        let ids: BTreeSet<_> = self
+            .buckets
+            .into_values()
+            .flat_map(HashNSampler::finish)
+            .collect();
+        ids.into_iter().collect()
+    }
+}
@@ -0,0 +1,259 @@
+/// Dataset archive tests: walk, GC, scrub, export and import from a DatasetRef
+use ed25519_dalek::SigningKey;
+use mythos_blob::{BlobRef, CODEC_RAW};
+use mythos_cas::{
+    collect_garbage, export_archive_with, import_archive, scrub, walk, BlobStore, Cid, GcOptions,
+    PinStore, Registry, ScrubOptions, WalkOptions,
+};
+use mythos_dataset::{
+    build_dataset, diff_datasets, export_jsonl, manifest_registry, DatasetRef, DiffDetail,
+    ExportOptions, StoreEpisodes,
+};
+use mythos_episode::{
+    attach_signal, ed25519_agent, put_episode, put_receipt, EpisodeIndex, EpisodeRef, Signal,
+    SignalType, SignedReceipt, TraceRef,
+};
+use mythos_hash::Receipt;
+use std::collections::HashSet;
+use std::time::Duration;
+
+mod common;
+use common::{build_options, Fixture, TempStore};
+
+/// A pinned dataset over four episodes, each with a stored trace, one
+/// signed receipt and one Signal
+///
+/// Also returns everything stored for the episodes: EpisodeRefs, traces,
+/// receipts and Signals.
+fn fixture(name: &str) -> (Fixture, HashSet<Cid>) {
+    let mut episode_objects = HashSet::new();
+    let fixture = common::fixture(name, |store, index| {
+        let key = SigningKey::from_bytes(&[1; 32]);
+        let mut ids = Vec::new();
+        for i in 0..4u8 {
+            let trace = vec![i; 64];
+            let trace_cid = store.put(&trace).unwrap();
+            let receipt = Receipt {
+                tool_id: vec![i; 32],
+                request_hash: vec![i; 32],
+                response_hash: vec![i; 32],
+                idempotency_key: vec![i; 32],
+                signer: ed25519_agent(&key.verifying_key()),
+                time_us: i as i64,
+                status: 200,
+                evidence: None,
+                notes: None,
+            };
+            let receipt = SignedReceipt::sign(receipt, &key).unwrap();
+            let receipt_cid = put_receipt(store, index, &receipt).unwrap();
+
+            let trace = TraceRef {
+                trace_blob: BlobRef {
+                    cid: trace_cid.to_vec(),
+                    size: trace.len() as u64,
+                    media: "application/mythos.trace".into(),
+                    codec: CODEC_RAW,
+                    chunks: 0,
+                    encryption: None,
+                    provenance: None,
+                },
+                receipt_ids: vec![receipt.receipt_id],
+            };
+            let episode = EpisodeRef::new(trace, [i; 32], [9; 32], i as i64).unwrap();
+            let episode_cid = put_episode(store, &episode).unwrap();
+            index.add_episode(episode_cid, &episode);
+            let signal =
+                Signal::sign(episode.episode_id, SignalType::Reward, 1, vec![i], 0, &key).unwrap();
+            let signal_cid = attach_signal(store, index, &signal).unwrap();
+
+            episode_objects.extend([trace_cid, receipt_cid, episode_cid, signal_cid]);
+            ids.push(episode.episode_id);
+        }
+        ids
+    });
+
+    // Sampling all of one corpus makes the manifest that same list, so its
+    // root is reached both as a corpus (by shape) and as a manifest
+    assert_eq!(
+        fixture.dataset.manifest_root().unwrap(),
+        fixture.def.corpus_roots[0]
+    );
+    let store = &fixture.temp.store;
+    assert!(store.has(&fixture.dataset_cid).unwrap());
+    store.pin(&fixture.dataset_cid, None).unwrap();
+
+    (fixture, episode_objects)
+}
+
+fn jsonl<S: BlobStore>(store: &S, dataset: &DatasetRef) -> String {
+    let index = EpisodeIndex::build(store).unwrap();
+    let episodes = StoreEpisodes {
+        store,
+        index: &index,
+    };
+    let mut out = Vec::new();
+    export_jsonl(
+        dataset,
+        &episodes,
+        store,
+        &ExportOptions::default(),
+        &mut out,
+    )
+    .unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+fn gc_options(registry: &Registry, dry_run: bool) -> GcOptions<'_> {
+    GcOptions {
+        grace: Duration::ZERO,
+        dry_run,
+        registry,
+    }
+}
+
+#[test]
+fn test_walk_reaches_episodes() {
+    let (fixture, episode_objects) = fixture("walk");
+    let store = &fixture.temp.store;
+    let registry = manifest_registry(EpisodeIndex::build(store).unwrap());
+
+    let mut visited = HashSet::new();
+    let report = walk(
+        store,
+        &registry,
+        &[fixture.dataset_cid],
+        &WalkOptions::default(),
+        |visit| {
+            visited.insert(visit.cid);
+            Ok(())
+        },
+    )
+    .unwrap();
+    assert!(report.missing.is_empty(), "{:?}", report.missing);
+    assert!(visited.contains(&fixture.dataset.manifest_root().unwrap()));
+    assert!(episode_objects.is_subset(&visited));
+}
+
+#[test]
+fn test_gc_keeps_dataset_episodes() {
+    let (fixture, episode_objects) = fixture("gc");
+    let store = &fixture.temp.store;
+
+    // Without the index the EpisodeIDs are optional and lead nowhere
+    let builtin = Registry::builtin();
+    let report = collect_garbage(store, &gc_options(builtin, true)).unwrap();
+    let swept: HashSet<Cid> = report.swept.into_iter().collect();
+    assert!(episode_objects.is_subset(&swept));
+
+    let registry = manifest_registry(EpisodeIndex::build(store).unwrap());
+    let report = collect_garbage(store, &gc_options(&registry, false)).unwrap();
+    assert!(report.missing.is_empty(), "{:?}", report.missing);
+    assert!(report.unreadable.is_empty());
+    for cid in &episode_objects {
+        assert!(store.has(cid).unwrap());
+    }
+    assert!(store
+        .has(&fixture.dataset.manifest_root().unwrap())
+        .unwrap());
+
+    // A second pass over what is left sweeps nothing
+    let report = collect_garbage(store, &gc_options(&registry, false)).unwrap();
+    assert!(report.swept.is_empty());
+}
+
+#[test]
+fn test_unindexed_episodes_missing() {
+    let (fixture, _) = fixture("unindexed");
+    let store = &fixture.temp.store;
+    let registry = manifest_registry(EpisodeIndex::new());
+
+    let report = collect_garbage(store, &gc_options(&registry, true)).unwrap();
+    let mut missing = report.missing;
+    missing.sort();
+    assert_eq!(missing, fixture.ids);
+
+    let report = scrub(
+        store,
+        &ScrubOptions {
+            registry: &registry,
+            ..ScrubOptions::default()
+        },
+    )
+    .unwrap();
+    let mut missing: Vec<Cid> = report.missing.iter().map(|m| m.child).collect();
+    missing.sort();
+    assert_eq!(missing, fixture.ids);
+}
+
+#[test]
+fn test_scrub_and_export_follow_manifest() {
+    let (fixture, episode_objects) = fixture("scrub-export");
+    let store = &fixture.temp.store;
+    let registry = manifest_registry(EpisodeIndex::build(store).unwrap());
+
+    let report = scrub(
+        store,
+        &ScrubOptions {
+            registry: &registry,
+            ..ScrubOptions::default()
+        },
+    )
+    .unwrap();
+    assert!(report.complete);
+    assert!(report.missing.is_empty(), "{:?}", report.missing);
+    assert!(report.invalid.is_empty(), "{:?}", report.invalid);
+    assert!(report.corrupt.is_empty());
+
+    let mut archive = Vec::new();
+    let summary =
+        export_archive_with(store, &registry, &[fixture.dataset_cid], &mut archive).unwrap();
+    assert_eq!(summary.roots, vec![fixture.dataset_cid]);
+    // DatasetRef, manifest node, and four objects per episode
+    assert_eq!(summary.objects, 2 + episode_objects.len() as u64);
+}
+
+#[test]
+fn test_archive_round_trip() {
+    let (fixture, _) = fixture("round-trip");
+    let store = &fixture.temp.store;
+    let registry = manifest_registry(EpisodeIndex::build(store).unwrap());
+    let mut archive = Vec::new();
+    export_archive_with(store, &registry, &[fixture.dataset_cid], &mut archive).unwrap();
+
+    let imported = TempStore::new("round-trip-import");
+    let summary = import_archive(&imported.store, &mut archive.as_slice()).unwrap();
+    assert_eq!(summary.roots, vec![fixture.dataset_cid]);
+    let bytes = imported.store.get(&fixture.dataset_cid).unwrap();
+    assert_eq!(DatasetRef::from_bytes(&bytes).unwrap(), fixture.dataset);
+
+    // The imported corpus rebuilds the same dataset
+    let index = EpisodeIndex::build(&imported.store).unwrap();
+    assert_eq!(index.episode_count(), fixture.ids.len());
+    let rebuilt = build_dataset(
+        &fixture.def,
+        &StoreEpisodes {
+            store: &imported.store,
+            index: &index,
+        },
+        &imported.store,
+        &build_options(),
+    )
+    .unwrap();
+    assert_eq!(rebuilt, fixture.dataset);
+
+    let diff = diff_datasets(
+        &imported.store,
+        &fixture.dataset,
+        &rebuilt,
+        DiffDetail::Full,
+    )
+    .unwrap();
+    assert_eq!(diff.overlap, fixture.ids.len() as u64);
+    assert_eq!(diff.shared, fixture.ids);
+
+    // Traces, receipts and Signals all came along
+    assert_eq!(
+        jsonl(&imported.store, &fixture.dataset),
+        jsonl(store, &fixture.dataset)
+    );
+}
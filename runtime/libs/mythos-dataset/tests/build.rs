@@ -0,0 +1,486 @@
+/// Dataset build tests (RFC-0003 §7)
+use ed25519_dalek::SigningKey;
+use mythos_blob::{BlobRef, CODEC_RAW};
+use mythos_can::Value;
+use mythos_cas::{put_nodes, BlobStore, FsStore};
+use mythos_dataset::StoreEpisodes;
+use mythos_dataset::{
+    build_code_hash, build_dataset, build_tool_id, cid_from_bytes, select_episodes, BuildOptions,
+    DatasetDef, DatasetRef, EpisodeId, EpisodeSource, Error, Expr, ExprOp, FieldPath,
+    NoEpisodeData, Operand, QueryDef, Result, SamplingDef, StratifyDef, BUILD_TRANSFORM,
+    MANIFEST_MEDIA,
+};
+use mythos_episode::{
+    attach_signal, ed25519_agent, put_episode, put_receipt, EpisodeIndex, EpisodeRef, Signal,
+    SignalType, SignedReceipt, TraceRef,
+};
+use mythos_hash::{compute_receipt_id, Receipt};
+use mythos_merkle::{build_merkle_list, cid_value, HashValue};
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::fs;
+
+const VECTORS_PATH: &str = "../../../mythos-v0.2-conformance/vectors/dataset";
+
+mod common;
+use common::TempStore;
+
+fn map(fields: Vec<(u64, Value)>) -> Value {
+    Value::Map(
+        fields
+            .into_iter()
+            .map(|(k, v)| (Value::UVarint(k), v))
+            .collect(),
+    )
+}
+
+fn options() -> BuildOptions {
+    BuildOptions {
+        key: SigningKey::from_bytes(&[7; 32]),
+        time_us: 1_700_000_000_000_000,
+    }
+}
+
+/// Store a corpus MerkleList over `ids` (sorted), returning its root
+fn put_corpus(store: &FsStore, ids: &[EpisodeId]) -> [u8; 32] {
+    let mut ids = ids.to_vec();
+    ids.sort();
+    let values: Vec<HashValue> = ids.iter().map(cid_value).collect();
+    let list = build_merkle_list(&values).unwrap();
+    put_nodes(store, &list.nodes).unwrap();
+    list.root
+}
+
+/// Episodes whose field 5 (time) is their index and whose one Signal
+/// carries value_kind `index % 3`; counts every load
+#[derive(Default)]
+struct Episodes {
+    data: HashMap<EpisodeId, (Value, Vec<Value>)>,
+    loads: Cell<usize>,
+}
+
+impl Episodes {
+    fn new(count: u64) -> (Self, Vec<EpisodeId>) {
+        let mut episodes = Episodes::default();
+        let mut ids = Vec::new();
+        for i in 0..count {
+            let id = cid_from_bytes(format!("episode {}", i).as_bytes());
+            let episode = map(vec![(5, Value::IVarint(i as i64))]);
+            let signal = map(vec![(4, Value::UVarint(i % 3))]);
+            episodes.data.insert(id, (episode, vec![signal]));
+            ids.push(id);
+        }
+        (episodes, ids)
+    }
+}
+
+impl EpisodeSource for Episodes {
+    fn episode(&self, id: &EpisodeId) -> Result<Option<Value>> {
+        self.loads.set(self.loads.get() + 1);
+        Ok(self.data.get(id).map(|(episode, _)| episode.clone()))
+    }
+
+    fn signals(&self, id: &EpisodeId) -> Result<Vec<Value>> {
+        self.loads.set(self.loads.get() + 1);
+        Ok(self
+            .data
+            .get(id)
+            .map(|(_, signals)| signals.clone())
+            .unwrap_or_default())
+    }
+
+    fn receipts(&self, _id: &EpisodeId) -> Result<Vec<Value>> {
+        self.loads.set(self.loads.get() + 1);
+        Ok(Vec::new())
+    }
+}
+
+fn def(
+    roots: Vec<[u8; 32]>,
+    predicate: Expr,
+    sampling: SamplingDef,
+    stratify: Option<StratifyDef>,
+) -> DatasetDef {
+    DatasetDef::new(roots, QueryDef::new(predicate).unwrap(), sampling, stratify).unwrap()
+}
+
+#[test]
+fn test_dataset_001_build() {
+    let temp = TempStore::new("dataset-001");
+    let store = &temp.store;
+    let read = |name: &str| fs::read(format!("{}/dataset_001_{}", VECTORS_PATH, name)).unwrap();
+
+    let def = DatasetDef::from_bytes(&read("def.bin")).unwrap();
+    store.put(&read("corpus_rootnode.bin")).unwrap();
+
+    let dataset = build_dataset(&def, &NoEpisodeData, store, &options()).unwrap();
+    let expected_root = String::from_utf8(read("manifest_rootcid.hex")).unwrap();
+    assert_eq!(hex::encode(&dataset.manifest.cid), expected_root.trim());
+    assert_eq!(dataset.count, 5);
+    assert_eq!(dataset.dataset_def_id, def.dataset_def_id);
+    assert_eq!(dataset.manifest.media, MANIFEST_MEDIA);
+
+    // Manifest node, receipt and DatasetRef are all in the store
+    let manifest_root = dataset.manifest_root().unwrap();
+    assert_eq!(
+        store.get(&manifest_root).unwrap(),
+        read("manifest_rootnode.bin")
+    );
+    assert_eq!(
+        dataset.manifest.size,
+        read("manifest_rootnode.bin").len() as u64
+    );
+    let index = EpisodeIndex::build(store).unwrap();
+    assert!(index.receipt_cid(&dataset.receipt_id).is_some());
+    let stored = store
+        .get(&cid_from_bytes(&dataset.to_bytes().unwrap()))
+        .unwrap();
+    assert_eq!(DatasetRef::from_bytes(&stored).unwrap(), dataset);
+
+    // Rebuilding is deterministic, receipt included
+    let again = build_dataset(&def, &NoEpisodeData, store, &options()).unwrap();
+    assert_eq!(again, dataset);
+}
+
+#[test]
+fn test_build_receipt_is_signed() {
+    let temp = TempStore::new("receipt");
+    let store = &temp.store;
+    let (episodes, ids) = Episodes::new(4);
+    let root = put_corpus(store, &ids);
+    let def = def(
+        vec![root],
+        Expr::constant(true),
+        SamplingDef::all(cid_from_bytes(b"seed")),
+        None,
+    );
+
+    let dataset = build_dataset(&def, &episodes, store, &options()).unwrap();
+    let index = EpisodeIndex::build(store).unwrap();
+    assert!(index.rejected.is_empty());
+    let receipt = index
+        .load_receipt(store, &dataset.receipt_id)
+        .unwrap()
+        .unwrap();
+    receipt.verify().unwrap();
+    assert_eq!(receipt.receipt.status, 200);
+    assert_eq!(receipt.receipt.time_us, options().time_us);
+    assert_eq!(
+        receipt.receipt.signer,
+        ed25519_agent(&options().key.verifying_key())
+    );
+    // Stored in full, so the id is not the CID
+    assert!(!store.has(&dataset.receipt_id).unwrap());
+
+    let rebuilt = Receipt {
+        tool_id: build_tool_id().to_vec(),
+        request_hash: def.dataset_def_id.to_vec(),
+        response_hash: dataset.manifest.cid.clone(),
+        idempotency_key: def.dataset_def_id.to_vec(),
+        signer: ed25519_agent(&options().key.verifying_key()),
+        time_us: options().time_us,
+        status: 200,
+        evidence: Some(vec![root.to_vec()]),
+        notes: None,
+    };
+    assert_eq!(compute_receipt_id(&rebuilt), dataset.receipt_id);
+}
+
+#[test]
+fn test_manifest_size_and_provenance() {
+    let temp = TempStore::new("manifest-size");
+    let store = &temp.store;
+    let ids: Vec<EpisodeId> = (0..1500u32)
+        .map(|i| cid_from_bytes(format!("episode {}", i).as_bytes()))
+        .collect();
+    let root = put_corpus(store, &ids);
+    let def = def(
+        vec![root],
+        Expr::constant(true),
+        SamplingDef::all(cid_from_bytes(b"seed")),
+        None,
+    );
+    let dataset = build_dataset(&def, &NoEpisodeData, store, &options()).unwrap();
+
+    // The size covers every manifest node, not just the root
+    let mut sorted = ids.clone();
+    sorted.sort();
+    let values: Vec<HashValue> = sorted.iter().map(cid_value).collect();
+    let manifest = build_merkle_list(&values).unwrap();
+    assert!(manifest.nodes.len() > 1);
+    let total: usize = manifest.nodes.iter().map(|(_, bytes)| bytes.len()).sum();
+    assert_eq!(dataset.manifest.size, total as u64);
+    let root_node = store.get(&dataset.manifest_root().unwrap()).unwrap();
+    assert!(dataset.manifest.size > root_node.len() as u64);
+
+    // code_hash names the build code, not the transform
+    let provenance = dataset.manifest.provenance.unwrap();
+    assert_eq!(provenance.transform, BUILD_TRANSFORM);
+    assert_eq!(provenance.code_hash, build_code_hash());
+    assert_ne!(provenance.code_hash, build_tool_id());
+    assert_eq!(provenance.parents, vec![root.to_vec()]);
+}
+
+#[test]
+fn test_filter_and_sample() {
+    let temp = TempStore::new("filter");
+    let store = &temp.store;
+    let (episodes, ids) = Episodes::new(60);
+
+    // Two corpus roots, listed out of order
+    let first = put_corpus(store, &ids[..30]);
+    let second = put_corpus(store, &ids[30..]);
+    let roots = vec![first.max(second), first.min(second)];
+
+    // time < 40 and any signal with value_kind 1
+    let predicate = Expr::and(vec![
+        Expr::compare(ExprOp::Lt, FieldPath::episode(&[5]), Operand::I64(40)),
+        Expr::eq(FieldPath::signal(&[4]), Operand::U64(1)),
+    ]);
+    let mut matching: Vec<EpisodeId> = (0..40).filter(|i| i % 3 == 1).map(|i| ids[i]).collect();
+    matching.sort();
+
+    let seed = cid_from_bytes(b"seed");
+    let all = def(
+        roots.clone(),
+        predicate.clone(),
+        SamplingDef::all(seed),
+        None,
+    );
+    assert_eq!(select_episodes(&all, &episodes, store).unwrap(), matching);
+
+    let hash_n = def(roots.clone(), predicate, SamplingDef::hash_n(5, seed), None);
+    let selected = select_episodes(&hash_n, &episodes, store).unwrap();
+    assert_eq!(selected.len(), 5);
+    assert!(selected.iter().all(|id| matching.contains(id)));
+    assert!(selected.windows(2).all(|w| w[0] < w[1]));
+
+    let dataset = build_dataset(&hash_n, &episodes, store, &options()).unwrap();
+    assert_eq!(dataset.count, 5);
+}
+
+#[test]
+fn test_loads_only_what_is_read() {
+    let temp = TempStore::new("loads");
+    let store = &temp.store;
+    let (episodes, ids) = Episodes::new(20);
+    let root = put_corpus(store, &ids);
+    let seed = cid_from_bytes(b"seed");
+
+    // A TRUE predicate loads nothing
+    let all = def(
+        vec![root],
+        Expr::constant(true),
+        SamplingDef::all(seed),
+        None,
+    );
+    assert_eq!(select_episodes(&all, &episodes, store).unwrap().len(), 20);
+    assert_eq!(episodes.loads.get(), 0);
+
+    // FIRST_N stops streaming once full
+    let first = def(
+        vec![root],
+        Expr::compare(ExprOp::Ge, FieldPath::episode(&[5]), Operand::I64(0)),
+        SamplingDef::first_n(3, seed),
+        None,
+    );
+    let selected = select_episodes(&first, &episodes, store).unwrap();
+    let mut sorted = ids.clone();
+    sorted.sort();
+    assert_eq!(selected, sorted[..3]);
+    assert_eq!(episodes.loads.get(), 3);
+}
+
+#[test]
+fn test_stratified_build() {
+    let temp = TempStore::new("stratified");
+    let store = &temp.store;
+    let (episodes, ids) = Episodes::new(90);
+    let root = put_corpus(store, &ids);
+
+    let stratify = StratifyDef {
+        key_path: FieldPath::signal(&[4]),
+        per_bucket_n: 2,
+        seed: cid_from_bytes(b"strata"),
+    };
+    let def = def(
+        vec![root],
+        Expr::constant(true),
+        SamplingDef::all(cid_from_bytes(b"seed")),
+        Some(stratify),
+    );
+
+    let selected = select_episodes(&def, &episodes, store).unwrap();
+    assert_eq!(selected.len(), 6);
+    for kind in 0..3 {
+        let in_bucket = selected
+            .iter()
+            .filter(|id| ids.iter().position(|x| x == *id).unwrap() % 3 == kind)
+            .count();
+        assert_eq!(in_bucket, 2, "bucket {}", kind);
+    }
+}
+
+#[test]
+fn test_build_errors() {
+    let temp = TempStore::new("errors");
+    let store = &temp.store;
+    let (episodes, ids) = Episodes::new(5);
+    let root = put_corpus(store, &ids);
+    let seed = cid_from_bytes(b"seed");
+
+    // Nothing selected
+    let none = def(
+        vec![root],
+        Expr::constant(false),
+        SamplingDef::all(seed),
+        None,
+    );
+    assert_eq!(
+        build_dataset(&none, &episodes, store, &options()),
+        Err(Error::EmptyDataset)
+    );
+
+    // The predicate reads an episode the source does not have
+    let reads_episode = def(
+        vec![root],
+        Expr::eq(FieldPath::episode(&[5]), Operand::I64(0)),
+        SamplingDef::all(seed),
+        None,
+    );
+    assert!(matches!(
+        select_episodes(&reads_episode, &NoEpisodeData, store),
+        Err(Error::EpisodeNotFound(_))
+    ));
+
+    // A corpus root that is not in the store
+    let absent = def(
+        vec![cid_from_bytes(b"absent")],
+        Expr::constant(true),
+        SamplingDef::all(seed),
+        None,
+    );
+    assert!(matches!(
+        select_episodes(&absent, &episodes, store),
+        Err(Error::Store(_))
+    ));
+
+    // A corpus root that is not a MerkleList node
+    let not_a_list = store.put(b"not a node").unwrap();
+    let not_a_list = def(
+        vec![not_a_list],
+        Expr::constant(true),
+        SamplingDef::all(seed),
+        None,
+    );
+    assert!(matches!(
+        select_episodes(&not_a_list, &episodes, store),
+        Err(Error::InvalidCorpus(_))
+    ));
+}
+
+#[test]
+fn test_build_from_store_episodes() {
+    let temp = TempStore::new("store-episodes");
+    let store = &temp.store;
+    let key = SigningKey::from_bytes(&[1; 32]);
+    let tool = cid_from_bytes(b"tool");
+    let mut index = EpisodeIndex::new();
+
+    // Episode i used `tool` when even, and carries a Reward when i % 3 == 0
+    let mut ids = Vec::new();
+    let mut matching = Vec::new();
+    for i in 0..12u8 {
+        let receipt = Receipt {
+            tool_id: if i % 2 == 0 { tool } else { [i; 32] }.to_vec(),
+            request_hash: vec![i; 32],
+            response_hash: vec![i; 32],
+            idempotency_key: vec![i; 32],
+            signer: ed25519_agent(&key.verifying_key()),
+            time_us: i as i64,
+            status: 200,
+            evidence: None,
+            notes: None,
+        };
+        let receipt = SignedReceipt::sign(receipt, &key).unwrap();
+        put_receipt(store, &mut index, &receipt).unwrap();
+        let trace = TraceRef {
+            trace_blob: BlobRef {
+                cid: cid_from_bytes(&[i]).to_vec(),
+                size: 1,
+                media: "application/mythos.trace".into(),
+                codec: CODEC_RAW,
+                chunks: 0,
+                encryption: None,
+                provenance: None,
+            },
+            receipt_ids: vec![receipt.receipt_id],
+        };
+        let episode = EpisodeRef::new(trace, [i; 32], [i; 32], i as i64).unwrap();
+        let cid = put_episode(store, &episode).unwrap();
+        index.add_episode(cid, &episode);
+
+        let signal_type = if i % 3 == 0 {
+            SignalType::Reward
+        } else {
+            SignalType::Label
+        };
+        let signal = Signal::sign(episode.episode_id, signal_type, 1, vec![i], 0, &key).unwrap();
+        attach_signal(store, &mut index, &signal).unwrap();
+
+        ids.push(episode.episode_id);
+        if i % 6 == 0 {
+            matching.push(episode.episode_id);
+        }
+    }
+    matching.sort();
+
+    let root = put_corpus(store, &ids);
+    let predicate = Expr::and(vec![
+        Expr::has_tool(tool),
+        Expr::eq(
+            FieldPath::signal(&[3]),
+            Operand::U64(SignalType::Reward as u64),
+        ),
+    ]);
+    let def = def(
+        vec![root],
+        predicate,
+        SamplingDef::all(cid_from_bytes(b"seed")),
+        None,
+    );
+
+    let episodes = StoreEpisodes {
+        store,
+        index: &index,
+    };
+    assert_eq!(select_episodes(&def, &episodes, store).unwrap(), matching);
+    let dataset = build_dataset(&def, &episodes, store, &options()).unwrap();
+    assert_eq!(dataset.count, matching.len() as u64);
+
+    // A receipt id the index cannot resolve fails the load
+    let unresolved = EpisodeRef::new(
+        TraceRef {
+            receipt_ids: vec![[0xee; 32]],
+            ..index
+                .load_episode(store, &ids[0])
+                .unwrap()
+                .unwrap()
+                .trace_ref
+        },
+        [0xee; 32],
+        [0xee; 32],
+        0,
+    )
+    .unwrap();
+    let cid = put_episode(store, &unresolved).unwrap();
+    index.add_episode(cid, &unresolved);
+    let episodes = StoreEpisodes {
+        store,
+        index: &index,
+    };
+    assert!(matches!(
+        episodes.receipts(&unresolved.episode_id),
+        Err(Error::ReceiptNotFound(_))
+    ));
+}
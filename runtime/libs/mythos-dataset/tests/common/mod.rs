@@ -0,0 +1,97 @@
+// Fixtures shared by the mythos-dataset integration tests
+#![allow(dead_code)]
+
+use ed25519_dalek::SigningKey;
+use mythos_cas::{put_nodes, Cid, FsStore};
+use mythos_dataset::{
+    build_dataset, cid_from_bytes, BuildOptions, DatasetDef, DatasetRef, EpisodeId, Expr, QueryDef,
+    SamplingDef, StoreEpisodes,
+};
+use mythos_episode::EpisodeIndex;
+use mythos_merkle::{build_merkle_list, cid_value, HashValue};
+use std::fs;
+use std::path::PathBuf;
+
+/// Fresh store directory, removed when dropped
+pub struct TempStore {
+    pub path: PathBuf,
+    pub store: FsStore,
+}
+
+impl TempStore {
+    pub fn new(name: &str) -> Self {
+        let path =
+            std::env::temp_dir().join(format!("mythos-dataset-{}-{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&path);
+        let store = FsStore::open(&path).unwrap();
+        TempStore { path, store }
+    }
+}
+
+impl Drop for TempStore {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
+
+/// A store holding some episodes and a dataset over all of them
+pub struct Fixture {
+    pub temp: TempStore,
+    pub index: EpisodeIndex,
+    pub def: DatasetDef,
+    pub dataset: DatasetRef,
+    pub dataset_cid: Cid,
+    /// Manifest order
+    pub ids: Vec<EpisodeId>,
+}
+
+/// Store episodes with `add`, which returns their IDs, then build a
+/// dataset sampling all of them from one corpus
+pub fn fixture<F>(name: &str, add: F) -> Fixture
+where
+    F: FnOnce(&FsStore, &mut EpisodeIndex) -> Vec<EpisodeId>,
+{
+    let temp = TempStore::new(name);
+    let store = &temp.store;
+    let mut index = EpisodeIndex::new();
+    let mut ids = add(store, &mut index);
+    ids.sort();
+
+    let values: Vec<HashValue> = ids.iter().map(cid_value).collect();
+    let corpus = build_merkle_list(&values).unwrap();
+    put_nodes(store, &corpus.nodes).unwrap();
+    let def = DatasetDef::new(
+        vec![corpus.root],
+        QueryDef::new(Expr::constant(true)).unwrap(),
+        SamplingDef::all(cid_from_bytes(b"seed")),
+        None,
+    )
+    .unwrap();
+    let dataset = build_dataset(
+        &def,
+        &StoreEpisodes {
+            store,
+            index: &index,
+        },
+        store,
+        &build_options(),
+    )
+    .unwrap();
+    let dataset_cid = cid_from_bytes(&dataset.to_bytes().unwrap());
+
+    Fixture {
+        temp,
+        index,
+        def,
+        dataset,
+        dataset_cid,
+        ids,
+    }
+}
+
+pub fn build_options() -> BuildOptions {
+    BuildOptions {
+        key: SigningKey::from_bytes(&[7; 32]),
+        time_us: 0,
+    }
+}
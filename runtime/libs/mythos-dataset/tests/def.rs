@@ -0,0 +1,210 @@
+/// Typed DatasetDef / Expr tests
+use mythos_can::Value;
+use mythos_dataset::{
+    cid_from_bytes, compute_dataset_def_id, DatasetDef, Error, Expr, ExprOp, FieldPath, Operand,
+    QueryDef, SamplingDef, SamplingMode, StratifyDef, MAX_EXPR_DEPTH,
+};
+use std::fs;
+
+const VECTORS_PATH: &str = "../../../mythos-v0.2-conformance/vectors/dataset";
+
+fn map(fields: Vec<(u64, Value)>) -> Value {
+    Value::Map(
+        fields
+            .into_iter()
+            .map(|(k, v)| (Value::UVarint(k), v))
+            .collect(),
+    )
+}
+
+fn hash(bytes: &[u8; 32]) -> Value {
+    map(vec![
+        (1, Value::UVarint(1)),
+        (2, Value::Bytes(bytes.to_vec())),
+    ])
+}
+
+fn predicate() -> Expr {
+    Expr::and(vec![
+        Expr::has_status(200),
+        Expr::negate(Expr::eq(FieldPath::episode(&[5]), Operand::I64(0))),
+        Expr::in_set(
+            FieldPath::signal(&[3]),
+            vec![Operand::U64(1), Operand::U64(2)],
+        ),
+        Expr::has_tool(cid_from_bytes(b"tool")),
+    ])
+}
+
+#[test]
+fn test_dataset_001_typed_roundtrip() {
+    let def_bin = fs::read(format!("{}/dataset_001_def.bin", VECTORS_PATH)).unwrap();
+    let expected = fs::read_to_string(format!("{}/dataset_001_defid.hex", VECTORS_PATH)).unwrap();
+
+    let def = DatasetDef::from_bytes(&def_bin).unwrap();
+    assert_eq!(hex::encode(def.dataset_def_id), expected.trim());
+    assert_eq!(def.query.predicate, Expr::constant(true));
+    assert_eq!(def.sampling.mode, SamplingMode::HashN);
+    assert_eq!(def.to_bytes().unwrap(), def_bin);
+
+    // Built in code, the same definition gets the same bytes
+    let built = DatasetDef::new(
+        def.corpus_roots.clone(),
+        QueryDef::new(Expr::constant(true)).unwrap(),
+        SamplingDef::hash_n(def.sampling.n.unwrap(), def.sampling.seed),
+        None,
+    )
+    .unwrap();
+    assert_eq!(built, def);
+}
+
+#[test]
+fn test_build_in_code() {
+    let query = QueryDef::new(predicate()).unwrap();
+    let def = DatasetDef::new(
+        vec![cid_from_bytes(b"corpus")],
+        query,
+        SamplingDef::first_n(10, cid_from_bytes(b"seed")),
+        Some(StratifyDef {
+            key_path: FieldPath::episode(&[2, 1, 3]),
+            per_bucket_n: 4,
+            seed: cid_from_bytes(b"bucket seed"),
+        }),
+    )
+    .unwrap();
+
+    let bytes = def.to_bytes().unwrap();
+    assert_eq!(DatasetDef::from_bytes(&bytes).unwrap(), def);
+
+    // The untyped helper agrees
+    let value = mythos_can::decode_value_exact(&bytes).unwrap();
+    assert_eq!(compute_dataset_def_id(&value).unwrap(), def.dataset_def_id);
+
+    let expr_bytes = def.query.predicate.to_bytes().unwrap();
+    assert_eq!(Expr::from_bytes(&expr_bytes).unwrap(), predicate());
+}
+
+#[test]
+fn test_ids_are_checked() {
+    let mut def = DatasetDef::new(
+        vec![cid_from_bytes(b"corpus")],
+        QueryDef::new(Expr::constant(false)).unwrap(),
+        SamplingDef::all(cid_from_bytes(b"seed")),
+        None,
+    )
+    .unwrap();
+    def.corpus_roots.push(cid_from_bytes(b"other corpus"));
+    let stale = def.to_bytes().unwrap();
+    assert!(matches!(
+        DatasetDef::from_bytes(&stale),
+        Err(Error::IdMismatch {
+            name: "dataset_def_id",
+            ..
+        })
+    ));
+
+    let mut query = QueryDef::new(Expr::constant(true)).unwrap();
+    query.predicate = Expr::constant(false);
+    assert!(matches!(
+        QueryDef::from_value(&query.to_value()),
+        Err(Error::IdMismatch {
+            name: "query_id",
+            ..
+        })
+    ));
+}
+
+#[test]
+fn test_sampling_validation() {
+    let seed = hash(&cid_from_bytes(b"seed"));
+    let hash_n_without_n = map(vec![(1, Value::UVarint(3)), (3, seed.clone())]);
+    assert!(matches!(
+        SamplingDef::from_value(&hash_n_without_n),
+        Err(Error::InvalidSampling(_))
+    ));
+
+    let all_with_n = map(vec![
+        (1, Value::UVarint(1)),
+        (2, Value::UVarint(5)),
+        (3, seed.clone()),
+    ]);
+    assert!(SamplingDef::from_value(&all_with_n).is_err());
+
+    let unknown = map(vec![(1, Value::UVarint(9)), (3, seed)]);
+    assert_eq!(
+        SamplingDef::from_value(&unknown),
+        Err(Error::UnknownSamplingMode(9))
+    );
+}
+
+#[test]
+fn test_corpus_roots_must_be_hashes() {
+    let def = DatasetDef::new(
+        vec![cid_from_bytes(b"corpus")],
+        QueryDef::new(Expr::constant(true)).unwrap(),
+        SamplingDef::all(cid_from_bytes(b"seed")),
+        None,
+    )
+    .unwrap();
+    let Value::Map(mut fields) = def.to_value() else {
+        unreachable!()
+    };
+    fields[1].1 = Value::List(vec![Value::Bytes(cid_from_bytes(b"corpus").to_vec())]);
+    assert!(matches!(
+        DatasetDef::from_value(&Value::Map(fields.clone())),
+        Err(Error::InvalidHash(_))
+    ));
+
+    fields[1].1 = Value::List(vec![map(vec![
+        (1, Value::UVarint(2)),
+        (2, Value::Bytes(vec![0; 32])),
+    ])]);
+    assert!(matches!(
+        DatasetDef::from_value(&Value::Map(fields)),
+        Err(Error::InvalidHash(_))
+    ));
+}
+
+#[test]
+fn test_expr_validation() {
+    assert_eq!(
+        Expr::from_value(&map(vec![(1, Value::UVarint(50))])),
+        Err(Error::UnknownOp(50))
+    );
+
+    // A comparison without a literal
+    let mut missing_lit = Expr::compare(ExprOp::Lt, FieldPath::episode(&[5]), Operand::I64(1));
+    missing_lit.lit = None;
+    assert!(matches!(missing_lit.validate(), Err(Error::InvalidExpr(_))));
+
+    assert!(Expr::and(vec![]).validate().is_err());
+    assert!(Expr::negate(Expr::constant(true)).validate().is_ok());
+
+    let mut two_args = Expr::negate(Expr::constant(true));
+    two_args.args.as_mut().unwrap().push(Expr::constant(false));
+    assert!(two_args.validate().is_err());
+
+    let mut bad_status = Expr::has_status(200);
+    bad_status.lit = Some(Operand::U64(70_000));
+    assert!(bad_status.validate().is_err());
+
+    assert!(FieldPath::episode(&[]).validate().is_err());
+
+    // IR-only operand kinds are not literals
+    let reg = map(vec![(1, Value::UVarint(1)), (2, Value::UVarint(0))]);
+    let expr = map(vec![
+        (1, Value::UVarint(10)),
+        (3, FieldPath::episode(&[1]).to_value()),
+        (4, reg),
+    ]);
+    assert!(matches!(
+        Expr::from_value(&expr),
+        Err(Error::InvalidOperand(_))
+    ));
+
+    let mut deep = Expr::constant(true);
+    for _ in 0..MAX_EXPR_DEPTH {
+        deep = Expr::negate(deep);
+    }
+    assert!(Expr::from_value(&deep.to_value()).is_err());
+}
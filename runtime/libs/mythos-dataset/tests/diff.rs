@@ -0,0 +1,183 @@
+/// Manifest diff tests
+use ed25519_dalek::SigningKey;
+use mythos_cas::{put_nodes, BlobStore, FsStore};
+use mythos_dataset::{
+    build_dataset, cid_from_bytes, diff_datasets, diff_manifests, BuildOptions, DatasetDef,
+    DiffDetail, EpisodeId, Error, Expr, ManifestDiff, NoEpisodeData, QueryDef, SamplingDef,
+};
+use mythos_merkle::{build_merkle_list, cid_value, HashValue};
+use std::fs;
+
+const VECTORS_PATH: &str = "../../../mythos-v0.2-conformance/vectors/dataset";
+
+mod common;
+use common::TempStore;
+
+fn id(n: u32) -> EpisodeId {
+    cid_from_bytes(&n.to_be_bytes())
+}
+
+fn sorted(range: impl Iterator<Item = u32>) -> Vec<EpisodeId> {
+    let mut ids: Vec<_> = range.map(id).collect();
+    ids.sort();
+    ids
+}
+
+/// Store a MerkleList over `ids` in the given order, returning its root
+fn put_list(store: &FsStore, ids: &[EpisodeId]) -> [u8; 32] {
+    let values: Vec<HashValue> = ids.iter().map(cid_value).collect();
+    let list = build_merkle_list(&values).unwrap();
+    put_nodes(store, &list.nodes).unwrap();
+    list.root
+}
+
+#[test]
+fn test_overlap_counts() {
+    let temp = TempStore::new("counts");
+    let store = &temp.store;
+
+    // A is 0..3000, B is 2000..2500 and 5000..5100; both span several leaves
+    let a_ids = sorted(0..3000);
+    let b_ids = sorted((2000..2500).chain(5000..5100));
+    let a = put_list(store, &a_ids);
+    let b = put_list(store, &b_ids);
+
+    let summary = diff_manifests(store, &a, &b, DiffDetail::Summary).unwrap();
+    assert_eq!(summary.a_count, 3000);
+    assert_eq!(summary.b_count, 600);
+    assert_eq!(summary.overlap, 500);
+    assert_eq!(summary.only_a_count(), 2500);
+    assert_eq!(summary.only_b_count(), 100);
+    assert!((summary.jaccard() - 500.0 / 3100.0).abs() < 1e-12);
+    assert!(summary.shared.is_empty() && summary.only_a.is_empty() && summary.only_b.is_empty());
+    assert_eq!(summary.ensure_disjoint(), Err(Error::Overlap(500)));
+
+    let full = diff_manifests(store, &a, &b, DiffDetail::Full).unwrap();
+    assert_eq!(full.shared, sorted(2000..2500));
+    assert_eq!(full.only_a, sorted((0..2000).chain(2500..3000)));
+    assert_eq!(full.only_b, sorted(5000..5100));
+    assert_eq!(
+        ManifestDiff {
+            shared: Vec::new(),
+            only_a: Vec::new(),
+            only_b: Vec::new(),
+            ..full.clone()
+        },
+        summary
+    );
+
+    // Swapping the sides swaps the report
+    let swapped = diff_manifests(store, &b, &a, DiffDetail::Full).unwrap();
+    assert_eq!(swapped.only_a, full.only_b);
+    assert_eq!(swapped.only_b, full.only_a);
+    assert_eq!(swapped.jaccard(), full.jaccard());
+}
+
+#[test]
+fn test_identical_and_disjoint() {
+    let temp = TempStore::new("edges");
+    let store = &temp.store;
+    let a = put_list(store, &sorted(0..10));
+    let b = put_list(store, &sorted(10..20));
+
+    let same = diff_manifests(store, &a, &a, DiffDetail::Full).unwrap();
+    assert_eq!(same.overlap, 10);
+    assert_eq!(same.jaccard(), 1.0);
+    assert!(same.only_a.is_empty() && same.only_b.is_empty());
+
+    let apart = diff_manifests(store, &a, &b, DiffDetail::Summary).unwrap();
+    assert!(apart.is_disjoint());
+    assert_eq!(apart.jaccard(), 0.0);
+    apart.ensure_disjoint().unwrap();
+}
+
+#[test]
+fn test_report() {
+    let temp = TempStore::new("report");
+    let store = &temp.store;
+    let a = put_list(store, &sorted(0..3));
+    let b = put_list(store, &sorted(2..4));
+
+    let summary = diff_manifests(store, &a, &b, DiffDetail::Summary).unwrap();
+    assert_eq!(
+        summary.report(),
+        "a_count: 3\nb_count: 2\noverlap: 1\nonly_a: 2\nonly_b: 1\njaccard: 0.250000\n"
+    );
+
+    let full = diff_manifests(store, &a, &b, DiffDetail::Full).unwrap();
+    let expected = format!(
+        "{}\nshared:\n{}\n\nonly_a:\n{}\n\nonly_b:\n{}\n",
+        summary.report(),
+        hex::encode(id(2)),
+        sorted(0..2)
+            .iter()
+            .map(hex::encode)
+            .collect::<Vec<_>>()
+            .join("\n"),
+        hex::encode(id(3)),
+    );
+    assert_eq!(full.report(), expected);
+}
+
+#[test]
+fn test_diff_datasets() {
+    let temp = TempStore::new("datasets");
+    let store = &temp.store;
+    let corpus = put_list(store, &sorted(0..50));
+    let options = BuildOptions {
+        key: SigningKey::from_bytes(&[7; 32]),
+        time_us: 0,
+    };
+    let build = |sampling| {
+        let def = DatasetDef::new(
+            vec![corpus],
+            QueryDef::new(Expr::constant(true)).unwrap(),
+            sampling,
+            None,
+        )
+        .unwrap();
+        build_dataset(&def, &NoEpisodeData, store, &options).unwrap()
+    };
+
+    let train = build(SamplingDef::hash_n(30, cid_from_bytes(b"train")));
+    let eval = build(SamplingDef::all(cid_from_bytes(b"eval")));
+    let diff = diff_datasets(store, &train, &eval, DiffDetail::Summary).unwrap();
+    assert_eq!((diff.a_count, diff.b_count, diff.overlap), (30, 50, 30));
+
+    // A DatasetRef whose count disagrees with its manifest
+    let mut wrong = train.clone();
+    wrong.count = 31;
+    assert!(matches!(
+        diff_datasets(store, &wrong, &eval, DiffDetail::Summary),
+        Err(Error::InvalidCorpus(_))
+    ));
+}
+
+#[test]
+fn test_unsorted_manifest_rejected() {
+    let temp = TempStore::new("unsorted");
+    let store = &temp.store;
+    let sorted_root = put_list(store, &sorted(0..5));
+
+    let mut ids = sorted(0..5);
+    ids.swap(1, 3);
+    let unsorted = put_list(store, &ids);
+    assert!(matches!(
+        diff_manifests(store, &sorted_root, &unsorted, DiffDetail::Summary),
+        Err(Error::InvalidCorpus(_))
+    ));
+
+    // The DATASET_001 corpus is not ascending, so it is no manifest
+    let corpus = fs::read(format!("{}/dataset_001_corpus_rootnode.bin", VECTORS_PATH)).unwrap();
+    let corpus = store.put(&corpus).unwrap();
+    assert!(matches!(
+        diff_manifests(store, &corpus, &sorted_root, DiffDetail::Summary),
+        Err(Error::InvalidCorpus(_))
+    ));
+
+    let missing = cid_from_bytes(b"missing");
+    assert!(matches!(
+        diff_manifests(store, &sorted_root, &missing, DiffDetail::Summary),
+        Err(Error::Store(_))
+    ));
+}
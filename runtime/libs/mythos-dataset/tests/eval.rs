@@ -0,0 +1,221 @@
+/// Expr evaluation tests (RFC-0003 §4.4)
+use mythos_can::Value;
+use mythos_dataset::{
+    cid_from_bytes, Error, EvalContext, Expr, ExprOp, FieldPath, Operand, QueryDef,
+};
+
+fn map(fields: Vec<(u64, Value)>) -> Value {
+    Value::Map(
+        fields
+            .into_iter()
+            .map(|(k, v)| (Value::UVarint(k), v))
+            .collect(),
+    )
+}
+
+fn hash(bytes: &[u8; 32]) -> Value {
+    map(vec![
+        (1, Value::UVarint(1)),
+        (2, Value::Bytes(bytes.to_vec())),
+    ])
+}
+
+fn context_hash() -> [u8; 32] {
+    cid_from_bytes(b"context")
+}
+
+fn tool() -> [u8; 32] {
+    cid_from_bytes(b"tool")
+}
+
+/// EpisodeRef with a nested TraceRef and a time of -5
+fn episode() -> Value {
+    map(vec![
+        (1, hash(&cid_from_bytes(b"episode"))),
+        (
+            2,
+            map(vec![
+                (1, map(vec![(2, Value::UVarint(4096))])),
+                (2, Value::List(vec![])),
+            ]),
+        ),
+        (3, hash(&context_hash())),
+        (4, hash(&cid_from_bytes(b"outcome"))),
+        (5, Value::IVarint(-5)),
+    ])
+}
+
+/// A reward signal (value_kind 1) and a label signal (3)
+fn signals() -> Vec<Value> {
+    vec![
+        map(vec![(3, Value::UVarint(1)), (4, Value::UVarint(1))]),
+        map(vec![(3, Value::UVarint(2)), (4, Value::UVarint(3))]),
+    ]
+}
+
+fn receipts() -> Vec<Value> {
+    vec![
+        map(vec![(2, hash(&tool())), (8, Value::UVarint(200))]),
+        map(vec![
+            (2, hash(&cid_from_bytes(b"other"))),
+            (8, Value::UVarint(404)),
+        ]),
+    ]
+}
+
+fn ep(segments: &[u32]) -> FieldPath {
+    FieldPath::episode(segments)
+}
+
+fn cmp(op: ExprOp, path: FieldPath, lit: Operand) -> Expr {
+    Expr::compare(op, path, lit)
+}
+
+#[test]
+fn test_ops_table() {
+    use ExprOp::*;
+    use Operand::*;
+
+    let episode = episode();
+    let signals = signals();
+    let receipts = receipts();
+    let context = EvalContext {
+        episode: &episode,
+        signals: &signals,
+        receipts: &receipts,
+    };
+
+    let missing = || ep(&[9]);
+    let cases: Vec<(&str, Expr, bool)> = vec![
+        ("true", Expr::constant(true), true),
+        ("false", Expr::constant(false), false),
+        // Integers, including across signedness
+        ("eq i64", cmp(Eq, ep(&[5]), I64(-5)), true),
+        ("ne i64", cmp(Ne, ep(&[5]), I64(-5)), false),
+        ("lt", cmp(Lt, ep(&[5]), I64(0)), true),
+        ("le equal", cmp(Le, ep(&[5]), I64(-5)), true),
+        ("gt", cmp(Gt, ep(&[5]), I64(-6)), true),
+        ("ge", cmp(Ge, ep(&[5]), I64(-4)), false),
+        ("ivarint vs u64", cmp(Lt, ep(&[5]), U64(0)), true),
+        ("nested field", cmp(Eq, ep(&[2, 1, 2]), U64(4096)), true),
+        ("nested gt", cmp(Gt, ep(&[2, 1, 2]), U64(4096)), false),
+        // Hashes
+        ("hash eq", cmp(Eq, ep(&[3]), Hash(context_hash())), true),
+        ("hash ne", cmp(Ne, ep(&[4]), Hash(context_hash())), true),
+        // Missing: every comparison FALSE except NE
+        ("missing eq", cmp(Eq, missing(), U64(0)), false),
+        ("missing ne", cmp(Ne, missing(), U64(0)), true),
+        ("missing lt", cmp(Lt, missing(), U64(0)), false),
+        ("missing le", cmp(Le, missing(), U64(0)), false),
+        ("missing gt", cmp(Gt, missing(), U64(0)), false),
+        ("missing ge", cmp(Ge, missing(), U64(0)), false),
+        (
+            "missing in set",
+            Expr::in_set(missing(), vec![U64(0)]),
+            false,
+        ),
+        ("path through non-map", cmp(Eq, ep(&[5, 1]), I64(-5)), false),
+        ("ne through non-map", cmp(Ne, ep(&[5, 1]), I64(-5)), true),
+        // Type mismatch is unequal and unordered
+        ("type mismatch eq", cmp(Eq, ep(&[3]), U64(1)), false),
+        ("type mismatch ne", cmp(Ne, ep(&[3]), U64(1)), true),
+        ("type mismatch lt", cmp(Lt, ep(&[3]), U64(1)), false),
+        // Sets
+        (
+            "in set",
+            Expr::in_set(ep(&[5]), vec![U64(1), I64(-5)]),
+            true,
+        ),
+        ("not in set", Expr::in_set(ep(&[5]), vec![U64(1)]), false),
+        ("empty set", Expr::in_set(ep(&[5]), vec![]), false),
+        // Signals: any attached Signal
+        ("signal eq", cmp(Eq, FieldPath::signal(&[4]), U64(3)), true),
+        ("signal gt", cmp(Gt, FieldPath::signal(&[4]), U64(3)), false),
+        (
+            "signal missing",
+            cmp(Eq, FieldPath::signal(&[7]), U64(0)),
+            false,
+        ),
+        (
+            "signal missing ne",
+            cmp(Ne, FieldPath::signal(&[7]), U64(0)),
+            true,
+        ),
+        // Receipts
+        ("has tool", Expr::has_tool(tool()), true),
+        ("lacks tool", Expr::has_tool(cid_from_bytes(b"nope")), false),
+        ("has status", Expr::has_status(404), true),
+        ("lacks status", Expr::has_status(500), false),
+        // Boolean connectives
+        (
+            "and",
+            Expr::and(vec![Expr::constant(true), Expr::has_status(200)]),
+            true,
+        ),
+        (
+            "and short",
+            Expr::and(vec![Expr::has_status(200), Expr::constant(false)]),
+            false,
+        ),
+        (
+            "or",
+            Expr::or(vec![Expr::constant(false), Expr::has_status(200)]),
+            true,
+        ),
+        (
+            "or none",
+            Expr::or(vec![Expr::constant(false), Expr::has_status(1)]),
+            false,
+        ),
+        ("not", Expr::negate(Expr::constant(false)), true),
+        (
+            "not missing eq",
+            Expr::negate(cmp(Eq, missing(), U64(0))),
+            true,
+        ),
+    ];
+
+    for (name, expr, expected) in cases {
+        expr.validate()
+            .unwrap_or_else(|e| panic!("{}: {}", name, e));
+        assert_eq!(expr.evaluate(&context).unwrap(), expected, "{}", name);
+    }
+}
+
+#[test]
+fn test_without_signals_or_receipts() {
+    let episode = episode();
+    let context = EvalContext::episode(&episode);
+
+    assert!(!Expr::has_status(200).evaluate(&context).unwrap());
+    assert!(!Expr::has_tool(tool()).evaluate(&context).unwrap());
+    let ne = Expr::compare(ExprOp::Ne, FieldPath::signal(&[4]), Operand::U64(3));
+    assert!(ne.evaluate(&context).unwrap());
+}
+
+#[test]
+fn test_disallowed_ops_rejected() {
+    let ordered_hash = Expr::compare(ExprOp::Lt, ep(&[3]), Operand::Hash(context_hash()));
+    assert!(matches!(
+        ordered_hash.validate(),
+        Err(Error::InvalidExpr(_))
+    ));
+    assert!(QueryDef::new(ordered_hash.clone()).is_err());
+
+    let mut status_hash = Expr::has_status(200);
+    status_hash.lit = Some(Operand::Hash(tool()));
+    assert!(status_hash.validate().is_err());
+
+    // A malformed node fails evaluation rather than guessing
+    let episode = episode();
+    let mut no_args = Expr::and(vec![]);
+    no_args.args = None;
+    assert!(matches!(
+        no_args.evaluate(&EvalContext::episode(&episode)),
+        Err(Error::InvalidExpr(_))
+    ));
+    assert!(matches!(
+        ordered_hash.evaluate(&EvalContext::episode(&episode)),
+        Err(Error::InvalidExpr(_))
+    ));
+}
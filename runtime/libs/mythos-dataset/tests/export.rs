@@ -0,0 +1,330 @@
+/// JSON Lines export tests
+use ed25519_dalek::SigningKey;
+use mythos_blob::{BlobRef, ChunkedBlobBuilder, CODEC_RAW, CODEC_ZSTD, MIN_CHUNK_SIZE};
+use mythos_can::Value;
+use mythos_cas::{put_nodes, BlobStore, FsStore};
+use mythos_dataset::{
+    export_jsonl, parse_field_path, to_json, Error, ExportOptions, Schema, StoreEpisodes,
+    EXPORT_FORMAT,
+};
+use mythos_episode::{
+    attach_signal, put_episode, EpisodeIndex, EpisodeRef, Signal, SignalType, TraceRef,
+};
+use serde_json::json;
+use std::fs;
+
+mod common;
+use common::Fixture;
+
+fn chunked(store: &FsStore, data: &[u8], codec: u8) -> BlobRef {
+    let build = ChunkedBlobBuilder::new()
+        .chunk_size(MIN_CHUNK_SIZE)
+        .codec(codec)
+        .media("application/mythos.trace")
+        .build_with(data, |_, hash, bytes| {
+            store
+                .put_with_cid(hash, bytes)
+                .map_err(|e| mythos_blob::Error::Source(e.to_string()))
+        })
+        .unwrap();
+    put_nodes(store, &build.nodes).unwrap();
+    build.blob_ref
+}
+
+/// A store with three episodes and a dataset over all of them
+///
+/// Episode 0's trace is a MYTHOS-CAN MAP stored whole, episode 1's is
+/// text chunked and zstd-compressed, episode 2's is raw chunked bytes
+/// whose BlobRef has a zero chunk count.
+/// Episode i carries one Reward signal with value_bytes [i].
+fn fixture(name: &str) -> Fixture {
+    common::fixture(name, |store, index| {
+        let key = SigningKey::from_bytes(&[1; 32]);
+
+        let can_trace = mythos_can::encode_value(&Value::Map(vec![(
+            Value::Text("step".into()),
+            Value::UVarint(1),
+        )]))
+        .unwrap();
+        let text_trace = "tool call\n".repeat(2000);
+        let raw_trace: Vec<u8> = (0..3 * MIN_CHUNK_SIZE as u32)
+            .map(|i| (i % 251) as u8 | 0x80)
+            .collect();
+        let blobs = vec![
+            BlobRef {
+                cid: store.put(&can_trace).unwrap().to_vec(),
+                size: can_trace.len() as u64,
+                media: "application/mythos.trace".into(),
+                codec: CODEC_RAW,
+                chunks: 0,
+                encryption: None,
+                provenance: None,
+            },
+            chunked(store, text_trace.as_bytes(), CODEC_ZSTD),
+            // A zero count also stands for counts above u32::MAX
+            BlobRef {
+                chunks: 0,
+                ..chunked(store, &raw_trace, CODEC_RAW)
+            },
+        ];
+
+        let mut ids = Vec::new();
+        for (i, blob) in blobs.into_iter().enumerate() {
+            let trace = TraceRef {
+                trace_blob: blob,
+                receipt_ids: vec![],
+            };
+            let episode = EpisodeRef::new(trace, [i as u8; 32], [9; 32], i as i64).unwrap();
+            let cid = put_episode(store, &episode).unwrap();
+            index.add_episode(cid, &episode);
+            let signal = Signal::sign(
+                episode.episode_id,
+                SignalType::Reward,
+                1,
+                vec![i as u8],
+                0,
+                &key,
+            )
+            .unwrap();
+            attach_signal(store, index, &signal).unwrap();
+            ids.push(episode.episode_id);
+        }
+        ids
+    })
+}
+
+fn export(fixture: &Fixture, options: &ExportOptions) -> Result<Vec<serde_json::Value>, Error> {
+    let store = &fixture.temp.store;
+    let episodes = StoreEpisodes {
+        store,
+        index: &fixture.index,
+    };
+    let mut out = Vec::new();
+    let written = export_jsonl(&fixture.dataset, &episodes, store, options, &mut out)?;
+
+    let text = String::from_utf8(out).unwrap();
+    assert!(text.ends_with('\n'));
+    let lines: Vec<serde_json::Value> = text
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(lines.len() as u64, written + 1);
+    Ok(lines)
+}
+
+#[test]
+fn test_json_mapping() {
+    let value = Value::Map(vec![
+        (Value::UVarint(1), Value::Null),
+        (Value::UVarint(2), Value::Bool(true)),
+        (Value::UVarint(3), Value::UVarint(u64::MAX)),
+        (Value::UVarint(4), Value::IVarint(i64::MIN)),
+        (Value::UVarint(5), Value::Bytes(vec![0xab, 0x01])),
+        (Value::UVarint(6), Value::Text("a \"q\"\n\u{1}é".into())),
+        (
+            Value::UVarint(7),
+            Value::List(vec![Value::List(vec![]), Value::Map(vec![])]),
+        ),
+        (Value::Text("k".into()), Value::UVarint(0)),
+        (Value::Bytes(vec![0xff]), Value::UVarint(0)),
+    ]);
+    let text = to_json(&value).unwrap();
+    assert_eq!(
+        text,
+        "{\"1\":null,\"2\":true,\"3\":18446744073709551615,\"4\":-9223372036854775808,\
+         \"5\":\"ab01\",\"6\":\"a \\\"q\\\"\\n\\u0001é\",\"7\":[[],{}],\"k\":0,\"ff\":0}"
+    );
+    let parsed: serde_json::Value = serde_json::from_str(&text).unwrap();
+    assert_eq!(parsed["3"], json!(u64::MAX));
+    assert_eq!(parsed["6"], json!("a \"q\"\n\u{1}é"));
+
+    // Keys without a JSON form, or that collide
+    let bool_key = Value::Map(vec![(Value::Bool(true), Value::Null)]);
+    assert!(matches!(
+        to_json(&bool_key),
+        Err(Error::InvalidStructure(_))
+    ));
+    let colliding = Value::Map(vec![
+        (Value::UVarint(1), Value::Null),
+        (Value::Text("1".into()), Value::Null),
+    ]);
+    assert!(matches!(
+        to_json(&colliding),
+        Err(Error::InvalidStructure(_))
+    ));
+}
+
+#[test]
+fn test_export_full() {
+    let fixture = fixture("full");
+    let lines = export(&fixture, &ExportOptions::default()).unwrap();
+
+    let dataset = &fixture.dataset;
+    assert_eq!(
+        lines[0],
+        json!({
+            "format": EXPORT_FORMAT,
+            "dataset_def_id": hex::encode(dataset.dataset_def_id),
+            "manifest": hex::encode(&dataset.manifest.cid),
+            "receipt_id": hex::encode(dataset.receipt_id),
+            "count": 3,
+            "fields": null,
+        })
+    );
+
+    // Manifest order, each with its EpisodeRef, trace and signal
+    let store = &fixture.temp.store;
+    for (line, id) in lines[1..].iter().zip(&fixture.ids) {
+        assert_eq!(line["episode_id"], json!(hex::encode(id)));
+        let episode =
+            EpisodeRef::from_bytes(&store.get(&fixture.index.episode_cid(id).unwrap()).unwrap())
+                .unwrap();
+        let expected: serde_json::Value =
+            serde_json::from_str(&to_json(&episode.to_value()).unwrap()).unwrap();
+        assert_eq!(line["episode"], expected);
+
+        let n = episode.context_hash[0];
+        assert_eq!(line["signals"].as_array().unwrap().len(), 1);
+        assert_eq!(line["signals"][0]["5"], json!(hex::encode([n])));
+        assert_eq!(line["trace"]["media"], json!("application/mythos.trace"));
+        match n {
+            0 => assert_eq!(line["trace"]["value"], json!({"step": 1})),
+            1 => assert_eq!(
+                line["trace"]["bytes"],
+                json!(hex::encode("tool call\n".repeat(2000)))
+            ),
+            _ => assert_eq!(
+                line["trace"]["bytes"].as_str().unwrap().len(),
+                6 * MIN_CHUNK_SIZE as usize
+            ),
+        }
+    }
+}
+
+#[test]
+fn test_export_projection() {
+    let fixture = fixture("projection");
+    let schema = Schema::a15();
+    let fields = [
+        "episode.time_observed",
+        "episode.trace_ref.trace_blob.size",
+        "episode.trace_ref.trace_blob.media",
+        "signal.value_bytes",
+        "signal.99",
+    ]
+    .iter()
+    .map(|text| parse_field_path(text, &schema).unwrap())
+    .collect();
+    let options = ExportOptions {
+        fields: Some(fields),
+        skip_traces: true,
+    };
+    let lines = export(&fixture, &options).unwrap();
+
+    assert_eq!(
+        lines[0]["fields"],
+        json!([
+            "episode.5",
+            "episode.2.1.2",
+            "episode.2.1.3",
+            "signal.5",
+            "signal.99"
+        ])
+    );
+    for line in &lines[1..] {
+        let keys: Vec<_> = line.as_object().unwrap().keys().cloned().collect();
+        assert_eq!(keys, ["episode", "episode_id", "signals"]);
+        let episode = line["episode"].as_object().unwrap();
+        assert_eq!(episode.len(), 2);
+        assert!(line["episode"]["5"].is_i64());
+        assert_eq!(
+            line["episode"]["2"]["1"]["3"],
+            json!("application/mythos.trace")
+        );
+        assert_eq!(line["episode"]["2"]["1"].as_object().unwrap().len(), 2);
+        // Absent paths select nothing
+        assert_eq!(line["signals"][0].as_object().unwrap().len(), 1);
+    }
+
+    // A shorter path takes the whole subtree; no signal path drops signals
+    let options = ExportOptions {
+        fields: Some(vec![
+            parse_field_path("episode.trace_ref.receipt_ids", &schema).unwrap(),
+            parse_field_path("episode.trace_ref", &schema).unwrap(),
+        ]),
+        skip_traces: true,
+    };
+    let lines = export(&fixture, &options).unwrap();
+    for line in &lines[1..] {
+        assert!(line.get("signals").is_none());
+        assert!(line["episode"]["2"]["1"]["1"]["2"].is_string());
+        assert_eq!(line["episode"]["2"]["2"], json!([]));
+    }
+}
+
+#[test]
+fn test_export_errors() {
+    let fixture = fixture("errors");
+    let store = &fixture.temp.store;
+    let mut out = Vec::new();
+
+    // An index that lacks the episodes
+    let empty = EpisodeIndex::new();
+    let episodes = StoreEpisodes {
+        store,
+        index: &empty,
+    };
+    assert!(matches!(
+        export_jsonl(
+            &fixture.dataset,
+            &episodes,
+            store,
+            &ExportOptions::default(),
+            &mut out
+        ),
+        Err(Error::EpisodeNotFound(_))
+    ));
+
+    // A count the manifest does not hold
+    let mut wrong = fixture.dataset.clone();
+    wrong.count = 4;
+    let episodes = StoreEpisodes {
+        store,
+        index: &fixture.index,
+    };
+    assert!(matches!(
+        export_jsonl(
+            &wrong,
+            &episodes,
+            store,
+            &ExportOptions::default(),
+            &mut out
+        ),
+        Err(Error::InvalidCorpus(_))
+    ));
+
+    // A missing trace blob
+    let trace_cid = {
+        let episode = EpisodeRef::from_bytes(
+            &store
+                .get(&fixture.index.episode_cid(&fixture.ids[0]).unwrap())
+                .unwrap(),
+        )
+        .unwrap();
+        episode.trace_ref.trace_blob.cid.clone()
+    };
+    fs::remove_file(store.object_path(&trace_cid.try_into().unwrap())).unwrap();
+    assert!(export_jsonl(
+        &fixture.dataset,
+        &episodes,
+        store,
+        &ExportOptions::default(),
+        &mut out
+    )
+    .is_err());
+    let skip = ExportOptions {
+        skip_traces: true,
+        ..ExportOptions::default()
+    };
+    export_jsonl(&fixture.dataset, &episodes, store, &skip, &mut Vec::new()).unwrap();
+}
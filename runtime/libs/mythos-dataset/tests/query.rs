@@ -0,0 +1,214 @@
+/// Query language tests
+use mythos_dataset::{
+    cid_from_bytes, compile_query, format_field_path, format_query, parse_field_path, Error, Expr,
+    ExprOp, FieldPath, Operand, PathRoot, QueryDef, Schema, MAX_EXPR_DEPTH,
+};
+
+fn compile(text: &str) -> Expr {
+    compile_query(text, &Schema::a15()).unwrap()
+}
+
+fn syntax_offset(text: &str) -> usize {
+    match compile_query(text, &Schema::a15()) {
+        Err(Error::QuerySyntax { offset, .. }) => offset,
+        other => panic!("{:?} compiled to {:?}", text, other),
+    }
+}
+
+#[test]
+fn test_compile_example() {
+    let tool = cid_from_bytes(b"tool");
+    let text = format!(
+        "episode.5 >= 100 and has_tool(0x{}) and signal.3 in {{1,2}}",
+        hex::encode(tool)
+    );
+    let expected = Expr::and(vec![
+        Expr::compare(ExprOp::Ge, FieldPath::episode(&[5]), Operand::U64(100)),
+        Expr::has_tool(tool),
+        Expr::in_set(
+            FieldPath::signal(&[3]),
+            vec![Operand::U64(1), Operand::U64(2)],
+        ),
+    ]);
+    assert_eq!(compile(&text), expected);
+}
+
+#[test]
+fn test_equivalent_text_is_byte_identical() {
+    let tool = hex::encode(cid_from_bytes(b"tool"));
+    let spellings = [
+        format!(
+            "episode.time_observed >= 100 and has_tool(0x{}) and signal.signal_type in {{1, 2}}",
+            tool
+        ),
+        format!(
+            "(episode.5>=100 AND (has_tool(0X{}) and signal.3 in {{2,1,2}}))",
+            tool.to_uppercase()
+        ),
+        format!(
+            "episode . 5 >= 100\n  and (has_tool(0x{}))\n  and signal.3 IN {{ 2, 1 }}",
+            tool
+        ),
+    ];
+
+    let expected = compile(&spellings[0]);
+    let query_id = QueryDef::new(expected.clone()).unwrap().query_id;
+    for text in &spellings[1..] {
+        let expr = compile(text);
+        assert_eq!(
+            expr.to_bytes().unwrap(),
+            expected.to_bytes().unwrap(),
+            "{}",
+            text
+        );
+        assert_eq!(QueryDef::new(expr).unwrap().query_id, query_id);
+    }
+
+    assert_eq!(compile("episode.5 == -0"), compile("episode.5 == 0"));
+    assert_eq!(
+        compile("true or (false or true)"),
+        Expr::or(vec![
+            Expr::constant(true),
+            Expr::constant(false),
+            Expr::constant(true)
+        ])
+    );
+}
+
+#[test]
+fn test_precedence() {
+    let a = || Expr::eq(FieldPath::episode(&[1, 2]), Operand::U64(1));
+    let b = || Expr::eq(FieldPath::episode(&[5]), Operand::I64(-2));
+    let c = || Expr::has_status(200);
+
+    assert_eq!(
+        compile("episode.1.2 == 1 or episode.5 == -2 and has_status(200)"),
+        Expr::or(vec![a(), Expr::and(vec![b(), c()])])
+    );
+    assert_eq!(
+        compile("(episode.1.2 == 1 or episode.5 == -2) and has_status(200)"),
+        Expr::and(vec![Expr::or(vec![a(), b()]), c()])
+    );
+    assert_eq!(
+        compile("not episode.1.2 == 1 and not not has_status(200)"),
+        Expr::and(vec![Expr::negate(a()), Expr::negate(Expr::negate(c()))])
+    );
+}
+
+#[test]
+fn test_format_round_trip() {
+    let schema = Schema::a15();
+    let context = hex::encode(cid_from_bytes(b"context"));
+    let texts = [
+        "true".to_string(),
+        "episode.time_observed < -5".to_string(),
+        format!("episode.context_hash != 0x{}", context),
+        "episode.trace_ref.trace_blob.size > 4096 or signal.value_kind in {1, 3}".to_string(),
+        "(signal.signal_type == 1 or signal.signal_type == 2) and not (has_status(200) or false)"
+            .to_string(),
+        "not not episode.9.4 <= 18446744073709551615 and signal.99 in {}".to_string(),
+        "signal.signer.key in {7, -9223372036854775808}".to_string(),
+    ];
+    for text in &texts {
+        let expr = compile_query(text, &schema).unwrap();
+        let printed = format_query(&expr, &schema).unwrap();
+        assert_eq!(&printed, text);
+        assert_eq!(compile_query(&printed, &schema).unwrap(), expr);
+    }
+
+    // Without names, paths print as field numbers
+    let expr = compile("episode.trace_ref.trace_blob.size > 1");
+    assert_eq!(
+        format_query(&expr, &Schema::empty()).unwrap(),
+        "episode.2.1.2 > 1"
+    );
+
+    // Hand-built nesting keeps its parentheses
+    let nested = Expr::and(vec![
+        Expr::and(vec![Expr::constant(true), Expr::constant(false)]),
+        Expr::constant(true),
+    ]);
+    assert_eq!(
+        format_query(&nested, &schema).unwrap(),
+        "(true and false) and true"
+    );
+}
+
+#[test]
+fn test_custom_schema() {
+    let mut schema = Schema::empty();
+    schema.add_field(PathRoot::Signal, &[], "score", 5).unwrap();
+    schema.add_field(PathRoot::Signal, &[5], "raw", 1).unwrap();
+    assert_eq!(
+        compile_query("signal.score.raw == 3", &schema).unwrap(),
+        Expr::eq(FieldPath::signal(&[5, 1]), Operand::U64(3))
+    );
+    // Names are per root
+    assert!(compile_query("episode.score == 3", &schema).is_err());
+
+    assert!(schema.add_field(PathRoot::Signal, &[], "score", 6).is_err());
+    assert!(schema.add_field(PathRoot::Signal, &[], "other", 5).is_err());
+    assert!(schema.add_field(PathRoot::Signal, &[], "1st", 7).is_err());
+    assert!(schema.add_field(PathRoot::Signal, &[], "zero", 0).is_err());
+}
+
+#[test]
+fn test_syntax_errors() {
+    assert_eq!(syntax_offset(""), 0);
+    assert_eq!(syntax_offset("episode.5 >= "), 13);
+    assert_eq!(syntax_offset("episode.nope == 1"), 8);
+    assert_eq!(syntax_offset("episode == 1"), 8);
+    assert_eq!(syntax_offset("episode.0 == 1"), 8);
+    assert_eq!(syntax_offset("episode.5 = 1"), 10);
+    assert_eq!(syntax_offset("has_tool(0xab)"), 9);
+    assert_eq!(syntax_offset("has_status(70000)"), 11);
+    assert_eq!(syntax_offset("true false"), 5);
+    assert_eq!(syntax_offset("(true"), 5);
+    assert_eq!(syntax_offset("episode.5 == 99999999999999999999"), 13);
+    assert_eq!(syntax_offset("episode.5 == -9223372036854775809"), 13);
+    assert_eq!(syntax_offset("sample(3)"), 0);
+    assert_eq!(syntax_offset("true # comment"), 5);
+
+    let deep = format!(
+        "{}true{}",
+        "(".repeat(MAX_EXPR_DEPTH + 1),
+        ")".repeat(MAX_EXPR_DEPTH + 1)
+    );
+    assert!(matches!(
+        compile_query(&deep, &Schema::a15()),
+        Err(Error::QuerySyntax { .. })
+    ));
+
+    // Well-formed text can still be an invalid Expr
+    let hash = hex::encode([0; 32]);
+    assert!(matches!(
+        compile_query(&format!("episode.3 < 0x{}", hash), &Schema::a15()),
+        Err(Error::InvalidExpr(_))
+    ));
+}
+
+#[test]
+fn test_field_paths() {
+    let schema = Schema::a15();
+    let path = parse_field_path("episode.trace_ref.1.size", &schema).unwrap();
+    assert_eq!(path, FieldPath::episode(&[2, 1, 2]));
+    assert_eq!(
+        format_field_path(&path, &schema),
+        "episode.trace_ref.trace_blob.size"
+    );
+    assert_eq!(
+        parse_field_path("SIGNAL.signer.key", &schema).unwrap(),
+        FieldPath::signal(&[6, 2])
+    );
+
+    for bad in ["", "episode", "receipt.1", "signal.5 == 1", "episode.nope"] {
+        assert!(
+            matches!(
+                parse_field_path(bad, &schema),
+                Err(Error::QuerySyntax { .. })
+            ),
+            "{}",
+            bad
+        );
+    }
+}
@@ -0,0 +1,182 @@
+/// HASH_N and stratified sampling tests (RFC-0003 §5–6)
+use mythos_can::Value;
+use mythos_dataset::{
+    bucket_seed, cid_from_bytes, score, DatasetDef, EpisodeId, FieldPath, HashNSampler, Sampler,
+    SamplingDef, StratifiedSampler, StratifyDef,
+};
+use mythos_merkle::MerkleListNode;
+use std::fs;
+
+const VECTORS_PATH: &str = "../../../mythos-v0.2-conformance/vectors/dataset";
+
+fn ids(count: u32) -> Vec<EpisodeId> {
+    (0..count)
+        .map(|i| cid_from_bytes(format!("episode {}", i).as_bytes()))
+        .collect()
+}
+
+/// Deterministic shuffle (LCG-driven Fisher-Yates)
+fn shuffled(ids: &[EpisodeId], mut state: u64) -> Vec<EpisodeId> {
+    let mut out = ids.to_vec();
+    for i in (1..out.len()).rev() {
+        state = state
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        out.swap(i, (state >> 33) as usize % (i + 1));
+    }
+    out
+}
+
+fn hash_n(seed: [u8; 32], n: u64, ids: &[EpisodeId]) -> Vec<EpisodeId> {
+    let mut sampler = HashNSampler::new(seed, n);
+    ids.iter().for_each(|id| sampler.push(*id));
+    sampler.finish()
+}
+
+/// Reference implementation: score everything and sort
+fn hash_n_by_sort(seed: [u8; 32], n: usize, ids: &[EpisodeId]) -> Vec<EpisodeId> {
+    let mut scored: Vec<_> = ids.iter().map(|id| (score(&seed, id), *id)).collect();
+    scored.sort();
+    let mut selected: Vec<_> = scored.into_iter().take(n).map(|(_, id)| id).collect();
+    selected.sort();
+    selected
+}
+
+fn hex_ids(text: &str) -> Vec<String> {
+    text.split('"')
+        .filter(|s| s.len() == 64 && s.bytes().all(|b| b.is_ascii_hexdigit()))
+        .map(String::from)
+        .collect()
+}
+
+#[test]
+fn test_dataset_001_selection() {
+    let def =
+        DatasetDef::from_bytes(&fs::read(format!("{}/dataset_001_def.bin", VECTORS_PATH)).unwrap())
+            .unwrap();
+    let corpus = fs::read(format!("{}/dataset_001_corpus_rootnode.bin", VECTORS_PATH)).unwrap();
+    let episodes: Vec<EpisodeId> = match mythos_merkle::decode_merkle_list_node(&corpus).unwrap() {
+        MerkleListNode::Leaf(leaf) => leaf
+            .values
+            .iter()
+            .map(|h| h.bytes[..].try_into().unwrap())
+            .collect(),
+        MerkleListNode::Internal(_) => panic!("DATASET_001 corpus is a single leaf"),
+    };
+
+    let mut sampler = Sampler::new(&def.sampling).unwrap();
+    episodes.iter().for_each(|id| sampler.push(*id));
+    let selected: Vec<String> = sampler.finish().iter().map(hex::encode).collect();
+
+    let expected =
+        fs::read_to_string(format!("{}/dataset_001_selected.json", VECTORS_PATH)).unwrap();
+    let expected = hex_ids(&expected);
+    assert_eq!(hex::encode(def.sampling.seed), expected[0]);
+    assert_eq!(selected, expected[1..]);
+}
+
+#[test]
+fn test_hash_n_matches_full_sort() {
+    let seed = cid_from_bytes(b"seed");
+    let all = ids(2000);
+    for n in [0, 1, 7, 100, 1999, 2000, 5000] {
+        assert_eq!(
+            hash_n(seed, n, &all),
+            hash_n_by_sort(seed, n as usize, &all),
+            "n = {}",
+            n
+        );
+    }
+}
+
+#[test]
+fn test_order_independent() {
+    let seed = cid_from_bytes(b"seed");
+    let all = ids(5000);
+    let expected = hash_n(seed, 50, &all);
+
+    for state in 1..6 {
+        assert_eq!(hash_n(seed, 50, &shuffled(&all, state)), expected);
+    }
+    let mut reversed = all.clone();
+    reversed.reverse();
+    assert_eq!(hash_n(seed, 50, &reversed), expected);
+
+    // Duplicates across corpus roots count once
+    let mut doubled = all.clone();
+    doubled.extend(shuffled(&all, 9));
+    assert_eq!(hash_n(seed, 50, &doubled), expected);
+
+    // A different seed picks a different sample
+    assert_ne!(hash_n(cid_from_bytes(b"other"), 50, &all), expected);
+}
+
+#[test]
+fn test_first_n_and_all() {
+    let all = ids(10);
+    let seed = cid_from_bytes(b"seed");
+
+    let mut first = Sampler::new(&SamplingDef::first_n(3, seed)).unwrap();
+    for id in &all {
+        if first.is_full() {
+            break;
+        }
+        first.push(*id);
+    }
+    let mut expected = all[..3].to_vec();
+    expected.sort();
+    assert_eq!(first.finish(), expected);
+
+    let mut every = Sampler::new(&SamplingDef::all(seed)).unwrap();
+    all.iter().chain(&all).for_each(|id| every.push(*id));
+    let mut expected = all.clone();
+    expected.sort();
+    assert_eq!(every.finish(), expected);
+}
+
+#[test]
+fn test_stratified() {
+    let stratify = StratifyDef {
+        key_path: FieldPath::episode(&[9]),
+        per_bucket_n: 3,
+        seed: cid_from_bytes(b"strata"),
+    };
+    let all = ids(300);
+    let key = |i: usize| match i % 4 {
+        3 => None,
+        k => Some(Value::UVarint(k as u64)),
+    };
+
+    let run = |order: &[usize]| {
+        let mut sampler = StratifiedSampler::new(&stratify);
+        for &i in order {
+            sampler.push(all[i], key(i).as_ref()).unwrap();
+        }
+        assert_eq!(sampler.bucket_count(), 4);
+        sampler.finish()
+    };
+
+    let forward: Vec<usize> = (0..all.len()).collect();
+    let selected = run(&forward);
+    assert_eq!(selected.len(), 12);
+    let backward: Vec<usize> = forward.iter().rev().copied().collect();
+    assert_eq!(run(&backward), selected);
+
+    // Each bucket is HASH_N under its derived seed
+    for k in 0..4 {
+        let members: Vec<EpisodeId> = (0..all.len())
+            .filter(|i| i % 4 == k)
+            .map(|i| all[i])
+            .collect();
+        let seed = bucket_seed(&stratify.seed, key(k).as_ref()).unwrap();
+        for id in hash_n(seed, 3, &members) {
+            assert!(selected.contains(&id));
+        }
+    }
+
+    // Missing keys share the empty-bytes bucket
+    assert_eq!(
+        bucket_seed(&stratify.seed, None).unwrap(),
+        bucket_seed(&stratify.seed, Some(&Value::Bytes(vec![]))).unwrap()
+    );
+}
@@ -0,0 +1,63 @@
+//! MYTHOS-CAN helpers for the typed structs that return this crate's
+//! errors; the field helpers live in `mythos_can::structs`
+
+use crate::cid_from_bytes;
+use crate::error::{Error, Result};
+use mythos_can::structs::{
+    field, known_fields, required_bytes, required_uint, struct_fields, without_fields,
+};
+use mythos_can::Value;
+use mythos_hash::AgentID;
+
+pub fn encode(value: &Value) -> Result<Vec<u8>> {
+    mythos_can::encode_value(value).map_err(|e| Error::InvalidStructure(format!("Encode: {}", e)))
+}
+
+pub fn decode(bytes: &[u8]) -> Result<Value> {
+    mythos_can::decode_value_exact(bytes)
+        .map_err(|e| Error::InvalidStructure(format!("Decode: {}", e)))
+}
+
+/// SHA-256 of a struct's canonical bytes without the `excluded` fields
+pub fn id_excluding(fields: Vec<(Value, Value)>, excluded: &[u64]) -> Result<[u8; 32]> {
+    Ok(cid_from_bytes(&encode(&without_fields(fields, excluded))?))
+}
+
+/// AgentID (A.3)
+pub fn agent_value(agent: &AgentID) -> Value {
+    let mut fields = vec![
+        (Value::UVarint(1), Value::UVarint(agent.scheme as u64)),
+        (Value::UVarint(2), Value::Bytes(agent.key.clone())),
+    ];
+    if let Some(hint) = &agent.hint {
+        fields.push((Value::UVarint(3), Value::Text(hint.clone())));
+    }
+    Value::Map(fields)
+}
+
+pub fn agent_from_value(value: &Value, name: &str) -> Result<AgentID> {
+    let fields = struct_fields(value, name)?;
+    known_fields(fields, 3, name)?;
+    Ok(AgentID {
+        scheme: required_uint(field(fields, 1), name, u8::MAX as u64)? as u8,
+        key: required_bytes(field(fields, 2), name)?.to_vec(),
+        hint: match field(fields, 3) {
+            None => None,
+            Some(Value::Text(hint)) => Some(hint.clone()),
+            Some(_) => {
+                return Err(Error::InvalidStructure(format!(
+                    "{} hint must be TEXT",
+                    name
+                )))
+            }
+        },
+    })
+}
+
+pub fn id_mismatch(name: &'static str, stored: &[u8; 32], computed: &[u8; 32]) -> Error {
+    Error::IdMismatch {
+        name,
+        stored: hex::encode(stored),
+        computed: hex::encode(computed),
+    }
+}
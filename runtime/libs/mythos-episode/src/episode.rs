@@ -0,0 +1,144 @@
+//! TraceRef and EpisodeRef (RFC-0001 A.15)
+//!
+//! `EpisodeRef::new` computes `episode_id` over the canonical bytes
+//! without field 1; decoding recomputes it and rejects a stale id.
+
+use crate::codec::{decode, encode, id_excluding, id_mismatch};
+use crate::error::{Error, Result};
+use mythos_blob::BlobRef;
+use mythos_can::structs::{
+    field, hash_value, known_fields, required, required_hash, required_time, struct_fields,
+};
+use mythos_can::Value;
+
+/// An episode's trace blob and the receipts it produced
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceRef {
+    pub trace_blob: BlobRef,        // Field 1
+    pub receipt_ids: Vec<[u8; 32]>, // Field 2
+}
+
+/// One recorded episode
+#[derive(Debug, Clone, PartialEq)]
+pub struct EpisodeRef {
+    pub episode_id: [u8; 32],   // Field 1 - SHA-256 of fields 2..5
+    pub trace_ref: TraceRef,    // Field 2
+    pub context_hash: [u8; 32], // Field 3
+    pub outcome_hash: [u8; 32], // Field 4
+    pub time_observed: i64,     // Field 5 - microseconds since epoch
+}
+
+impl TraceRef {
+    pub fn validate(&self) -> Result<()> {
+        self.trace_blob
+            .validate()
+            .map_err(|e| Error::InvalidStructure(format!("TraceRef.trace_blob: {}", e)))
+    }
+
+    pub fn to_value(&self) -> Value {
+        Value::Map(vec![
+            (Value::UVarint(1), self.trace_blob.to_value()),
+            (
+                Value::UVarint(2),
+                Value::List(self.receipt_ids.iter().map(hash_value).collect()),
+            ),
+        ])
+    }
+
+    pub fn from_value(value: &Value) -> Result<Self> {
+        let fields = struct_fields(value, "TraceRef")?;
+        known_fields(fields, 2, "TraceRef")?;
+
+        let receipt_ids = match required(field(fields, 2), "TraceRef.receipt_ids")? {
+            Value::List(ids) => ids
+                .iter()
+                .map(|id| required_hash(Some(id), "TraceRef receipt id"))
+                .collect::<mythos_can::Result<Vec<_>>>()?,
+            _ => {
+                return Err(Error::InvalidStructure(
+                    "TraceRef.receipt_ids must be LIST".into(),
+                ))
+            }
+        };
+        let trace = TraceRef {
+            trace_blob: BlobRef::from_value(required(field(fields, 1), "TraceRef.trace_blob")?)
+                .map_err(|e| Error::InvalidStructure(format!("TraceRef.trace_blob: {}", e)))?,
+            receipt_ids,
+        };
+        trace.validate()?;
+        Ok(trace)
+    }
+}
+
+impl EpisodeRef {
+    /// Build an episode and compute its id
+    pub fn new(
+        trace_ref: TraceRef,
+        context_hash: [u8; 32],
+        outcome_hash: [u8; 32],
+        time_observed: i64,
+    ) -> Result<Self> {
+        let mut episode = EpisodeRef {
+            episode_id: [0; 32],
+            trace_ref,
+            context_hash,
+            outcome_hash,
+            time_observed,
+        };
+        episode.episode_id = episode.compute_id()?;
+        episode.validate()?;
+        Ok(episode)
+    }
+
+    /// SHA-256 of the canonical bytes without field 1
+    pub fn compute_id(&self) -> Result<[u8; 32]> {
+        id_excluding(self.fields(), &[1])
+    }
+
+    /// Check the TraceRef and that the stored id is current
+    pub fn validate(&self) -> Result<()> {
+        self.trace_ref.validate()?;
+        let computed = self.compute_id()?;
+        if computed != self.episode_id {
+            return Err(id_mismatch("episode_id", &self.episode_id, &computed));
+        }
+        Ok(())
+    }
+
+    fn fields(&self) -> Vec<(Value, Value)> {
+        vec![
+            (Value::UVarint(1), hash_value(&self.episode_id)),
+            (Value::UVarint(2), self.trace_ref.to_value()),
+            (Value::UVarint(3), hash_value(&self.context_hash)),
+            (Value::UVarint(4), hash_value(&self.outcome_hash)),
+            (Value::UVarint(5), Value::IVarint(self.time_observed)),
+        ]
+    }
+
+    pub fn to_value(&self) -> Value {
+        Value::Map(self.fields())
+    }
+
+    pub fn from_value(value: &Value) -> Result<Self> {
+        let fields = struct_fields(value, "EpisodeRef")?;
+        known_fields(fields, 5, "EpisodeRef")?;
+
+        let episode = EpisodeRef {
+            episode_id: required_hash(field(fields, 1), "EpisodeRef.episode_id")?,
+            trace_ref: TraceRef::from_value(required(field(fields, 2), "EpisodeRef.trace_ref")?)?,
+            context_hash: required_hash(field(fields, 3), "EpisodeRef.context_hash")?,
+            outcome_hash: required_hash(field(fields, 4), "EpisodeRef.outcome_hash")?,
+            time_observed: required_time(field(fields, 5), "EpisodeRef.time_observed")?,
+        };
+        episode.validate()?;
+        Ok(episode)
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        encode(&self.to_value())
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        EpisodeRef::from_value(&decode(bytes)?)
+    }
+}
@@ -0,0 +1,52 @@
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum Error {
+    #[error("Invalid structure: {0}")]
+    InvalidStructure(String),
+
+    #[error("Hash must be SHA-256 with 32 bytes: {0}")]
+    InvalidHash(String),
+
+    #[error("Unknown signal type {0}")]
+    UnknownSignalType(u64),
+
+    #[error("Unsupported AgentID scheme {0}")]
+    UnsupportedScheme(u8),
+
+    #[error("Unsupported signature algorithm {0}")]
+    UnsupportedSignature(u8),
+
+    #[error("Object is not signed")]
+    Unsigned,
+
+    #[error("Invalid signature: {0}")]
+    BadSignature(String),
+
+    #[error("{name} mismatch: stored {stored}, computed {computed}")]
+    IdMismatch {
+        name: &'static str,
+        stored: String,
+        computed: String,
+    },
+
+    #[error("Episode not found: {0}")]
+    EpisodeNotFound(String),
+
+    #[error("Store error: {0}")]
+    Store(String),
+}
+
+/// Struct decoding errors keep their variant; anything else the codec
+/// reports is a malformed structure
+impl From<mythos_can::Error> for Error {
+    fn from(e: mythos_can::Error) -> Self {
+        match e {
+            mythos_can::Error::InvalidStructure(reason) => Error::InvalidStructure(reason),
+            mythos_can::Error::InvalidHash(reason) => Error::InvalidHash(reason),
+            other => Error::InvalidStructure(other.to_string()),
+        }
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
@@ -0,0 +1,29 @@
+//! MYTHOS Episodes (RFC-0001 A.15)
+//!
+//! Typed TraceRef, EpisodeRef and Signal with canonical encode/decode and
+//! id computation with field exclusion. Signals are signed with Ed25519
+//! over their signal_id, and `SignedReceipt` signs a Receipt the same
+//! way. `EpisodeIndex` attaches verified Signals to episodes held in a CAS
+//! store and resolves receipt_ids to stored Receipts.
+
+mod codec;
+mod episode;
+mod error;
+mod receipt;
+mod signal;
+mod store;
+
+pub use episode::{EpisodeRef, TraceRef};
+pub use error::{Error, Result};
+pub use receipt::SignedReceipt;
+pub use signal::{ed25519_agent, Signal, SignalType, Signature, SCHEME_ED25519, SIG_ALG_ED25519};
+pub use store::{attach_signal, put_episode, put_receipt, EpisodeIndex};
+
+use sha2::{Digest, Sha256};
+
+/// Compute CID from canonical bytes
+pub fn cid_from_bytes(bytes: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(bytes);
+    hasher.finalize().into()
+}
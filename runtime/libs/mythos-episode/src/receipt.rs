@@ -0,0 +1,130 @@
+//! Signed Receipts (RFC-0001 A.9, §14)
+//!
+//! `mythos_hash::Receipt` holds fields 2..10, the body receipt_id is
+//! computed over. `SignedReceipt` adds the id (field 1) and, as for
+//! Signals, an Ed25519 signature over the 32 receipt_id bytes by the
+//! `signer` key (field 11).
+//!
+//! The stored form carries all eleven fields, so a receipt's CID is not
+//! its receipt_id; `EpisodeIndex` maps one to the other.
+
+use crate::cid_from_bytes;
+use crate::codec::{agent_from_value, decode, encode, id_mismatch};
+use crate::error::{Error, Result};
+use crate::signal::{ed25519_agent, sign_id, verify_signature, Signature};
+use ed25519_dalek::SigningKey;
+use mythos_can::structs::{
+    field, hash_value, known_fields, optional_list, required, required_bytes, required_hash,
+    required_time, required_uint, struct_fields,
+};
+use mythos_can::Value;
+use mythos_hash::{canonical_encode_receipt_for_id, Receipt};
+
+/// A Receipt with its id and signature
+#[derive(Debug, Clone, PartialEq)]
+pub struct SignedReceipt {
+    pub receipt_id: [u8; 32], // Field 1 - SHA-256 of fields 2..10
+    pub receipt: Receipt,     // Fields 2..10
+    pub signature: Signature, // Field 11
+}
+
+impl SignedReceipt {
+    /// Compute the id of `receipt` and sign it with `key`
+    ///
+    /// The signer becomes the key's Ed25519 AgentID; a hint is kept.
+    pub fn sign(mut receipt: Receipt, key: &SigningKey) -> Result<Self> {
+        let hint = receipt.signer.hint.take();
+        receipt.signer = ed25519_agent(&key.verifying_key());
+        receipt.signer.hint = hint;
+
+        let receipt_id = compute_id(&receipt)?;
+        Ok(SignedReceipt {
+            receipt_id,
+            signature: sign_id(&receipt_id, key),
+            receipt,
+        })
+    }
+
+    /// SHA-256 of the canonical bytes without fields 1 and 11
+    pub fn compute_id(&self) -> Result<[u8; 32]> {
+        compute_id(&self.receipt)
+    }
+
+    /// Check that the stored id is current
+    pub fn validate(&self) -> Result<()> {
+        let computed = self.compute_id()?;
+        if computed != self.receipt_id {
+            return Err(id_mismatch("receipt_id", &self.receipt_id, &computed));
+        }
+        Ok(())
+    }
+
+    /// Check the id, then the signer's Ed25519 signature over it
+    pub fn verify(&self) -> Result<()> {
+        self.validate()?;
+        verify_signature(&self.receipt.signer, &self.signature, &self.receipt_id)
+    }
+
+    pub fn to_value(&self) -> Result<Value> {
+        let Value::Map(mut fields) = decode(&body_bytes(&self.receipt)?)? else {
+            return Err(Error::InvalidStructure("Receipt must be MAP".into()));
+        };
+        fields.insert(0, (Value::UVarint(1), hash_value(&self.receipt_id)));
+        fields.push((Value::UVarint(11), self.signature.to_value()));
+        Ok(Value::Map(fields))
+    }
+
+    pub fn from_value(value: &Value) -> Result<Self> {
+        let fields = struct_fields(value, "Receipt")?;
+        known_fields(fields, 11, "Receipt")?;
+
+        let receipt = Receipt {
+            tool_id: required_hash(field(fields, 2), "Receipt.tool_id")?.to_vec(),
+            request_hash: required_hash(field(fields, 3), "Receipt.request_hash")?.to_vec(),
+            response_hash: required_hash(field(fields, 4), "Receipt.response_hash")?.to_vec(),
+            idempotency_key: required_bytes(field(fields, 5), "Receipt.idempotency_key")?.to_vec(),
+            signer: agent_from_value(required(field(fields, 6), "Receipt.signer")?, "AgentID")?,
+            time_us: required_time(field(fields, 7), "Receipt.time_observed")?,
+            status: required_uint(field(fields, 8), "Receipt.status", u16::MAX as u64)? as u16,
+            evidence: optional_list(field(fields, 9), "Receipt.evidence")?
+                .map(|hashes| {
+                    hashes
+                        .iter()
+                        .map(|hash| required_hash(Some(hash), "Receipt evidence").map(Vec::from))
+                        .collect::<mythos_can::Result<Vec<_>>>()
+                })
+                .transpose()?,
+            notes: match field(fields, 10) {
+                None => None,
+                Some(Value::Text(notes)) => Some(notes.clone()),
+                Some(_) => {
+                    return Err(Error::InvalidStructure("Receipt.notes must be TEXT".into()))
+                }
+            },
+        };
+        let signed = SignedReceipt {
+            receipt_id: required_hash(field(fields, 1), "Receipt.receipt_id")?,
+            receipt,
+            signature: Signature::from_value(required(field(fields, 11), "Receipt.signature")?)?,
+        };
+        signed.validate()?;
+        Ok(signed)
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        encode(&self.to_value()?)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        SignedReceipt::from_value(&decode(bytes)?)
+    }
+}
+
+fn body_bytes(receipt: &Receipt) -> Result<Vec<u8>> {
+    canonical_encode_receipt_for_id(receipt)
+        .map_err(|e| Error::InvalidStructure(format!("Encode: {}", e)))
+}
+
+fn compute_id(receipt: &Receipt) -> Result<[u8; 32]> {
+    Ok(cid_from_bytes(&body_bytes(receipt)?))
+}
@@ -0,0 +1,232 @@
+//! Signal and Signature (RFC-0001 A.4, A.15)
+//!
+//! Like receipt_id, `signal_id` is SHA-256 of the canonical bytes without
+//! fields 1 (the id) and 8 (the signature), and the signature is Ed25519
+//! over the 32 signal_id bytes by the `signer` key.
+//!
+//! Decoding checks the id; `verify` also checks the signature. Signals
+//! are only trusted once verified.
+
+use crate::codec::{agent_from_value, agent_value, decode, encode, id_excluding, id_mismatch};
+use crate::error::{Error, Result};
+use ed25519_dalek::{Signer, SigningKey, VerifyingKey};
+use mythos_can::structs::{
+    field, hash_value, known_fields, required, required_bytes, required_hash, required_time,
+    required_uint, struct_fields,
+};
+use mythos_can::Value;
+use mythos_hash::AgentID;
+
+/// AgentID scheme: Ed25519 public key (A.3)
+pub const SCHEME_ED25519: u8 = 1;
+
+/// Signature algorithm: Ed25519 (A.4)
+pub const SIG_ALG_ED25519: u8 = 1;
+
+/// Signal types (A.15)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum SignalType {
+    Reward = 1,
+    Label = 2,
+    Constraint = 3,
+}
+
+/// Signature over an object id (A.4)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Signature {
+    pub alg: u8,                  // Field 1
+    pub key_id: Option<[u8; 32]>, // Field 2 - optional
+    pub sig_bytes: Vec<u8>,       // Field 3
+}
+
+/// A signed judgement about an episode (A.15)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Signal {
+    pub signal_id: [u8; 32],     // Field 1 - SHA-256 of fields 2..7
+    pub episode_id: [u8; 32],    // Field 2
+    pub signal_type: SignalType, // Field 3
+    pub value_kind: u8,          // Field 4
+    pub value_bytes: Vec<u8>,    // Field 5
+    pub signer: AgentID,         // Field 6
+    pub time_observed: i64,      // Field 7 - microseconds since epoch
+    pub signature: Signature,    // Field 8
+}
+
+/// Ed25519 AgentID for a public key
+pub fn ed25519_agent(key: &VerifyingKey) -> AgentID {
+    AgentID {
+        scheme: SCHEME_ED25519,
+        key: key.to_bytes().to_vec(),
+        hint: None,
+    }
+}
+
+/// Ed25519 signature over `id` by the key `signer` names
+pub(crate) fn sign_id(id: &[u8; 32], key: &SigningKey) -> Signature {
+    Signature {
+        alg: SIG_ALG_ED25519,
+        key_id: None,
+        sig_bytes: key.sign(id).to_bytes().to_vec(),
+    }
+}
+
+/// Check `signature` is `signer`'s Ed25519 signature over `id`
+pub(crate) fn verify_signature(
+    signer: &AgentID,
+    signature: &Signature,
+    id: &[u8; 32],
+) -> Result<()> {
+    if signer.scheme != SCHEME_ED25519 {
+        return Err(Error::UnsupportedScheme(signer.scheme));
+    }
+    if signature.alg != SIG_ALG_ED25519 {
+        return Err(Error::UnsupportedSignature(signature.alg));
+    }
+    if signature.sig_bytes.is_empty() {
+        return Err(Error::Unsigned);
+    }
+
+    let key: [u8; 32] =
+        signer.key.as_slice().try_into().map_err(|_| {
+            Error::BadSignature(format!("signer key has {} bytes", signer.key.len()))
+        })?;
+    let key = VerifyingKey::from_bytes(&key).map_err(|e| Error::BadSignature(e.to_string()))?;
+    let sig = ed25519_dalek::Signature::from_slice(&signature.sig_bytes)
+        .map_err(|e| Error::BadSignature(e.to_string()))?;
+    key.verify_strict(id, &sig)
+        .map_err(|e| Error::BadSignature(e.to_string()))
+}
+
+impl SignalType {
+    pub fn from_u64(value: u64) -> Result<Self> {
+        match value {
+            1 => Ok(SignalType::Reward),
+            2 => Ok(SignalType::Label),
+            3 => Ok(SignalType::Constraint),
+            other => Err(Error::UnknownSignalType(other)),
+        }
+    }
+}
+
+impl Signature {
+    pub fn to_value(&self) -> Value {
+        let mut fields = vec![(Value::UVarint(1), Value::UVarint(self.alg as u64))];
+        if let Some(key_id) = &self.key_id {
+            fields.push((Value::UVarint(2), hash_value(key_id)));
+        }
+        fields.push((Value::UVarint(3), Value::Bytes(self.sig_bytes.clone())));
+        Value::Map(fields)
+    }
+
+    pub fn from_value(value: &Value) -> Result<Self> {
+        let fields = struct_fields(value, "Signature")?;
+        known_fields(fields, 3, "Signature")?;
+        Ok(Signature {
+            alg: required_uint(field(fields, 1), "Signature.alg", u8::MAX as u64)? as u8,
+            key_id: field(fields, 2)
+                .map(|key_id| required_hash(Some(key_id), "Signature.key_id"))
+                .transpose()?,
+            sig_bytes: required_bytes(field(fields, 3), "Signature.sig_bytes")?.to_vec(),
+        })
+    }
+}
+
+impl Signal {
+    /// Build a signal, compute its id and sign it with `key`
+    ///
+    /// The signer is the key's Ed25519 AgentID without a hint.
+    pub fn sign(
+        episode_id: [u8; 32],
+        signal_type: SignalType,
+        value_kind: u8,
+        value_bytes: Vec<u8>,
+        time_observed: i64,
+        key: &SigningKey,
+    ) -> Result<Self> {
+        let mut signal = Signal {
+            signal_id: [0; 32],
+            episode_id,
+            signal_type,
+            value_kind,
+            value_bytes,
+            signer: ed25519_agent(&key.verifying_key()),
+            time_observed,
+            signature: Signature {
+                alg: SIG_ALG_ED25519,
+                key_id: None,
+                sig_bytes: Vec::new(),
+            },
+        };
+        signal.signal_id = signal.compute_id()?;
+        signal.signature = sign_id(&signal.signal_id, key);
+        Ok(signal)
+    }
+
+    /// SHA-256 of the canonical bytes without fields 1 and 8
+    pub fn compute_id(&self) -> Result<[u8; 32]> {
+        id_excluding(self.fields(), &[1, 8])
+    }
+
+    /// Check that the stored id is current
+    pub fn validate(&self) -> Result<()> {
+        let computed = self.compute_id()?;
+        if computed != self.signal_id {
+            return Err(id_mismatch("signal_id", &self.signal_id, &computed));
+        }
+        Ok(())
+    }
+
+    /// Check the id, then the signer's Ed25519 signature over it
+    pub fn verify(&self) -> Result<()> {
+        self.validate()?;
+        verify_signature(&self.signer, &self.signature, &self.signal_id)
+    }
+
+    fn fields(&self) -> Vec<(Value, Value)> {
+        vec![
+            (Value::UVarint(1), hash_value(&self.signal_id)),
+            (Value::UVarint(2), hash_value(&self.episode_id)),
+            (Value::UVarint(3), Value::UVarint(self.signal_type as u64)),
+            (Value::UVarint(4), Value::UVarint(self.value_kind as u64)),
+            (Value::UVarint(5), Value::Bytes(self.value_bytes.clone())),
+            (Value::UVarint(6), agent_value(&self.signer)),
+            (Value::UVarint(7), Value::IVarint(self.time_observed)),
+            (Value::UVarint(8), self.signature.to_value()),
+        ]
+    }
+
+    pub fn to_value(&self) -> Value {
+        Value::Map(self.fields())
+    }
+
+    pub fn from_value(value: &Value) -> Result<Self> {
+        let fields = struct_fields(value, "Signal")?;
+        known_fields(fields, 8, "Signal")?;
+
+        let signal = Signal {
+            signal_id: required_hash(field(fields, 1), "Signal.signal_id")?,
+            episode_id: required_hash(field(fields, 2), "Signal.episode_id")?,
+            signal_type: SignalType::from_u64(required_uint(
+                field(fields, 3),
+                "Signal.signal_type",
+                u8::MAX as u64,
+            )?)?,
+            value_kind: required_uint(field(fields, 4), "Signal.value_kind", u8::MAX as u64)? as u8,
+            value_bytes: required_bytes(field(fields, 5), "Signal.value_bytes")?.to_vec(),
+            signer: agent_from_value(required(field(fields, 6), "Signal.signer")?, "AgentID")?,
+            time_observed: required_time(field(fields, 7), "Signal.time_observed")?,
+            signature: Signature::from_value(required(field(fields, 8), "Signal.signature")?)?,
+        };
+        signal.validate()?;
+        Ok(signal)
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        encode(&self.to_value())
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        Signal::from_value(&decode(bytes)?)
+    }
+}
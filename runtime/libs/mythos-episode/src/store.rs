@@ -0,0 +1,201 @@
+//! Episodes and Signals in a CAS store
+//!
+//! Objects are stored as their canonical bytes. EpisodeRef, Signal and
+//! Receipt ids leave fields out, so an object's CID is not its id:
+//! `EpisodeIndex` maps each episode_id to its EpisodeRef CID and to the
+//! CIDs of the verified Signals attached to it, and each receipt_id to the
+//! CID of its verified signed Receipt.
+
+use crate::episode::EpisodeRef;
+use crate::error::{Error, Result};
+use crate::receipt::SignedReceipt;
+use crate::signal::Signal;
+use mythos_can::Value;
+use mythos_cas::{BlobStore, Cid};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// episode_id -> EpisodeRef and Signal CIDs, receipt_id -> Receipt CID
+#[derive(Debug, Clone, Default)]
+pub struct EpisodeIndex {
+    /// EpisodeRef CID and the receipt ids in its TraceRef
+    episodes: BTreeMap<[u8; 32], (Cid, Vec<[u8; 32]>)>,
+    signals: BTreeMap<[u8; 32], BTreeSet<Cid>>,
+    receipts: BTreeMap<[u8; 32], Cid>,
+    /// Stored Signals and Receipts whose signature did not verify; never
+    /// indexed
+    pub rejected: Vec<Cid>,
+}
+
+/// Store an EpisodeRef, returning its CID
+pub fn put_episode<S: BlobStore + ?Sized>(store: &S, episode: &EpisodeRef) -> Result<Cid> {
+    episode.validate()?;
+    store.put(&episode.to_bytes()?).map_err(store_error)
+}
+
+/// Verify a Signal, store it and attach it to its episode
+///
+/// The episode must already be in `index`.
+pub fn attach_signal<S: BlobStore + ?Sized>(
+    store: &S,
+    index: &mut EpisodeIndex,
+    signal: &Signal,
+) -> Result<Cid> {
+    signal.verify()?;
+    if index.episode_cid(&signal.episode_id).is_none() {
+        return Err(Error::EpisodeNotFound(hex::encode(signal.episode_id)));
+    }
+    let cid = store.put(&signal.to_bytes()?).map_err(store_error)?;
+    index.add_signal(cid, signal)?;
+    Ok(cid)
+}
+
+/// Verify a signed Receipt, store it and index its receipt_id
+pub fn put_receipt<S: BlobStore + ?Sized>(
+    store: &S,
+    index: &mut EpisodeIndex,
+    receipt: &SignedReceipt,
+) -> Result<Cid> {
+    receipt.verify()?;
+    let cid = store.put(&receipt.to_bytes()?).map_err(store_error)?;
+    index.add_receipt(cid, receipt)?;
+    Ok(cid)
+}
+
+impl EpisodeIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Index every EpisodeRef, Signal and signed Receipt in the store
+    ///
+    /// Other objects are skipped; Signals and Receipts failing
+    /// verification are listed in `rejected`.
+    pub fn build<S: BlobStore + ?Sized>(store: &S) -> Result<Self> {
+        let mut index = EpisodeIndex::new();
+        let mut cids = store.list().map_err(store_error)?;
+        cids.sort_unstable();
+
+        for cid in cids {
+            let bytes = match store.get(&cid) {
+                Ok(bytes) => bytes,
+                // Deleted since listing
+                Err(mythos_cas::Error::NotFound(_)) => continue,
+                Err(e) => return Err(store_error(e)),
+            };
+            let Ok(value) = mythos_can::decode_value_exact(&bytes) else {
+                continue;
+            };
+            index.add_object(cid, &value);
+        }
+        Ok(index)
+    }
+
+    /// Index one decoded object if it is an EpisodeRef, Signal or signed
+    /// Receipt
+    pub fn add_object(&mut self, cid: Cid, value: &Value) {
+        if let Ok(episode) = EpisodeRef::from_value(value) {
+            self.add_episode(cid, &episode);
+        } else if let Ok(signal) = Signal::from_value(value) {
+            if self.add_signal(cid, &signal).is_err() {
+                self.rejected.push(cid);
+            }
+        } else if let Ok(receipt) = SignedReceipt::from_value(value) {
+            if self.add_receipt(cid, &receipt).is_err() {
+                self.rejected.push(cid);
+            }
+        }
+    }
+
+    pub fn add_episode(&mut self, cid: Cid, episode: &EpisodeRef) {
+        self.episodes.insert(
+            episode.episode_id,
+            (cid, episode.trace_ref.receipt_ids.clone()),
+        );
+    }
+
+    /// Attach a stored Signal after verifying it
+    pub fn add_signal(&mut self, cid: Cid, signal: &Signal) -> Result<()> {
+        signal.verify()?;
+        self.signals
+            .entry(signal.episode_id)
+            .or_default()
+            .insert(cid);
+        Ok(())
+    }
+
+    /// Index a stored signed Receipt after verifying it
+    pub fn add_receipt(&mut self, cid: Cid, receipt: &SignedReceipt) -> Result<()> {
+        receipt.verify()?;
+        self.receipts.insert(receipt.receipt_id, cid);
+        Ok(())
+    }
+
+    pub fn episode_count(&self) -> usize {
+        self.episodes.len()
+    }
+
+    /// EpisodeIDs, ascending
+    pub fn episode_ids(&self) -> impl Iterator<Item = &[u8; 32]> {
+        self.episodes.keys()
+    }
+
+    pub fn episode_cid(&self, episode_id: &[u8; 32]) -> Option<Cid> {
+        self.episodes.get(episode_id).map(|(cid, _)| *cid)
+    }
+
+    /// Receipt ids in an indexed episode's TraceRef
+    pub fn receipt_ids(&self, episode_id: &[u8; 32]) -> &[[u8; 32]] {
+        self.episodes
+            .get(episode_id)
+            .map(|(_, ids)| ids.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// CIDs of the Signals attached to an episode, ascending
+    pub fn signal_cids(&self, episode_id: &[u8; 32]) -> Vec<Cid> {
+        self.signals
+            .get(episode_id)
+            .map(|cids| cids.iter().copied().collect())
+            .unwrap_or_default()
+    }
+
+    pub fn receipt_cid(&self, receipt_id: &[u8; 32]) -> Option<Cid> {
+        self.receipts.get(receipt_id).copied()
+    }
+
+    pub fn load_episode<S: BlobStore + ?Sized>(
+        &self,
+        store: &S,
+        episode_id: &[u8; 32],
+    ) -> Result<Option<EpisodeRef>> {
+        self.episode_cid(episode_id)
+            .map(|cid| EpisodeRef::from_bytes(&store.get(&cid).map_err(store_error)?))
+            .transpose()
+    }
+
+    /// Attached Signals in CID order
+    pub fn load_signals<S: BlobStore + ?Sized>(
+        &self,
+        store: &S,
+        episode_id: &[u8; 32],
+    ) -> Result<Vec<Signal>> {
+        self.signal_cids(episode_id)
+            .iter()
+            .map(|cid| Signal::from_bytes(&store.get(cid).map_err(store_error)?))
+            .collect()
+    }
+
+    pub fn load_receipt<S: BlobStore + ?Sized>(
+        &self,
+        store: &S,
+        receipt_id: &[u8; 32],
+    ) -> Result<Option<SignedReceipt>> {
+        self.receipt_cid(receipt_id)
+            .map(|cid| SignedReceipt::from_bytes(&store.get(&cid).map_err(store_error)?))
+            .transpose()
+    }
+}
+
+fn store_error(e: mythos_cas::Error) -> Error {
+    Error::Store(e.to_string())
+}
@@ -0,0 +1,28 @@
+// Fixtures shared by the mythos-episode integration tests
+#![allow(dead_code)]
+
+use mythos_cas::FsStore;
+use std::fs;
+use std::path::PathBuf;
+
+/// Fresh store directory, removed when dropped
+pub struct TempStore {
+    pub path: PathBuf,
+    pub store: FsStore,
+}
+
+impl TempStore {
+    pub fn new(name: &str) -> Self {
+        let path =
+            std::env::temp_dir().join(format!("mythos-episode-{}-{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&path);
+        let store = FsStore::open(&path).unwrap();
+        TempStore { path, store }
+    }
+}
+
+impl Drop for TempStore {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
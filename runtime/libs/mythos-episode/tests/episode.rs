@@ -0,0 +1,246 @@
+/// EpisodeRef, TraceRef, Signal and signed Receipt tests (RFC-0001 A.9,
+/// A.15)
+use ed25519_dalek::SigningKey;
+use mythos_blob::{BlobRef, CODEC_RAW};
+use mythos_can::Value;
+use mythos_episode::{
+    cid_from_bytes, EpisodeRef, Error, Signal, SignalType, SignedReceipt, TraceRef, SCHEME_ED25519,
+};
+use mythos_hash::{compute_receipt_id, AgentID, Receipt};
+use std::fs;
+
+const KEYS_PATH: &str = "../../../mythos-v0.2-conformance/keys";
+
+/// The conformance pack's test key
+fn test_key() -> SigningKey {
+    let seed = fs::read_to_string(format!("{}/ed25519_test_seed.hex", KEYS_PATH)).unwrap();
+    let seed: [u8; 32] = hex::decode(seed.trim()).unwrap().try_into().unwrap();
+    SigningKey::from_bytes(&seed)
+}
+
+fn trace() -> TraceRef {
+    TraceRef {
+        trace_blob: BlobRef {
+            cid: cid_from_bytes(b"trace").to_vec(),
+            size: 4096,
+            media: "application/mythos.trace".into(),
+            codec: CODEC_RAW,
+            chunks: 1,
+            encryption: None,
+            provenance: None,
+        },
+        receipt_ids: vec![cid_from_bytes(b"receipt 1"), cid_from_bytes(b"receipt 2")],
+    }
+}
+
+fn episode() -> EpisodeRef {
+    EpisodeRef::new(
+        trace(),
+        cid_from_bytes(b"context"),
+        cid_from_bytes(b"outcome"),
+        1_700_000_000_000_000,
+    )
+    .unwrap()
+}
+
+fn fields(value: Value) -> Vec<(Value, Value)> {
+    match value {
+        Value::Map(fields) => fields,
+        _ => panic!("expected MAP"),
+    }
+}
+
+#[test]
+fn test_episode_id_excludes_field_1() {
+    let episode = episode();
+    let without_id: Vec<_> = fields(episode.to_value())
+        .into_iter()
+        .filter(|(k, _)| *k != Value::UVarint(1))
+        .collect();
+    let bytes = mythos_can::encode_value(&Value::Map(without_id)).unwrap();
+    assert_eq!(episode.episode_id, cid_from_bytes(&bytes));
+
+    let bytes = episode.to_bytes().unwrap();
+    assert_eq!(EpisodeRef::from_bytes(&bytes).unwrap(), episode);
+
+    // Any change to the body changes the id
+    let mut later = episode.clone();
+    later.time_observed += 1;
+    assert_ne!(later.compute_id().unwrap(), episode.episode_id);
+    assert!(matches!(
+        EpisodeRef::from_value(&later.to_value()),
+        Err(Error::IdMismatch {
+            name: "episode_id",
+            ..
+        })
+    ));
+}
+
+#[test]
+fn test_trace_ref_validation() {
+    let mut fields = fields(trace().to_value());
+    fields[1].1 = Value::List(vec![Value::Bytes(vec![0; 32])]);
+    assert!(matches!(
+        TraceRef::from_value(&Value::Map(fields)),
+        Err(Error::InvalidHash(_))
+    ));
+
+    let mut bad_blob = trace();
+    bad_blob.trace_blob.cid = vec![0; 16];
+    assert!(EpisodeRef::new(bad_blob, [0; 32], [0; 32], 0).is_err());
+}
+
+#[test]
+fn test_signal_sign_and_verify() {
+    let key = test_key();
+    let public = fs::read_to_string(format!("{}/ed25519_test_public.hex", KEYS_PATH)).unwrap();
+    assert_eq!(hex::encode(key.verifying_key().to_bytes()), public.trim());
+
+    let episode = episode();
+    let signal = Signal::sign(
+        episode.episode_id,
+        SignalType::Reward,
+        1,
+        vec![0x2a],
+        1_700_000_000_000_001,
+        &key,
+    )
+    .unwrap();
+    assert_eq!(signal.signer.scheme, SCHEME_ED25519);
+    assert_eq!(signal.signer.key, key.verifying_key().to_bytes());
+    signal.verify().unwrap();
+
+    // signal_id leaves out the id and the signature
+    let body: Vec<_> = fields(signal.to_value())
+        .into_iter()
+        .filter(|(k, _)| *k != Value::UVarint(1) && *k != Value::UVarint(8))
+        .collect();
+    let bytes = mythos_can::encode_value(&Value::Map(body)).unwrap();
+    assert_eq!(signal.signal_id, cid_from_bytes(&bytes));
+
+    let decoded = Signal::from_bytes(&signal.to_bytes().unwrap()).unwrap();
+    assert_eq!(decoded, signal);
+    decoded.verify().unwrap();
+
+    // Signing is deterministic
+    let again = Signal::sign(
+        episode.episode_id,
+        SignalType::Reward,
+        1,
+        vec![0x2a],
+        1_700_000_000_000_001,
+        &key,
+    )
+    .unwrap();
+    assert_eq!(again, signal);
+}
+
+#[test]
+fn test_signal_tampering_detected() {
+    let key = test_key();
+    let signal = Signal::sign([7; 32], SignalType::Label, 3, b"good".to_vec(), 5, &key).unwrap();
+
+    // A changed body no longer matches its id
+    let mut relabelled = signal.clone();
+    relabelled.value_bytes = b"bad".to_vec();
+    assert!(matches!(relabelled.verify(), Err(Error::IdMismatch { .. })));
+
+    // Re-deriving the id does not help without the key
+    relabelled.signal_id = relabelled.compute_id().unwrap();
+    assert!(matches!(relabelled.verify(), Err(Error::BadSignature(_))));
+
+    // Another key's signature over the same id
+    let other = SigningKey::from_bytes(&[2; 32]);
+    let mut forged = signal.clone();
+    forged.signature = Signal::sign([7; 32], SignalType::Label, 3, b"good".to_vec(), 5, &other)
+        .unwrap()
+        .signature;
+    assert!(matches!(forged.verify(), Err(Error::BadSignature(_))));
+
+    let mut unsigned = signal.clone();
+    unsigned.signature.sig_bytes.clear();
+    assert_eq!(unsigned.verify(), Err(Error::Unsigned));
+
+    let mut scheme = signal;
+    scheme.signer.scheme = 9;
+    scheme.signal_id = scheme.compute_id().unwrap();
+    assert_eq!(scheme.verify(), Err(Error::UnsupportedScheme(9)));
+}
+
+#[test]
+fn test_unknown_signal_type() {
+    let key = test_key();
+    let signal = Signal::sign([7; 32], SignalType::Constraint, 0, vec![], 0, &key).unwrap();
+    let mut fields = fields(signal.to_value());
+    fields[2].1 = Value::UVarint(4);
+    assert_eq!(
+        Signal::from_value(&Value::Map(fields)),
+        Err(Error::UnknownSignalType(4))
+    );
+}
+
+fn receipt() -> Receipt {
+    Receipt {
+        tool_id: cid_from_bytes(b"tool").to_vec(),
+        request_hash: cid_from_bytes(b"request").to_vec(),
+        response_hash: cid_from_bytes(b"response").to_vec(),
+        idempotency_key: b"once".to_vec(),
+        signer: AgentID {
+            scheme: 0,
+            key: Vec::new(),
+            hint: Some("builder".into()),
+        },
+        time_us: 1_700_000_000_000_002,
+        status: 200,
+        evidence: Some(vec![cid_from_bytes(b"evidence").to_vec()]),
+        notes: Some("ok".into()),
+    }
+}
+
+#[test]
+fn test_receipt_sign_and_verify() {
+    let key = test_key();
+    let signed = SignedReceipt::sign(receipt(), &key).unwrap();
+    assert_eq!(signed.receipt.signer.scheme, SCHEME_ED25519);
+    assert_eq!(signed.receipt.signer.key, key.verifying_key().to_bytes());
+    assert_eq!(signed.receipt.signer.hint.as_deref(), Some("builder"));
+    signed.verify().unwrap();
+
+    // receipt_id is the RECEIPT_001 id of the body
+    assert_eq!(signed.receipt_id, compute_receipt_id(&signed.receipt));
+
+    // The stored form keeps the id and signature
+    let value = signed.to_value().unwrap();
+    let keys: Vec<_> = fields(value).into_iter().map(|(k, _)| k).collect();
+    assert_eq!(keys.first(), Some(&Value::UVarint(1)));
+    assert_eq!(keys.last(), Some(&Value::UVarint(11)));
+    let bytes = signed.to_bytes().unwrap();
+    assert_ne!(cid_from_bytes(&bytes), signed.receipt_id);
+    let decoded = SignedReceipt::from_bytes(&bytes).unwrap();
+    assert_eq!(decoded, signed);
+    decoded.verify().unwrap();
+}
+
+#[test]
+fn test_receipt_tampering_detected() {
+    let key = test_key();
+    let signed = SignedReceipt::sign(receipt(), &key).unwrap();
+
+    let mut failed = signed.clone();
+    failed.receipt.status = 500;
+    assert!(matches!(
+        SignedReceipt::from_value(&failed.to_value().unwrap()),
+        Err(Error::IdMismatch {
+            name: "receipt_id",
+            ..
+        })
+    ));
+
+    // Re-deriving the id does not help without the key
+    failed.receipt_id = failed.compute_id().unwrap();
+    assert!(matches!(failed.verify(), Err(Error::BadSignature(_))));
+
+    let mut unsigned = signed;
+    unsigned.signature.sig_bytes.clear();
+    assert_eq!(unsigned.verify(), Err(Error::Unsigned));
+}
@@ -0,0 +1,158 @@
+/// EpisodeIndex and signal attachment tests
+use ed25519_dalek::SigningKey;
+use mythos_blob::{BlobRef, CODEC_RAW};
+use mythos_cas::BlobStore;
+use mythos_episode::{
+    attach_signal, cid_from_bytes, put_episode, put_receipt, EpisodeIndex, EpisodeRef, Error,
+    Signal, SignalType, SignedReceipt, TraceRef,
+};
+use mythos_hash::{AgentID, Receipt};
+
+mod common;
+use common::TempStore;
+
+fn episode(n: u8) -> EpisodeRef {
+    let trace = TraceRef {
+        trace_blob: BlobRef {
+            cid: cid_from_bytes(&[n]).to_vec(),
+            size: 10,
+            media: "application/mythos.trace".into(),
+            codec: CODEC_RAW,
+            chunks: 0,
+            encryption: None,
+            provenance: None,
+        },
+        receipt_ids: vec![],
+    };
+    EpisodeRef::new(trace, [n; 32], [n; 32], n as i64).unwrap()
+}
+
+#[test]
+fn test_attach_and_load() {
+    let temp = TempStore::new("attach");
+    let store = &temp.store;
+    let key = SigningKey::from_bytes(&[1; 32]);
+    let mut index = EpisodeIndex::new();
+
+    let first = episode(1);
+    let second = episode(2);
+    for episode in [&first, &second] {
+        let cid = put_episode(store, episode).unwrap();
+        index.add_episode(cid, episode);
+    }
+
+    let reward = Signal::sign(first.episode_id, SignalType::Reward, 1, vec![1], 10, &key).unwrap();
+    let label = Signal::sign(first.episode_id, SignalType::Label, 3, vec![2], 11, &key).unwrap();
+    attach_signal(store, &mut index, &reward).unwrap();
+    attach_signal(store, &mut index, &label).unwrap();
+
+    assert_eq!(
+        index.load_episode(store, &first.episode_id).unwrap(),
+        Some(first.clone())
+    );
+    let mut signals = index.load_signals(store, &first.episode_id).unwrap();
+    signals.sort_by_key(|s| s.time_observed);
+    assert_eq!(signals, vec![reward, label]);
+    assert!(index
+        .load_signals(store, &second.episode_id)
+        .unwrap()
+        .is_empty());
+    assert_eq!(index.load_episode(store, &[9; 32]).unwrap(), None);
+
+    // A rebuilt index sees the same attachments
+    let rebuilt = EpisodeIndex::build(store).unwrap();
+    assert_eq!(rebuilt.episode_count(), 2);
+    assert_eq!(
+        rebuilt.signal_cids(&first.episode_id),
+        index.signal_cids(&first.episode_id)
+    );
+    let mut ids: Vec<_> = vec![first.episode_id, second.episode_id];
+    ids.sort();
+    assert_eq!(rebuilt.episode_ids().copied().collect::<Vec<_>>(), ids);
+}
+
+#[test]
+fn test_attach_rejects_bad_signals() {
+    let temp = TempStore::new("reject");
+    let store = &temp.store;
+    let key = SigningKey::from_bytes(&[1; 32]);
+    let mut index = EpisodeIndex::new();
+
+    let known = episode(1);
+    let cid = put_episode(store, &known).unwrap();
+    index.add_episode(cid, &known);
+
+    // Unknown episode
+    let orphan = Signal::sign([9; 32], SignalType::Reward, 1, vec![], 0, &key).unwrap();
+    assert!(matches!(
+        attach_signal(store, &mut index, &orphan),
+        Err(Error::EpisodeNotFound(_))
+    ));
+
+    // Forged signature
+    let mut forged =
+        Signal::sign(known.episode_id, SignalType::Reward, 1, vec![], 0, &key).unwrap();
+    forged.signature.sig_bytes[0] ^= 1;
+    assert!(matches!(
+        attach_signal(store, &mut index, &forged),
+        Err(Error::BadSignature(_))
+    ));
+    assert!(index.signal_cids(&known.episode_id).is_empty());
+
+    // Stored behind the index's back, it is rejected on rebuild
+    let forged_cid = store.put(&forged.to_bytes().unwrap()).unwrap();
+    let rebuilt = EpisodeIndex::build(store).unwrap();
+    assert_eq!(rebuilt.rejected, vec![forged_cid]);
+    assert!(rebuilt.signal_cids(&known.episode_id).is_empty());
+}
+
+fn receipt(status: u16, key: &SigningKey) -> SignedReceipt {
+    let receipt = Receipt {
+        tool_id: cid_from_bytes(b"tool").to_vec(),
+        request_hash: cid_from_bytes(b"request").to_vec(),
+        response_hash: cid_from_bytes(b"response").to_vec(),
+        idempotency_key: vec![status as u8],
+        signer: AgentID {
+            scheme: 0,
+            key: Vec::new(),
+            hint: None,
+        },
+        time_us: 0,
+        status,
+        evidence: None,
+        notes: None,
+    };
+    SignedReceipt::sign(receipt, key).unwrap()
+}
+
+#[test]
+fn test_receipts_resolve_by_id() {
+    let temp = TempStore::new("receipts");
+    let store = &temp.store;
+    let key = SigningKey::from_bytes(&[1; 32]);
+    let mut index = EpisodeIndex::new();
+
+    let ok = receipt(200, &key);
+    let cid = put_receipt(store, &mut index, &ok).unwrap();
+    assert_ne!(cid, ok.receipt_id);
+    assert_eq!(index.receipt_cid(&ok.receipt_id), Some(cid));
+    assert_eq!(
+        index.load_receipt(store, &ok.receipt_id).unwrap(),
+        Some(ok.clone())
+    );
+    assert_eq!(index.load_receipt(store, &cid).unwrap(), None);
+
+    // Forged receipts are refused, and rejected when found on rebuild
+    let mut forged = receipt(500, &key);
+    forged.signature.sig_bytes[0] ^= 1;
+    assert!(matches!(
+        put_receipt(store, &mut index, &forged),
+        Err(Error::BadSignature(_))
+    ));
+    let forged_cid = store.put(&forged.to_bytes().unwrap()).unwrap();
+
+    let rebuilt = EpisodeIndex::build(store).unwrap();
+    assert_eq!(rebuilt.receipt_cid(&ok.receipt_id), Some(cid));
+    assert_eq!(rebuilt.receipt_cid(&forged.receipt_id), None);
+    assert_eq!(rebuilt.rejected, vec![forged_cid]);
+}
@@ -10,7 +10,7 @@ use crate::hash::sha256;
 use mythos_can::Value;
 
 /// AgentID structure (RFC-MYTHOS-0001 Appendix A.3)
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct AgentID {
     pub scheme: u8,           // Field 1 - 1=Ed25519
     pub key: Vec<u8>,         // Field 2 - public key bytes
@@ -31,7 +31,7 @@ pub struct AgentID {
 /// 9: evidence (list(Hash), optional)
 /// 10: notes (text, optional)
 /// 11: signature (Signature) - EXCLUDED from hash computation
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Receipt {
     pub tool_id: Vec<u8>,               // Field 2 - tool_id hash bytes
     pub request_hash: Vec<u8>,          // Field 3
@@ -49,6 +49,7 @@ pub struct Receipt {
 ///
 /// Excludes fields 1 (receipt_id) and 11 (signature).
 /// Returns canonical MYTHOS-CAN bytes.
+#[allow(clippy::vec_init_then_push)]
 pub fn canonical_encode_receipt_for_id(receipt: &Receipt) -> mythos_can::Result<Vec<u8>> {
     // Build MAP with only fields 2-10 (excluding 1 and 11)
     let mut fields = Vec::new();
@@ -0,0 +1,130 @@
+/// MerkleList construction (RFC-0004 §5.5)
+///
+/// Partitions values into leaves of at most FANOUT, then builds internal
+/// layers until a single root remains. Node bytes are canonical MYTHOS-CAN.
+///
+/// RFC-0004 requires 2 to FANOUT children per internal node, so a layer
+/// whose last group would hold a single child carries that child up to the
+/// next layer unchanged instead of wrapping it.
+use crate::cid_from_bytes;
+use crate::types::*;
+use crate::validation::{Error, Result};
+use mythos_can::Value;
+
+/// Result of building a MerkleList
+#[derive(Debug, Clone)]
+pub struct MerkleListBuild {
+    /// Root CID (CID of the final node)
+    pub root: [u8; 32],
+    /// Total number of values in the list
+    pub count: u64,
+    /// Every node as (cid, canonical bytes), leaves first, root last
+    pub nodes: Vec<([u8; 32], Vec<u8>)>,
+}
+
+/// Encode the outer MerkleNode wrapper around a canonical payload
+pub fn encode_merkle_node(kind: u64, payload: Vec<u8>) -> Result<Vec<u8>> {
+    let node = Value::Map(vec![
+        (Value::UVarint(1), Value::UVarint(VERSION)),
+        (Value::UVarint(2), Value::UVarint(kind)),
+        (Value::UVarint(3), Value::Bytes(payload)),
+    ]);
+
+    mythos_can::encode_value(&node)
+        .map_err(|e| Error::InvalidStructure(format!("Node encode failed: {}", e)))
+}
+
+/// Encode a MerkleListLeaf node (kind 1)
+pub fn encode_merkle_list_leaf(values: &[HashValue]) -> Result<Vec<u8>> {
+    if values.is_empty() || values.len() > FANOUT {
+        return Err(Error::InvalidListLength(values.len()));
+    }
+
+    let payload = Value::Map(vec![(
+        Value::UVarint(1),
+        Value::List(values.iter().map(hash_value_to_can).collect()),
+    )]);
+
+    let payload_bytes = mythos_can::encode_value(&payload)
+        .map_err(|e| Error::InvalidStructure(format!("Payload encode failed: {}", e)))?;
+
+    encode_merkle_node(KIND_MERKLE_LIST_LEAF, payload_bytes)
+}
+
+/// Encode a MerkleListInternal node (kind 2)
+pub fn encode_merkle_list_internal(children: &[HashValue], count: u64) -> Result<Vec<u8>> {
+    if children.len() < 2 || children.len() > FANOUT {
+        return Err(Error::InvalidChildCount(children.len()));
+    }
+
+    let payload = Value::Map(vec![
+        (
+            Value::UVarint(1),
+            Value::List(children.iter().map(hash_value_to_can).collect()),
+        ),
+        (Value::UVarint(2), Value::UVarint(count)),
+    ]);
+
+    let payload_bytes = mythos_can::encode_value(&payload)
+        .map_err(|e| Error::InvalidStructure(format!("Payload encode failed: {}", e)))?;
+
+    encode_merkle_node(KIND_MERKLE_LIST_INTERNAL, payload_bytes)
+}
+
+/// Build a MerkleList from an ordered list of Hash values
+///
+/// Values are stored in the given order; they are never sorted.
+pub fn build_merkle_list(values: &[HashValue]) -> Result<MerkleListBuild> {
+    if values.is_empty() {
+        return Err(Error::InvalidListLength(0));
+    }
+
+    let mut nodes = Vec::new();
+
+    // Leaf layer: (cid, count) per leaf
+    let mut layer: Vec<([u8; 32], u64)> = Vec::new();
+    for group in values.chunks(FANOUT) {
+        let bytes = encode_merkle_list_leaf(group)?;
+        let cid = cid_from_bytes(&bytes);
+        layer.push((cid, group.len() as u64));
+        nodes.push((cid, bytes));
+    }
+
+    // Upper layers until a single root exists
+    while layer.len() > 1 {
+        let mut next = Vec::with_capacity(layer.len().div_ceil(FANOUT));
+        for group in layer.chunks(FANOUT) {
+            if group.len() == 1 {
+                // A trailing single child is carried up unchanged
+                next.push(group[0]);
+                continue;
+            }
+
+            let children: Vec<HashValue> = group.iter().map(|(cid, _)| cid_value(cid)).collect();
+            let count = group.iter().map(|(_, c)| c).sum();
+            let bytes = encode_merkle_list_internal(&children, count)?;
+            let cid = cid_from_bytes(&bytes);
+            next.push((cid, count));
+            nodes.push((cid, bytes));
+        }
+        layer = next;
+    }
+
+    let (root, count) = layer[0];
+    Ok(MerkleListBuild { root, count, nodes })
+}
+
+/// Wrap a raw CID as a SHA-256 Hash value
+pub fn cid_value(cid: &[u8; 32]) -> HashValue {
+    HashValue {
+        alg: SHA256_ALG,
+        bytes: cid.to_vec(),
+    }
+}
+
+fn hash_value_to_can(hash: &HashValue) -> Value {
+    Value::Map(vec![
+        (Value::UVarint(1), Value::UVarint(hash.alg)),
+        (Value::UVarint(2), Value::Bytes(hash.bytes.clone())),
+    ])
+}
@@ -0,0 +1,245 @@
+/// MerkleList diff between two roots
+///
+/// Walks both trees from the root and only descends into child pairs whose
+/// CIDs differ, so identical subtrees are never fetched.
+///
+/// The diff is positional: values are compared index by index. An item
+/// inserted in the middle of a list shifts every later index, so it shows
+/// up as a changed range to the end of the shorter list plus an inserted
+/// tail.
+use crate::source::{fetch_verified, NodeSource};
+use crate::types::*;
+use crate::validation::{decode_merkle_list_node, Error, Result};
+use std::ops::Range;
+
+/// A contiguous range of differing indices
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiffRange {
+    /// Indices present in both lists whose values differ
+    Changed(Range<u64>),
+    /// Indices present only in B (B is longer than A)
+    Inserted(Range<u64>),
+    /// Indices present only in A (A is longer than B)
+    Removed(Range<u64>),
+}
+
+/// Result of diffing two MerkleLists
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MerkleDiff {
+    pub len_a: u64,
+    pub len_b: u64,
+    /// Ranges in ascending index order; changed ranges are coalesced
+    pub ranges: Vec<DiffRange>,
+}
+
+impl MerkleDiff {
+    /// True when both lists hold identical values
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+}
+
+/// Diff two MerkleLists stored in `source`
+///
+/// Every fetched node is re-hashed against its CID before use.
+pub fn diff_merkle_lists<S: NodeSource + ?Sized>(
+    source: &S,
+    root_a: &[u8; 32],
+    root_b: &[u8; 32],
+) -> Result<MerkleDiff> {
+    let node_a = load_node(source, root_a)?;
+    let len_a = node_a.count();
+
+    if root_a == root_b {
+        return Ok(MerkleDiff {
+            len_a,
+            len_b: len_a,
+            ranges: Vec::new(),
+        });
+    }
+
+    let node_b = load_node(source, root_b)?;
+    let len_b = node_b.count();
+
+    let mut walker = DiffWalker {
+        source,
+        limit: len_a.min(len_b),
+        changed: Vec::new(),
+    };
+    walker.compare(node_a, node_b, 0)?;
+
+    let mut ranges: Vec<DiffRange> = walker.changed.into_iter().map(DiffRange::Changed).collect();
+
+    // Length difference is a pure tail; no need to fetch it
+    if len_b > len_a {
+        ranges.push(DiffRange::Inserted(len_a..len_b));
+    } else if len_a > len_b {
+        ranges.push(DiffRange::Removed(len_b..len_a));
+    }
+
+    Ok(MerkleDiff {
+        len_a,
+        len_b,
+        ranges,
+    })
+}
+
+struct DiffWalker<'a, S: ?Sized> {
+    source: &'a S,
+    /// Exclusive end of the index range present in both lists
+    limit: u64,
+    changed: Vec<Range<u64>>,
+}
+
+impl<S: NodeSource + ?Sized> DiffWalker<'_, S> {
+    /// Compare two nodes whose subtrees both start at index `start`
+    fn compare(&mut self, a: MerkleListNode, b: MerkleListNode, start: u64) -> Result<()> {
+        if start >= self.limit {
+            return Ok(());
+        }
+
+        match (&a, &b) {
+            (MerkleListNode::Leaf(leaf_a), MerkleListNode::Leaf(leaf_b)) => {
+                for (i, (va, vb)) in leaf_a.values.iter().zip(&leaf_b.values).enumerate() {
+                    let index = start + i as u64;
+                    if index >= self.limit {
+                        break;
+                    }
+                    if va != vb {
+                        self.mark(index);
+                    }
+                }
+                Ok(())
+            }
+            _ => {
+                let span_a = child_span(&a)?;
+                let span_b = child_span(&b)?;
+
+                // Heights differ only along the right spine, where the smaller
+                // subtree fits inside the first child of the larger one
+                if span_a > span_b {
+                    let child = self.load_child(&a, 0, span_a)?;
+                    return self.compare(child, b, start);
+                }
+                if span_b > span_a {
+                    let child = self.load_child(&b, 0, span_b)?;
+                    return self.compare(a, child, start);
+                }
+
+                let (internal_a, internal_b) = match (&a, &b) {
+                    (MerkleListNode::Internal(x), MerkleListNode::Internal(y)) => (x, y),
+                    _ => unreachable!("equal spans above 1 imply internal nodes"),
+                };
+
+                for j in 0..internal_a.children.len().max(internal_b.children.len()) {
+                    let child_start = start + j as u64 * span_a;
+                    if child_start >= self.limit {
+                        break;
+                    }
+
+                    let (Some(cid_a), Some(cid_b)) =
+                        (internal_a.children.get(j), internal_b.children.get(j))
+                    else {
+                        return Err(Error::InvalidStructure(format!(
+                            "Missing child {} below shared limit {}",
+                            j, self.limit
+                        )));
+                    };
+
+                    // Identical subtree: skip without fetching
+                    if cid_a == cid_b {
+                        continue;
+                    }
+
+                    let child_a = self.load_child(&a, j, span_a)?;
+                    let child_b = self.load_child(&b, j, span_b)?;
+                    self.compare(child_a, child_b, child_start)?;
+                }
+
+                Ok(())
+            }
+        }
+    }
+
+    /// Fetch child `j` of an internal node and check its count against the parent
+    fn load_child(&self, parent: &MerkleListNode, j: usize, span: u64) -> Result<MerkleListNode> {
+        let internal = match parent {
+            MerkleListNode::Internal(internal) => internal,
+            MerkleListNode::Leaf(_) => {
+                return Err(Error::InvalidStructure("Leaf has no children".into()))
+            }
+        };
+
+        let cid = cid_array(&internal.children[j])?;
+        let child = load_node(self.source, &cid)?;
+
+        // Every child but the last is a full subtree
+        let last = internal.children.len() - 1;
+        let expected = if j < last {
+            span
+        } else {
+            internal.count - last as u64 * span
+        };
+
+        if child.count() != expected {
+            return Err(Error::InvalidStructure(format!(
+                "Child {} covers {} values, parent implies {}",
+                j,
+                child.count(),
+                expected
+            )));
+        }
+
+        Ok(child)
+    }
+
+    fn mark(&mut self, index: u64) {
+        if let Some(last) = self.changed.last_mut() {
+            if last.end == index {
+                last.end += 1;
+                return;
+            }
+        }
+        self.changed.push(index..index + 1);
+    }
+}
+
+/// Number of values covered by each full child of a node
+///
+/// Leaves cover one value per entry. For internal nodes the span is the
+/// unique power of FANOUT with `(n - 1) * span < count <= n * span`.
+fn child_span(node: &MerkleListNode) -> Result<u64> {
+    let internal = match node {
+        MerkleListNode::Leaf(_) => return Ok(1),
+        MerkleListNode::Internal(internal) => internal,
+    };
+
+    let n = internal.children.len() as u64;
+    let mut span = FANOUT as u64;
+    while n.saturating_mul(span) < internal.count {
+        span = span
+            .checked_mul(FANOUT as u64)
+            .ok_or_else(|| Error::InvalidStructure("Count overflows tree height".into()))?;
+    }
+
+    if (n - 1) * span >= internal.count {
+        return Err(Error::InvalidStructure(format!(
+            "Count {} inconsistent with {} children",
+            internal.count, n
+        )));
+    }
+
+    Ok(span)
+}
+
+fn load_node<S: NodeSource + ?Sized>(source: &S, cid: &[u8; 32]) -> Result<MerkleListNode> {
+    let bytes = fetch_verified(source, cid)?;
+    decode_merkle_list_node(&bytes)
+}
+
+fn cid_array(hash: &HashValue) -> Result<[u8; 32]> {
+    hash.bytes
+        .as_slice()
+        .try_into()
+        .map_err(|_| Error::InvalidHashLength(hash.bytes.len()))
+}
@@ -1,13 +1,28 @@
 //! MYTHOS Merkle Structures (RFC-0004)
 //!
-//! Minimal implementation for MERKLE_001 conformance.
-//! Validates MerkleListLeaf structure and computes CIDs.
+//! MerkleList construction, validation and diffing.
+//! Validates MerkleListLeaf/MerkleListInternal structure and computes CIDs.
 
+mod build;
+mod diff;
+mod source;
 mod types;
 mod validation;
 
-pub use types::{HashValue, MerkleListLeaf, MerkleNodeHeader};
-pub use validation::{parse_merkle_node, validate_merkle_list_leaf};
+pub use build::{
+    build_merkle_list, cid_value, encode_merkle_list_internal, encode_merkle_list_leaf,
+    encode_merkle_node, MerkleListBuild,
+};
+pub use diff::{diff_merkle_lists, DiffRange, MerkleDiff};
+pub use source::{fetch_verified, NodeSource};
+pub use types::{
+    HashValue, MerkleListInternal, MerkleListLeaf, MerkleListNode, MerkleNodeHeader, FANOUT,
+    KIND_MERKLE_LIST_INTERNAL, KIND_MERKLE_LIST_LEAF,
+};
+pub use validation::{
+    decode_merkle_list_node, parse_merkle_node, validate_merkle_list_internal,
+    validate_merkle_list_leaf, Error, Result,
+};
 
 use sha2::{Digest, Sha256};
 
@@ -0,0 +1,43 @@
+/// Node sources for lazy tree traversal
+///
+/// A `NodeSource` returns canonical node bytes by CID. It may be backed by
+/// memory, a local store or a remote CAS; callers never trust it and
+/// re-hash every node they fetch.
+use crate::cid_from_bytes;
+use crate::validation::{Error, Result};
+use std::collections::HashMap;
+
+/// Fetch Merkle node bytes by CID
+pub trait NodeSource {
+    /// Return the bytes stored under `cid`, or `Error::NodeNotFound`
+    fn fetch_node(&self, cid: &[u8; 32]) -> Result<Vec<u8>>;
+}
+
+impl NodeSource for HashMap<[u8; 32], Vec<u8>> {
+    fn fetch_node(&self, cid: &[u8; 32]) -> Result<Vec<u8>> {
+        self.get(cid)
+            .cloned()
+            .ok_or_else(|| Error::NodeNotFound(hex::encode(cid)))
+    }
+}
+
+impl<S: NodeSource + ?Sized> NodeSource for &S {
+    fn fetch_node(&self, cid: &[u8; 32]) -> Result<Vec<u8>> {
+        (**self).fetch_node(cid)
+    }
+}
+
+/// Fetch a node and check that its bytes hash to the requested CID
+pub fn fetch_verified<S: NodeSource + ?Sized>(source: &S, cid: &[u8; 32]) -> Result<Vec<u8>> {
+    let bytes = source.fetch_node(cid)?;
+    let computed = cid_from_bytes(&bytes);
+
+    if &computed != cid {
+        return Err(Error::CidMismatch {
+            expected: hex::encode(cid),
+            computed: hex::encode(computed),
+        });
+    }
+
+    Ok(bytes)
+}
@@ -1,4 +1,4 @@
-/// Merkle structure types
+// Merkle structure types
 
 /// MerkleNode outer structure (MAP with 3 fields)
 #[derive(Debug, Clone)]
@@ -14,6 +14,30 @@ pub struct MerkleListLeaf {
     pub values: Vec<HashValue>, // Ordered list of Hash structs
 }
 
+/// MerkleListInternal (nested payload structure)
+#[derive(Debug, Clone)]
+pub struct MerkleListInternal {
+    pub children: Vec<HashValue>, // Field 1: child node CIDs, in order
+    pub count: u64,               // Field 2: total values in the subtree
+}
+
+/// Decoded MerkleList node of either kind
+#[derive(Debug, Clone)]
+pub enum MerkleListNode {
+    Leaf(MerkleListLeaf),
+    Internal(MerkleListInternal),
+}
+
+impl MerkleListNode {
+    /// Number of values covered by this node
+    pub fn count(&self) -> u64 {
+        match self {
+            MerkleListNode::Leaf(leaf) => leaf.values.len() as u64,
+            MerkleListNode::Internal(internal) => internal.count,
+        }
+    }
+}
+
 /// Hash value (32-byte digest with algorithm ID)
 #[derive(Debug, Clone, PartialEq)]
 pub struct HashValue {
@@ -23,9 +47,7 @@ pub struct HashValue {
 
 // Constants from RFC-0004
 pub const VERSION: u64 = 1;
-#[allow(dead_code)]
 pub const KIND_MERKLE_LIST_LEAF: u64 = 1;
-#[allow(dead_code)]
 pub const KIND_MERKLE_LIST_INTERNAL: u64 = 2;
 pub const FANOUT: usize = 1024;
 pub const SHA256_ALG: u64 = 1;
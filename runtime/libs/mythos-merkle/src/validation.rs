@@ -11,7 +11,7 @@ pub enum Error {
     #[error("Version must be 1, got {0}")]
     InvalidVersion(u64),
 
-    #[error("Kind must be 1 (MerkleListLeaf) for MERKLE_001, got {0}")]
+    #[error("Kind must be 1 (MerkleListLeaf) or 2 (MerkleListInternal), got {0}")]
     InvalidKind(u64),
 
     #[error("Hash algorithm must be 1 (SHA-256), got {0}")]
@@ -22,9 +22,21 @@ pub enum Error {
 
     #[error("List must contain 1 to {FANOUT} items, got {0}")]
     InvalidListLength(usize),
+
+    #[error("Internal node must contain 2 to {FANOUT} children, got {0}")]
+    InvalidChildCount(usize),
+
+    #[error("Node not found: {0}")]
+    NodeNotFound(String),
+
+    #[error("Node bytes do not match CID: expected {expected}, computed {computed}")]
+    CidMismatch { expected: String, computed: String },
+
+    #[error("Node source error: {0}")]
+    Source(String),
 }
 
-type Result<T> = std::result::Result<T, Error>;
+pub type Result<T> = std::result::Result<T, Error>;
 
 /// Parse MerkleNode from decoded Value
 pub fn parse_merkle_node(decoded: &Value) -> Result<MerkleNodeHeader> {
@@ -110,6 +122,72 @@ pub fn validate_merkle_list_leaf(payload: &[u8]) -> Result<MerkleListLeaf> {
     Ok(MerkleListLeaf { values })
 }
 
+/// Validate and parse MerkleListInternal from payload bytes
+pub fn validate_merkle_list_internal(payload: &[u8]) -> Result<MerkleListInternal> {
+    let decoded = mythos_can::decode_value_exact(payload)
+        .map_err(|e| Error::InvalidStructure(format!("Payload decode failed: {}", e)))?;
+
+    let fields = match decoded {
+        Value::Map(pairs) => pairs,
+        _ => {
+            return Err(Error::InvalidStructure(
+                "MerkleListInternal must be MAP".into(),
+            ))
+        }
+    };
+
+    let get_field = |n: u64| {
+        fields
+            .iter()
+            .find(|(k, _)| matches!(k, Value::UVarint(x) if *x == n))
+            .map(|(_, v)| v)
+    };
+
+    // Field 1: children (list of Hash structs)
+    let children_list = match get_field(1) {
+        Some(Value::List(items)) => items,
+        _ => return Err(Error::InvalidStructure("Missing children field".into())),
+    };
+
+    // Validate child count (2 to FANOUT)
+    if children_list.len() < 2 || children_list.len() > FANOUT {
+        return Err(Error::InvalidChildCount(children_list.len()));
+    }
+
+    let mut children = Vec::with_capacity(children_list.len());
+    for (i, item) in children_list.iter().enumerate() {
+        let hash = parse_hash_value(item)
+            .map_err(|e| Error::InvalidStructure(format!("children[{}]: {}", i, e)))?;
+        children.push(hash);
+    }
+
+    // Field 2: count
+    let count = match get_field(2) {
+        Some(Value::UVarint(c)) => *c,
+        _ => return Err(Error::InvalidStructure("Missing count field".into())),
+    };
+
+    Ok(MerkleListInternal { children, count })
+}
+
+/// Decode canonical MerkleNode bytes into a MerkleList leaf or internal node
+pub fn decode_merkle_list_node(bytes: &[u8]) -> Result<MerkleListNode> {
+    let decoded = mythos_can::decode_value_exact(bytes)
+        .map_err(|e| Error::InvalidStructure(format!("Node decode failed: {}", e)))?;
+
+    let node = parse_merkle_node(&decoded)?;
+
+    match node.kind {
+        KIND_MERKLE_LIST_LEAF => Ok(MerkleListNode::Leaf(validate_merkle_list_leaf(
+            &node.payload,
+        )?)),
+        KIND_MERKLE_LIST_INTERNAL => Ok(MerkleListNode::Internal(validate_merkle_list_internal(
+            &node.payload,
+        )?)),
+        other => Err(Error::InvalidKind(other)),
+    }
+}
+
 fn parse_hash_value(val: &Value) -> Result<HashValue> {
     let fields = match val {
         Value::Map(pairs) => pairs,
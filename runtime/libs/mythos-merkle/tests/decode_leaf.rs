@@ -3,7 +3,7 @@ use std::fs;
 
 #[test]
 fn decode_merkle_001_leaf() {
-    let path = "/Users/djperussina/Code/MYTHOS/mythos-v0.2-conformance/vectors/merkle/merklelist_001_leaf.bin";
+    let path = "../../../mythos-v0.2-conformance/vectors/merkle/merklelist_001_leaf.bin";
     let bytes = fs::read(path).expect("Failed to read leaf bin");
 
     println!("Total size: {} bytes", bytes.len());
@@ -0,0 +1,176 @@
+/// MerkleList diff tests
+use mythos_merkle::{
+    build_merkle_list, diff_merkle_lists, DiffRange, Error, HashValue, NodeSource, Result,
+};
+use std::cell::Cell;
+use std::collections::HashMap;
+
+fn value(i: u64) -> HashValue {
+    HashValue {
+        alg: 1,
+        bytes: mythos_hash::sha256(&i.to_be_bytes()).to_vec(),
+    }
+}
+
+fn values(n: u64) -> Vec<HashValue> {
+    (0..n).map(value).collect()
+}
+
+/// In-memory source that counts fetches
+#[derive(Default)]
+struct CountingSource {
+    nodes: HashMap<[u8; 32], Vec<u8>>,
+    fetches: Cell<usize>,
+}
+
+impl CountingSource {
+    fn add(&mut self, values: &[HashValue]) -> [u8; 32] {
+        let build = build_merkle_list(values).unwrap();
+        self.nodes.extend(build.nodes);
+        build.root
+    }
+}
+
+impl NodeSource for CountingSource {
+    fn fetch_node(&self, cid: &[u8; 32]) -> Result<Vec<u8>> {
+        self.fetches.set(self.fetches.get() + 1);
+        self.nodes.fetch_node(cid)
+    }
+}
+
+#[test]
+fn test_identical_roots_are_empty() {
+    let mut source = CountingSource::default();
+    let root = source.add(&values(3000));
+
+    let diff = diff_merkle_lists(&source, &root, &root).unwrap();
+
+    assert!(diff.is_empty());
+    assert_eq!(diff.len_a, 3000);
+    assert_eq!(diff.len_b, 3000);
+    assert_eq!(source.fetches.get(), 1, "Only the root is fetched");
+}
+
+#[test]
+fn test_single_leaf_changes_coalesce() {
+    let mut source = CountingSource::default();
+    let a = values(10);
+    let mut b = a.clone();
+    b[3] = value(100);
+    b[4] = value(101);
+    b[8] = value(102);
+
+    let root_a = source.add(&a);
+    let root_b = source.add(&b);
+    let diff = diff_merkle_lists(&source, &root_a, &root_b).unwrap();
+
+    assert_eq!(
+        diff.ranges,
+        vec![DiffRange::Changed(3..5), DiffRange::Changed(8..9)]
+    );
+}
+
+#[test]
+fn test_only_differing_subtrees_are_fetched() {
+    let mut source = CountingSource::default();
+    let a = values(5000); // 5 leaves under one internal root
+    let mut b = a.clone();
+    b[2500] = value(99_999);
+
+    let root_a = source.add(&a);
+    let root_b = source.add(&b);
+    let diff = diff_merkle_lists(&source, &root_a, &root_b).unwrap();
+
+    assert_eq!(diff.ranges, vec![DiffRange::Changed(2500..2501)]);
+    // Two roots plus the one differing leaf on each side
+    assert_eq!(source.fetches.get(), 4);
+}
+
+#[test]
+fn test_append_reports_inserted_tail() {
+    let mut source = CountingSource::default();
+    let root_a = source.add(&values(1000));
+    let root_b = source.add(&values(2100));
+
+    let diff = diff_merkle_lists(&source, &root_a, &root_b).unwrap();
+
+    assert_eq!(diff.len_a, 1000);
+    assert_eq!(diff.len_b, 2100);
+    assert_eq!(diff.ranges, vec![DiffRange::Inserted(1000..2100)]);
+}
+
+#[test]
+fn test_truncate_reports_removed_tail() {
+    let mut source = CountingSource::default();
+    let root_a = source.add(&values(4097));
+    let mut b = values(2048);
+    b[0] = value(7_000_000);
+    let root_b = source.add(&b);
+
+    let diff = diff_merkle_lists(&source, &root_a, &root_b).unwrap();
+
+    assert_eq!(
+        diff.ranges,
+        vec![DiffRange::Changed(0..1), DiffRange::Removed(2048..4097)]
+    );
+}
+
+#[test]
+fn test_diff_is_symmetric_in_lengths() {
+    let mut source = CountingSource::default();
+    let root_a = source.add(&values(1500));
+    let root_b = source.add(&values(1024));
+
+    let forward = diff_merkle_lists(&source, &root_a, &root_b).unwrap();
+    let backward = diff_merkle_lists(&source, &root_b, &root_a).unwrap();
+
+    assert_eq!(forward.ranges, vec![DiffRange::Removed(1024..1500)]);
+    assert_eq!(backward.ranges, vec![DiffRange::Inserted(1024..1500)]);
+}
+
+#[test]
+fn test_tampered_node_is_rejected() {
+    let mut source = CountingSource::default();
+    let root_a = source.add(&values(10));
+    let root_b = source.add(&values(11));
+
+    // Serve A's bytes under B's CID
+    let bytes_a = source.nodes[&root_a].clone();
+    source.nodes.insert(root_b, bytes_a);
+
+    let result = diff_merkle_lists(&source, &root_a, &root_b);
+    assert!(matches!(result, Err(Error::CidMismatch { .. })));
+}
+
+#[test]
+fn test_missing_node_is_reported() {
+    let mut source = CountingSource::default();
+    let root_a = source.add(&values(10));
+
+    let result = diff_merkle_lists(&source, &root_a, &[0u8; 32]);
+    assert!(matches!(result, Err(Error::NodeNotFound(_))));
+}
+
+#[test]
+fn test_carried_up_spine_with_different_heights() {
+    // 1025 leaves: the last leaf is carried up beside a full internal node
+    let full = 1024 * 1024;
+    let mut source = CountingSource::default();
+    let a = values(full + 1);
+    let mut b = values(full + 2000);
+    b[5] = value(u64::MAX);
+    b[full as usize] = value(u64::MAX - 1);
+
+    let root_a = source.add(&a);
+    let root_b = source.add(&b);
+    let diff = diff_merkle_lists(&source, &root_a, &root_b).unwrap();
+
+    assert_eq!(
+        diff.ranges,
+        vec![
+            DiffRange::Changed(5..6),
+            DiffRange::Changed(full..full + 1),
+            DiffRange::Inserted(full + 1..full + 2000),
+        ]
+    );
+}
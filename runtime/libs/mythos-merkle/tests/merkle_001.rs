@@ -73,3 +73,30 @@ fn test_merkle_001_roundtrip() {
         "Roundtrip must produce identical bytes"
     );
 }
+
+#[test]
+fn test_merkle_001_rebuild_from_values() {
+    let leaf_bin_path = format!("{}/merklelist_001_leaf.bin", VECTORS_PATH);
+    let rootcid_path = format!("{}/merklelist_001_rootcid.hex", VECTORS_PATH);
+
+    let leaf_bytes = fs::read(&leaf_bin_path).expect("Failed to read");
+    let decoded = mythos_can::decode_value_exact(&leaf_bytes).expect("Decode failed");
+    let node = parse_merkle_node(&decoded).expect("Parse failed");
+    let leaf = validate_merkle_list_leaf(&node.payload).expect("Validate failed");
+
+    // Rebuilding from the values must reproduce the exact node bytes
+    let build = mythos_merkle::build_merkle_list(&leaf.values).expect("Build failed");
+
+    let expected_cid = fs::read_to_string(&rootcid_path)
+        .expect("Failed to read expected CID")
+        .trim()
+        .to_string();
+
+    assert_eq!(
+        hex::encode(build.root),
+        expected_cid,
+        "Rebuilt root CID mismatch"
+    );
+    assert_eq!(build.count, 10);
+    assert_eq!(build.nodes, vec![(build.root, leaf_bytes)]);
+}
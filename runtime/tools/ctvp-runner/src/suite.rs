@@ -1,7 +1,7 @@
-/// Suite inference and routing
-///
-/// Centralizes all suite-related logic to prevent drift between
-/// manifest filtering and verification dispatching.
+// Suite inference and routing
+//
+// Centralizes all suite-related logic to prevent drift between
+// manifest filtering and verification dispatching.
 
 /// Infer suite name from vector ID
 pub fn infer_suite_from_id(id: &str) -> &'static str {
@@ -85,13 +85,13 @@ mod tests {
         assert!(is_implemented("can"));
         assert!(is_implemented("receipts"));
         assert!(is_implemented("ledger"));
+        assert!(is_implemented("merkle"));
+        assert!(is_implemented("blob"));
+        assert!(is_implemented("dataset"));
+        assert!(is_implemented("codebook"));
+        assert!(is_implemented("wire"));
 
-        // Not yet implemented
-        assert!(!is_implemented("merkle"));
-        assert!(!is_implemented("blob"));
-        assert!(!is_implemented("dataset"));
-        assert!(!is_implemented("codebook"));
-        assert!(!is_implemented("wire"));
+        // Unknown prefixes are never implemented
         assert!(!is_implemented("unknown"));
     }
 }
@@ -1,4 +1,8 @@
 /// Codebook Suite Verification
+///
+/// The baseline is regenerated from RFC-0001 Appendix B; the shipped
+/// entries must match it byte for byte, load as a valid codebook and
+/// hash to the expected ID.
 use crate::manifest::VectorEntry;
 use anyhow::{bail, Result};
 use std::fs;
@@ -19,7 +23,17 @@ pub fn verify_codebook_vector(entry: &VectorEntry, pack_dir: &Path) -> Result<()
     let entries_path = pack_dir.join(entries_file);
     let entries_bytes = fs::read(&entries_path)?;
 
-    let computed_id = mythos_codebook::codebook_id_from_bytes(&entries_bytes);
+    let baseline = mythos_codebook::baseline_bytes()?;
+    if baseline != entries_bytes {
+        bail!(
+            "Baseline entries mismatch: generated {} bytes, shipped {} bytes",
+            baseline.len(),
+            entries_bytes.len()
+        );
+    }
+
+    let codebook = mythos_codebook::Codebook::from_bytes(&entries_bytes)?;
+    let computed_id = codebook.codebook_id();
 
     if hex::encode(computed_id) != expected_id {
         bail!(
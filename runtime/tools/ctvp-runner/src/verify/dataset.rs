@@ -1,6 +1,9 @@
 /// Dataset Suite Verification
 use crate::manifest::VectorEntry;
-use anyhow::{bail, Result};
+use crate::verify::utils::TempStore;
+use anyhow::{bail, Context, Result};
+use ed25519_dalek::SigningKey;
+use mythos_cas::BlobStore;
 use std::fs;
 use std::path::Path;
 
@@ -47,19 +50,83 @@ pub fn verify_dataset_vector(entry: &VectorEntry, pack_dir: &Path) -> Result<()>
         }
     }
 
-    // Verify manifest root CID
-    if let Some(expected_cid) = entry
-        .expected
-        .get("manifest_root_cid")
-        .and_then(|v| v.as_str())
-    {
+    // Run the RFC-0003 §7 builder over the corpus and check its manifest
+    if let (Some(def_file), Some(corpus_file)) = (
+        entry.files.get("dataset_def_bin"),
+        entry.files.get("corpus_rootnode_bin"),
+    ) {
+        let def = mythos_dataset::DatasetDef::from_bytes(&fs::read(pack_dir.join(def_file))?)
+            .map_err(|e| anyhow::anyhow!("DatasetDef: {}", e))?;
+
+        let temp = TempStore::new(&entry.id)?;
+        temp.store.put(&fs::read(pack_dir.join(corpus_file))?)?;
+        // The receipt is not part of the vector, so any key will do
+        let options = mythos_dataset::BuildOptions {
+            key: SigningKey::from_bytes(&[0; 32]),
+            time_us: 0,
+        };
+        let dataset = mythos_dataset::build_dataset(
+            &def,
+            &mythos_dataset::NoEpisodeData,
+            &temp.store,
+            &options,
+        )
+        .map_err(|e| anyhow::anyhow!("Dataset build failed: {}", e))?;
+        let manifest_root = dataset
+            .manifest_root()
+            .map_err(|e| anyhow::anyhow!("{}", e))?;
+
+        if let Some(expected_cid) = entry
+            .expected
+            .get("manifest_root_cid")
+            .and_then(|v| v.as_str())
+        {
+            if hex::encode(manifest_root) != expected_cid {
+                bail!(
+                    "Manifest root CID mismatch:\n  Expected: {}\n  Computed: {}",
+                    expected_cid,
+                    hex::encode(manifest_root)
+                );
+            }
+        }
+
         if let Some(manifest_node_file) = entry.files.get("manifest_rootnode_bin") {
-            let manifest_path = pack_dir.join(manifest_node_file);
-            let manifest_bytes = fs::read(&manifest_path)?;
-            let computed = mythos_dataset::cid_from_bytes(&manifest_bytes);
+            let expected = fs::read(pack_dir.join(manifest_node_file))?;
+            if temp.store.get(&manifest_root)? != expected {
+                bail!("Manifest root node bytes differ from the built manifest");
+            }
+        }
 
-            if hex::encode(computed) != expected_cid {
-                bail!("Manifest root CID mismatch");
+        if let Some(selected_file) = entry.files.get("selected_json") {
+            let selected: serde_json::Value =
+                serde_json::from_slice(&fs::read(pack_dir.join(selected_file))?)?;
+            if let Some(expected) = selected
+                .get("selected_episode_ids")
+                .and_then(|ids| ids.as_array())
+            {
+                let expected = expected
+                    .iter()
+                    .map(|id| id.as_str().map(str::to_string))
+                    .collect::<Option<Vec<_>>>()
+                    .context("selected_episode_ids must be hex strings")?;
+                let built = mythos_dataset::ListIds::new(&temp.store, &manifest_root)
+                    .map(|id| id.map(hex::encode))
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|e| anyhow::anyhow!("Manifest read failed: {}", e))?;
+                if built != expected {
+                    bail!(
+                        "Selected episodes mismatch:\n  Expected: {:?}\n  Built: {:?}",
+                        expected,
+                        built
+                    );
+                }
+                if dataset.count != built.len() as u64 {
+                    bail!(
+                        "DatasetRef count {} does not match {} manifest entries",
+                        dataset.count,
+                        built.len()
+                    );
+                }
             }
         }
     }
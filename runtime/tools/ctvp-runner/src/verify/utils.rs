@@ -1,5 +1,8 @@
 use anyhow::{bail, Result};
+use mythos_cas::FsStore;
 use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::PathBuf;
 
 /// Verify SHA256 of data against expected hex string
 pub fn verify_sha256(data: &[u8], expected_hex: &str) -> bool {
@@ -54,3 +57,24 @@ fn hex_window(data: &[u8], start: usize, end: usize) -> String {
         .collect::<Vec<_>>()
         .join(" ")
 }
+
+/// Scratch store for verifiers that build objects, removed when dropped
+pub struct TempStore {
+    path: PathBuf,
+    pub store: FsStore,
+}
+
+impl TempStore {
+    pub fn new(name: &str) -> Result<Self> {
+        let path = std::env::temp_dir().join(format!("ctvp-{}-{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&path);
+        let store = FsStore::open(&path)?;
+        Ok(TempStore { path, store })
+    }
+}
+
+impl Drop for TempStore {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
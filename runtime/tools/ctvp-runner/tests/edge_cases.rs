@@ -1,4 +1,4 @@
-use ctvp_runner::report::{TestResult, TestStatus};
+use ctvp_runner::report::TestResult;
 /// Edge case tests to prevent silent-green regressions
 use ctvp_runner::suite::{infer_suite_from_id, is_implemented};
 
@@ -12,9 +12,9 @@ fn test_unknown_prefix_detection() {
 
 #[test]
 fn test_known_unimplemented_vs_unknown() {
-    // Known-but-unimplemented suites
+    // Known suites (all are implemented as of v0.2)
     assert_eq!(infer_suite_from_id("MERKLE_001"), "merkle");
-    assert!(!is_implemented("merkle"));
+    assert!(is_implemented("merkle"));
 
     // Unknown prefix
     assert_eq!(infer_suite_from_id("BOGUS_001"), "unknown");
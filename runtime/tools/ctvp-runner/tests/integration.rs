@@ -1,5 +1,5 @@
 /// Integration tests for ctvp-runner
-use ctvp_runner::report::{TestResult, TestStatus};
+use ctvp_runner::report::TestResult;
 
 #[test]
 fn test_exit_code_logic() {
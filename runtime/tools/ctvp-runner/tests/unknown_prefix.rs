@@ -14,10 +14,10 @@ fn test_unknown_prefix_fails_not_skips() {
 }
 
 #[test]
-fn test_known_unimplemented_skips() {
-    // Known-but-not-yet-implemented suites should SKIP
+fn test_known_suite_is_implemented() {
+    // Known suites are routed to a verifier, never skipped
     assert_eq!(suite::infer_suite_from_id("MERKLE_001"), "merkle");
-    assert!(!suite::is_implemented("merkle"));
+    assert!(suite::is_implemented("merkle"));
 }
 
 #[test]